@@ -2,6 +2,7 @@ use crate::PrivateKey;
 use anyhow::{anyhow, Result};
 use defi_wallet_core_common::contract::ContractCall;
 use defi_wallet_core_common::contract::DynamicContract;
+use defi_wallet_core_common::node::ethereum::nonce::NonceTracker;
 use defi_wallet_core_common::EthAbiTokenBind;
 use defi_wallet_core_common::EthError;
 use ethers::abi::Detokenize;
@@ -20,8 +21,16 @@ type SigningingDynamicContractHttp = DynamicContract<SignerHttpWallet>;
 pub struct EthContract {
     dynamic_contract: Option<DynamicContractHttp>,
     signing_contract: Option<(SigningingDynamicContractHttp, u64)>,
+    /// the locally tracked nonces of the signer, shared with the other sending functions
+    nonce_tracker: Option<NonceTracker>,
 }
 
+/// Decoder of EVM transactions and their calldata
+pub struct EthTxDecoder(defi_wallet_core_common::EthTxDecoder);
+
+/// Decoder of the function outputs, event logs and errors of a contract ABI
+pub struct EthAbiContract(defi_wallet_core_common::EthAbiContract);
+
 pub struct EthDetokenizer {
     json: String,
 }
@@ -46,6 +55,12 @@ mod ffi {
         type CronosTransactionReceiptRaw = crate::ffi::CronosTransactionReceiptRaw;
     }
 
+    /// the 8-bit grayscale image of a QR code (`width * width` pixels, row by row)
+    pub struct QrGrayscaleImage {
+        pub width: usize,
+        pub pixels: Vec<u8>,
+    }
+
     extern "Rust" {
         type EthContract;
 
@@ -63,6 +78,23 @@ mod ffi {
             chainid: u64,
         ) -> Result<Box<EthContract>>;
 
+        /// create the contract from human-readable ABI fragments,
+        /// e.g. "function transfer(address to, uint256 amount) returns (bool)"
+        fn new_eth_contract_from_human_readable(
+            rpcserver: String,
+            contract_address: String,
+            fragments: Vec<String>,
+        ) -> Result<Box<EthContract>>;
+
+        /// create the signing contract from human-readable ABI fragments
+        fn new_signing_eth_contract_from_human_readable(
+            rpcserver: String,
+            contract_address: String,
+            fragments: Vec<String>,
+            private_key: &PrivateKey,
+            chainid: u64,
+        ) -> Result<Box<EthContract>>;
+
         // extract toplevel json with keyname
         fn read_json(filepath: String, keyname: String) -> Result<String>;
 
@@ -83,9 +115,515 @@ mod ffi {
             function_name: &str,
             function_args: &str, // json
         ) -> Result<CronosTransactionReceiptRaw>;
+
+        type EthTxDecoder;
+
+        /// create the decoder with the user-supplied ABI contracts
+        /// (they take precedence over the bundled ERC-20/721/1155/4907 ones)
+        fn new_eth_tx_decoder(abi_contracts: Vec<String>) -> Result<Box<EthTxDecoder>>;
+
+        /// decode the calldata into the function name and named parameters (json);
+        /// it returns "null" if the data doesn't match any of the known ABIs
+        fn decode_calldata(self: &EthTxDecoder, data: &[u8]) -> Result<String>;
+
+        /// decode the raw signed or unsigned transaction bytes (json)
+        fn decode_raw_tx(self: &EthTxDecoder, raw_tx: &[u8]) -> Result<String>;
+
+        /// decode the transaction request in json (as used by `eth_sign_transaction`)
+        fn decode_tx_request(self: &EthTxDecoder, json_str: &str) -> Result<String>;
+
+        type EthAbiContract;
+
+        /// load the contract ABI (json)
+        fn new_eth_abi_contract(abi_json: String) -> Result<Box<EthAbiContract>>;
+
+        /// load the contract ABI from human-readable fragments,
+        /// e.g. "function transfer(address to, uint256 amount) returns (bool)"
+        fn new_eth_abi_contract_from_human_readable(
+            fragments: Vec<String>,
+        ) -> Result<Box<EthAbiContract>>;
+
+        /// encode the input data of the function with the arguments
+        /// (json array of `EthAbiTokenBind` values)
+        fn encode(
+            self: &EthAbiContract,
+            function_name: &str,
+            function_args: &str,
+        ) -> Result<Vec<u8>>;
+
+        /// decode the output data (returned by `eth_call`) of the function
+        /// into the json array of `EthAbiTokenBind` values
+        fn decode_output(self: &EthAbiContract, function_name: &str, data: &[u8])
+            -> Result<String>;
+
+        /// decode the event log (one of the json logs of the transaction receipt)
+        fn decode_event_log(self: &EthAbiContract, log_json: &str) -> Result<String>;
+
+        /// decode the revert data into a readable message;
+        /// it returns an empty string if the data doesn't match any known error
+        fn decode_error(self: &EthAbiContract, data: &[u8]) -> String;
+
+        /// query the balances of the account in the token contracts (json array of
+        /// `ContractBalance` values) with batched calls; it returns the json array
+        /// of `{ balance, error }` (a failed query doesn't fail the others)
+        fn get_contract_balances(
+            account_address: &str,
+            contract_details_json: &str,
+            web3api_url: &str,
+        ) -> Result<String>;
+
+        /// set the address of the ENS-compatible name registry of the chain
+        /// (e.g. the Cronos ID registry, or a locally deployed one)
+        fn set_eth_name_registry(chain_id: u64, registry_address: &str) -> Result<()>;
+
+        /// resolve the name (e.g. ENS or Cronos ID) to its checksummed address
+        fn resolve_eth_name(name: &str, chain_id: u64, web3api_url: &str) -> Result<String>;
+
+        /// the primary name of the address, only if it resolves back to the address;
+        /// it returns an empty string if there's no such name
+        fn lookup_eth_name(address: &str, chain_id: u64, web3api_url: &str) -> Result<String>;
+
+        /// build the EIP-712 typed data (json) of the permit (json of `EthPermitRequest`),
+        /// querying the token nonce, name and domain version where they aren't given
+        fn build_eth_permit(request_json: &str, chain_id: u64, web3api_url: &str)
+            -> Result<String>;
+
+        /// build and sign the permit (json of `EthPermitRequest`);
+        /// it returns the json of the typed data, the signature and its v, r and s
+        fn sign_eth_permit(
+            request_json: &str,
+            chain_id: u64,
+            private_key: &PrivateKey,
+            web3api_url: &str,
+        ) -> Result<String>;
+
+        /// inspect the EIP-712 typed data (json) before signing it; it returns the json of
+        /// the domain separator, the struct hash, the final hash, the encoded types
+        /// and the flattened domain and message fields
+        fn preview_eip712_typed_data(json_typed_data: &str) -> Result<String>;
+
+        /// recover the (checksummed) signer address of the EIP-712 typed data (json)
+        fn recover_eip712_signer(json_typed_data: &str, signature: &str) -> Result<String>;
+
+        /// check if the EIP-712 typed data (json) was signed by the expected address
+        fn verify_eip712_signature(
+            json_typed_data: &str,
+            signature: &str,
+            expected_address: &str,
+        ) -> Result<bool>;
+
+        /// normalize the signature (65 bytes or EIP-2098 compact) to 65 bytes
+        /// with `v` of 27 or 28
+        fn normalize_eth_signature(signature: &str) -> Result<String>;
+
+        /// convert the signature to the EIP-2098 compact form of 64 bytes
+        fn compact_eth_signature(signature: &str) -> Result<String>;
+
+        /// recover the (checksummed) signer address of the message signed as per EIP-191
+        fn recover_eth_personal_signer(message: &str, signature: &str) -> Result<String>;
+
+        /// recover the (checksummed) signer address of the hash value signed directly
+        fn recover_eth_hash_signer(hash: &str, signature: &str) -> Result<String>;
+
+        /// check if the message was signed as per EIP-191 by the expected address
+        fn verify_eth_personal_signature(
+            message: &str,
+            signature: &str,
+            expected_address: &str,
+        ) -> Result<bool>;
+
+        /// check if the hash value was signed directly by the expected address
+        fn verify_eth_hash_signature(
+            hash: &str,
+            signature: &str,
+            expected_address: &str,
+        ) -> Result<bool>;
+
+        /// check if the contract wallet accepts the signature of the hash value (EIP-1271)
+        fn is_valid_eip1271_signature(
+            contract_address: &str,
+            hash: &str,
+            signature: &str,
+            web3api_url: &str,
+        ) -> Result<bool>;
+
+        /// check if the hash value was signed by the account
+        /// (an externally owned account or an EIP-1271 contract wallet)
+        fn verify_eth_account_signature(
+            address: &str,
+            hash: &str,
+            signature: &str,
+            web3api_url: &str,
+        ) -> Result<bool>;
+
+        /// check if the message was signed as per EIP-191 by the account
+        /// (an externally owned account or an EIP-1271 contract wallet)
+        fn verify_eth_account_personal_signature(
+            address: &str,
+            message: &str,
+            signature: &str,
+            web3api_url: &str,
+        ) -> Result<bool>;
+
+        /// parse the EIP-681 request (e.g. scanned from a QR code), resolve its names
+        /// and convert it to the transaction (json) on the chain: the payment of `value`,
+        /// or the ABI-encoded call of the function of the target contract
+        fn build_eip681_transaction(
+            request: &str,
+            chain_id: u64,
+            legacy: bool,
+            web3api_url: &str,
+        ) -> Result<String>;
+
+        /// render the QR code of the payload (e.g. an EIP-681 or a Cosmos payment request URI)
+        /// to the SVG image; `error_correction` is "L", "M", "Q" or "H"
+        fn render_qr_svg(
+            payload: &str,
+            error_correction: &str,
+            quiet_zone: usize,
+            module_size: usize,
+        ) -> Result<String>;
+
+        /// render the QR code of the payload to the PNG image (8-bit grayscale)
+        fn render_qr_png(
+            payload: &str,
+            error_correction: &str,
+            quiet_zone: usize,
+            module_size: usize,
+        ) -> Result<Vec<u8>>;
+
+        /// render the QR code of the payload to the 8-bit grayscale pixels,
+        /// which can be decoded with `decode_qr_grayscale`
+        fn render_qr_grayscale(
+            payload: &str,
+            error_correction: &str,
+            quiet_zone: usize,
+            module_size: usize,
+        ) -> Result<QrGrayscaleImage>;
+
+        /// render the QR code of the payload to the text to be printed on a terminal
+        fn render_qr_terminal(
+            payload: &str,
+            error_correction: &str,
+            quiet_zone: usize,
+        ) -> Result<String>;
+
+        /// decode the QR code in the 8-bit grayscale image (`width * height` pixels,
+        /// row by row) back into its payload, e.g. the payment request URI
+        fn decode_qr_grayscale(width: usize, height: usize, pixels: Vec<u8>) -> Result<String>;
     }
 } // end of ffi
 
+fn qr_render_options(
+    error_correction: &str,
+    quiet_zone: usize,
+    module_size: usize,
+) -> Result<defi_wallet_core_common::QrRenderOptions> {
+    Ok(defi_wallet_core_common::QrRenderOptions {
+        error_correction: error_correction.parse()?,
+        quiet_zone,
+        module_size,
+    })
+}
+
+fn render_qr_svg(
+    payload: &str,
+    error_correction: &str,
+    quiet_zone: usize,
+    module_size: usize,
+) -> Result<String> {
+    let options = qr_render_options(error_correction, quiet_zone, module_size)?;
+    Ok(defi_wallet_core_common::render_qr_svg(payload, &options)?)
+}
+
+fn render_qr_png(
+    payload: &str,
+    error_correction: &str,
+    quiet_zone: usize,
+    module_size: usize,
+) -> Result<Vec<u8>> {
+    let options = qr_render_options(error_correction, quiet_zone, module_size)?;
+    Ok(defi_wallet_core_common::render_qr_png(payload, &options)?)
+}
+
+fn render_qr_grayscale(
+    payload: &str,
+    error_correction: &str,
+    quiet_zone: usize,
+    module_size: usize,
+) -> Result<ffi::QrGrayscaleImage> {
+    let options = qr_render_options(error_correction, quiet_zone, module_size)?;
+    let (width, pixels) = defi_wallet_core_common::render_qr_grayscale(payload, &options)?;
+    Ok(ffi::QrGrayscaleImage { width, pixels })
+}
+
+fn render_qr_terminal(payload: &str, error_correction: &str, quiet_zone: usize) -> Result<String> {
+    // the module size is not used in the text
+    let options = qr_render_options(error_correction, quiet_zone, 1)?;
+    Ok(defi_wallet_core_common::render_qr_terminal(
+        payload, &options,
+    )?)
+}
+
+fn decode_qr_grayscale(width: usize, height: usize, pixels: Vec<u8>) -> Result<String> {
+    Ok(defi_wallet_core_common::decode_qr_grayscale(
+        width, height, &pixels,
+    )?)
+}
+
+fn build_eip681_transaction(
+    request: &str,
+    chain_id: u64,
+    legacy: bool,
+    web3api_url: &str,
+) -> Result<String> {
+    let tx = defi_wallet_core_common::build_eip681_transaction_blocking(
+        request,
+        defi_wallet_core_common::EthNetwork::Custom { chain_id, legacy },
+        web3api_url,
+    )?;
+    Ok(serde_json::to_string(&tx)?)
+}
+
+fn normalize_eth_signature(signature: &str) -> Result<String> {
+    Ok(defi_wallet_core_common::normalize_eth_signature(signature)?)
+}
+
+fn compact_eth_signature(signature: &str) -> Result<String> {
+    Ok(defi_wallet_core_common::compact_eth_signature(signature)?)
+}
+
+fn recover_eth_personal_signer(message: &str, signature: &str) -> Result<String> {
+    Ok(defi_wallet_core_common::recover_eth_personal_signer(
+        message, signature,
+    )?)
+}
+
+fn recover_eth_hash_signer(hash: &str, signature: &str) -> Result<String> {
+    Ok(defi_wallet_core_common::recover_eth_hash_signer(
+        hash, signature,
+    )?)
+}
+
+fn verify_eth_personal_signature(
+    message: &str,
+    signature: &str,
+    expected_address: &str,
+) -> Result<bool> {
+    Ok(defi_wallet_core_common::verify_eth_personal_signature(
+        message,
+        signature,
+        expected_address,
+    )?)
+}
+
+fn verify_eth_hash_signature(hash: &str, signature: &str, expected_address: &str) -> Result<bool> {
+    Ok(defi_wallet_core_common::verify_eth_hash_signature(
+        hash,
+        signature,
+        expected_address,
+    )?)
+}
+
+fn is_valid_eip1271_signature(
+    contract_address: &str,
+    hash: &str,
+    signature: &str,
+    web3api_url: &str,
+) -> Result<bool> {
+    Ok(
+        defi_wallet_core_common::is_valid_eip1271_signature_blocking(
+            contract_address,
+            hash,
+            signature,
+            web3api_url,
+        )?,
+    )
+}
+
+fn verify_eth_account_signature(
+    address: &str,
+    hash: &str,
+    signature: &str,
+    web3api_url: &str,
+) -> Result<bool> {
+    Ok(
+        defi_wallet_core_common::verify_eth_account_signature_blocking(
+            address,
+            hash,
+            signature,
+            web3api_url,
+        )?,
+    )
+}
+
+fn verify_eth_account_personal_signature(
+    address: &str,
+    message: &str,
+    signature: &str,
+    web3api_url: &str,
+) -> Result<bool> {
+    Ok(
+        defi_wallet_core_common::verify_eth_account_personal_signature_blocking(
+            address,
+            message,
+            signature,
+            web3api_url,
+        )?,
+    )
+}
+
+fn preview_eip712_typed_data(json_typed_data: &str) -> Result<String> {
+    let preview = defi_wallet_core_common::preview_eip712_typed_data(json_typed_data)?;
+    Ok(serde_json::to_string(&preview)?)
+}
+
+fn recover_eip712_signer(json_typed_data: &str, signature: &str) -> Result<String> {
+    Ok(defi_wallet_core_common::recover_eip712_signer(
+        json_typed_data,
+        signature,
+    )?)
+}
+
+fn verify_eip712_signature(
+    json_typed_data: &str,
+    signature: &str,
+    expected_address: &str,
+) -> Result<bool> {
+    Ok(defi_wallet_core_common::verify_eip712_signature(
+        json_typed_data,
+        signature,
+        expected_address,
+    )?)
+}
+
+fn build_eth_permit(request_json: &str, chain_id: u64, web3api_url: &str) -> Result<String> {
+    let request = serde_json::from_str(request_json)?;
+    Ok(defi_wallet_core_common::build_eth_permit_blocking(
+        request,
+        defi_wallet_core_common::EthNetwork::Custom {
+            chain_id,
+            legacy: false,
+        },
+        web3api_url,
+    )?)
+}
+
+fn sign_eth_permit(
+    request_json: &str,
+    chain_id: u64,
+    private_key: &PrivateKey,
+    web3api_url: &str,
+) -> Result<String> {
+    let request = serde_json::from_str(request_json)?;
+    let permit = defi_wallet_core_common::sign_eth_permit_blocking(
+        request,
+        defi_wallet_core_common::EthNetwork::Custom {
+            chain_id,
+            legacy: false,
+        },
+        private_key.key.clone(),
+        web3api_url,
+    )?;
+    Ok(serde_json::to_string(&permit)?)
+}
+
+fn set_eth_name_registry(chain_id: u64, registry_address: &str) -> Result<()> {
+    Ok(defi_wallet_core_common::set_eth_name_registry(
+        chain_id,
+        registry_address,
+    )?)
+}
+
+fn resolve_eth_name(name: &str, chain_id: u64, web3api_url: &str) -> Result<String> {
+    Ok(defi_wallet_core_common::resolve_eth_name_blocking(
+        name,
+        chain_id,
+        web3api_url,
+    )?)
+}
+
+fn lookup_eth_name(address: &str, chain_id: u64, web3api_url: &str) -> Result<String> {
+    let name = defi_wallet_core_common::lookup_eth_name_blocking(address, chain_id, web3api_url)?;
+    Ok(name.unwrap_or_default())
+}
+
+fn get_contract_balances(
+    account_address: &str,
+    contract_details_json: &str,
+    web3api_url: &str,
+) -> Result<String> {
+    let contract_details = serde_json::from_str(contract_details_json)?;
+    let balances = defi_wallet_core_common::get_contract_balances_blocking(
+        account_address,
+        contract_details,
+        web3api_url,
+    )?;
+    Ok(serde_json::to_string(&balances)?)
+}
+
+fn new_eth_tx_decoder(abi_contracts: Vec<String>) -> Result<Box<EthTxDecoder>> {
+    let decoder = defi_wallet_core_common::EthTxDecoder::new(abi_contracts)?;
+    Ok(Box::new(EthTxDecoder(decoder)))
+}
+
+fn new_eth_abi_contract(abi_json: String) -> Result<Box<EthAbiContract>> {
+    let contract = defi_wallet_core_common::EthAbiContract::new(&abi_json)?;
+    Ok(Box::new(EthAbiContract(contract)))
+}
+
+fn new_eth_abi_contract_from_human_readable(fragments: Vec<String>) -> Result<Box<EthAbiContract>> {
+    let contract = defi_wallet_core_common::EthAbiContract::from_human_readable(fragments)?;
+    Ok(Box::new(EthAbiContract(contract)))
+}
+
+impl EthAbiContract {
+    fn encode(&self, function_name: &str, function_args: &str) -> Result<Vec<u8>> {
+        let params: Vec<EthAbiTokenBind> = serde_json::from_str(function_args)?;
+        let tokens = params
+            .iter()
+            .map(TryInto::try_into)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(self.0.encode(function_name, tokens)?)
+    }
+
+    fn decode_output(&self, function_name: &str, data: &[u8]) -> Result<String> {
+        Ok(serde_json::to_string(
+            &self.0.decode_output(function_name, data)?,
+        )?)
+    }
+
+    fn decode_event_log(&self, log_json: &str) -> Result<String> {
+        let log: Log = serde_json::from_str(log_json)?;
+        let topics = log
+            .topics
+            .iter()
+            .map(|topic| topic.as_bytes().to_vec())
+            .collect();
+        Ok(serde_json::to_string(
+            &self.0.decode_event_log(topics, &log.data)?,
+        )?)
+    }
+
+    fn decode_error(&self, data: &[u8]) -> String {
+        self.0.decode_error(data).unwrap_or_default()
+    }
+}
+
+impl EthTxDecoder {
+    fn decode_calldata(&self, data: &[u8]) -> Result<String> {
+        Ok(serde_json::to_string(&self.0.decode_calldata(data))?)
+    }
+
+    fn decode_raw_tx(&self, raw_tx: &[u8]) -> Result<String> {
+        Ok(serde_json::to_string(&self.0.decode_raw_tx(raw_tx)?)?)
+    }
+
+    fn decode_tx_request(&self, json_str: &str) -> Result<String> {
+        Ok(serde_json::to_string(&self.0.decode_tx_request(json_str)?)?)
+    }
+}
+
 fn new_eth_contract(
     rpcserver: String,
     contract_address: String,
@@ -97,6 +635,7 @@ fn new_eth_contract(
     Ok(Box::new(EthContract {
         dynamic_contract: Some(dynamic_contract),
         signing_contract: None,
+        nonce_tracker: None,
     }))
 }
 
@@ -107,23 +646,66 @@ fn new_signing_eth_contract(
     private_key: &PrivateKey,
     chainid: u64,
 ) -> Result<Box<EthContract>> {
-    let client: Provider<Http> = Provider::<Http>::try_from(&rpcserver)?;
-    let binding = private_key.to_bytes();
-    let bytes = binding.as_slice(); // 32 bytes
-    let signing_key = SigningKey::from_bytes(bytes.into())?;
-    let wallet: Wallet<SigningKey> = signing_key.into();
-    let wallet = wallet.with_chain_id(chainid);
-    let signer: SignerMiddleware<Provider<Http>, Wallet<SigningKey>> =
-        SignerMiddleware::new(client, wallet);
-    let signing_contract: DynamicContract<SignerMiddleware<Provider<Http>, Wallet<SigningKey>>> =
+    let (signer, nonce_tracker) = new_signer(&rpcserver, private_key, chainid)?;
+    let signing_contract: SigningingDynamicContractHttp =
         DynamicContract::new(&contract_address, &abi_json, signer)?;
 
     Ok(Box::new(EthContract {
         dynamic_contract: None,
         signing_contract: Some((signing_contract, chainid)),
+        nonce_tracker: Some(nonce_tracker),
+    }))
+}
+
+fn new_eth_contract_from_human_readable(
+    rpcserver: String,
+    contract_address: String,
+    fragments: Vec<String>,
+) -> Result<Box<EthContract>> {
+    let client: Provider<Http> = Provider::<Http>::try_from(&rpcserver)?;
+    let dynamic_contract: DynamicContractHttp =
+        DynamicContract::from_human_readable(&contract_address, &fragments, client)?;
+    Ok(Box::new(EthContract {
+        dynamic_contract: Some(dynamic_contract),
+        signing_contract: None,
+        nonce_tracker: None,
+    }))
+}
+
+fn new_signing_eth_contract_from_human_readable(
+    rpcserver: String,
+    contract_address: String,
+    fragments: Vec<String>,
+    private_key: &PrivateKey,
+    chainid: u64,
+) -> Result<Box<EthContract>> {
+    let (signer, nonce_tracker) = new_signer(&rpcserver, private_key, chainid)?;
+    let signing_contract: SigningingDynamicContractHttp =
+        DynamicContract::from_human_readable(&contract_address, &fragments, signer)?;
+
+    Ok(Box::new(EthContract {
+        dynamic_contract: None,
+        signing_contract: Some((signing_contract, chainid)),
+        nonce_tracker: Some(nonce_tracker),
     }))
 }
 
+/// the signer middleware of the private key and the nonce tracker of its address
+fn new_signer(
+    rpcserver: &str,
+    private_key: &PrivateKey,
+    chainid: u64,
+) -> Result<(SignerHttpWallet, NonceTracker)> {
+    let client: Provider<Http> = Provider::<Http>::try_from(rpcserver)?;
+    let binding = private_key.to_bytes();
+    let bytes = binding.as_slice(); // 32 bytes
+    let signing_key = SigningKey::from_bytes(bytes.into())?;
+    let wallet: Wallet<SigningKey> = signing_key.into();
+    let wallet = wallet.with_chain_id(chainid);
+    let nonce_tracker = NonceTracker::new(client.clone(), chainid, wallet.address());
+    Ok((SignerMiddleware::new(client, wallet), nonce_tracker))
+}
+
 fn read_json(filepath: String, keyname: String) -> Result<String> {
     let src = std::fs::read_to_string(filepath)?;
     let json: serde_json::Value = serde_json::from_str(&src)?;
@@ -197,6 +779,9 @@ impl EthContract {
             ethcontract.0.function_call(function_name, params)?;
         let chainid = ethcontract.1;
         ethcontractcall.contract_call.tx.set_chain_id(chainid);
+        if let Some(nonce_tracker) = &self.nonce_tracker {
+            ethcontractcall = ethcontractcall.nonce_tracker(nonce_tracker.clone());
+        }
 
         let ethersreceipt = ethcontractcall.send().await?;
         let defireceipt: defi_wallet_core_common::TransactionReceipt = ethersreceipt.into();
@@ -4,9 +4,11 @@ use defi_wallet_core_common::node::ethereum::provider::set_ethers_httpagent;
 use defi_wallet_core_common::{
     broadcast_tx_sync_blocking, build_signed_msg_tx, build_signed_single_msg_tx,
     get_account_balance_blocking, get_account_details_blocking, get_single_msg_sign_payload,
-    CosmosSDKMsg, CosmosSDKTxInfo, EthError, EthNetwork, EthTxInfo, HDWallet, Height, LoginInfo,
-    Network, PublicKeyBytesWrapper, RawRpcAccountResponse, SecretKey, SingleCoin,
-    TransactionReceipt, TxBroadcastResult, WalletCoin, COMPRESSED_SECP256K1_PUBKEY_SIZE,
+    CosmosPaymentRequest, CosmosPaymentUriFormat, CosmosSDKMsg, CosmosSDKTxInfo, EthError,
+    EthFeeEstimate, EthFeeTier, EthNetwork, EthTxInfo, HDWallet, Height, LoginInfo,
+    LoginInfoBuilder, LoginVerificationOpts, Network, PublicKeyBytesWrapper, RawRpcAccountResponse,
+    SecretKey, SingleCoin, TransactionReceipt, TxBroadcastResult, WalletCoin,
+    COMPRESSED_SECP256K1_PUBKEY_SIZE,
 };
 
 use ethers::types::Signature;
@@ -340,6 +342,29 @@ pub mod ffi {
         pub data: Vec<u8>,
     }
 
+    /// the suggested fees of a speed tier
+    pub struct EthFeeTierRaw {
+        /// the max fee per gas in wei (the gas price on legacy chains)
+        pub max_fee_per_gas: String,
+        /// the max priority fee per gas in wei (the gas price on legacy chains)
+        pub max_priority_fee_per_gas: String,
+        /// the expected cost of the gas limit in ether / native token decimals
+        pub estimated_cost: String,
+        /// the cost of the gas limit at the max fee per gas in ether / native token decimals
+        pub max_cost: String,
+    }
+
+    /// the fee estimation with the slow, normal and fast tiers
+    pub struct EthFeeEstimateRaw {
+        /// if the fees are from `eth_gasPrice` (the legacy transaction request)
+        pub legacy: bool,
+        /// the base fee per gas of the next block in wei ("0" on legacy chains)
+        pub base_fee_per_gas: String,
+        pub slow: EthFeeTierRaw,
+        pub normal: EthFeeTierRaw,
+        pub fast: EthFeeTierRaw,
+    }
+
     pub struct CosmosSDKTxInfoRaw {
         /// global account number of the sender
         pub account_number: u64,
@@ -362,6 +387,19 @@ pub mod ffi {
         /// the coin type to use
         pub coin_type: u32,
     }
+    /// the payment request of a Cosmos SDK chain (empty strings are unset fields)
+    pub struct CosmosPaymentRequestRaw {
+        /// the bech32 address of the recipient
+        pub recipient_address: String,
+        /// the amount (a decimal integer string) in the base denomination
+        pub amount: String,
+        /// the base denomination
+        pub denom: String,
+        /// the memo of the transaction
+        pub memo: String,
+        /// the Tendermint chain id
+        pub chain_id: String,
+    }
     pub struct CosmosAccountInfoRaw {
         pub account_number: u64,
         pub sequence_number: u64,
@@ -453,6 +491,25 @@ pub mod ffi {
             amount: u64,
             denom: String,
         ) -> Result<Vec<u8>>;
+
+        /// parse the Cosmos SDK payment request URI (e.g. scanned from a QR code)
+        fn parse_cosmos_payment_uri(uri: &str) -> Result<CosmosPaymentRequestRaw>;
+
+        /// the URI of the payment request, in the Keplr-compatible style
+        /// (the denomination in the amount) if `keplr` is true
+        fn build_cosmos_payment_uri(
+            request: CosmosPaymentRequestRaw,
+            keplr: bool,
+        ) -> Result<String>;
+
+        /// creates the signed transaction of `MsgSend` for the payment request,
+        /// checking it's for the chain of `tx_info` (its fee denomination is used
+        /// if the request has no denomination, and its memo if the request has none)
+        fn get_cosmos_payment_signed_tx(
+            tx_info: CosmosSDKTxInfoRaw,
+            private_key: &PrivateKey,
+            request: CosmosPaymentRequestRaw,
+        ) -> Result<Vec<u8>>;
         type Wallet;
         /// generates the HD wallet with a BIP39 backup phrase (English words) and password
         fn new_wallet(password: String, word_count: MnemonicWordCount) -> Result<Box<Wallet>>;
@@ -574,6 +631,40 @@ pub mod ffi {
         /// (e.g. verify chain-id, nonce, uri + possibly fetch additional data associated
         /// with the given Ethereum address, such as ERC-20/ERC-721/ERC-1155 asset ownership)
         fn verify_logininfo(self: &CppLoginInfo, signature: &[u8]) -> Result<()>;
+        /// Build Login Info (EIP-4361 message); empty strings are unset fields
+        /// (a random nonce is generated and the issued-at time is the current time if unset)
+        /// and the times are in RFC 3339 (e.g. "2021-09-30T16:25:24Z")
+        #[allow(clippy::too_many_arguments)]
+        fn build_logininfo(
+            domain: &str,
+            address: &str,
+            uri: &str,
+            chain_id: u64,
+            statement: &str,
+            nonce: &str,
+            issued_at: &str,
+            expiration_time: &str,
+            not_before: &str,
+            resources: Vec<String>,
+        ) -> Result<Box<CppLoginInfo>>;
+        /// Generate a random nonce for Login Info
+        fn generate_login_nonce() -> String;
+        /// Get the plaintext message of Login Info (to be shown to the user and signed)
+        fn get_logininfo_message(self: &CppLoginInfo) -> String;
+        /// Verify Login Info with the options (empty strings are unset):
+        /// the expected domain and nonce, the time (RFC 3339) it should be valid at
+        /// (by default, the current time), and the node Web3 connection URL to verify
+        /// the signature of a contract wallet as per EIP-1271.
+        /// The error describes the failure, e.g. a domain / nonce / signer mismatch
+        /// or an expired message
+        fn verify_logininfo_with_opts(
+            self: &CppLoginInfo,
+            signature: &[u8],
+            domain: &str,
+            nonce: &str,
+            timestamp: &str,
+            web3api_url: &str,
+        ) -> Result<()>;
 
         /// create cronos tx info to sign
         pub fn new_eth_tx_info() -> EthTxInfoRaw;
@@ -596,6 +687,22 @@ pub mod ffi {
         /// given the account address, it returns the amount of native token it owns
         pub fn get_eth_balance(address: &str, api_url: &str) -> Result<U256>;
 
+        /// estimate the slow, normal and fast fees (EIP-1559 max fees,
+        /// or the gas price on legacy chains) for a transaction with the given gas limit
+        pub fn estimate_eth_fees(
+            api_url: &str,
+            network: &str,
+            gas_limit: &str,
+        ) -> Result<EthFeeEstimateRaw>;
+        #[cxx_name = "estimate_eth_fees"]
+        /// estimate the slow, normal and fast fees in custom network
+        pub fn estimate_custom_eth_fees(
+            api_url: &str,
+            chain_id: u64,
+            legacy: bool,
+            gas_limit: &str,
+        ) -> Result<EthFeeEstimateRaw>;
+
         /// Returns the corresponding account's nonce / number of transactions
         /// sent from it.
         pub fn get_eth_nonce(address: &str, api_url: &str) -> Result<String>;
@@ -669,6 +776,29 @@ impl From<TxBroadcastResult> for ffi::CosmosTransactionReceiptRaw {
     }
 }
 
+impl From<EthFeeTier> for ffi::EthFeeTierRaw {
+    fn from(src: EthFeeTier) -> Self {
+        ffi::EthFeeTierRaw {
+            max_fee_per_gas: src.max_fee_per_gas,
+            max_priority_fee_per_gas: src.max_priority_fee_per_gas,
+            estimated_cost: src.estimated_cost,
+            max_cost: src.max_cost,
+        }
+    }
+}
+
+impl From<EthFeeEstimate> for ffi::EthFeeEstimateRaw {
+    fn from(src: EthFeeEstimate) -> Self {
+        ffi::EthFeeEstimateRaw {
+            legacy: src.legacy,
+            base_fee_per_gas: src.base_fee_per_gas,
+            slow: src.slow.into(),
+            normal: src.normal.into(),
+            fast: src.fast.into(),
+        }
+    }
+}
+
 use ffi::CoinType;
 impl From<CoinType> for WalletCoin {
     fn from(coin: CoinType) -> Self {
@@ -967,6 +1097,71 @@ pub fn get_single_bank_send_signed_tx(
     Ok(ret)
 }
 
+impl From<CosmosPaymentRequest> for ffi::CosmosPaymentRequestRaw {
+    fn from(request: CosmosPaymentRequest) -> Self {
+        Self {
+            recipient_address: request.recipient_address,
+            amount: request.amount.unwrap_or_default(),
+            denom: request.denom.unwrap_or_default(),
+            memo: request.memo.unwrap_or_default(),
+            chain_id: request.chain_id.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<ffi::CosmosPaymentRequestRaw> for CosmosPaymentRequest {
+    fn from(request: ffi::CosmosPaymentRequestRaw) -> Self {
+        Self {
+            recipient_address: request.recipient_address,
+            amount: non_empty(&request.amount),
+            denom: non_empty(&request.denom),
+            memo: non_empty(&request.memo),
+            chain_id: non_empty(&request.chain_id),
+        }
+    }
+}
+
+/// parse the Cosmos SDK payment request URI (e.g. scanned from a QR code)
+fn parse_cosmos_payment_uri(uri: &str) -> Result<ffi::CosmosPaymentRequestRaw> {
+    Ok(defi_wallet_core_common::parse_cosmos_payment_uri(uri)?.into())
+}
+
+/// the URI of the payment request, in the Keplr-compatible style if `keplr` is true
+fn build_cosmos_payment_uri(request: ffi::CosmosPaymentRequestRaw, keplr: bool) -> Result<String> {
+    let format = if keplr {
+        CosmosPaymentUriFormat::Keplr
+    } else {
+        CosmosPaymentUriFormat::Standard
+    };
+    Ok(defi_wallet_core_common::build_cosmos_payment_uri(
+        request.into(),
+        format,
+    )?)
+}
+
+/// creates the signed transaction of `MsgSend` for the payment request on the chain of `tx_info`
+fn get_cosmos_payment_signed_tx(
+    tx_info: ffi::CosmosSDKTxInfoRaw,
+    private_key: &PrivateKey,
+    request: ffi::CosmosPaymentRequestRaw,
+) -> Result<Vec<u8>> {
+    let mut request = CosmosPaymentRequest::from(request);
+    request.check_chain(&tx_info.chain_id, Some(&tx_info.bech32hrp))?;
+    if request.denom.is_none() {
+        request.denom = Some(tx_info.fee_denom.clone());
+    }
+    let msg = request.to_bank_send()?;
+    let mut tx_info = CosmosSDKTxInfo::from(tx_info);
+    if request.memo.is_some() {
+        tx_info.memo_note = request.memo;
+    }
+    Ok(build_signed_single_msg_tx(
+        tx_info,
+        msg,
+        private_key.key.clone(),
+    )?)
+}
+
 /// creates the signed transaction
 /// for `MsgDelegate` from the Cosmos SDK staking module
 pub fn get_staking_delegate_signed_tx(
@@ -1190,6 +1385,57 @@ fn new_logininfo(msg: String) -> Result<Box<CppLoginInfo>> {
     Ok(Box::new(CppLoginInfo { logininfo }))
 }
 
+/// None if the string is empty
+fn non_empty(value: &str) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_owned())
+    }
+}
+
+/// build Login Info (EIP-4361 message); empty strings are unset fields
+#[allow(clippy::too_many_arguments)]
+fn build_logininfo(
+    domain: &str,
+    address: &str,
+    uri: &str,
+    chain_id: u64,
+    statement: &str,
+    nonce: &str,
+    issued_at: &str,
+    expiration_time: &str,
+    not_before: &str,
+    resources: Vec<String>,
+) -> Result<Box<CppLoginInfo>> {
+    let mut builder = LoginInfoBuilder::new(domain, address, uri, chain_id);
+    if let Some(statement) = non_empty(statement) {
+        builder = builder.statement(&statement);
+    }
+    if let Some(nonce) = non_empty(nonce) {
+        builder = builder.nonce(&nonce);
+    }
+    if let Some(issued_at) = non_empty(issued_at) {
+        builder = builder.issued_at(&issued_at);
+    }
+    if let Some(expiration_time) = non_empty(expiration_time) {
+        builder = builder.expiration_time(&expiration_time);
+    }
+    if let Some(not_before) = non_empty(not_before) {
+        builder = builder.not_before(&not_before);
+    }
+    for resource in resources {
+        builder = builder.resource(&resource);
+    }
+    let logininfo = builder.build()?;
+    Ok(Box::new(CppLoginInfo { logininfo }))
+}
+
+/// generate a random nonce for Login Info
+fn generate_login_nonce() -> String {
+    defi_wallet_core_common::generate_login_nonce()
+}
+
 impl CppLoginInfo {
     /// Sign Login Info
     /// constructs the plaintext message and signs it according to EIP-191
@@ -1210,9 +1456,33 @@ impl CppLoginInfo {
     pub fn verify_logininfo(&self, signature: &[u8]) -> anyhow::Result<()> {
         // TODO Reuse runtime on blocking function
         let rt = tokio::runtime::Runtime::new()?;
-        // FIXME: domain, nonce, timestamp
         Ok(rt.block_on(self.logininfo.verify(signature))?)
     }
+
+    /// Get the plaintext message of Login Info
+    pub fn get_logininfo_message(&self) -> String {
+        self.logininfo.to_string()
+    }
+
+    /// Verify Login Info with the expected domain and nonce, the time it should be valid at
+    /// and the node Web3 connection URL for EIP-1271 (empty strings are unset)
+    pub fn verify_logininfo_with_opts(
+        &self,
+        signature: &[u8],
+        domain: &str,
+        nonce: &str,
+        timestamp: &str,
+        web3api_url: &str,
+    ) -> anyhow::Result<()> {
+        let opts = LoginVerificationOpts {
+            domain: non_empty(domain),
+            nonce: non_empty(nonce),
+            timestamp: non_empty(timestamp),
+            web3api_url: non_empty(web3api_url),
+        };
+        let rt = tokio::runtime::Runtime::new()?;
+        Ok(rt.block_on(self.logininfo.verify_with_opts(signature, &opts))?)
+    }
 }
 fn convert_amount(
     amount: &str,
@@ -1306,6 +1576,37 @@ pub fn get_eth_balance(address: &str, api_url: &str) -> Result<ffi::U256> {
     Ok(res.into())
 }
 
+/// estimate the slow, normal and fast fees for a transaction with the given gas limit
+pub fn estimate_eth_fees(
+    api_url: &str,
+    network: &str,
+    gas_limit: &str,
+) -> Result<ffi::EthFeeEstimateRaw> {
+    let estimate = defi_wallet_core_common::estimate_eth_fees_blocking(
+        api_url,
+        EthNetwork::Known {
+            name: network.into(),
+        },
+        gas_limit,
+    )?;
+    Ok(estimate.into())
+}
+
+/// estimate the slow, normal and fast fees in custom network
+pub fn estimate_custom_eth_fees(
+    api_url: &str,
+    chain_id: u64,
+    legacy: bool,
+    gas_limit: &str,
+) -> Result<ffi::EthFeeEstimateRaw> {
+    let estimate = defi_wallet_core_common::estimate_eth_fees_blocking(
+        api_url,
+        EthNetwork::Custom { chain_id, legacy },
+        gas_limit,
+    )?;
+    Ok(estimate.into())
+}
+
 /// Returns the corresponding account's nonce / number of transactions
 /// sent from it.
 pub fn get_eth_nonce(address: &str, api_url: &str) -> Result<String> {
@@ -0,0 +1,184 @@
+use crate::{format_to_js_error, CosmosClientConfig, CosmosSDKTxInfoRaw, PrivateKey, Wallet};
+use defi_wallet_core_common::{ChainDescriptor, SingleCoin};
+use wasm_bindgen::prelude::*;
+
+/// the gas price tier used for the fee calculation
+/// (wasm only supports C-style enums)
+#[wasm_bindgen]
+pub enum GasPriceTier {
+    /// the lowest price usually accepted by validators
+    Low,
+    /// the recommended price
+    Average,
+    /// the price for a faster inclusion
+    High,
+}
+
+impl From<GasPriceTier> for defi_wallet_core_common::GasPriceTier {
+    fn from(tier: GasPriceTier) -> Self {
+        match tier {
+            GasPriceTier::Low => Self::Low,
+            GasPriceTier::Average => Self::Average,
+            GasPriceTier::High => Self::High,
+        }
+    }
+}
+
+/// Chain registry wrapper for Wasm
+/// (with the bundled descriptors of the supported networks)
+#[wasm_bindgen]
+pub struct ChainRegistry {
+    registry: defi_wallet_core_common::ChainRegistry,
+}
+
+#[wasm_bindgen]
+impl ChainRegistry {
+    /// Create the registry with the bundled descriptors.
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            registry: defi_wallet_core_common::ChainRegistry::with_defaults(),
+        }
+    }
+
+    /// Add (or replace) the descriptors from JSON (a single descriptor or a list).
+    #[wasm_bindgen]
+    pub fn load_json(&mut self, json: &str) -> Result<(), JsValue> {
+        self.registry.load_json(json).map_err(format_to_js_error)
+    }
+
+    /// Add (or replace) the descriptors from TOML.
+    #[wasm_bindgen]
+    pub fn load_toml(&mut self, toml: &str) -> Result<(), JsValue> {
+        self.registry.load_toml(toml).map_err(format_to_js_error)
+    }
+
+    /// Return the descriptor of the chain (as in `chain.json` of cosmos/chain-registry).
+    #[wasm_bindgen]
+    pub fn get_chain(&self, chain_name: &str) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(self.chain(chain_name)?).map_err(format_to_js_error)
+    }
+
+    /// Return the names of the registered chains (sorted).
+    #[wasm_bindgen]
+    pub fn chain_names(&self) -> Result<JsValue, JsValue> {
+        let names: Vec<&str> = self
+            .registry
+            .chains()
+            .map(|chain| chain.chain_name.as_str())
+            .collect();
+        serde_wasm_bindgen::to_value(&names).map_err(format_to_js_error)
+    }
+
+    /// Return the client configuration of the default endpoints of the chain.
+    #[wasm_bindgen]
+    pub fn get_cosmos_client_config(
+        &self,
+        chain_name: &str,
+    ) -> Result<CosmosClientConfig, JsValue> {
+        let chain = self.chain(chain_name)?;
+        Ok(CosmosClientConfig::new(
+            chain.rest_url().unwrap_or_default(),
+            chain.grpc_web_url().unwrap_or_default(),
+            chain.tendermint_rpc_url().map_err(format_to_js_error)?,
+        ))
+    }
+
+    /// Return the transaction info with the fee computed from the gas price tier.
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_tx_info(
+        &self,
+        chain_name: &str,
+        account_number: u64,
+        sequence_number: u64,
+        gas_limit: u64,
+        tier: GasPriceTier,
+        timeout_height: u32,
+        memo_note: Option<String>,
+    ) -> Result<CosmosSDKTxInfoRaw, JsValue> {
+        let chain = self.chain(chain_name)?;
+        let (fee_amount, fee_denom) = match chain
+            .fee_amount(gas_limit, tier.into())
+            .map_err(format_to_js_error)?
+        {
+            SingleCoin::Other { amount, denom } => {
+                (amount.parse::<u64>().map_err(format_to_js_error)?, denom)
+            }
+            coin => return Err(format_to_js_error(format!("unexpected fee: {coin:?}"))),
+        };
+        let network = chain.to_network().map_err(format_to_js_error)?;
+        Ok(CosmosSDKTxInfoRaw::new(
+            account_number,
+            sequence_number,
+            gas_limit,
+            fee_amount,
+            fee_denom,
+            timeout_height,
+            memo_note,
+            chain.chain_id.clone(),
+            network.get_bech32_hrp().to_owned(),
+            network.get_coin_type(),
+        ))
+    }
+
+    /// Return the address of the wallet on the chain for a given index.
+    #[wasm_bindgen]
+    pub fn get_address(
+        &self,
+        wallet: &Wallet,
+        chain_name: &str,
+        index: u32,
+    ) -> Result<String, JsValue> {
+        let coin = self
+            .chain(chain_name)?
+            .to_wallet_coin()
+            .map_err(format_to_js_error)?;
+        Ok(wallet.wallet.get_address(coin, index)?)
+    }
+
+    /// Return the signing key of the wallet on the chain for a given index.
+    #[wasm_bindgen]
+    pub fn get_key(
+        &self,
+        wallet: &Wallet,
+        chain_name: &str,
+        index: u32,
+    ) -> Result<PrivateKey, JsValue> {
+        let coin = self
+            .chain(chain_name)?
+            .to_wallet_coin()
+            .map_err(format_to_js_error)?;
+        let key = wallet.wallet.get_key_from_index(coin, index)?;
+        Ok(PrivateKey { key })
+    }
+
+    /// Return the default Web3 JSON-RPC endpoint of the chain.
+    #[wasm_bindgen]
+    pub fn get_web3api_url(&self, chain_name: &str) -> Result<String, JsValue> {
+        self.chain(chain_name)?
+            .web3api_url()
+            .map_err(format_to_js_error)
+    }
+
+    /// Return the transaction page on the default explorer of the chain.
+    #[wasm_bindgen]
+    pub fn explorer_tx_url(&self, chain_name: &str, tx_hash: &str) -> Option<String> {
+        self.registry.get(chain_name)?.explorer_tx_url(tx_hash)
+    }
+
+    /// Return the account page on the default explorer of the chain.
+    #[wasm_bindgen]
+    pub fn explorer_account_url(&self, chain_name: &str, address: &str) -> Option<String> {
+        self.registry.get(chain_name)?.explorer_account_url(address)
+    }
+}
+
+impl ChainRegistry {
+    fn chain(&self, chain_name: &str) -> Result<&ChainDescriptor, JsValue> {
+        self.registry
+            .get(chain_name)
+            .ok_or_else(|| format_to_js_error(format!("unknown chain: {chain_name}")))
+    }
+}
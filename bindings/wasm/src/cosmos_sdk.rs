@@ -1,8 +1,9 @@
 use crate::{format_to_js_error, PrivateKey};
 use cosmos_sdk_proto::cosmos::base::query::v1beta1::PageRequest;
 use defi_wallet_core_common::{
-    broadcast_tx_sync, build_signed_msg_tx, get_account_balance, get_account_details, node,
-    CosmosSDKMsg, CosmosSDKTxInfo, Height, Network, SingleCoin,
+    broadcast_tx_sync, build_signed_msg_tx, get_counterparty_state, node, query_account_balance,
+    query_account_details, CosmosPaymentRequest, CosmosPaymentUriFormat, CosmosSDKMsg,
+    CosmosSDKTxInfo, Height, Network, QueryTransport, RelativeIbcTimeout, SingleCoin,
 };
 use js_sys::Promise;
 use serde::{Deserialize, Serialize};
@@ -27,20 +28,22 @@ impl CosmosClient {
         Self { config }
     }
 
+    /// Retrieve the balance of a given denomination
+    /// (over gRPC-web, or the REST API if the gRPC-web URL isn't configured).
     pub fn query_account_balance(&self, address: String, denom: String) -> Promise {
-        let grpc_web_url = self.config.grpc_web_url.to_string();
+        let transport = self.config.transport();
         future_to_promise(async move {
-            let balance = get_account_balance(&grpc_web_url, &address, &denom).await?;
+            let balance = query_account_balance(&transport?, &address, &denom).await?;
             serde_wasm_bindgen::to_value(&balance).map_err(format_to_js_error)
         })
     }
 
-    /// Retrieve the account details (e.g. sequence and account number) for a given address.
-    /// TODO: switch to grpc-web
+    /// Retrieve the account details (e.g. sequence and account number) for a given address
+    /// (over gRPC-web, or the REST API if the gRPC-web URL isn't configured).
     pub fn query_account_details(&self, address: String) -> Promise {
-        let api_url = self.config.api_url.to_owned();
+        let transport = self.config.transport();
         future_to_promise(async move {
-            let account_details = get_account_details(&api_url, &address).await?;
+            let account_details = query_account_details(&transport?, &address).await?;
             serde_wasm_bindgen::to_value(&account_details).map_err(format_to_js_error)
         })
     }
@@ -62,13 +65,62 @@ impl CosmosClient {
             serde_wasm_bindgen::to_value(&resp).map_err(format_to_js_error)
         })
     }
+
+    /// Compute the absolute timeout of an IBC transfer over the channel
+    /// from the counterparty chain's latest state
+    /// (the channel's light client advanced to the latest block of the counterparty node);
+    /// an offset is disabled when set to 0, but not both.
+    pub fn get_ibc_timeout(
+        &self,
+        port_id: String,
+        channel_id: String,
+        counterparty_tendermint_rpc_url: String,
+        height_offset: u64,
+        timestamp_offset_secs: u64,
+    ) -> Promise {
+        let grpc_web_url = self.config.grpc_web_url.to_owned();
+        future_to_promise(async move {
+            let state = get_counterparty_state(
+                &grpc_web_url,
+                &port_id,
+                &channel_id,
+                &counterparty_tendermint_rpc_url,
+            )
+            .await?;
+            let local_timestamp = (js_sys::Date::now() as u64).saturating_mul(1_000_000);
+            let timeout = RelativeIbcTimeout {
+                height_offset,
+                timestamp_offset_secs,
+            }
+            .to_absolute(&state, local_timestamp)
+            .map_err(format_to_js_error)?;
+            Ok(IbcTimeoutRaw {
+                revision_number: timeout.timeout_height.revision_number,
+                revision_height: timeout.timeout_height.revision_height,
+                timeout_timestamp: timeout.timeout_timestamp,
+            }
+            .into())
+        })
+    }
+}
+
+/// the absolute timeout of an IBC transfer
+/// (raw duplicate needed for Wasm, as the timestamp doesn't fit in a JS number)
+#[wasm_bindgen]
+pub struct IbcTimeoutRaw {
+    /// the revision number of the timeout height
+    pub revision_number: u64,
+    /// the timeout height on the counterparty chain (disabled when 0)
+    pub revision_height: u64,
+    /// the timeout timestamp (in nanoseconds) on the counterparty chain (disabled when 0)
+    pub timeout_timestamp: u64,
 }
 
 /// Cosmos client configuration
 #[derive(Serialize, Deserialize)]
 #[wasm_bindgen]
 pub struct CosmosClientConfig {
-    // TODO: Delete after replacing all with grpc-web.
+    // the fallback when gRPC-web is unavailable
     api_url: String,
     grpc_web_url: String,
     tendermint_rpc_url: String,
@@ -87,6 +139,12 @@ impl CosmosClientConfig {
     }
 }
 
+impl CosmosClientConfig {
+    fn transport(&self) -> Result<QueryTransport, node::RestError> {
+        QueryTransport::select(&self.grpc_web_url, &self.api_url)
+    }
+}
+
 /// Cosmos message wrapper
 #[wasm_bindgen]
 pub struct CosmosMsg {
@@ -109,6 +167,17 @@ impl CosmosMsg {
         }
     }
 
+    /// construct BankSend message of the payment request object
+    /// `{ recipient_address, amount, denom, memo, chain_id }` (e.g. of `parse_cosmos_payment_uri`);
+    /// the amount (a decimal integer string) and the denomination must be set
+    #[wasm_bindgen]
+    pub fn build_cosmos_payment_msg(request: JsValue) -> Result<CosmosMsg, JsValue> {
+        let request: CosmosPaymentRequest =
+            serde_wasm_bindgen::from_value(request).map_err(format_to_js_error)?;
+        let msg = request.to_bank_send().map_err(format_to_js_error)?;
+        Ok(Self { msg })
+    }
+
     /// construct NftIssueDenom message
     #[wasm_bindgen]
     pub fn build_nft_issue_denom_msg(id: String, name: String, schema: String) -> Self {
@@ -448,3 +517,28 @@ impl GrpcWebClient {
         serde_wasm_bindgen::to_value(&nft).map_err(format_to_js_error)
     }
 }
+
+/// parse the Cosmos SDK payment request URI (e.g. scanned from a QR code) into the object
+/// `{ recipient_address, amount, denom, memo, chain_id }` (the missing fields are null)
+#[wasm_bindgen]
+pub fn parse_cosmos_payment_uri(uri: String) -> Result<JsValue, JsValue> {
+    let request =
+        defi_wallet_core_common::parse_cosmos_payment_uri(&uri).map_err(format_to_js_error)?;
+    request
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(format_to_js_error)
+}
+
+/// the URI of the payment request object `{ recipient_address, amount, denom, memo, chain_id }`,
+/// in the Keplr-compatible style (the denomination in the amount) if `keplr` is true
+#[wasm_bindgen]
+pub fn build_cosmos_payment_uri(request: JsValue, keplr: bool) -> Result<String, JsValue> {
+    let request: CosmosPaymentRequest =
+        serde_wasm_bindgen::from_value(request).map_err(format_to_js_error)?;
+    let format = if keplr {
+        CosmosPaymentUriFormat::Keplr
+    } else {
+        CosmosPaymentUriFormat::Standard
+    };
+    defi_wallet_core_common::build_cosmos_payment_uri(request, format).map_err(format_to_js_error)
+}
@@ -11,9 +11,11 @@ use defi_wallet_core_common::{
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+mod eip1193;
 mod signer;
 
 use js_sys::{BigInt, Object};
+pub use eip1193::*;
 pub use signer::*;
 
 /// Ethereum contract
@@ -32,6 +34,22 @@ impl EthContract {
         Ok(Self { abi_contract })
     }
 
+    /// Create an instance via an array of human-readable ABI fragments, e.g.
+    /// `function transfer(address to, uint256 amount) returns (bool)`.
+    #[wasm_bindgen]
+    pub fn from_human_readable(fragments: Vec<JsValue>) -> Result<EthContract, JsValue> {
+        let fragments = fragments
+            .into_iter()
+            .map(|fragment| {
+                fragment
+                    .as_string()
+                    .ok_or_else(|| JsValue::from_str("ABI fragment must be a string"))
+            })
+            .collect::<Result<Vec<String>, _>>()?;
+        let abi_contract = EthAbiContract::from_human_readable(fragments)?;
+        Ok(Self { abi_contract })
+    }
+
     /// Encode input data of specified function and arguments. The encoded data
     /// should be set to field data of EthTxInfo when invoking function
     /// build_signed_eth_tx.
@@ -44,6 +62,80 @@ impl EthContract {
             .collect::<Result<Vec<EthAbiToken>, _>>()?;
         Ok(self.abi_contract.encode(function_name, tokens)?)
     }
+
+    /// Decode the output data (returned by `eth_call`) of the specified function
+    /// into an array of `EthAbiTokenBind` values.
+    #[wasm_bindgen]
+    pub fn decode_output(&self, function_name: &str, data: &[u8]) -> Result<JsValue, JsValue> {
+        let tokens = self.abi_contract.decode_output(function_name, data)?;
+        serde_wasm_bindgen::to_value(&tokens).map_err(format_to_js_error)
+    }
+
+    /// Decode the event log given its topics (an array of 32-byte `Uint8Array`) and data.
+    #[wasm_bindgen]
+    pub fn decode_event_log(&self, topics: Vec<JsValue>, data: &[u8]) -> Result<JsValue, JsValue> {
+        let topics = topics
+            .iter()
+            .map(|topic| js_sys::Uint8Array::new(topic).to_vec())
+            .collect();
+        let event = self.abi_contract.decode_event_log(topics, data)?;
+        serde_wasm_bindgen::to_value(&event).map_err(format_to_js_error)
+    }
+
+    /// Decode the revert data into a readable message (`Error(string)`, `Panic(uint256)`
+    /// or one of the custom errors declared in the ABI).
+    #[wasm_bindgen]
+    pub fn decode_error(&self, data: &[u8]) -> Option<String> {
+        self.abi_contract.decode_error(data)
+    }
+}
+
+/// Decoder of EVM transactions and their calldata
+/// against the bundled token ABIs and the user-supplied ones
+#[wasm_bindgen]
+pub struct EthTxDecoder {
+    decoder: common::EthTxDecoder,
+}
+
+#[wasm_bindgen]
+impl EthTxDecoder {
+    /// Create an instance with an array of ABI contract strings
+    /// (they take precedence over the bundled ERC-20/721/1155/4907 ones).
+    #[wasm_bindgen(constructor)]
+    pub fn new(abi_contracts: Vec<JsValue>) -> Result<EthTxDecoder, JsValue> {
+        let abi_contracts = abi_contracts
+            .into_iter()
+            .map(|abi_contract| {
+                abi_contract
+                    .as_string()
+                    .ok_or_else(|| JsValue::from_str("ABI contract must be a string"))
+            })
+            .collect::<Result<Vec<String>, _>>()?;
+        let decoder = common::EthTxDecoder::new(abi_contracts)?;
+        Ok(Self { decoder })
+    }
+
+    /// Decode the calldata into the function name and named parameters;
+    /// it returns undefined if the data doesn't match any of the known ABIs
+    #[wasm_bindgen]
+    pub fn decode_calldata(&self, data: &[u8]) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.decoder.decode_calldata(data))
+            .map_err(format_to_js_error)
+    }
+
+    /// Decode the raw signed or unsigned transaction bytes
+    #[wasm_bindgen]
+    pub fn decode_raw_tx(&self, raw_tx: &[u8]) -> Result<JsValue, JsValue> {
+        let tx = self.decoder.decode_raw_tx(raw_tx)?;
+        serde_wasm_bindgen::to_value(&tx).map_err(format_to_js_error)
+    }
+
+    /// Decode the transaction request in JSON (as used by `eth_sign_transaction`)
+    #[wasm_bindgen]
+    pub fn decode_tx_request(&self, json_str: &str) -> Result<JsValue, JsValue> {
+        let tx = self.decoder.decode_tx_request(json_str)?;
+        serde_wasm_bindgen::to_value(&tx).map_err(format_to_js_error)
+    }
 }
 
 /// Contract function arguments
@@ -306,6 +398,280 @@ pub async fn query_account_eth_balance(
     )?)
 }
 
+/// estimate the slow, normal and fast fees (EIP-1559 max fees, or the gas price on legacy chains)
+/// for a transaction with the given gas limit
+#[wasm_bindgen]
+pub async fn estimate_eth_fees(
+    web3_api_url: String,
+    chain_id: u64,
+    legacy: bool,
+    gas_limit: String,
+) -> Result<JsValue, JsValue> {
+    let estimate = common::estimate_eth_fees(
+        &web3_api_url,
+        EthNetwork::Custom { chain_id, legacy },
+        &gas_limit,
+    )
+    .await?;
+    serde_wasm_bindgen::to_value(&estimate).map_err(format_to_js_error)
+}
+
+/// speed up the pending transaction: re-sign it with the same nonce
+/// and the fees bumped by at least 10%; it returns the new signed raw transaction
+#[wasm_bindgen]
+pub async fn speed_up_eth_tx(
+    web3_api_url: String,
+    tx_hash: String,
+    private_key: PrivateKey,
+) -> Result<Vec<u8>, JsValue> {
+    Ok(common::speed_up_eth_tx_by_hash(&tx_hash, &web3_api_url, private_key.key).await?)
+}
+
+/// cancel the pending transaction: sign a zero-value self-transfer with the same nonce
+/// and the fees bumped by at least 10%; it returns the new signed raw transaction
+#[wasm_bindgen]
+pub async fn cancel_eth_tx(
+    web3_api_url: String,
+    tx_hash: String,
+    private_key: PrivateKey,
+) -> Result<Vec<u8>, JsValue> {
+    Ok(common::cancel_eth_tx_by_hash(&tx_hash, &web3_api_url, private_key.key).await?)
+}
+
+/// get the ERC-20, ERC-721 and ERC-1155 transfers from or to the address
+/// (optionally only of the given contract) from the checkpoint block
+/// to the given block (the latest one if not set);
+/// the result's `next_block` is the checkpoint to resume from
+#[wasm_bindgen]
+pub async fn get_eth_token_transfers(
+    web3_api_url: String,
+    address: String,
+    contract_address: Option<String>,
+    from_block: u64,
+    to_block: Option<u64>,
+) -> Result<JsValue, JsValue> {
+    let history = common::get_eth_token_transfers(
+        &web3_api_url,
+        &address,
+        contract_address,
+        from_block,
+        to_block,
+    )
+    .await?;
+    serde_wasm_bindgen::to_value(&history).map_err(format_to_js_error)
+}
+
+/// set the address of the ENS-compatible name registry of the chain
+/// (e.g. the Cronos ID registry, or a locally deployed one)
+#[wasm_bindgen]
+pub fn set_eth_name_registry(chain_id: u64, registry_address: String) -> Result<(), JsValue> {
+    Ok(common::set_eth_name_registry(chain_id, &registry_address)?)
+}
+
+/// check if the contract wallet accepts the signature of the hash value via its
+/// `isValidSignature` (EIP-1271)
+#[wasm_bindgen]
+pub async fn is_valid_eip1271_signature(
+    web3_api_url: String,
+    contract_address: String,
+    hash: String,
+    signature: String,
+) -> Result<bool, JsValue> {
+    Ok(
+        common::is_valid_eip1271_signature(&contract_address, &hash, &signature, &web3_api_url)
+            .await?,
+    )
+}
+
+/// check if the hash value was signed (`eth_sign`) by the account,
+/// which is either an externally owned account or a contract wallet (EIP-1271)
+#[wasm_bindgen]
+pub async fn verify_eth_account_signature(
+    web3_api_url: String,
+    address: String,
+    hash: String,
+    signature: String,
+) -> Result<bool, JsValue> {
+    Ok(common::verify_eth_account_signature(&address, &hash, &signature, &web3_api_url).await?)
+}
+
+/// check if the message was signed as per EIP-191 (`personal_sign`) by the account,
+/// which is either an externally owned account or a contract wallet (EIP-1271)
+#[wasm_bindgen]
+pub async fn verify_eth_account_personal_signature(
+    web3_api_url: String,
+    address: String,
+    message: String,
+    signature: String,
+) -> Result<bool, JsValue> {
+    Ok(
+        common::verify_eth_account_personal_signature(
+            &address,
+            &message,
+            &signature,
+            &web3_api_url,
+        )
+        .await?,
+    )
+}
+
+/// resolve the name (e.g. ENS or Cronos ID) to its checksummed address
+#[wasm_bindgen]
+pub async fn resolve_eth_name(
+    web3_api_url: String,
+    name: String,
+    chain_id: u64,
+) -> Result<String, JsValue> {
+    let address = common::resolve_eth_name(&name, chain_id, &web3_api_url).await?;
+    Ok(ethers::utils::to_checksum(&address, None))
+}
+
+/// the primary name of the address, only if it resolves back to the address;
+/// it returns undefined if there's no such name
+#[wasm_bindgen]
+pub async fn lookup_eth_name(
+    web3_api_url: String,
+    address: String,
+    chain_id: u64,
+) -> Result<Option<String>, JsValue> {
+    Ok(common::lookup_eth_name(&address, chain_id, &web3_api_url).await?)
+}
+
+/// parse the EIP-681 request (e.g. "ethereum:0x...@25/transfer?address=0x...&uint256=1"),
+/// resolve its names and convert it to the transaction object on the chain
+/// (the payment of `value`, or the ABI-encoded call of the function of the target contract)
+#[wasm_bindgen]
+pub async fn build_eip681_transaction(
+    web3_api_url: String,
+    request: String,
+    chain_id: u64,
+    legacy: bool,
+) -> Result<JsValue, JsValue> {
+    let tx = common::build_eip681_transaction(
+        &request,
+        EthNetwork::Custom { chain_id, legacy },
+        &web3_api_url,
+    )
+    .await?;
+    serde_wasm_bindgen::to_value(&tx).map_err(format_to_js_error)
+}
+
+/// the QR code rendering options from the error correction level ("L", "M", "Q" or "H"),
+/// the quiet zone (in modules) and the module size (in pixels or SVG user units)
+fn qr_render_options(
+    error_correction: &str,
+    quiet_zone: usize,
+    module_size: usize,
+) -> Result<common::QrRenderOptions, JsValue> {
+    Ok(common::QrRenderOptions {
+        error_correction: error_correction.parse().map_err(format_to_js_error)?,
+        quiet_zone,
+        module_size,
+    })
+}
+
+/// render the QR code of the payload (e.g. an EIP-681 or a Cosmos payment request URI)
+/// to the SVG image
+#[wasm_bindgen]
+pub fn render_qr_svg(
+    payload: String,
+    error_correction: String,
+    quiet_zone: usize,
+    module_size: usize,
+) -> Result<String, JsValue> {
+    let options = qr_render_options(&error_correction, quiet_zone, module_size)?;
+    common::render_qr_svg(&payload, &options).map_err(format_to_js_error)
+}
+
+/// render the QR code of the payload to the PNG image (8-bit grayscale)
+#[wasm_bindgen]
+pub fn render_qr_png(
+    payload: String,
+    error_correction: String,
+    quiet_zone: usize,
+    module_size: usize,
+) -> Result<Vec<u8>, JsValue> {
+    let options = qr_render_options(&error_correction, quiet_zone, module_size)?;
+    common::render_qr_png(&payload, &options).map_err(format_to_js_error)
+}
+
+/// the 8-bit grayscale image of a QR code (`width * width` pixels, row by row)
+#[wasm_bindgen(getter_with_clone)]
+pub struct QrGrayscaleImage {
+    pub width: usize,
+    pub pixels: Vec<u8>,
+}
+
+/// render the QR code of the payload to the 8-bit grayscale pixels,
+/// which can be decoded with `decode_qr_grayscale`
+#[wasm_bindgen]
+pub fn render_qr_grayscale(
+    payload: String,
+    error_correction: String,
+    quiet_zone: usize,
+    module_size: usize,
+) -> Result<QrGrayscaleImage, JsValue> {
+    let options = qr_render_options(&error_correction, quiet_zone, module_size)?;
+    let (width, pixels) =
+        common::render_qr_grayscale(&payload, &options).map_err(format_to_js_error)?;
+    Ok(QrGrayscaleImage { width, pixels })
+}
+
+/// decode the QR code in the 8-bit grayscale image (`width * height` pixels, row by row)
+/// back into its payload, e.g. the payment request URI
+#[wasm_bindgen]
+pub fn decode_qr_grayscale(
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+) -> Result<String, JsValue> {
+    common::decode_qr_grayscale(width, height, &pixels).map_err(format_to_js_error)
+}
+
+/// build the EIP-712 typed data (JSON) of the permit, querying the token nonce,
+/// name and domain version where they aren't given.
+/// Parameter `request` must be an `EthPermitRequest` object, e.g.
+/// `{ Erc2612: { token_address, owner, spender, value, deadline } }`
+#[wasm_bindgen]
+pub async fn build_eth_permit(
+    web3_api_url: String,
+    chain_id: u64,
+    request: JsValue,
+) -> Result<String, JsValue> {
+    let request = serde_wasm_bindgen::from_value(request).map_err(format_to_js_error)?;
+    Ok(common::build_eth_permit(
+        request,
+        EthNetwork::Custom {
+            chain_id,
+            legacy: false,
+        },
+        &web3_api_url,
+    )
+    .await?)
+}
+
+/// build and sign the permit; it returns `{ typed_data, signature, v, r, s }`
+#[wasm_bindgen]
+pub async fn sign_eth_permit(
+    web3_api_url: String,
+    chain_id: u64,
+    request: JsValue,
+    private_key: PrivateKey,
+) -> Result<JsValue, JsValue> {
+    let request = serde_wasm_bindgen::from_value(request).map_err(format_to_js_error)?;
+    let permit = common::sign_eth_permit(
+        request,
+        EthNetwork::Custom {
+            chain_id,
+            legacy: false,
+        },
+        private_key.key,
+        &web3_api_url,
+    )
+    .await?;
+    serde_wasm_bindgen::to_value(&permit).map_err(format_to_js_error)
+}
+
 /// the token contract type
 #[wasm_bindgen]
 pub enum ContractType {
@@ -334,7 +700,17 @@ pub async fn query_account_contract_token_balance(
     contract_type: ContractType,
     token_id: Option<String>,
 ) -> Result<BigInt, JsValue> {
-    let details = match (contract_type, token_id) {
+    let details = contract_balance_details(contract_address, contract_type, token_id)?;
+    let balance = get_contract_balance(&address, details, &web3_api_url).await?;
+    Ok(BigInt::new(&JsValue::from_str(&balance.to_string()))?)
+}
+
+fn contract_balance_details(
+    contract_address: String,
+    contract_type: ContractType,
+    token_id: Option<String>,
+) -> Result<ContractBalance, JsValue> {
+    match (contract_type, token_id) {
         (ContractType::Erc20, _) => Ok(ContractBalance::Erc20 { contract_address }),
         (ContractType::Erc721, _) => Ok(ContractBalance::Erc721 { contract_address }),
         (ContractType::Erc1155, Some(token_id)) => Ok(ContractBalance::Erc1155 {
@@ -342,9 +718,99 @@ pub async fn query_account_contract_token_balance(
             token_id,
         }),
         (ContractType::Erc1155, None) => Err(JsValue::from_str("missing token id")),
-    }?;
-    let balance = get_contract_balance(&address, details, &web3_api_url).await?;
-    Ok(BigInt::new(&JsValue::from_str(&balance.to_string()))?)
+    }
+}
+
+/// return the account's balances in the token contracts with batched calls
+/// (Multicall3, or JSON-RPC batch requests on chains without it).
+/// Parameter `contract_details` must be an array of `ContractBalance` objects,
+/// e.g. `{ Erc1155: { contract_address: "0x...", token_id: "1" } }`;
+/// it returns an array of `{ balance, error }` (a failed query doesn't fail the others)
+#[wasm_bindgen]
+pub async fn query_account_contract_token_balances(
+    web3_api_url: String,
+    address: String,
+    contract_details: JsValue,
+) -> Result<JsValue, JsValue> {
+    let contract_details: Vec<ContractBalance> =
+        serde_wasm_bindgen::from_value(contract_details).map_err(format_to_js_error)?;
+    let balances = common::get_contract_balances(&address, contract_details, &web3_api_url).await?;
+    serde_wasm_bindgen::to_value(&balances).map_err(format_to_js_error)
+}
+
+/// Batched read-only contract calls
+/// (Multicall3, or JSON-RPC batch requests on chains without it)
+#[wasm_bindgen]
+pub struct EthMulticall {
+    multicall: common::EthMulticall,
+}
+
+#[wasm_bindgen]
+impl EthMulticall {
+    /// Create an instance with the max number of calls and the max calldata size
+    /// (in bytes) per batch, or the default ones
+    #[wasm_bindgen(constructor)]
+    pub fn new(max_calls: Option<usize>, max_calldata_bytes: Option<usize>) -> EthMulticall {
+        let mut multicall = common::EthMulticall::new();
+        if let Some(max_calls) = max_calls {
+            multicall = multicall.max_calls(max_calls);
+        }
+        if let Some(max_calldata_bytes) = max_calldata_bytes {
+            multicall = multicall.max_calldata_bytes(max_calldata_bytes);
+        }
+        Self { multicall }
+    }
+
+    /// Add the call of the contract with the encoded calldata;
+    /// it returns the index of its result
+    #[wasm_bindgen]
+    pub fn add_call(&mut self, contract_address: &str, data: Vec<u8>) -> Result<usize, JsValue> {
+        Ok(self.multicall.add_call(contract_address, data)?)
+    }
+
+    /// Add the call of the contract function (its output can be decoded with
+    /// `EthContract.decode_output`); parameter `args` must a vector of type
+    /// `EthContractFunctionArg`. It returns the index of its result
+    #[wasm_bindgen]
+    pub fn add_contract_call(
+        &mut self,
+        contract_address: &str,
+        contract: &EthContract,
+        function_name: &str,
+        args: Vec<JsValue>,
+    ) -> Result<usize, JsValue> {
+        let tokens = args
+            .into_iter()
+            .map(|val| val.try_into().map(|arg: EthContractFunctionArg| arg.token))
+            .collect::<Result<Vec<EthAbiToken>, _>>()?;
+        Ok(self.multicall.add_abi_call(
+            contract_address,
+            &contract.abi_contract,
+            function_name,
+            tokens,
+        )?)
+    }
+
+    /// Add the token balance query of the account; it returns the index of its result
+    #[wasm_bindgen]
+    pub fn add_balance_query(
+        &mut self,
+        address: &str,
+        contract_address: String,
+        contract_type: ContractType,
+        token_id: Option<String>,
+    ) -> Result<usize, JsValue> {
+        let details = contract_balance_details(contract_address, contract_type, token_id)?;
+        Ok(self.multicall.add_balance_query(address, &details)?)
+    }
+
+    /// Send the calls; it returns an array of `{ success, return_data, error }`
+    /// in the order the calls were added
+    #[wasm_bindgen]
+    pub async fn execute(self, web3_api_url: String) -> Result<JsValue, JsValue> {
+        let results = self.multicall.execute(&web3_api_url).await?;
+        serde_wasm_bindgen::to_value(&results).map_err(format_to_js_error)
+    }
 }
 
 /// construct, sign and broadcast a plain transfer of eth/native token
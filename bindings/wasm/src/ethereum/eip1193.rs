@@ -0,0 +1,217 @@
+use crate::{format_to_js_error, PrivateKey};
+use defi_wallet_core_common::{
+    Eip1193Approval, Eip1193Approver, Eip1193Chain, Eip1193Dispatcher, Eip1193Error,
+    Eip1193Request, EIP1193_DISCONNECTED,
+};
+use js_sys::{Array, Function, Object, Promise, Reflect};
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+
+/// the approval callbacks of JavaScript, called with the approval object
+/// and returning a boolean (or a promise of it)
+struct JsApprover {
+    callbacks: Object,
+}
+
+impl JsApprover {
+    /// the name of the callback of the approval
+    fn callback_name(approval: &Eip1193Approval) -> &'static str {
+        match approval {
+            Eip1193Approval::RequestAccounts { .. } => "requestAccounts",
+            Eip1193Approval::PersonalSign { .. } => "personalSign",
+            Eip1193Approval::SignTypedData { .. } => "signTypedData",
+            Eip1193Approval::SendTransaction { .. } => "sendTransaction",
+            Eip1193Approval::SwitchChain { .. } => "switchChain",
+            Eip1193Approval::AddChain { .. } => "addChain",
+        }
+    }
+
+    async fn call(&self, approval: &Eip1193Approval) -> Result<bool, JsValue> {
+        let callback: Function = Reflect::get(
+            &self.callbacks,
+            &JsValue::from_str(Self::callback_name(approval)),
+        )?
+        .dyn_into()?;
+        let approval = approval
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(format_to_js_error)?;
+        let result = callback.call1(&JsValue::NULL, &approval)?;
+        let result = JsFuture::from(Promise::resolve(&result)).await?;
+        Ok(result.as_bool().unwrap_or(false))
+    }
+}
+
+impl Eip1193Approver for JsApprover {
+    fn approve<'a>(
+        &'a self,
+        approval: &'a Eip1193Approval,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        // a missing or failing callback rejects the request
+        Box::pin(async move { self.call(approval).await.unwrap_or(false) })
+    }
+}
+
+/// the event listeners of the provider, and the chain and connection
+/// which were last notified to them
+struct Eip1193Events {
+    listeners: RefCell<HashMap<String, Vec<Function>>>,
+    chain_id: Cell<u64>,
+    connected: Cell<bool>,
+}
+
+impl Eip1193Events {
+    fn emit(&self, event: &str, payload: &JsValue) {
+        // the listeners are cloned, as they may remove themselves
+        let listeners = self
+            .listeners
+            .borrow()
+            .get(event)
+            .cloned()
+            .unwrap_or_default();
+        for listener in listeners {
+            // the errors of the listeners must not fail the requests
+            let _ = listener.call1(&JsValue::NULL, payload);
+        }
+    }
+
+    /// emit `chainChanged`, `connect`, `accountsChanged` and `disconnect`
+    /// for the changes of the dispatcher since the last call
+    fn notify(&self, dispatcher: &Eip1193Dispatcher<JsApprover>) {
+        let chain_id = dispatcher.chain_id();
+        if self.chain_id.replace(chain_id) != chain_id {
+            self.emit(
+                "chainChanged",
+                &JsValue::from_str(&format!("{chain_id:#x}")),
+            );
+        }
+        let connected = dispatcher.is_connected();
+        if self.connected.replace(connected) != connected {
+            let accounts: Array = dispatcher
+                .accounts()
+                .iter()
+                .map(|account| JsValue::from_str(account))
+                .collect();
+            if connected {
+                let info = Object::new();
+                let _ = Reflect::set(
+                    &info,
+                    &JsValue::from_str("chainId"),
+                    &JsValue::from_str(&format!("{chain_id:#x}")),
+                );
+                self.emit("connect", &info);
+                self.emit("accountsChanged", &accounts);
+            } else {
+                self.emit("accountsChanged", &accounts);
+                let error = Eip1193Error {
+                    code: EIP1193_DISCONNECTED,
+                    message: "The provider is disconnected.".to_owned(),
+                }
+                .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+                .unwrap_or(JsValue::NULL);
+                self.emit("disconnect", &error);
+            }
+        }
+    }
+}
+
+/// EIP-1193 provider to be injected into dApps (e.g. as `window.ethereum`)
+#[wasm_bindgen]
+pub struct Eip1193Provider {
+    dispatcher: Rc<Eip1193Dispatcher<JsApprover>>,
+    events: Rc<Eip1193Events>,
+}
+
+#[wasm_bindgen]
+impl Eip1193Provider {
+    /// Create the provider of the account of the key.
+    /// Parameter `chains` must be an array of `Eip1193Chain` objects, e.g.
+    /// `[{ chain_id: 25, chain_name: "Cronos", rpc_url: "https://evm.cronos.org" }]`,
+    /// and `chain_id` is the initial one.
+    /// Parameter `callbacks` is an object of the approval functions `requestAccounts`,
+    /// `personalSign`, `signTypedData`, `sendTransaction`, `switchChain` and `addChain`,
+    /// which are called with the request to be confirmed and return a boolean
+    /// (or a promise of it); the requests without callbacks are rejected.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        private_key: PrivateKey,
+        chains: JsValue,
+        chain_id: u64,
+        callbacks: Object,
+    ) -> Result<Eip1193Provider, JsValue> {
+        let chains: Vec<Eip1193Chain> =
+            serde_wasm_bindgen::from_value(chains).map_err(format_to_js_error)?;
+        let dispatcher =
+            Eip1193Dispatcher::new(private_key.key, chains, chain_id, JsApprover { callbacks })?;
+        let events = Eip1193Events {
+            listeners: RefCell::new(HashMap::new()),
+            chain_id: Cell::new(dispatcher.chain_id()),
+            connected: Cell::new(dispatcher.is_connected()),
+        };
+        Ok(Self {
+            dispatcher: Rc::new(dispatcher),
+            events: Rc::new(events),
+        })
+    }
+
+    /// EIP-1193 `request({ method, params })`; the promise is rejected with
+    /// `{ code, message }` (`ProviderRpcError`)
+    pub fn request(&self, args: JsValue) -> Promise {
+        let dispatcher = self.dispatcher.clone();
+        let events = self.events.clone();
+        future_to_promise(async move {
+            let request: Eip1193Request =
+                serde_wasm_bindgen::from_value(args).map_err(format_to_js_error)?;
+            let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+            let result = dispatcher.request(request).await;
+            events.notify(&dispatcher);
+            match result {
+                Ok(result) => result.serialize(&serializer).map_err(format_to_js_error),
+                Err(error) => Err(error.serialize(&serializer).map_err(format_to_js_error)?),
+            }
+        })
+    }
+
+    /// the current chain ID
+    pub fn chain_id(&self) -> u64 {
+        self.dispatcher.chain_id()
+    }
+
+    /// if the dApp is connected to the account
+    pub fn is_connected(&self) -> bool {
+        self.dispatcher.is_connected()
+    }
+
+    /// disconnect the dApp (it has to call `eth_requestAccounts` again);
+    /// `accountsChanged` and `disconnect` are emitted if it was connected
+    pub fn disconnect(&self) {
+        self.dispatcher.disconnect();
+        self.events.notify(&self.dispatcher);
+    }
+
+    /// Add the listener of the event: `chainChanged` (with the hex chain ID),
+    /// `accountsChanged` (with the addresses), `connect` (with `{ chainId }`)
+    /// or `disconnect` (with `{ code, message }`)
+    pub fn on(&self, event: String, listener: Function) {
+        self.events
+            .listeners
+            .borrow_mut()
+            .entry(event)
+            .or_default()
+            .push(listener);
+    }
+
+    /// Remove the listener of the event added by `on`
+    #[wasm_bindgen(js_name = removeListener)]
+    pub fn remove_listener(&self, event: String, listener: Function) {
+        if let Some(listeners) = self.events.listeners.borrow_mut().get_mut(&event) {
+            listeners.retain(|added| !Object::is(added, &listener));
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::PrivateKey;
+use crate::{format_to_js_error, PrivateKey};
 use defi_wallet_core_common::EthSigner;
 use wasm_bindgen::prelude::*;
 
@@ -71,3 +71,98 @@ pub fn eth_sign_typed_data(
 ) -> Result<String, JsValue> {
     Ok(EthSigner::new(private_key.key).sign_typed_data(json_typed_data)?)
 }
+
+/// Inspect an EIP-712 typed data (JSON) before signing it.
+/// Return `{ primary_type, domain_separator, struct_hash, hash, encoded_types, domain, message }`
+/// where `domain` and `message` are the flattened fields `{ path, name, field_type, value, depth }`.
+#[wasm_bindgen]
+pub fn preview_eip712_typed_data(json_typed_data: &str) -> Result<JsValue, JsValue> {
+    let preview = defi_wallet_core_common::preview_eip712_typed_data(json_typed_data)?;
+    serde_wasm_bindgen::to_value(&preview).map_err(format_to_js_error)
+}
+
+/// Recover the signer of an EIP-712 typed data (JSON) from the signature.
+/// Return the checksummed address.
+#[wasm_bindgen]
+pub fn recover_eip712_signer(json_typed_data: &str, signature: &str) -> Result<String, JsValue> {
+    Ok(defi_wallet_core_common::recover_eip712_signer(
+        json_typed_data,
+        signature,
+    )?)
+}
+
+/// Check if an EIP-712 typed data (JSON) was signed by the expected address.
+#[wasm_bindgen]
+pub fn verify_eip712_signature(
+    json_typed_data: &str,
+    signature: &str,
+    expected_address: &str,
+) -> Result<bool, JsValue> {
+    Ok(defi_wallet_core_common::verify_eip712_signature(
+        json_typed_data,
+        signature,
+        expected_address,
+    )?)
+}
+
+/// Normalize the signature (65 bytes or EIP-2098 compact of 64 bytes)
+/// to 65 bytes with `v` of 27 or 28.
+/// Return a signature of hex string with prefix `0x`.
+#[wasm_bindgen]
+pub fn normalize_eth_signature(signature: &str) -> Result<String, JsValue> {
+    Ok(defi_wallet_core_common::normalize_eth_signature(signature)?)
+}
+
+/// Convert the signature to the EIP-2098 compact form of 64 bytes.
+/// Return a signature of hex string with prefix `0x`.
+#[wasm_bindgen]
+pub fn compact_eth_signature(signature: &str) -> Result<String, JsValue> {
+    Ok(defi_wallet_core_common::compact_eth_signature(signature)?)
+}
+
+/// Recover the signer of the message signed as per EIP-191 (`personal_sign`).
+/// Return the checksummed address.
+#[wasm_bindgen]
+pub fn recover_personal_signer(message: &str, signature: &str) -> Result<String, JsValue> {
+    Ok(defi_wallet_core_common::recover_eth_personal_signer(
+        message, signature,
+    )?)
+}
+
+/// Recover the signer of the hash value signed directly (`eth_sign`).
+/// Argument `hash` must be a hex value of 32 bytes (H256).
+/// Return the checksummed address.
+#[wasm_bindgen(js_name = eth_recoverSigner)]
+pub fn recover_eth_hash_signer(hash: &str, signature: &str) -> Result<String, JsValue> {
+    Ok(defi_wallet_core_common::recover_eth_hash_signer(
+        hash, signature,
+    )?)
+}
+
+/// Check if the message was signed as per EIP-191 (`personal_sign`) by the expected address.
+#[wasm_bindgen]
+pub fn verify_personal_signature(
+    message: &str,
+    signature: &str,
+    expected_address: &str,
+) -> Result<bool, JsValue> {
+    Ok(defi_wallet_core_common::verify_eth_personal_signature(
+        message,
+        signature,
+        expected_address,
+    )?)
+}
+
+/// Check if the hash value was signed directly (`eth_sign`) by the expected address.
+#[wasm_bindgen(js_name = eth_verifySignature)]
+pub fn verify_eth_hash_signature(
+    hash: &str,
+    signature: &str,
+    expected_address: &str,
+) -> Result<bool, JsValue> {
+    Ok(defi_wallet_core_common::verify_eth_hash_signature(
+        hash,
+        signature,
+        expected_address,
+    )?)
+}
@@ -4,10 +4,12 @@ use defi_wallet_core_common::{
 use std::sync::Arc;
 use wasm_bindgen::prelude::*;
 
+mod chain_registry;
 mod cosmos_sdk;
 mod ethereum;
 mod utils;
 
+pub use chain_registry::*;
 pub use cosmos_sdk::*;
 pub use ethereum::*;
 pub use utils::*;
@@ -0,0 +1,597 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::CosmosSDKClient;
+use crate::{
+    CosmosSDKTxInfo, EthNetwork, Network, SingleCoin, WalletCoin, COSMOS_CHAIN_ID, CRONOS_CHAIN_ID,
+    CRYPTO_ORG_CHAIN_ID, CRYPTO_ORG_CHAIN_TESTNET_ID,
+};
+use eyre::{eyre, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+mod uniffi_binding;
+pub use uniffi_binding::*;
+
+/// the chain descriptors bundled for the networks supported out of the box
+const BUNDLED_CHAINS: &str = include_str!("chain_registry/chains.json");
+
+/// the placeholder of the transaction hash in the explorer templates
+const TX_HASH_PLACEHOLDER: &str = "${txHash}";
+/// the placeholder of the account address in the explorer templates
+const ACCOUNT_ADDRESS_PLACEHOLDER: &str = "${accountAddress}";
+
+/// the gas price tier used for the fee calculation
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum GasPriceTier {
+    /// the lowest price usually accepted by validators
+    Low,
+    /// the recommended price
+    Average,
+    /// the price for a faster inclusion
+    High,
+}
+
+/// a token accepted for the transaction fees
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct FeeToken {
+    /// the base denomination
+    pub denom: String,
+    /// the minimal gas price enforced by the chain (if any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed_min_gas_price: Option<f64>,
+    /// the low tier gas price
+    #[serde(default)]
+    pub low_gas_price: f64,
+    /// the average tier gas price
+    #[serde(default)]
+    pub average_gas_price: f64,
+    /// the high tier gas price
+    #[serde(default)]
+    pub high_gas_price: f64,
+}
+
+impl FeeToken {
+    /// return the gas price of the tier (not lower than the minimal one)
+    pub fn gas_price(&self, tier: GasPriceTier) -> f64 {
+        let price = match tier {
+            GasPriceTier::Low => self.low_gas_price,
+            GasPriceTier::Average => self.average_gas_price,
+            GasPriceTier::High => self.high_gas_price,
+        };
+        price.max(self.fixed_min_gas_price.unwrap_or_default())
+    }
+}
+
+/// the fee configuration
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ChainFees {
+    /// the accepted fee tokens (the first one is the default)
+    #[serde(default)]
+    pub fee_tokens: Vec<FeeToken>,
+}
+
+/// a token that can be staked
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct StakingToken {
+    /// the base denomination
+    pub denom: String,
+}
+
+/// the staking configuration
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ChainStaking {
+    /// the staking tokens (the first one is the default)
+    #[serde(default)]
+    pub staking_tokens: Vec<StakingToken>,
+}
+
+/// a node endpoint
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ChainEndpoint {
+    /// the endpoint URL
+    pub address: String,
+    /// the endpoint operator
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+}
+
+/// the node endpoints
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ChainApis {
+    /// Tendermint JSON-RPC endpoints
+    #[serde(default)]
+    pub rpc: Vec<ChainEndpoint>,
+    /// Cosmos SDK REST (LCD) endpoints
+    #[serde(default)]
+    pub rest: Vec<ChainEndpoint>,
+    /// Cosmos SDK gRPC endpoints
+    #[serde(default)]
+    pub grpc: Vec<ChainEndpoint>,
+    /// Cosmos SDK gRPC-web endpoints
+    #[serde(default, rename = "grpc-web")]
+    pub grpc_web: Vec<ChainEndpoint>,
+    /// Web3 JSON-RPC endpoints
+    #[serde(default, rename = "evm-http-jsonrpc")]
+    pub evm_http_jsonrpc: Vec<ChainEndpoint>,
+}
+
+/// a block explorer with its URL templates
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ChainExplorer {
+    /// the explorer name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// the explorer home page
+    pub url: String,
+    /// the transaction page template with the `${txHash}` placeholder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_page: Option<String>,
+    /// the account page template with the `${accountAddress}` placeholder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_page: Option<String>,
+}
+
+/// the EVM configuration (for EVM chains and Ethermint-based Cosmos SDK chains)
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ChainEvm {
+    /// the EIP-155 chain id
+    pub chain_id: u64,
+    /// whether the chain needs the legacy transaction request
+    #[serde(default)]
+    pub legacy: bool,
+}
+
+/// the chain descriptor following the cosmos/chain-registry `chain.json` layout
+/// (with an extra `evm` section)
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ChainDescriptor {
+    /// the unique name in the registry
+    pub chain_name: String,
+    /// the human-readable name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pretty_name: Option<String>,
+    /// Tendermint Chain Id (or the EIP-155 chain id for EVM-only chains)
+    pub chain_id: String,
+    /// bech32 human-readable prefix (for Cosmos SDK chains)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bech32_prefix: Option<String>,
+    /// HD wallet coin type
+    pub slip44: u32,
+    /// the fee configuration
+    #[serde(default)]
+    pub fees: ChainFees,
+    /// the staking configuration
+    #[serde(default)]
+    pub staking: ChainStaking,
+    /// the node endpoints
+    #[serde(default)]
+    pub apis: ChainApis,
+    /// the block explorers
+    #[serde(default)]
+    pub explorers: Vec<ChainExplorer>,
+    /// the EVM configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evm: Option<ChainEvm>,
+}
+
+/// the gas price as the exact decimal fraction `(numerator, scale)` of `numerator / 10^scale`,
+/// taken from the shortest representation of the price (i.e. the decimal written in the descriptor)
+fn gas_price_fraction(gas_price: f64) -> eyre::Result<(u128, u32)> {
+    if !gas_price.is_finite() || gas_price < 0.0 {
+        return Err(eyre!("invalid gas price: {}", gas_price));
+    }
+    let decimal = gas_price.to_string();
+    let (integer, fraction) = decimal.split_once('.').unwrap_or((&decimal, ""));
+    let numerator = format!("{}{}", integer, fraction)
+        .parse::<u128>()
+        .with_context(|| format!("gas price out of range: {}", decimal))?;
+    Ok((numerator, fraction.len() as u32))
+}
+
+fn first_address(endpoints: &[ChainEndpoint], kind: &str) -> eyre::Result<String> {
+    endpoints
+        .first()
+        .map(|endpoint| endpoint.address.clone())
+        .ok_or_else(|| eyre!("no {} endpoint", kind))
+}
+
+impl ChainDescriptor {
+    /// the network used for Cosmos SDK addresses and transactions
+    pub fn to_network(&self) -> eyre::Result<Network> {
+        Ok(match self.chain_id.as_str() {
+            CRYPTO_ORG_CHAIN_ID => Network::CryptoOrgMainnet,
+            CRYPTO_ORG_CHAIN_TESTNET_ID => Network::CryptoOrgTestnet,
+            CRONOS_CHAIN_ID => Network::CronosMainnet,
+            COSMOS_CHAIN_ID => Network::CosmosHub,
+            chain_id => Network::Other {
+                chain_id: chain_id.to_owned(),
+                coin_type: self.slip44,
+                bech32hrp: self
+                    .bech32_prefix
+                    .clone()
+                    .ok_or_else(|| eyre!("{} is not a Cosmos SDK chain", self.chain_name))?,
+            },
+        })
+    }
+
+    /// the network used for EVM transactions
+    pub fn to_eth_network(&self) -> eyre::Result<EthNetwork> {
+        let evm = self
+            .evm
+            .as_ref()
+            .ok_or_else(|| eyre!("{} is not an EVM chain", self.chain_name))?;
+        Ok(EthNetwork::Custom {
+            chain_id: evm.chain_id,
+            legacy: evm.legacy,
+        })
+    }
+
+    /// the wallet coin for the address derivation
+    /// (Cosmos SDK addresses if the chain has a bech32 prefix, Ethereum ones otherwise)
+    pub fn to_wallet_coin(&self) -> eyre::Result<WalletCoin> {
+        if self.bech32_prefix.is_some() {
+            Ok(WalletCoin::CosmosSDK {
+                network: self.to_network()?,
+            })
+        } else {
+            Ok(WalletCoin::Ethereum {
+                network: self.to_eth_network()?,
+            })
+        }
+    }
+
+    /// the default fee token
+    pub fn fee_token(&self) -> eyre::Result<&FeeToken> {
+        self.fees
+            .fee_tokens
+            .first()
+            .ok_or_else(|| eyre!("no fee token for {}", self.chain_name))
+    }
+
+    /// the default staking denomination
+    pub fn staking_denom(&self) -> eyre::Result<&str> {
+        self.staking
+            .staking_tokens
+            .first()
+            .map(|token| token.denom.as_str())
+            .ok_or_else(|| eyre!("no staking token for {}", self.chain_name))
+    }
+
+    /// the fee to be paid (gas_limit * gas_price of the tier, rounded up) in the default fee token;
+    /// it's computed with integers, as the float one is off for large gas limits or prices
+    pub fn fee_amount(&self, gas_limit: u64, tier: GasPriceTier) -> eyre::Result<SingleCoin> {
+        let fee_token = self.fee_token()?;
+        let (numerator, scale) = gas_price_fraction(fee_token.gas_price(tier))?;
+        let denominator = 10u128
+            .checked_pow(scale)
+            .ok_or_else(|| eyre!("too many decimals in the gas price"))?;
+        let product = u128::from(gas_limit)
+            .checked_mul(numerator)
+            .ok_or_else(|| eyre!("fee amount overflow"))?;
+        let amount = product / denominator + u128::from(product % denominator != 0);
+        Ok(SingleCoin::Other {
+            amount: amount.to_string(),
+            denom: fee_token.denom.clone(),
+        })
+    }
+
+    /// the Cosmos SDK transaction info with the fee computed from the gas price tier
+    pub fn to_tx_info(
+        &self,
+        account_number: u64,
+        sequence_number: u64,
+        gas_limit: u64,
+        tier: GasPriceTier,
+        timeout_height: u32,
+        memo_note: Option<String>,
+    ) -> eyre::Result<CosmosSDKTxInfo> {
+        Ok(CosmosSDKTxInfo {
+            account_number,
+            sequence_number,
+            gas_limit,
+            fee_amount: self.fee_amount(gas_limit, tier)?,
+            timeout_height,
+            memo_note,
+            network: self.to_network()?,
+        })
+    }
+
+    /// the default Tendermint JSON-RPC endpoint
+    pub fn tendermint_rpc_url(&self) -> eyre::Result<String> {
+        first_address(&self.apis.rpc, "Tendermint RPC")
+    }
+
+    /// the default Cosmos SDK REST (LCD) endpoint
+    pub fn rest_url(&self) -> eyre::Result<String> {
+        first_address(&self.apis.rest, "REST")
+    }
+
+    /// the default Cosmos SDK gRPC endpoint
+    pub fn grpc_url(&self) -> eyre::Result<String> {
+        first_address(&self.apis.grpc, "gRPC")
+    }
+
+    /// the default Cosmos SDK gRPC-web endpoint (falls back to the gRPC one)
+    pub fn grpc_web_url(&self) -> eyre::Result<String> {
+        first_address(&self.apis.grpc_web, "gRPC-web").or_else(|_| self.grpc_url())
+    }
+
+    /// the default Web3 JSON-RPC endpoint
+    pub fn web3api_url(&self) -> eyre::Result<String> {
+        first_address(&self.apis.evm_http_jsonrpc, "EVM JSON-RPC")
+    }
+
+    /// the client for the default Tendermint RPC and gRPC endpoints
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_cosmos_sdk_client(&self) -> eyre::Result<CosmosSDKClient> {
+        Ok(CosmosSDKClient::new(
+            self.tendermint_rpc_url()?,
+            self.grpc_url()?,
+        ))
+    }
+
+    /// the transaction page on the default explorer
+    pub fn explorer_tx_url(&self, tx_hash: &str) -> Option<String> {
+        self.explorers
+            .iter()
+            .find_map(|explorer| explorer.tx_page.as_ref())
+            .map(|template| template.replace(TX_HASH_PLACEHOLDER, tx_hash))
+    }
+
+    /// the account page on the default explorer
+    pub fn explorer_account_url(&self, address: &str) -> Option<String> {
+        self.explorers
+            .iter()
+            .find_map(|explorer| explorer.account_page.as_ref())
+            .map(|template| template.replace(ACCOUNT_ADDRESS_PLACEHOLDER, address))
+    }
+}
+
+/// the content of a chain descriptor file: a single descriptor or a list of them
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChainDescriptorFile {
+    One(Box<ChainDescriptor>),
+    List(Vec<ChainDescriptor>),
+    Table { chains: Vec<ChainDescriptor> },
+}
+
+impl From<ChainDescriptorFile> for Vec<ChainDescriptor> {
+    fn from(file: ChainDescriptorFile) -> Self {
+        match file {
+            ChainDescriptorFile::One(chain) => vec![*chain],
+            ChainDescriptorFile::List(chains) | ChainDescriptorFile::Table { chains } => chains,
+        }
+    }
+}
+
+/// the chain descriptors keyed by their names
+#[derive(Clone, Debug, Default)]
+pub struct ChainRegistry {
+    chains: BTreeMap<String, ChainDescriptor>,
+}
+
+impl ChainRegistry {
+    /// an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a registry with the bundled descriptors of the supported networks
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry
+            .load_json(BUNDLED_CHAINS)
+            .expect("valid bundled chain descriptors");
+        registry
+    }
+
+    /// add (or replace) the descriptors from JSON (a single descriptor or a list)
+    pub fn load_json(&mut self, json: &str) -> eyre::Result<()> {
+        let file: ChainDescriptorFile =
+            serde_json::from_str(json).context("invalid chain descriptor JSON")?;
+        self.extend(file.into());
+        Ok(())
+    }
+
+    /// add (or replace) the descriptors from TOML
+    /// (a single descriptor or a list in the `[[chains]]` array of tables)
+    pub fn load_toml(&mut self, toml: &str) -> eyre::Result<()> {
+        let file: ChainDescriptorFile =
+            toml::from_str(toml).context("invalid chain descriptor TOML")?;
+        self.extend(file.into());
+        Ok(())
+    }
+
+    fn extend(&mut self, chains: Vec<ChainDescriptor>) {
+        for chain in chains {
+            self.insert(chain);
+        }
+    }
+
+    /// add (or replace) the descriptor
+    pub fn insert(&mut self, chain: ChainDescriptor) {
+        self.chains.insert(chain.chain_name.clone(), chain);
+    }
+
+    /// the descriptor with the registry name
+    pub fn get(&self, chain_name: &str) -> Option<&ChainDescriptor> {
+        self.chains.get(chain_name)
+    }
+
+    /// the descriptor with the Tendermint chain id
+    pub fn get_by_chain_id(&self, chain_id: &str) -> Option<&ChainDescriptor> {
+        self.chains
+            .values()
+            .find(|chain| chain.chain_id == chain_id)
+    }
+
+    /// the descriptor with the EIP-155 chain id
+    pub fn get_by_evm_chain_id(&self, chain_id: u64) -> Option<&ChainDescriptor> {
+        self.chains.values().find(|chain| {
+            chain
+                .evm
+                .as_ref()
+                .map(|evm| evm.chain_id == chain_id)
+                .unwrap_or(false)
+        })
+    }
+
+    /// all the descriptors sorted by name
+    pub fn chains(&self) -> impl Iterator<Item = &ChainDescriptor> {
+        self.chains.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_chains_match_the_networks() {
+        let registry = ChainRegistry::with_defaults();
+        let chain = registry.get("cryptoorgchain").unwrap();
+        assert_eq!(chain.to_network().unwrap(), Network::CryptoOrgMainnet);
+        assert_eq!(
+            chain.to_network().unwrap().get_coin_type(),
+            Network::CryptoOrgMainnet.get_coin_type()
+        );
+        assert_eq!(chain.staking_denom().unwrap(), "basecro");
+
+        let chain = registry.get_by_chain_id("testnet-croeseid-4").unwrap();
+        assert_eq!(chain.to_network().unwrap(), Network::CryptoOrgTestnet);
+
+        let chain = registry.get_by_evm_chain_id(25).unwrap();
+        assert_eq!(chain.to_network().unwrap(), Network::CronosMainnet);
+        assert_eq!(
+            chain.to_eth_network().unwrap().to_chain_params().unwrap(),
+            (25, false)
+        );
+
+        let chain = registry.get("ethereum").unwrap();
+        assert!(chain.to_network().is_err());
+        assert_eq!(chain.web3api_url().unwrap(), "https://cloudflare-eth.com");
+    }
+
+    #[test]
+    fn fee_is_computed_from_the_tier() {
+        let registry = ChainRegistry::with_defaults();
+        let chain = registry.get("cosmoshub").unwrap();
+        assert_eq!(
+            chain.fee_amount(200000, GasPriceTier::Average).unwrap(),
+            SingleCoin::Other {
+                amount: "5000".to_owned(),
+                denom: "uatom".to_owned(),
+            }
+        );
+        let tx_info = chain
+            .to_tx_info(1, 0, 100000, GasPriceTier::High, 0, None)
+            .unwrap();
+        assert_eq!(
+            tx_info.fee_amount,
+            SingleCoin::Other {
+                amount: "3000".to_owned(),
+                denom: "uatom".to_owned(),
+            }
+        );
+        assert_eq!(tx_info.network, Network::CosmosHub);
+    }
+
+    #[test]
+    fn fee_is_exact_for_large_amounts() {
+        let chain = |gas_price| ChainDescriptor {
+            chain_name: "cronos".to_owned(),
+            pretty_name: None,
+            chain_id: "cronosmainnet_25-1".to_owned(),
+            bech32_prefix: Some("crc".to_owned()),
+            slip44: 60,
+            fees: ChainFees {
+                fee_tokens: vec![FeeToken {
+                    denom: "basecro".to_owned(),
+                    fixed_min_gas_price: None,
+                    low_gas_price: gas_price,
+                    average_gas_price: gas_price,
+                    high_gas_price: gas_price,
+                }],
+            },
+            staking: ChainStaking::default(),
+            apis: ChainApis::default(),
+            explorers: vec![],
+            evm: None,
+        };
+        let amount = |chain: ChainDescriptor, gas_limit| match chain
+            .fee_amount(gas_limit, GasPriceTier::Average)
+            .unwrap()
+        {
+            SingleCoin::Other { amount, .. } => amount,
+            coin => panic!("unexpected fee: {:?}", coin),
+        };
+
+        // an 18-decimal gas price with the gas limit above 2^53 / price
+        assert_eq!(
+            amount(chain(5000000000000.1), 123456789),
+            "617283945000012345679"
+        );
+        assert_eq!(amount(chain(1.0), 9007199254740993), "9007199254740993");
+        // rounded up
+        assert_eq!(amount(chain(0.025), 100001), "2501");
+        assert_eq!(amount(chain(0.0), 100000), "0");
+        assert!(chain(-1.0)
+            .fee_amount(100000, GasPriceTier::Average)
+            .is_err());
+    }
+
+    #[test]
+    fn explorer_templates_are_filled() {
+        let registry = ChainRegistry::with_defaults();
+        let chain = registry.get("cronos").unwrap();
+        assert_eq!(
+            chain.explorer_tx_url("0x1234").unwrap(),
+            "https://cronoscan.com/tx/0x1234"
+        );
+        assert_eq!(
+            chain.explorer_account_url("0xabcd").unwrap(),
+            "https://cronoscan.com/address/0xabcd"
+        );
+    }
+
+    #[test]
+    fn toml_descriptors_are_loaded() {
+        let mut registry = ChainRegistry::new();
+        registry
+            .load_toml(
+                r#"
+[[chains]]
+chain_name = "osmosis"
+chain_id = "osmosis-1"
+bech32_prefix = "osmo"
+slip44 = 118
+
+[[chains.fees.fee_tokens]]
+denom = "uosmo"
+fixed_min_gas_price = 0.0025
+low_gas_price = 0.0025
+average_gas_price = 0.025
+high_gas_price = 0.04
+
+[[chains.apis.rpc]]
+address = "https://rpc.osmosis.zone"
+"#,
+            )
+            .unwrap();
+        let chain = registry.get("osmosis").unwrap();
+        assert_eq!(
+            chain.to_network().unwrap(),
+            Network::Other {
+                chain_id: "osmosis-1".to_owned(),
+                coin_type: 118,
+                bech32hrp: "osmo".to_owned(),
+            }
+        );
+        assert_eq!(
+            chain.tendermint_rpc_url().unwrap(),
+            "https://rpc.osmosis.zone"
+        );
+        assert!(chain.grpc_url().is_err());
+        assert!(chain.to_eth_network().is_err());
+    }
+}
@@ -0,0 +1,155 @@
+#![cfg(feature = "uniffi-binding")]
+
+use super::{ChainDescriptor, ChainRegistry, GasPriceTier};
+use crate::{CosmosSDKClient, CosmosSDKTxInfo, EthNetwork, Network, WalletCoin};
+use std::sync::{Arc, RwLock};
+
+/// Errors of the chain registry binding
+#[derive(Debug, thiserror::Error)]
+pub enum ChainRegistryError {
+    #[error("Invalid chain descriptor: {0}")]
+    InvalidDescriptor(String),
+    #[error("Unknown chain: {0}")]
+    UnknownChain(String),
+    #[error("Chain registry error: {0}")]
+    ErrorReport(String),
+}
+
+impl From<eyre::Report> for ChainRegistryError {
+    fn from(report: eyre::Report) -> Self {
+        ChainRegistryError::ErrorReport(format!("{report:#}"))
+    }
+}
+
+/// The chain registry (with the bundled descriptors of the supported networks)
+/// for setting up the clients and transactions by the chain name
+pub struct ChainRegistryWrapper {
+    inner: RwLock<ChainRegistry>,
+}
+
+impl Default for ChainRegistryWrapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChainRegistryWrapper {
+    /// Create the registry with the bundled descriptors.
+    pub fn new() -> Self {
+        Self {
+            inner: RwLock::new(ChainRegistry::with_defaults()),
+        }
+    }
+
+    /// Add (or replace) the descriptors from JSON (a single descriptor or a list).
+    pub fn load_json(&self, json: &str) -> Result<(), ChainRegistryError> {
+        self.inner
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .load_json(json)
+            .map_err(|e| ChainRegistryError::InvalidDescriptor(format!("{e:#}")))
+    }
+
+    /// Add (or replace) the descriptors from TOML.
+    pub fn load_toml(&self, toml: &str) -> Result<(), ChainRegistryError> {
+        self.inner
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .load_toml(toml)
+            .map_err(|e| ChainRegistryError::InvalidDescriptor(format!("{e:#}")))
+    }
+
+    /// The names of the registered chains (sorted).
+    pub fn chain_names(&self) -> Vec<String> {
+        self.with_registry(|registry| {
+            registry
+                .chains()
+                .map(|chain| chain.chain_name.clone())
+                .collect()
+        })
+    }
+
+    /// The network of the chain for Cosmos SDK addresses and transactions.
+    pub fn get_network(&self, chain_name: &str) -> Result<Network, ChainRegistryError> {
+        self.with_chain(chain_name, |chain| Ok(chain.to_network()?))
+    }
+
+    /// The network of the chain for EVM transactions.
+    pub fn get_eth_network(&self, chain_name: &str) -> Result<EthNetwork, ChainRegistryError> {
+        self.with_chain(chain_name, |chain| Ok(chain.to_eth_network()?))
+    }
+
+    /// The wallet coin of the chain for the address derivation.
+    pub fn get_wallet_coin(&self, chain_name: &str) -> Result<WalletCoin, ChainRegistryError> {
+        self.with_chain(chain_name, |chain| Ok(chain.to_wallet_coin()?))
+    }
+
+    /// The Cosmos SDK transaction info with the fee computed from the gas price tier.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_tx_info(
+        &self,
+        chain_name: &str,
+        account_number: u64,
+        sequence_number: u64,
+        gas_limit: u64,
+        tier: GasPriceTier,
+        timeout_height: u32,
+        memo_note: Option<String>,
+    ) -> Result<CosmosSDKTxInfo, ChainRegistryError> {
+        self.with_chain(chain_name, |chain| {
+            Ok(chain.to_tx_info(
+                account_number,
+                sequence_number,
+                gas_limit,
+                tier,
+                timeout_height,
+                memo_note,
+            )?)
+        })
+    }
+
+    /// The client of the default Tendermint RPC and gRPC endpoints of the chain.
+    pub fn get_cosmos_sdk_client(
+        &self,
+        chain_name: &str,
+    ) -> Result<Arc<CosmosSDKClient>, ChainRegistryError> {
+        self.with_chain(chain_name, |chain| {
+            Ok(Arc::new(chain.to_cosmos_sdk_client()?))
+        })
+    }
+
+    /// The default Web3 JSON-RPC endpoint of the chain.
+    pub fn get_web3api_url(&self, chain_name: &str) -> Result<String, ChainRegistryError> {
+        self.with_chain(chain_name, |chain| Ok(chain.web3api_url()?))
+    }
+
+    /// The transaction page on the default explorer of the chain.
+    pub fn explorer_tx_url(&self, chain_name: &str, tx_hash: &str) -> Option<String> {
+        self.with_registry(|registry| registry.get(chain_name)?.explorer_tx_url(tx_hash))
+    }
+
+    /// The account page on the default explorer of the chain.
+    pub fn explorer_account_url(&self, chain_name: &str, address: &str) -> Option<String> {
+        self.with_registry(|registry| registry.get(chain_name)?.explorer_account_url(address))
+    }
+
+    fn with_registry<T>(&self, f: impl FnOnce(&ChainRegistry) -> T) -> T {
+        f(&self
+            .inner
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+
+    fn with_chain<T>(
+        &self,
+        chain_name: &str,
+        f: impl FnOnce(&ChainDescriptor) -> Result<T, ChainRegistryError>,
+    ) -> Result<T, ChainRegistryError> {
+        self.with_registry(|registry| {
+            let chain = registry
+                .get(chain_name)
+                .ok_or_else(|| ChainRegistryError::UnknownChain(chain_name.to_owned()))?;
+            f(chain)
+        })
+    }
+}
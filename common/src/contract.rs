@@ -1,14 +1,20 @@
 use crate::EthError;
 #[cfg(feature = "abi-contract")]
-use crate::{abi::EthAbiToken, EthAbiTokenBind};
-use ethers::abi::Detokenize;
+use crate::{
+    abi::{parse_human_readable_abi, EthAbiToken},
+    EthAbiTokenBind,
+};
 #[cfg(feature = "abi-contract")]
 use ethers::abi::Token;
-use ethers::contract::builders;
+use ethers::abi::{Abi, Detokenize};
+use ethers::contract::{builders, ContractError};
 use ethers::prelude::{abigen, Middleware, TransactionReceipt, U256};
 use ethers::types::transaction::eip2718::TypedTransaction;
 use std::sync::Arc;
 
+mod revert;
+pub use revert::*;
+
 abigen!(
     Erc20Contract,
     "$CARGO_MANIFEST_DIR/src/contract/erc20-abi.json"
@@ -31,7 +37,7 @@ abigen!(
 ///  TODO Put utils.rs contract related functions under it
 pub struct Contract;
 
-use crate::node::ethereum::utils::address_from_str;
+use crate::node::ethereum::{nonce::NonceTracker, utils::address_from_str};
 
 impl Contract {
     /// Construct an ERC20 contract
@@ -104,8 +110,32 @@ impl<M: Middleware> DynamicContract<M> {
         abi_json: &str,
         client: M,
     ) -> Result<DynamicContract<M>, EthError> {
-        let contract_address = address_from_str(contract_address)?;
         let abi: ethers::abi::Abi = serde_json::from_str(abi_json).map_err(EthError::JsonError)?;
+        Self::with_abi(contract_address, abi, client)
+    }
+
+    /// Construct a dynamic contract from human-readable ABI fragments, e.g.
+    /// `function transfer(address to, uint256 amount) returns (bool)`
+    ///
+    /// # Arguments
+    /// * `contract_address` - The address of the contract
+    /// * `fragments` - The human-readable ABI fragments
+    /// * `client` - The client middleware
+    pub fn from_human_readable<S: AsRef<str>>(
+        contract_address: &str,
+        fragments: &[S],
+        client: M,
+    ) -> Result<DynamicContract<M>, EthError> {
+        let abi = parse_human_readable_abi(fragments)?;
+        Self::with_abi(contract_address, abi, client)
+    }
+
+    fn with_abi(
+        contract_address: &str,
+        abi: ethers::abi::Abi,
+        client: M,
+    ) -> Result<DynamicContract<M>, EthError> {
+        let contract_address = address_from_str(contract_address)?;
         let contract = ethers::contract::Contract::new(contract_address, abi, Arc::new(client));
         Ok(Self(contract))
     }
@@ -136,7 +166,7 @@ impl<M: Middleware> DynamicContract<M> {
             .0
             .method::<_, D>(method_name, Token::Tuple(tokens))
             .map_err(EthError::DynamicAbiError)?;
-        Ok(method.into())
+        Ok(ContractCall::from(method).abi(self.0.abi().clone()))
     }
 }
 
@@ -147,6 +177,10 @@ where
     D: Detokenize,
 {
     pub contract_call: builders::ContractCall<M, D>,
+    /// the locally tracked nonces of the sender (the nonce is fetched from the node if not set)
+    pub nonce_tracker: Option<NonceTracker>,
+    /// the contract ABI with the custom errors (the standard revert reasons are decoded without it)
+    pub abi: Option<Abi>,
 }
 
 impl<M, D> ContractCall<M, D>
@@ -168,6 +202,28 @@ where
         self
     }
 
+    /// Uses the locally tracked nonces of the sender, so that several transactions
+    /// can be sent back-to-back
+    pub fn nonce_tracker(mut self, nonce_tracker: NonceTracker) -> Self {
+        self.nonce_tracker = Some(nonce_tracker);
+        self
+    }
+
+    /// Uses the contract ABI to decode the custom errors in the revert reasons
+    pub fn abi(mut self, abi: Abi) -> Self {
+        self.abi = Some(abi);
+        self
+    }
+
+    /// the readable revert reason if the call reverted, otherwise the error itself
+    fn error_message(&self, error: ContractError<M>) -> String {
+        error
+            .as_revert()
+            .and_then(|data| decode_revert_reason(data, self.abi.as_ref()))
+            .map(|reason| format!("execution reverted: {reason}"))
+            .unwrap_or_else(|| error.to_string())
+    }
+
     /// Returns the raw transaction request
     pub fn get_tx(&self) -> TypedTransaction {
         self.contract_call.tx.clone()
@@ -175,12 +231,25 @@ where
 
     /// Signs and broadcasts the provided transaction
     pub async fn send(&self) -> Result<TransactionReceipt, EthError> {
-        let pending_tx = self
-            .contract_call
-            .send()
-            .await
-            .map_err(|e| EthError::ContractSendError(e.to_string()))?
-            .await;
+        let mut contract_call = self.contract_call.clone();
+        let nonce = match &self.nonce_tracker {
+            Some(nonce_tracker) => {
+                let nonce = nonce_tracker.next().await?;
+                contract_call.tx.set_nonce(nonce);
+                Some((nonce_tracker, nonce))
+            }
+            None => None,
+        };
+        let pending_tx = match contract_call.send().await {
+            Ok(pending_tx) => pending_tx,
+            Err(e) => {
+                if let Some((nonce_tracker, nonce)) = nonce {
+                    nonce_tracker.release(nonce, &e.to_string());
+                }
+                return Err(EthError::ContractSendError(self.error_message(e)));
+            }
+        }
+        .await;
         let tx_receipt = pending_tx
             .map_err(EthError::BroadcastTxFail)?
             .ok_or(EthError::MempoolDrop)?;
@@ -192,7 +261,7 @@ where
         self.contract_call
             .estimate_gas()
             .await
-            .map_err(|e| EthError::ContractSendError(e.to_string()))
+            .map_err(|e| EthError::ContractSendError(self.error_message(e)))
     }
 
     /// Queries the blockchain via an eth_call for the provided transaction.
@@ -200,7 +269,7 @@ where
         self.contract_call
             .call()
             .await
-            .map_err(|e| EthError::ContractCallError(e.to_string()))
+            .map_err(|e| EthError::ContractCallError(self.error_message(e)))
     }
 }
 
@@ -210,6 +279,10 @@ where
     D: Detokenize,
 {
     fn from(contract_call: builders::ContractCall<M, D>) -> Self {
-        Self { contract_call }
+        Self {
+            contract_call,
+            nonce_tracker: None,
+            abi: None,
+        }
     }
 }
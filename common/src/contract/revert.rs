@@ -0,0 +1,161 @@
+use ethers::abi::{decode, Abi, ParamType, Token};
+use ethers::prelude::{I256, U256};
+use ethers::utils::{hex, to_checksum};
+
+/// the selector of `Error(string)` used by `require` and `revert`
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// the selector of `Panic(uint256)` used by the compiler checks
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// the description of the Solidity panic codes
+/// (https://docs.soliditylang.org/en/latest/control-structures.html#panic-via-assert-and-error-via-require)
+fn panic_description(code: U256) -> &'static str {
+    if code > U256::from(u8::MAX) {
+        return "unknown panic";
+    }
+    match code.low_u64() {
+        0x00 => "generic compiler panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "invalid storage byte array encoding",
+        0x31 => "pop on an empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call to a zero-initialized internal function",
+        _ => "unknown panic",
+    }
+}
+
+/// the human-readable value of the token
+pub(crate) fn format_token(token: &Token) -> String {
+    let format_tokens = |tokens: &[Token]| {
+        tokens
+            .iter()
+            .map(format_token)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match token {
+        Token::Address(address) => to_checksum(address, None),
+        Token::FixedBytes(bytes) | Token::Bytes(bytes) => format!("0x{}", hex::encode(bytes)),
+        Token::Int(value) => I256::from_raw(*value).to_string(),
+        Token::Uint(value) => value.to_string(),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => format!("{value:?}"),
+        Token::FixedArray(tokens) | Token::Array(tokens) => format!("[{}]", format_tokens(tokens)),
+        Token::Tuple(tokens) => format!("({})", format_tokens(tokens)),
+    }
+}
+
+/// Decode the revert data into a readable message:
+/// `Error(string)`, `Panic(uint256)` or one of the custom errors declared in the ABI;
+/// none if the data doesn't match any of them
+pub fn decode_revert_reason(data: &[u8], abi: Option<&Abi>) -> Option<String> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, args) = data.split_at(4);
+    if selector == ERROR_SELECTOR {
+        return match decode(&[ParamType::String], args).ok()?.pop()? {
+            Token::String(reason) => Some(reason),
+            _ => None,
+        };
+    }
+    if selector == PANIC_SELECTOR {
+        let code = decode(&[ParamType::Uint(256)], args)
+            .ok()?
+            .pop()?
+            .into_uint()?;
+        // the width is ignored by the hex formatting of `U256`
+        let code_hex = if code <= U256::from(u64::MAX) {
+            format!("{:02x}", code.low_u64())
+        } else {
+            format!("{code:x}")
+        };
+        return Some(format!("Panic(0x{code_hex}): {}", panic_description(code)));
+    }
+    abi?.errors()
+        .filter(|error| error.signature()[..4] == *selector)
+        .find_map(|error| {
+            let kinds = error
+                .inputs
+                .iter()
+                .map(|param| param.kind.clone())
+                .collect::<Vec<_>>();
+            let tokens = decode(&kinds, args).ok()?;
+            let params = error
+                .inputs
+                .iter()
+                .zip(tokens.iter())
+                .map(|(param, token)| {
+                    if param.name.is_empty() {
+                        format_token(token)
+                    } else {
+                        format!("{}: {}", param.name, format_token(token))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            Some(format!("{}({})", error.name, params))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+
+    #[test]
+    fn standard_revert_reasons_are_decoded() {
+        let mut data = ERROR_SELECTOR.to_vec();
+        data.extend(encode(&[Token::String("not enough balance".to_owned())]));
+        assert_eq!(
+            decode_revert_reason(&data, None).as_deref(),
+            Some("not enough balance")
+        );
+
+        let mut data = PANIC_SELECTOR.to_vec();
+        data.extend(encode(&[Token::Uint(U256::from(0x11))]));
+        assert_eq!(
+            decode_revert_reason(&data, None).as_deref(),
+            Some("Panic(0x11): arithmetic overflow or underflow")
+        );
+
+        let mut data = PANIC_SELECTOR.to_vec();
+        data.extend(encode(&[Token::Uint(U256::from(0x01))]));
+        assert_eq!(
+            decode_revert_reason(&data, None).as_deref(),
+            Some("Panic(0x01): assertion failed")
+        );
+
+        let mut data = PANIC_SELECTOR.to_vec();
+        data.extend(encode(&[Token::Uint(U256::from(u64::MAX) + 1)]));
+        assert_eq!(
+            decode_revert_reason(&data, None).as_deref(),
+            Some("Panic(0x10000000000000000): unknown panic")
+        );
+
+        assert!(decode_revert_reason(&[0xde, 0xad], None).is_none());
+    }
+
+    #[test]
+    fn custom_error_is_decoded() {
+        let abi: Abi = serde_json::from_str(
+            r#"[{"inputs":[{"name":"available","type":"uint256"},{"name":"required","type":"int256"}],"name":"InsufficientBalance","type":"error"}]"#,
+        )
+        .unwrap();
+        let error = abi.errors().next().unwrap();
+        let mut data = error.signature()[..4].to_vec();
+        data.extend(encode(&[
+            Token::Uint(U256::from(10)),
+            Token::Int(I256::from(-20).into_raw()),
+        ]));
+        assert_eq!(
+            decode_revert_reason(&data, Some(&abi)).as_deref(),
+            Some("InsufficientBalance(available: 10, required: -20)")
+        );
+        assert!(decode_revert_reason(&data, None).is_none());
+    }
+}
@@ -0,0 +1,433 @@
+use std::{fmt::Display, str::FromStr};
+
+use cosmrs::AccountId;
+use serde::{Deserialize, Serialize};
+use url::form_urlencoded;
+
+use crate::{
+    ChainDescriptor, ChainRegistry, CosmosSDKMsg, CosmosSDKTxInfo, GasPriceTier, SingleCoin,
+};
+
+/// the URI scheme of Cosmos SDK payment requests
+pub const COSMOS_PAYMENT_SCHEME: &str = "cosmos";
+
+/// the gas limit of the bank transfer used by default
+pub const DEFAULT_PAYMENT_GAS_LIMIT: u64 = 200_000;
+
+/// The query key styles of the payment request URIs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CosmosPaymentUriFormat {
+    /// `cosmos:<address>?amount=<amount>&denom=<denom>&memo=<memo>&chain_id=<chain id>`
+    Standard,
+    /// the Keplr-compatible style with the denomination in the amount:
+    /// `cosmos:<address>?amount=<amount><denom>&memo=<memo>&chainId=<chain id>`
+    Keplr,
+}
+
+/// Parsing and conversion errors of the payment requests
+#[derive(Debug, thiserror::Error)]
+pub enum CosmosPaymentUriError {
+    #[error("Invalid Cosmos payment request URI: {0}")]
+    InvalidUri(String),
+    #[error("Invalid recipient address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("Duplicated parameter: {0}")]
+    DuplicatedParameter(String),
+    #[error("Missing {0}")]
+    MissingField(&'static str),
+    #[error("The chain is not known: {0}")]
+    UnknownChain(String),
+    #[error("The request is for {requested}, not for {chain}")]
+    ChainMismatch { requested: String, chain: String },
+    #[error("Cannot build the transaction info: {0}")]
+    TxInfoError(eyre::Report),
+}
+
+/// Structure holding the payment request of a Cosmos SDK chain
+/// (e.g. Crypto.org Chain or Cosmos Hub)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CosmosPaymentRequest {
+    /// the bech32 address of the recipient
+    pub recipient_address: String,
+    /// the amount in the base denomination (e.g. basecro or uatom);
+    /// the user should be prompted to enter it if missing
+    pub amount: Option<String>,
+    /// the base denomination (by default, the fee token of the chain)
+    pub denom: Option<String>,
+    /// the memo of the transaction (e.g. the order or invoice id)
+    pub memo: Option<String>,
+    /// the Tendermint chain id (by default, the chain of the address bech32 prefix)
+    pub chain_id: Option<String>,
+}
+
+/// check the amount is an integer in the base denomination
+fn check_amount(amount: &str) -> Result<(), CosmosPaymentUriError> {
+    if amount.is_empty() || !amount.chars().all(|c| c.is_ascii_digit()) {
+        return Err(CosmosPaymentUriError::InvalidAmount(amount.to_owned()));
+    }
+    Ok(())
+}
+
+/// set the parameter only once
+fn set_once(
+    field: &mut Option<String>,
+    key: &str,
+    value: String,
+) -> Result<(), CosmosPaymentUriError> {
+    if field.replace(value).is_some() {
+        return Err(CosmosPaymentUriError::DuplicatedParameter(key.to_owned()));
+    }
+    Ok(())
+}
+
+impl CosmosPaymentRequest {
+    /// Constructor of the payment request of the amount (a decimal integer string,
+    /// as it may not fit in `u64`) in the base denomination
+    pub fn new(
+        recipient_address: &str,
+        amount: &str,
+        denom: &str,
+        chain_id: Option<String>,
+    ) -> Result<Self, CosmosPaymentUriError> {
+        AccountId::from_str(recipient_address)
+            .map_err(|e| CosmosPaymentUriError::InvalidAddress(e.to_string()))?;
+        check_amount(amount)?;
+        Ok(Self {
+            recipient_address: recipient_address.to_owned(),
+            amount: Some(amount.to_owned()),
+            denom: Some(denom.to_owned()),
+            memo: None,
+            chain_id,
+        })
+    }
+
+    /// the URI of the request in the format
+    pub fn to_uri(&self, format: CosmosPaymentUriFormat) -> String {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        match (format, &self.amount, &self.denom) {
+            (CosmosPaymentUriFormat::Keplr, Some(amount), Some(denom)) => {
+                query.append_pair("amount", &format!("{amount}{denom}"));
+            }
+            _ => {
+                if let Some(amount) = &self.amount {
+                    query.append_pair("amount", amount);
+                }
+                if let Some(denom) = &self.denom {
+                    query.append_pair("denom", denom);
+                }
+            }
+        }
+        if let Some(memo) = &self.memo {
+            query.append_pair("memo", memo);
+        }
+        if let Some(chain_id) = &self.chain_id {
+            let key = match format {
+                CosmosPaymentUriFormat::Standard => "chain_id",
+                CosmosPaymentUriFormat::Keplr => "chainId",
+            };
+            query.append_pair(key, chain_id);
+        }
+        let query = query.finish();
+        if query.is_empty() {
+            format!("{COSMOS_PAYMENT_SCHEME}:{}", self.recipient_address)
+        } else {
+            format!("{COSMOS_PAYMENT_SCHEME}:{}?{query}", self.recipient_address)
+        }
+    }
+
+    /// Check the recipient address and the amount of the request
+    pub fn validate(&self) -> Result<(), CosmosPaymentUriError> {
+        self.bech32_prefix()?;
+        if let Some(amount) = &self.amount {
+            check_amount(amount)?;
+        }
+        Ok(())
+    }
+
+    /// the bech32 prefix of the recipient address
+    pub fn bech32_prefix(&self) -> Result<String, CosmosPaymentUriError> {
+        let account = AccountId::from_str(&self.recipient_address)
+            .map_err(|e| CosmosPaymentUriError::InvalidAddress(e.to_string()))?;
+        Ok(account.prefix().to_owned())
+    }
+
+    /// the descriptor of the chain of the request in the registry:
+    /// the one of the chain id, or else the one of the address bech32 prefix
+    pub fn find_chain<'a>(
+        &self,
+        registry: &'a ChainRegistry,
+    ) -> Result<&'a ChainDescriptor, CosmosPaymentUriError> {
+        match &self.chain_id {
+            Some(chain_id) => registry
+                .get_by_chain_id(chain_id)
+                .ok_or_else(|| CosmosPaymentUriError::UnknownChain(chain_id.clone())),
+            None => {
+                let prefix = self.bech32_prefix()?;
+                registry
+                    .chains()
+                    .find(|chain| chain.bech32_prefix.as_deref() == Some(prefix.as_str()))
+                    .ok_or(CosmosPaymentUriError::UnknownChain(prefix))
+            }
+        }
+    }
+
+    /// Convert the request to the `MsgSend` message, checking its address and amount
+    /// (the denomination must be set, e.g. by `with_chain_defaults`)
+    pub fn to_bank_send(&self) -> Result<CosmosSDKMsg, CosmosPaymentUriError> {
+        self.validate()?;
+        let amount = self
+            .amount
+            .clone()
+            .ok_or(CosmosPaymentUriError::MissingField("amount"))?;
+        let denom = self
+            .denom
+            .clone()
+            .ok_or(CosmosPaymentUriError::MissingField("denom"))?;
+        Ok(CosmosSDKMsg::BankSend {
+            recipient_address: self.recipient_address.clone(),
+            amount: SingleCoin::Other { amount, denom },
+        })
+    }
+
+    /// Check the request is for the chain of the chain id and the bech32 prefix
+    pub fn check_chain(
+        &self,
+        chain_id: &str,
+        bech32_prefix: Option<&str>,
+    ) -> Result<(), CosmosPaymentUriError> {
+        if let Some(requested) = &self.chain_id {
+            if requested != chain_id {
+                return Err(CosmosPaymentUriError::ChainMismatch {
+                    requested: requested.clone(),
+                    chain: chain_id.to_owned(),
+                });
+            }
+        }
+        let prefix = self.bech32_prefix()?;
+        if bech32_prefix != Some(prefix.as_str()) {
+            return Err(CosmosPaymentUriError::ChainMismatch {
+                requested: prefix,
+                chain: chain_id.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Fill the missing chain id and denomination with the ones of the chain
+    /// (its fee token), checking the request is for the chain
+    pub fn with_chain_defaults(
+        mut self,
+        chain: &ChainDescriptor,
+    ) -> Result<Self, CosmosPaymentUriError> {
+        self.check_chain(&chain.chain_id, chain.bech32_prefix.as_deref())?;
+        if self.denom.is_none() {
+            let fee_token = chain
+                .fee_token()
+                .map_err(CosmosPaymentUriError::TxInfoError)?;
+            self.denom = Some(fee_token.denom.clone());
+        }
+        self.chain_id = Some(chain.chain_id.clone());
+        Ok(self)
+    }
+
+    /// Convert the request to the `MsgSend` message and the transaction info on the chain
+    /// with the default gas limit, the average gas price and the memo of the request
+    pub fn to_bank_send_tx(
+        &self,
+        chain: &ChainDescriptor,
+        account_number: u64,
+        sequence_number: u64,
+    ) -> Result<(CosmosSDKTxInfo, CosmosSDKMsg), CosmosPaymentUriError> {
+        let request = self.clone().with_chain_defaults(chain)?;
+        let tx_info = chain
+            .to_tx_info(
+                account_number,
+                sequence_number,
+                DEFAULT_PAYMENT_GAS_LIMIT,
+                GasPriceTier::Average,
+                0,
+                request.memo.clone(),
+            )
+            .map_err(CosmosPaymentUriError::TxInfoError)?;
+        Ok((tx_info, request.to_bank_send()?))
+    }
+}
+
+/// Parse the payment request URI (e.g. scanned from a QR code)
+pub fn parse_cosmos_payment_uri(uri: &str) -> Result<CosmosPaymentRequest, CosmosPaymentUriError> {
+    CosmosPaymentRequest::from_str(uri)
+}
+
+/// the URI of the payment request in the format, checking its address and amount
+pub fn build_cosmos_payment_uri(
+    request: CosmosPaymentRequest,
+    format: CosmosPaymentUriFormat,
+) -> Result<String, CosmosPaymentUriError> {
+    request.validate()?;
+    Ok(request.to_uri(format))
+}
+
+/// Convert the payment request (with the denomination) to the `MsgSend` message
+pub fn cosmos_payment_to_bank_send(
+    request: CosmosPaymentRequest,
+) -> Result<CosmosSDKMsg, CosmosPaymentUriError> {
+    request.to_bank_send()
+}
+
+impl Display for CosmosPaymentRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_uri(CosmosPaymentUriFormat::Standard))
+    }
+}
+
+impl FromStr for CosmosPaymentRequest {
+    type Err = CosmosPaymentUriError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, rest) = s
+            .split_once(':')
+            .ok_or_else(|| CosmosPaymentUriError::InvalidUri(s.to_owned()))?;
+        if !scheme.eq_ignore_ascii_case(COSMOS_PAYMENT_SCHEME) {
+            return Err(CosmosPaymentUriError::InvalidUri(s.to_owned()));
+        }
+        let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+        // some wallets add the "//" of the hierarchical URIs
+        let address = address.trim_start_matches("//").trim_end_matches('/');
+        AccountId::from_str(address)
+            .map_err(|e| CosmosPaymentUriError::InvalidAddress(e.to_string()))?;
+
+        let mut amount = None;
+        let mut denom = None;
+        let mut memo = None;
+        let mut chain_id = None;
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            let value = value.into_owned();
+            match key.as_ref() {
+                "amount" => set_once(&mut amount, &key, value)?,
+                "denom" => set_once(&mut denom, &key, value)?,
+                "memo" => set_once(&mut memo, &key, value)?,
+                "chain_id" | "chainId" | "chain-id" => set_once(&mut chain_id, &key, value)?,
+                // unknown parameters are ignored as they're only suggestions
+                _ => {}
+            }
+        }
+        // the Keplr-compatible amount with the denomination, e.g. "1000basecro"
+        if let Some(coin) = amount.take() {
+            let split = coin
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(coin.len());
+            let (value, coin_denom) = coin.split_at(split);
+            check_amount(value)?;
+            if !coin_denom.is_empty() {
+                set_once(&mut denom, "denom", coin_denom.to_owned())?;
+            }
+            amount = Some(value.to_owned());
+        }
+        Ok(Self {
+            recipient_address: address.to_owned(),
+            amount,
+            denom,
+            memo,
+            chain_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CRO_ADDRESS: &str = "cro1cvqgv7qaxdv9j9yswttr8xndyyyf30wfczx936";
+    const COSMOS_ADDRESS: &str = "cosmos19dyl0uyzes4k23lscla02n06fc22h4uqsdwq6z";
+
+    #[test]
+    pub fn test_parse_and_display() {
+        let uri = format!(
+            "cosmos:{CRO_ADDRESS}?amount=1000&denom=basecro&memo=order+%2342&chain_id=crypto-org-chain-mainnet-1"
+        );
+        let request = CosmosPaymentRequest::from_str(&uri).unwrap();
+        assert_eq!(request.recipient_address, CRO_ADDRESS);
+        assert_eq!(request.amount.as_deref(), Some("1000"));
+        assert_eq!(request.denom.as_deref(), Some("basecro"));
+        assert_eq!(request.memo.as_deref(), Some("order #42"));
+        assert_eq!(
+            request.chain_id.as_deref(),
+            Some("crypto-org-chain-mainnet-1")
+        );
+        assert_eq!(request.to_string(), uri);
+        assert_eq!(
+            request.to_uri(CosmosPaymentUriFormat::Keplr),
+            format!("cosmos:{CRO_ADDRESS}?amount=1000basecro&memo=order+%2342&chainId=crypto-org-chain-mainnet-1")
+        );
+
+        let keplr = CosmosPaymentRequest::from_str(&format!(
+            "cosmos:{COSMOS_ADDRESS}?amount=25uatom&chainId=cosmoshub-4"
+        ))
+        .unwrap();
+        assert_eq!(keplr.amount.as_deref(), Some("25"));
+        assert_eq!(keplr.denom.as_deref(), Some("uatom"));
+        assert_eq!(keplr.chain_id.as_deref(), Some("cosmoshub-4"));
+
+        assert!(CosmosPaymentRequest::from_str(&format!("ethereum:{CRO_ADDRESS}")).is_err());
+        assert!(CosmosPaymentRequest::from_str("cosmos:cro1invalid").is_err());
+        assert!(
+            CosmosPaymentRequest::from_str(&format!("cosmos:{CRO_ADDRESS}?amount=1.5")).is_err()
+        );
+        assert!(CosmosPaymentRequest::from_str(&format!(
+            "cosmos:{CRO_ADDRESS}?amount=1basecro&denom=basetcro"
+        ))
+        .is_err());
+    }
+
+    #[test]
+    pub fn test_bank_send_tx() {
+        let registry = ChainRegistry::with_defaults();
+        let request =
+            CosmosPaymentRequest::from_str(&format!("cosmos:{CRO_ADDRESS}?amount=1000&memo=42"))
+                .unwrap();
+        let chain = request.find_chain(&registry).unwrap();
+        assert_eq!(chain.chain_id, "crypto-org-chain-mainnet-1");
+        let (tx_info, msg) = request.to_bank_send_tx(chain, 1, 2).unwrap();
+        assert_eq!(tx_info.account_number, 1);
+        assert_eq!(tx_info.sequence_number, 2);
+        assert_eq!(tx_info.gas_limit, DEFAULT_PAYMENT_GAS_LIMIT);
+        assert_eq!(tx_info.memo_note.as_deref(), Some("42"));
+        match msg {
+            CosmosSDKMsg::BankSend {
+                recipient_address,
+                amount,
+            } => {
+                assert_eq!(recipient_address, CRO_ADDRESS);
+                assert_eq!(
+                    amount,
+                    SingleCoin::Other {
+                        amount: "1000".to_owned(),
+                        denom: "basecro".to_owned()
+                    }
+                );
+            }
+            _ => panic!("not a bank send"),
+        }
+
+        // the request is for Crypto.org Chain, not for Cosmos Hub
+        let cosmos_hub = registry.get_by_chain_id("cosmoshub-4").unwrap();
+        assert!(request.to_bank_send_tx(cosmos_hub, 1, 2).is_err());
+        let request = CosmosPaymentRequest::new(COSMOS_ADDRESS, "25", "uatom", None).unwrap();
+        assert!(request.to_bank_send_tx(cosmos_hub, 1, 2).is_ok());
+
+        // the amounts beyond u64 are kept
+        let amount = "100000000000000000000000";
+        let request = CosmosPaymentRequest::new(COSMOS_ADDRESS, amount, "uatom", None).unwrap();
+        match cosmos_payment_to_bank_send(request).unwrap() {
+            CosmosSDKMsg::BankSend {
+                amount: SingleCoin::Other { amount: sent, .. },
+                ..
+            } => assert_eq!(sent, amount),
+            _ => panic!("not a bank send"),
+        }
+        assert!(CosmosPaymentRequest::new(COSMOS_ADDRESS, "1.5", "uatom", None).is_err());
+        assert!(CosmosPaymentRequest::new(COSMOS_ADDRESS, "", "uatom", None).is_err());
+    }
+}
@@ -1,4 +1,6 @@
 #![allow(ambiguous_glob_reexports)]
+/// Chain registry: chain descriptors with endpoints, fee tokens and explorers
+pub mod chain_registry;
 /// Eth contract types generated from ABI
 pub mod contract;
 /// interactions with remote node RPC / API (querying, broadcast etc.)
@@ -12,7 +14,11 @@ mod wallet;
 #[cfg(feature = "login")]
 mod login;
 
-/// QR code module: encoding and decoding of EIP-681 strings
+/// Cosmos payment module: encoding and decoding of Cosmos SDK payment request URIs
+#[cfg(feature = "qr-code")]
+mod cosmos_payment;
+/// QR code module: encoding and decoding of EIP-681 strings, QR images
+/// and the UR payloads of air-gapped signing
 #[cfg(feature = "qr-code")]
 mod qr_code;
 
@@ -25,15 +31,32 @@ pub use defi_wallet_core_proto as proto;
 pub use proto::chainmain::nft::v1::*;
 pub use proto::luna_classic::wasm::v1beta1::*;
 
+pub use chain_registry::*;
 pub use cosmrs::{tx::Msg, AccountId, Coin};
 pub use eyre::{Report as ErrorReport, Result};
 pub use ibc_proto::ibc::core::client::v1::Height;
 
+#[cfg(feature = "qr-code")]
+pub use cosmos_payment::*;
 #[cfg(feature = "login")]
 pub use login::*;
 pub use node::*;
 #[cfg(feature = "qr-code")]
-pub use qr_code::EIP681Request;
+pub use qr_code::{
+    build_eip681_transaction, decode_qr_grayscale, render_qr_png, render_qr_svg,
+    render_qr_terminal, EIP681ParseError, EIP681Request, Parameter as EIP681Parameter, QrCodeError,
+    QrErrorCorrection, QrMatrix, QrRenderOptions, Value as EIP681Value,
+};
+
+#[cfg(all(feature = "qr-code", not(target_arch = "wasm32")))]
+pub use qr_code::build_eip681_transaction_blocking;
+#[cfg(feature = "qr-code")]
+pub use qr_code::{
+    AirGapError, AirGapPayload, CosmosSignDataType, CosmosSignRequest, CosmosSignature,
+    EthSignDataType, EthSignRequest, EthSignature, UrFrameDecoder, UrFrameEncoder, UrKeyPath,
+    UrPathComponent, UrPayload, COSMOS_SIGNATURE_UR_TYPE, COSMOS_SIGN_REQUEST_UR_TYPE,
+    DEFAULT_UR_FRAGMENT_LENGTH, ETH_SIGNATURE_UR_TYPE, ETH_SIGN_REQUEST_UR_TYPE,
+};
 pub use transaction::*;
 pub use wallet::*;
 #[cfg(feature = "uniffi-binding")]
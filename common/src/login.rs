@@ -1,6 +1,240 @@
-use crate::{EthError, SecretKey};
-use siwe::{Message, VerificationOpts};
+use crate::{is_valid_eip1271_signature, recover_eth_personal_signer, EthError, SecretKey};
+use ethers::prelude::Address;
+use ethers::utils::{hash_message, hex, to_checksum};
+use siwe::{Message, TimeStamp, Version};
 use std::fmt::Display;
+use std::str::FromStr;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// The reasons why the EIP-4361 message cannot be built or verified
+#[derive(Debug, thiserror::Error)]
+pub enum LoginError {
+    #[error("Invalid {field}: {reason}")]
+    InvalidField { field: String, reason: String },
+    #[error("Domain mismatch: expected {expected}, got {actual}")]
+    DomainMismatch { expected: String, actual: String },
+    #[error("Nonce mismatch: expected {expected}, got {actual}")]
+    NonceMismatch { expected: String, actual: String },
+    #[error("The message is not valid before {0}")]
+    NotYetValid(String),
+    #[error("The message expired at {0}")]
+    Expired(String),
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(EthError),
+    #[error("Signer mismatch: expected {expected}, recovered {recovered}")]
+    SignerMismatch { expected: String, recovered: String },
+    #[error("The contract wallet {0} rejected the signature")]
+    ContractSignatureRejected(String),
+    #[error("Contract wallet verification failed: {0}")]
+    ContractVerificationError(EthError),
+}
+
+impl From<LoginError> for EthError {
+    fn from(error: LoginError) -> Self {
+        match error {
+            LoginError::InvalidSignature(error) | LoginError::ContractVerificationError(error) => {
+                error
+            }
+            _ => EthError::SignatureError,
+        }
+    }
+}
+
+impl LoginError {
+    fn invalid_field(field: &str, reason: impl Display) -> Self {
+        LoginError::InvalidField {
+            field: field.to_owned(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// the timestamp from its RFC 3339 string
+fn parse_timestamp(field: &str, value: &str) -> Result<OffsetDateTime, LoginError> {
+    OffsetDateTime::parse(value, &Rfc3339).map_err(|e| LoginError::invalid_field(field, e))
+}
+
+/// the timestamp of the EIP-4361 message from its RFC 3339 string
+fn parse_message_timestamp(field: &str, value: &str) -> Result<TimeStamp, LoginError> {
+    TimeStamp::from_str(value).map_err(|e| LoginError::invalid_field(field, e))
+}
+
+/// Generate a random nonce (alphanumeric, as required by EIP-4361)
+/// to be sent to the client and checked in the verification
+pub fn generate_login_nonce() -> String {
+    siwe::generate_nonce()
+}
+
+/// The builder of the EIP-4361 (Sign-In with Ethereum) message;
+/// the nonce is generated and the issued-at time is the current time if they aren't set
+pub struct LoginInfoBuilder {
+    domain: String,
+    address: String,
+    uri: String,
+    chain_id: u64,
+    statement: Option<String>,
+    nonce: Option<String>,
+    nonce_generator: fn() -> String,
+    issued_at: Option<String>,
+    expiration_time: Option<String>,
+    not_before: Option<String>,
+    request_id: Option<String>,
+    resources: Vec<String>,
+}
+
+impl LoginInfoBuilder {
+    /// the domain (RFC 3986 authority, e.g. "service.org") requesting the signing,
+    /// the (hex) address of the signer, the URI (e.g. "https://service.org/login")
+    /// referring to the resource that is the subject of the signing, and the chain id
+    pub fn new(domain: &str, address: &str, uri: &str, chain_id: u64) -> Self {
+        Self {
+            domain: domain.to_owned(),
+            address: address.to_owned(),
+            uri: uri.to_owned(),
+            chain_id,
+            statement: None,
+            nonce: None,
+            nonce_generator: generate_login_nonce,
+            issued_at: None,
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: vec![],
+        }
+    }
+
+    /// the human-readable statement (a single line) the user is asked to sign
+    pub fn statement(mut self, statement: &str) -> Self {
+        self.statement = Some(statement.to_owned());
+        self
+    }
+
+    /// the nonce (at least 8 alphanumeric characters) to prevent replay attacks
+    pub fn nonce(mut self, nonce: &str) -> Self {
+        self.nonce = Some(nonce.to_owned());
+        self
+    }
+
+    /// the function generating the nonce if it's not set
+    /// (by default, `generate_login_nonce`)
+    pub fn nonce_generator(mut self, nonce_generator: fn() -> String) -> Self {
+        self.nonce_generator = nonce_generator;
+        self
+    }
+
+    /// the issued-at time (RFC 3339, e.g. "2021-09-30T16:25:24Z")
+    pub fn issued_at(mut self, issued_at: &str) -> Self {
+        self.issued_at = Some(issued_at.to_owned());
+        self
+    }
+
+    /// the expiration time (RFC 3339)
+    pub fn expiration_time(mut self, expiration_time: &str) -> Self {
+        self.expiration_time = Some(expiration_time.to_owned());
+        self
+    }
+
+    /// the time (RFC 3339) when the message becomes valid
+    pub fn not_before(mut self, not_before: &str) -> Self {
+        self.not_before = Some(not_before.to_owned());
+        self
+    }
+
+    /// the system-specific identifier of the request
+    pub fn request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.to_owned());
+        self
+    }
+
+    /// add the URI of a resource the user wishes to have resolved as part of the authentication
+    pub fn resource(mut self, resource: &str) -> Self {
+        self.resources.push(resource.to_owned());
+        self
+    }
+
+    /// build the message, validating its fields
+    pub fn build(self) -> Result<LoginInfo, LoginError> {
+        let address = Address::from_str(&self.address)
+            .map_err(|e| LoginError::invalid_field("address", e))?;
+        if let Some(statement) = &self.statement {
+            if statement.contains('\n') {
+                return Err(LoginError::invalid_field(
+                    "statement",
+                    "it must be a single line",
+                ));
+            }
+        }
+        let nonce = self.nonce.unwrap_or_else(self.nonce_generator);
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(LoginError::invalid_field(
+                "nonce",
+                "it must be at least 8 alphanumeric characters",
+            ));
+        }
+        let issued_at = match self.issued_at {
+            Some(issued_at) => parse_message_timestamp("issued-at time", &issued_at)?,
+            None => {
+                let now = OffsetDateTime::now_utc()
+                    .format(&Rfc3339)
+                    .map_err(|e| LoginError::invalid_field("issued-at time", e))?;
+                parse_message_timestamp("issued-at time", &now)?
+            }
+        };
+        let expiration_time = self
+            .expiration_time
+            .map(|time| parse_message_timestamp("expiration time", &time))
+            .transpose()?;
+        let not_before = self
+            .not_before
+            .map(|time| parse_message_timestamp("not-before time", &time))
+            .transpose()?;
+        let resources = self
+            .resources
+            .iter()
+            .map(|resource| {
+                resource
+                    .parse()
+                    .map_err(|e| LoginError::invalid_field("resource", e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let msg = Message {
+            domain: self
+                .domain
+                .parse()
+                .map_err(|e| LoginError::invalid_field("domain", e))?,
+            address: address.into(),
+            statement: self.statement,
+            uri: self
+                .uri
+                .parse()
+                .map_err(|e| LoginError::invalid_field("uri", e))?,
+            version: Version::V1,
+            chain_id: self.chain_id,
+            nonce,
+            issued_at,
+            expiration_time,
+            not_before,
+            request_id: self.request_id,
+            resources,
+        };
+        Ok(LoginInfo { msg })
+    }
+}
+
+/// The options of the EIP-4361 message verification
+#[derive(Debug, Clone, Default)]
+pub struct LoginVerificationOpts {
+    /// the expected domain (the one of the server)
+    pub domain: Option<String>,
+    /// the expected nonce (the one issued to the client)
+    pub nonce: Option<String>,
+    /// the time (RFC 3339) the message should be valid at (by default, the current time)
+    pub timestamp: Option<String>,
+    /// the node Web3 connection URL, used to verify the signature of a contract wallet
+    /// (as per EIP-1271) if it's not signed by a key of the address
+    pub web3api_url: Option<String>,
+}
 
 /// The wrapper structure that contains
 /// all information from the EIP-4361 plaintext message:
@@ -31,12 +265,89 @@ impl LoginInfo {
     /// ...
     /// NOTE: the server may still need to do extra verifications according to its needs
     /// (e.g. verify chain-id, nonce, uri + possibly fetch additional data associated
-    /// with the given Ethereum address, such as ERC-20/ERC-721/ERC-1155 asset ownership)
+    /// with the given Ethereum address, such as ERC-20/ERC-721/ERC-1155 asset ownership);
+    /// `verify_with_opts` checks the domain and the nonce as well, and returns the reason
+    /// of the failure
     pub async fn verify(&self, signature: &[u8]) -> Result<(), EthError> {
-        // FIXME: domain, nonce, timestamp to be passed
-        let opts = VerificationOpts::default();
-        let result = self.msg.verify(signature, &opts).await;
-        result.map_err(|_e| EthError::SignatureError).map(|_| ())
+        Ok(self
+            .verify_with_opts(signature, &LoginVerificationOpts::default())
+            .await?)
+    }
+
+    /// It verifies the message as `verify` does, plus:
+    /// - the domain and the nonce match the expected ones (if given)
+    /// - the time is valid at the given timestamp (if given)
+    /// - if the signature isn't of a key of the address, the address is a contract wallet
+    ///   accepting it as per EIP-1271 (if the node Web3 connection URL is given)
+    pub async fn verify_with_opts(
+        &self,
+        signature: &[u8],
+        opts: &LoginVerificationOpts,
+    ) -> Result<(), LoginError> {
+        if let Some(domain) = &opts.domain {
+            let actual = self.msg.domain.to_string();
+            if &actual != domain {
+                return Err(LoginError::DomainMismatch {
+                    expected: domain.clone(),
+                    actual,
+                });
+            }
+        }
+        if let Some(nonce) = &opts.nonce {
+            if &self.msg.nonce != nonce {
+                return Err(LoginError::NonceMismatch {
+                    expected: nonce.clone(),
+                    actual: self.msg.nonce.clone(),
+                });
+            }
+        }
+        self.verify_time(opts.timestamp.as_deref())?;
+
+        let message = self.msg.to_string();
+        let address = to_checksum(&Address::from(self.msg.address), None);
+        let signature = format!("0x{}", hex::encode(signature));
+        let recovered = recover_eth_personal_signer(&message, &signature);
+        if matches!(&recovered, Ok(recovered) if recovered == &address) {
+            return Ok(());
+        }
+        match &opts.web3api_url {
+            Some(web3api_url) => {
+                let hash = format!("0x{}", hex::encode(hash_message(&message)));
+                let valid = is_valid_eip1271_signature(&address, &hash, &signature, web3api_url)
+                    .await
+                    .map_err(LoginError::ContractVerificationError)?;
+                if valid {
+                    Ok(())
+                } else {
+                    Err(LoginError::ContractSignatureRejected(address))
+                }
+            }
+            None => Err(LoginError::SignerMismatch {
+                expected: address,
+                recovered: recovered.map_err(LoginError::InvalidSignature)?,
+            }),
+        }
+    }
+
+    /// check the message is valid at the timestamp (RFC 3339) or the current time
+    fn verify_time(&self, timestamp: Option<&str>) -> Result<(), LoginError> {
+        let timestamp = match timestamp {
+            Some(timestamp) => parse_timestamp("timestamp", timestamp)?,
+            None => OffsetDateTime::now_utc(),
+        };
+        if let Some(not_before) = &self.msg.not_before {
+            let not_before = not_before.to_string();
+            if timestamp < parse_timestamp("not-before time", &not_before)? {
+                return Err(LoginError::NotYetValid(not_before));
+            }
+        }
+        if let Some(expiration_time) = &self.msg.expiration_time {
+            let expiration_time = expiration_time.to_string();
+            if timestamp >= parse_timestamp("expiration time", &expiration_time)? {
+                return Err(LoginError::Expired(expiration_time));
+            }
+        }
+        Ok(())
     }
 }
 
@@ -48,7 +359,10 @@ impl Display for LoginInfo {
 
 #[cfg(test)]
 mod tests {
-    use crate::{EthNetwork, LoginInfo, SecretKey, WalletCoin, WalletCoinFunc};
+    use crate::{
+        EthError, EthNetwork, LoginError, LoginInfo, LoginInfoBuilder, LoginVerificationOpts,
+        SecretKey, WalletCoin, WalletCoinFunc,
+    };
     use ethers::prelude::Address;
     use siwe::Message;
     use std::str::FromStr;
@@ -100,6 +414,115 @@ mod tests {
         assert!(login_info.verify(&sig).await.is_ok());
     }
 
+    #[tokio::test]
+    pub async fn test_verify_with_opts() {
+        let wallet = SecretKey::default();
+        let login_info = get_logininfo(Some(&wallet));
+        let sig = login_info.sign(&wallet).unwrap();
+
+        let opts = LoginVerificationOpts {
+            domain: Some("service.org".to_owned()),
+            nonce: Some("32891756".to_owned()),
+            timestamp: Some("2021-09-30T16:30:00Z".to_owned()),
+            web3api_url: None,
+        };
+        assert!(login_info.verify_with_opts(&sig, &opts).await.is_ok());
+
+        let wrong_domain = LoginVerificationOpts {
+            domain: Some("evil.org".to_owned()),
+            ..opts.clone()
+        };
+        assert!(matches!(
+            login_info.verify_with_opts(&sig, &wrong_domain).await,
+            Err(LoginError::DomainMismatch { .. })
+        ));
+        let wrong_nonce = LoginVerificationOpts {
+            nonce: Some("12345678".to_owned()),
+            ..opts.clone()
+        };
+        assert!(matches!(
+            login_info.verify_with_opts(&sig, &wrong_nonce).await,
+            Err(LoginError::NonceMismatch { .. })
+        ));
+
+        let other_wallet = SecretKey::default();
+        let other_sig = login_info.sign(&other_wallet).unwrap();
+        assert!(matches!(
+            login_info.verify_with_opts(&other_sig, &opts).await,
+            Err(LoginError::SignerMismatch { .. })
+        ));
+        assert!(matches!(
+            login_info.verify_with_opts(&sig[..10], &opts).await,
+            Err(LoginError::InvalidSignature(_))
+        ));
+    }
+
+    #[tokio::test]
+    pub async fn test_builder() {
+        let wallet = SecretKey::default();
+        let address = WalletCoinFunc {
+            coin: WalletCoin::Ethereum {
+                network: EthNetwork::Mainnet,
+            },
+        }
+        .derive_address(&wallet)
+        .unwrap();
+        let login_info =
+            LoginInfoBuilder::new("service.org", &address, "https://service.org/login", 1)
+                .statement("I accept the ServiceOrg Terms of Service: https://service.org/tos")
+                .issued_at("2021-09-30T16:25:24Z")
+                .not_before("2021-09-30T16:25:24Z")
+                .expiration_time("2021-09-30T17:25:24Z")
+                .resource("https://example.com/my-web2-claim.json")
+                .build()
+                .unwrap();
+        assert!(login_info.msg.nonce.len() >= 8);
+        assert_eq!(
+            Message::from_str(&login_info.to_string()).unwrap().nonce,
+            login_info.msg.nonce
+        );
+
+        let sig = login_info.sign(&wallet).unwrap();
+        let at = |timestamp: &str| LoginVerificationOpts {
+            timestamp: Some(timestamp.to_owned()),
+            ..Default::default()
+        };
+        assert!(login_info
+            .verify_with_opts(&sig, &at("2021-09-30T16:30:00Z"))
+            .await
+            .is_ok());
+        assert!(matches!(
+            login_info
+                .verify_with_opts(&sig, &at("2021-09-30T16:00:00Z"))
+                .await,
+            Err(LoginError::NotYetValid(_))
+        ));
+        assert!(matches!(
+            login_info
+                .verify_with_opts(&sig, &at("2021-09-30T17:25:24Z"))
+                .await,
+            Err(LoginError::Expired(_))
+        ));
+        // the current time is after the expiration
+        assert!(matches!(
+            login_info.verify(&sig).await,
+            Err(EthError::SignatureError)
+        ));
+
+        assert!(
+            LoginInfoBuilder::new("service.org", &address, "https://service.org/login", 1)
+                .nonce("short")
+                .build()
+                .is_err()
+        );
+        assert!(
+            LoginInfoBuilder::new("service.org", &address, "https://service.org/login", 1)
+                .statement("two\nlines")
+                .build()
+                .is_err()
+        );
+    }
+
     #[test]
     pub fn test_display() {
         let expected_text = r#"service.org wants you to sign in with your Ethereum account:
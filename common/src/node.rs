@@ -1,10 +1,18 @@
 #![allow(ambiguous_glob_reexports)]
 
+/// gRPC, gRPC-web or LCD REST transport of the Cosmos SDK queries
+#[macro_use]
+mod transport;
+
 /// wrappers around Cosmos SDK REST API and Tendermint RPC
 /// FIXME: switch to grpc when grpc-web works in CosmRS: https://github.com/cosmos/cosmos-rust/pull/157
 mod cosmos_sdk;
 /// wrappers around Web3 API + basic contract types
 pub mod ethereum;
+/// ICS-20 denomination traces and transfer path helpers
+pub mod ibc_denom;
+/// relative timeouts of ICS-20 transfers computed from the counterparty chain
+pub mod ibc_timeout;
 /// wrappers around chainmain NFT grpc/grpc-web API
 pub mod nft;
 /// wasm binding related functions
@@ -14,6 +22,9 @@ mod error;
 pub use cosmos_sdk::*;
 pub use error::*;
 pub use ethereum::*;
+pub use ibc_denom::*;
+pub use ibc_timeout::*;
 pub use nft::*;
+pub use transport::*;
 #[cfg(target_arch = "wasm32")]
 pub use wasm_binding::*;
@@ -1,10 +1,13 @@
 use super::error::RestError;
 #[cfg(not(target_arch = "wasm32"))]
+use super::transport::block_on;
+use super::transport::{rest_get, rest_post, QueryTransport};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use cosmos_sdk_proto::cosmos::{
+    auth::v1beta1::{query_client::QueryClient as AuthQueryClient, QueryAccountRequest},
     bank::v1beta1::{query_client::QueryClient, Metadata, QueryDenomMetadataRequest},
     tx::v1beta1::{service_client::ServiceClient, SimulateRequest},
 };
-#[cfg(not(target_arch = "wasm32"))]
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
@@ -13,9 +16,14 @@ use tendermint_rpc::{
     request, response,
 };
 
+mod account;
 mod balance_query;
+#[cfg(not(target_arch = "wasm32"))]
+mod light_client;
 
 pub use balance_query::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use light_client::*;
 
 /// The raw response from the account API
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -120,30 +128,88 @@ pub fn get_account_details_blocking(
     Ok(resp)
 }
 
+/// return the account details over the given transport
+pub async fn query_account_details(
+    transport: &QueryTransport,
+    address: &str,
+) -> Result<RawRpcAccountResponse, RestError> {
+    if let QueryTransport::Rest(api_url) = transport {
+        return get_account_details(api_url, address).await;
+    }
+    let mut client = grpc_client!(AuthQueryClient, transport.grpc_url()?);
+    let request = QueryAccountRequest {
+        address: address.to_owned(),
+    };
+    match client.account(request).await {
+        Ok(response) => {
+            let account = response
+                .into_inner()
+                .account
+                .ok_or(RestError::MissingResult)?;
+            Ok(RawRpcAccountResponse::OkResponse {
+                account: account::decode_account_any(account).map_err(RestError::DecodeError)?,
+            })
+        }
+        Err(status) if status.code() == tonic::Code::NotFound => {
+            Ok(RawRpcAccountResponse::ErrorResponse {
+                code: account::NOT_FOUND_CODE,
+                message: status.message().to_owned(),
+                details: vec![],
+            })
+        }
+        Err(status) => Err(RestError::GRPCError(status)),
+    }
+}
+
+/// the gas info in the simulation response of the REST API
+#[serde_as]
+#[derive(Deserialize)]
+struct RestGasInfo {
+    #[serde_as(as = "DisplayFromStr")]
+    gas_used: u64,
+}
+
+/// the simulation response of the REST API
+#[derive(Deserialize)]
+struct RestSimulateResponse {
+    gas_info: Option<RestGasInfo>,
+}
+
+/// given the query transport and the raw signed transaction bytes,
+/// it'll submit the transaction for simulating its execution and return the used gas.
+pub async fn query_simulate(transport: &QueryTransport, tx: Vec<u8>) -> Result<u64, RestError> {
+    if let QueryTransport::Rest(api_url) = transport {
+        let body = serde_json::json!({ "tx_bytes": STANDARD.encode(tx) });
+        let response: RestSimulateResponse =
+            rest_post(api_url, "/cosmos/tx/v1beta1/simulate", &body).await?;
+        return response
+            .gas_info
+            .map(|gas_info| gas_info.gas_used)
+            .ok_or(RestError::MissingResult);
+    }
+    let mut client = grpc_client!(ServiceClient, transport.grpc_url()?);
+    let request = SimulateRequest {
+        tx_bytes: tx,
+        ..Default::default()
+    };
+    let res = client
+        .simulate(request)
+        .await
+        .map_err(RestError::GRPCError)?;
+    res.into_inner()
+        .gas_info
+        .map(|gas_info| gas_info.gas_used)
+        .ok_or(RestError::MissingResult)
+}
+
 /// given the gRPC endpoint and the raw signed transaction bytes,
 /// it'll submit the transaction for simulating its execution and return the used gas.
 #[cfg(not(target_arch = "wasm32"))]
 pub fn simulate_blocking(grpc_url: &str, tx: Vec<u8>) -> Result<u64, RestError> {
-    let rt = tokio::runtime::Runtime::new().map_err(|_err| RestError::AsyncRuntimeError)?;
-    let result = rt.block_on(async move {
-        let mut client = ServiceClient::connect(grpc_url.to_owned())
-            .await
-            .map_err(RestError::GRPCTransportError)?;
-        let request = SimulateRequest {
-            tx_bytes: tx,
-            ..Default::default()
-        };
-        let res = client
-            .simulate(request)
-            .await
-            .map_err(RestError::GRPCError)?;
-        res.into_inner().gas_info.ok_or(RestError::MissingResult)
-    })?;
-    Ok(result.gas_used)
+    block_on(query_simulate(&QueryTransport::grpc(grpc_url), tx))
 }
 
 /// Metadata about a coin denomination
-#[cfg(not(target_arch = "wasm32"))]
 #[derive(Debug)]
 pub struct DenomMetadata {
     /// "base represents the base denom (should be the DenomUnit with exponent = 0)."
@@ -160,22 +226,72 @@ pub struct DenomMetadata {
     pub denom_units: String,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
+/// format the unit measures with aliases in json
+fn format_denom_units<'a>(units: impl Iterator<Item = (&'a str, u32, &'a [String])>) -> String {
+    format!(
+        "[{}]",
+        units
+            .map(|(denom, exponent, aliases)| {
+                let aliases = aliases.iter().map(|x| format!("\"{}\"", x)).join(",");
+
+                format!(
+                    "{{\"denom\":\"{}\",\"exponent\":{},\"aliases\":[{}]}}",
+                    denom, exponent, aliases
+                )
+            })
+            .join(",")
+    )
+}
+
 impl From<Metadata> for DenomMetadata {
     fn from(md: Metadata) -> Self {
-        let denom_units = format!(
-            "[{}]",
+        let denom_units = format_denom_units(
+            md.denom_units
+                .iter()
+                .map(|unit| (unit.denom.as_str(), unit.exponent, unit.aliases.as_slice())),
+        );
+        Self {
+            base: md.base,
+            name: md.name,
+            description: md.description,
+            display: md.display,
+            symbol: md.symbol,
+            denom_units,
+        }
+    }
+}
+
+/// the denomination unit in the metadata response of the REST API
+#[derive(Deserialize)]
+struct RestDenomUnit {
+    denom: String,
+    exponent: u32,
+    #[serde(default)]
+    aliases: Vec<String>,
+}
+
+/// the denomination metadata in the response of the REST API
+#[derive(Deserialize)]
+struct RestMetadata {
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    denom_units: Vec<RestDenomUnit>,
+    base: String,
+    #[serde(default)]
+    display: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    symbol: String,
+}
+
+impl From<RestMetadata> for DenomMetadata {
+    fn from(md: RestMetadata) -> Self {
+        let denom_units = format_denom_units(
             md.denom_units
                 .iter()
-                .map(|unit| {
-                    let aliases = unit.aliases.iter().map(|x| format!("\"{}\"", x)).join(",");
-
-                    format!(
-                        "{{\"denom\":\"{}\",\"exponent\":{},\"aliases\":[{}]}}",
-                        unit.denom, unit.exponent, aliases
-                    )
-                })
-                .join(",")
+                .map(|unit| (unit.denom.as_str(), unit.exponent, unit.aliases.as_slice())),
         );
         Self {
             base: md.base,
@@ -188,25 +304,57 @@ impl From<Metadata> for DenomMetadata {
     }
 }
 
+/// the denomination metadata response of the REST API
+#[derive(Deserialize)]
+struct RestMetadataResponse {
+    metadata: Option<RestMetadata>,
+}
+
+/// given the query transport and the denomination,
+/// it'll return the denomination metadata
+pub async fn query_denom_metadata(
+    transport: &QueryTransport,
+    denom: &str,
+) -> Result<DenomMetadata, RestError> {
+    if let QueryTransport::Rest(api_url) = transport {
+        let response: RestMetadataResponse = rest_get(
+            api_url,
+            &format!("/cosmos/bank/v1beta1/denoms_metadata/{}", denom),
+            &[],
+        )
+        .await?;
+        return response
+            .metadata
+            .map(Into::into)
+            .ok_or(RestError::MissingResult);
+    }
+    let mut client = grpc_client!(QueryClient, transport.grpc_url()?);
+    let request = QueryDenomMetadataRequest {
+        denom: denom.to_owned(),
+    };
+    let res = client
+        .denom_metadata(request)
+        .await
+        .map_err(RestError::GRPCError)?;
+    res.into_inner()
+        .metadata
+        .map(Into::into)
+        .ok_or(RestError::MissingResult)
+}
+
 /// given the gRPC endpoint and the denomination,
 /// it'll return the denomination metadata
 #[cfg(not(target_arch = "wasm32"))]
-fn get_denom_metadata_blocking(grpc_url: &str, denom: String) -> Result<DenomMetadata, RestError> {
+pub(crate) fn get_denom_metadata_blocking(
+    grpc_url: &str,
+    denom: String,
+) -> Result<DenomMetadata, RestError> {
     // TODO: pass-in runtime (constructed inside the client?)
     // as part of this refactoring: https://github.com/crypto-com/defi-wallet-core-rs/issues/511 ?
-    let rt = tokio::runtime::Runtime::new().map_err(|_err| RestError::AsyncRuntimeError)?;
-    let result = rt.block_on(async move {
-        let mut client = QueryClient::connect(grpc_url.to_owned())
-            .await
-            .map_err(RestError::GRPCTransportError)?;
-        let request = QueryDenomMetadataRequest { denom };
-        let res = client
-            .denom_metadata(request)
-            .await
-            .map_err(RestError::GRPCError)?;
-        res.into_inner().metadata.ok_or(RestError::MissingResult)
-    })?;
-    Ok(result.into())
+    block_on(query_denom_metadata(
+        &QueryTransport::grpc(grpc_url),
+        &denom,
+    ))
 }
 
 /// broadcast the tx (async for JS/WASM)
@@ -327,20 +475,54 @@ pub fn broadcast_tx_sync_blocking(
 pub struct CosmosSDKClient {
     /// the Tendermint JSON-RPC (usually on 26657)
     tendermint_rpc_url: String,
-    /// the Cosmos gRPC (usually on 9090)
-    grpc_url: String,
+    /// the transport of the queries (gRPC usually on 9090 or LCD REST usually on 1317)
+    transport: QueryTransport,
+    /// if set, the balance and account queries are verified against the light client
+    light_client: Option<LightClient>,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 impl CosmosSDKClient {
-    /// a new client using a set of URLs
+    /// a new client using a set of URLs (the queries are sent over gRPC);
+    /// see `with_transport` for the LCD REST API
     pub fn new(tendermint_rpc_url: String, grpc_url: String) -> Self {
+        Self::with_transport(tendermint_rpc_url, QueryTransport::grpc(&grpc_url))
+    }
+
+    /// a new client using the explicit query transport
+    pub fn with_transport(tendermint_rpc_url: String, transport: QueryTransport) -> Self {
         Self {
             tendermint_rpc_url,
-            grpc_url,
+            transport,
+            light_client: None,
         }
     }
 
+    /// a new client that verifies the balance and account query results
+    /// with a Tendermint light client initialized from the trusted header
+    /// at `trusted_height` with the hash `trusted_hash` (hexadecimal).
+    /// `trusting_period_secs` should be less than the unbonding period of the chain.
+    pub fn new_verified(
+        tendermint_rpc_url: String,
+        grpc_url: String,
+        trusted_height: u64,
+        trusted_hash: String,
+        trusting_period_secs: u64,
+    ) -> Result<Self, RestError> {
+        let light_client = LightClient::new(
+            tendermint_rpc_url.clone(),
+            trusted_height,
+            &trusted_hash,
+            trusting_period_secs,
+        )
+        .map_err(RestError::VerificationError)?;
+        Ok(Self {
+            tendermint_rpc_url,
+            transport: QueryTransport::grpc(&grpc_url),
+            light_client: Some(light_client),
+        })
+    }
+
     /// broadcast the tx (blocking)
     /// default mode is "sync"
     pub fn broadcast_tx(
@@ -358,22 +540,32 @@ impl CosmosSDKClient {
         address: &str,
         denom: &str,
     ) -> Result<RawRpcBalance, RestError> {
-        get_account_balance_blocking(&self.grpc_url, address, denom)
+        match &self.light_client {
+            Some(light_client) => light_client
+                .get_account_balance(address, denom)
+                .map_err(RestError::VerificationError),
+            None => block_on(query_account_balance(&self.transport, address, denom)),
+        }
     }
 
     /// return the account details (blocking)
     pub fn get_account_details(&self, address: &str) -> Result<RawRpcAccountResponse, RestError> {
-        get_account_details_blocking(&self.grpc_url, address)
+        match &self.light_client {
+            Some(light_client) => light_client
+                .get_account_details(address)
+                .map_err(RestError::VerificationError),
+            None => block_on(query_account_details(&self.transport, address)),
+        }
     }
 
     /// return the denomination metadata (blocking)
     pub fn get_denom_metadata(&self, denom: &str) -> Result<DenomMetadata, RestError> {
-        get_denom_metadata_blocking(&self.grpc_url, denom.to_owned())
+        block_on(query_denom_metadata(&self.transport, denom))
     }
 
     /// it'll submit the transaction for simulating its execution and return the used gas.
     /// (blocking)
     pub fn simulate(&self, raw_signed_tx: Vec<u8>) -> Result<u64, RestError> {
-        simulate_blocking(&self.grpc_url, raw_signed_tx)
+        block_on(query_simulate(&self.transport, raw_signed_tx))
     }
 }
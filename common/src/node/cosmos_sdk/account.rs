@@ -0,0 +1,97 @@
+use crate::{RawRpcAccountStatus, RawRpcPubKey};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use cosmos_sdk_proto::cosmos::{auth::v1beta1::BaseAccount, crypto::secp256k1::PubKey};
+use cosmrs::Any;
+use prost::Message;
+
+/// the gRPC code returned by the account API when the account doesn't exist
+pub(crate) const NOT_FOUND_CODE: i64 = 5;
+
+/// the account types (module, vesting, Ethermint...) that embed
+/// the base account as their first field
+#[derive(Clone, PartialEq, Message)]
+struct WrappedBaseAccount {
+    #[prost(message, optional, tag = "1")]
+    base_account: Option<BaseAccount>,
+}
+
+/// the account types that embed the base account two levels deep (vesting accounts)
+#[derive(Clone, PartialEq, Message)]
+struct WrappedVestingAccount {
+    #[prost(message, optional, tag = "1")]
+    base_vesting_account: Option<WrappedBaseAccount>,
+}
+
+fn decode_base_account(account: &Any) -> Result<BaseAccount, String> {
+    let value = account.value.as_slice();
+    let base_account = if account.type_url.ends_with(".BaseAccount") {
+        BaseAccount::decode(value).ok()
+    } else if account.type_url.contains(".vesting.") {
+        WrappedVestingAccount::decode(value)
+            .ok()
+            .and_then(|account| account.base_vesting_account)
+            .and_then(|account| account.base_account)
+    } else {
+        WrappedBaseAccount::decode(value)
+            .ok()
+            .and_then(|account| account.base_account)
+    };
+    base_account.ok_or_else(|| format!("unsupported account type {}", account.type_url))
+}
+
+/// decode the protobuf account into the same type as returned by the account REST API
+pub(crate) fn decode_account_any(account: Any) -> Result<RawRpcAccountStatus, String> {
+    let base_account = decode_base_account(&account)?;
+    let pub_key = match base_account.pub_key {
+        Some(pub_key) => {
+            let key = PubKey::decode(pub_key.value.as_slice())
+                .map_err(|e| e.to_string())?
+                .key;
+            Some(RawRpcPubKey {
+                pub_key_type: pub_key.type_url,
+                key: STANDARD.encode(key),
+            })
+        }
+        None => None,
+    };
+    Ok(RawRpcAccountStatus {
+        account_type: account.type_url,
+        address: base_account.address,
+        pub_key,
+        account_number: base_account.account_number,
+        sequence: base_account.sequence,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_is_decoded_from_wrapped_types() {
+        let base_account = BaseAccount {
+            address: "cro1u08u5dvtnpmlpdq333uj9tcj75yceggszxpnsy".to_owned(),
+            pub_key: None,
+            account_number: 12,
+            sequence: 3,
+        };
+        let wrapped = WrappedBaseAccount {
+            base_account: Some(base_account.clone()),
+        };
+        let any = Any {
+            type_url: "/ethermint.types.v1.EthAccount".to_owned(),
+            value: wrapped.encode_to_vec(),
+        };
+        let status = decode_account_any(any).unwrap();
+        assert_eq!(status.address, base_account.address);
+        assert_eq!(status.account_number, 12);
+        assert_eq!(status.sequence, 3);
+        assert_eq!(status.account_type, "/ethermint.types.v1.EthAccount");
+
+        let unknown = Any {
+            type_url: "/cosmos.auth.v1beta1.ModuleAccount".to_owned(),
+            value: vec![0xff],
+        };
+        assert!(decode_account_any(unknown).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::node::transport::{rest_get, QueryTransport};
 use crate::RestError;
 use cosmos_sdk_proto::cosmos::bank::v1beta1::query_client::QueryClient;
 use cosmos_sdk_proto::cosmos::bank::v1beta1::{QueryBalanceRequest, QueryBalanceResponse};
@@ -22,14 +23,30 @@ impl From<QueryBalanceResponse> for RawRpcBalance {
     }
 }
 
-/// return the balance (async for JS/WASM)
-#[cfg(target_arch = "wasm32")]
-pub async fn get_account_balance(
-    grpc_web_url: &str,
+/// the balance response of the REST API
+#[derive(Deserialize)]
+struct RestBalanceResponse {
+    balance: Option<RawRpcBalance>,
+}
+
+/// return the balance over the given transport
+pub async fn query_account_balance(
+    transport: &QueryTransport,
     address: &str,
     denom: &str,
 ) -> Result<RawRpcBalance, RestError> {
-    let mut client = QueryClient::new(tonic_web_wasm_client::Client::new(grpc_web_url.to_string()));
+    if let QueryTransport::Rest(api_url) = transport {
+        let response: RestBalanceResponse = rest_get(
+            api_url,
+            &format!("/cosmos/bank/v1beta1/balances/{}/by_denom", address),
+            &[("denom", denom.to_owned())],
+        )
+        .await?;
+        return Ok(response
+            .balance
+            .unwrap_or_else(|| QueryBalanceResponse { balance: None }.into()));
+    }
+    let mut client = grpc_client!(QueryClient, transport.grpc_url()?);
     let request = QueryBalanceRequest {
         address: address.to_string(),
         denom: denom.to_string(),
@@ -42,6 +59,16 @@ pub async fn get_account_balance(
         .into())
 }
 
+/// return the balance (async for JS/WASM)
+#[cfg(target_arch = "wasm32")]
+pub async fn get_account_balance(
+    grpc_web_url: &str,
+    address: &str,
+    denom: &str,
+) -> Result<RawRpcBalance, RestError> {
+    query_account_balance(&QueryTransport::grpc(grpc_web_url), address, denom).await
+}
+
 /// return the balance (blocking for other platforms;
 /// platform-guarded as JS/WASM doesn't support the reqwest blocking)
 #[cfg(not(target_arch = "wasm32"))]
@@ -50,21 +77,9 @@ pub fn get_account_balance_blocking(
     address: &str,
     denom: &str,
 ) -> Result<RawRpcBalance, RestError> {
-    tokio::runtime::Runtime::new()
-        .map_err(|_err| RestError::AsyncRuntimeError)?
-        .block_on(async move {
-            let mut client = QueryClient::connect(grpc_url.to_string())
-                .await
-                .map_err(RestError::GRPCTransportError)?;
-            let request = QueryBalanceRequest {
-                address: address.to_string(),
-                denom: denom.to_string(),
-            };
-            Ok(client
-                .balance(request)
-                .await
-                .map_err(RestError::GRPCError)?
-                .into_inner()
-                .into())
-        })
+    crate::node::transport::block_on(query_account_balance(
+        &QueryTransport::grpc(grpc_url),
+        address,
+        denom,
+    ))
 }
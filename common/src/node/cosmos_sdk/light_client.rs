@@ -0,0 +1,604 @@
+use super::account::{decode_account_any, NOT_FOUND_CODE};
+use crate::{LightClientError, RawRpcAccountResponse, RawRpcAccountStatus, RawRpcBalance};
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin;
+use cosmrs::{AccountId, Any};
+use ics23::{commitment_proof::Proof, CommitmentProof, HostFunctionsManager};
+use prost::Message;
+use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use tendermint::{
+    block::{signed_header::SignedHeader, Height},
+    merkle::proof::ProofOps,
+    validator, Hash, Time,
+};
+use tendermint_light_client_verifier::{
+    options::Options,
+    types::{TrustThreshold, TrustedBlockState, UntrustedBlockState},
+    ProdVerifier, Verdict, Verifier,
+};
+use tendermint_rpc::{
+    endpoint::{abci_query, commit, validators},
+    request, response, PageNumber, PerPage,
+};
+
+/// the bank module store name
+const BANK_STORE: &str = "bank";
+/// the auth module store name
+const AUTH_STORE: &str = "acc";
+/// the key prefix of balances in the bank store
+const BALANCES_PREFIX: u8 = 0x02;
+/// the key prefix of accounts in the auth store
+const ACCOUNTS_PREFIX: u8 = 0x01;
+/// the maximum page size of the validators endpoint
+const VALIDATORS_PER_PAGE: u8 = 100;
+/// the tolerated clock drift between the node and the local clock
+const CLOCK_DRIFT_SECS: u64 = 5;
+
+/// the header and validator sets at a given height
+struct LightBlock {
+    signed_header: SignedHeader,
+    validators: validator::Set,
+    next_validators: validator::Set,
+}
+
+impl LightBlock {
+    fn height(&self) -> u64 {
+        self.signed_header.header.height.value()
+    }
+}
+
+/// Tendermint light client that keeps the latest verified header
+/// and verifies the ABCI query proofs against its app hash
+pub struct LightClient {
+    /// the Tendermint JSON-RPC (usually on 26657)
+    tendermint_rpc_url: String,
+    /// the trust threshold, trusting period and clock drift
+    options: Options,
+    /// the latest verified block
+    trusted: Mutex<LightBlock>,
+}
+
+impl LightClient {
+    /// a new light client initialized from a trusted header height and hash
+    /// (e.g. obtained from a block explorer or another trusted source)
+    pub fn new(
+        tendermint_rpc_url: String,
+        trusted_height: u64,
+        trusted_hash: &str,
+        trusting_period_secs: u64,
+    ) -> Result<Self, LightClientError> {
+        let expected = Hash::from_str(&trusted_hash.to_uppercase())
+            .map_err(|e| LightClientError::InvalidTrustedHash(e.to_string()))?;
+        let trusted = fetch_light_block(&tendermint_rpc_url, trusted_height)?;
+        let actual = trusted.signed_header.header.hash();
+        if actual != expected {
+            return Err(LightClientError::TrustedHashMismatch {
+                expected: expected.to_string(),
+                actual: actual.to_string(),
+            });
+        }
+        Ok(Self {
+            tendermint_rpc_url,
+            options: Options {
+                trust_threshold: TrustThreshold::ONE_THIRD,
+                trusting_period: Duration::from_secs(trusting_period_secs),
+                clock_drift: Duration::from_secs(CLOCK_DRIFT_SECS),
+            },
+            trusted: Mutex::new(trusted),
+        })
+    }
+
+    /// the height of the latest verified header
+    pub fn trusted_height(&self) -> u64 {
+        self.trusted
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .height()
+    }
+
+    /// return the balance verified against the app hash (blocking)
+    pub fn get_account_balance(
+        &self,
+        address: &str,
+        denom: &str,
+    ) -> Result<RawRpcBalance, LightClientError> {
+        let key = balance_store_key(address, denom)?;
+        let value = self.verified_query(BANK_STORE, key)?;
+        decode_balance(value, denom)
+    }
+
+    /// return the account details verified against the app hash (blocking)
+    pub fn get_account_details(
+        &self,
+        address: &str,
+    ) -> Result<RawRpcAccountResponse, LightClientError> {
+        let key = account_store_key(address)?;
+        match self.verified_query(AUTH_STORE, key)? {
+            Some(value) => Ok(RawRpcAccountResponse::OkResponse {
+                account: decode_account(&value)?,
+            }),
+            None => Ok(RawRpcAccountResponse::ErrorResponse {
+                code: NOT_FOUND_CODE,
+                message: format!("account {} not found", address),
+                details: vec![],
+            }),
+        }
+    }
+
+    /// query the raw store value at the latest height and verify its (non-)existence proof.
+    /// The state after the block `h` is committed in the app hash of the header `h + 1`,
+    /// so the query is done one block below the latest verified header.
+    fn verified_query(
+        &self,
+        store: &str,
+        key: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, LightClientError> {
+        let latest: commit::Response =
+            rpc_call(&self.tendermint_rpc_url, commit::Request { height: None })?;
+        let latest_height = latest.signed_header.header.height.value();
+        let mut trusted = self.trusted.lock().unwrap_or_else(PoisonError::into_inner);
+        self.verify_to_height(&mut trusted, latest_height)?;
+        let verified_height = trusted.height();
+        let query_height = verified_height
+            .checked_sub(1)
+            .ok_or(LightClientError::InvalidHeight(verified_height))?;
+
+        let request = abci_query::Request::new(
+            Some(format!("store/{}/key", store)),
+            key.clone(),
+            Some(to_height(query_height)?),
+            true,
+        );
+        let response = rpc_call(&self.tendermint_rpc_url, request)?.response;
+        if response.code.is_err() {
+            return Err(LightClientError::AbciQueryFailed {
+                code: response.code.value(),
+                log: response.log.to_string(),
+            });
+        }
+        if response.height.value() != query_height {
+            return Err(LightClientError::InvalidHeight(response.height.value()));
+        }
+        let proof = response.proof.ok_or(LightClientError::MissingProof)?;
+        verify_store_proof(
+            &proof,
+            trusted.signed_header.header.app_hash.as_bytes(),
+            verified_height,
+            store,
+            &key,
+            &response.value,
+        )?;
+        if response.value.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(response.value))
+        }
+    }
+
+    /// move the trusted state to `target_height` using skipping verification
+    /// (with bisection when the validator set changed too much)
+    fn verify_to_height(
+        &self,
+        trusted: &mut LightBlock,
+        target_height: u64,
+    ) -> Result<(), LightClientError> {
+        if target_height < trusted.height() {
+            return Err(LightClientError::TargetBelowTrusted {
+                target: target_height,
+                trusted: trusted.height(),
+            });
+        }
+        let verifier = ProdVerifier::default();
+        let mut pending = vec![target_height];
+        while let Some(&height) = pending.last() {
+            let current = trusted.height();
+            if height <= current {
+                pending.pop();
+                continue;
+            }
+            let untrusted = fetch_light_block(&self.tendermint_rpc_url, height)?;
+            let verdict = verifier.verify(
+                UntrustedBlockState {
+                    signed_header: &untrusted.signed_header,
+                    validators: &untrusted.validators,
+                    next_validators: Some(&untrusted.next_validators),
+                },
+                TrustedBlockState {
+                    chain_id: &trusted.signed_header.header.chain_id,
+                    header_time: trusted.signed_header.header.time,
+                    height: trusted.signed_header.header.height,
+                    next_validators: &trusted.next_validators,
+                    next_validators_hash: trusted.signed_header.header.next_validators_hash,
+                },
+                &self.options,
+                Time::now(),
+            );
+            match verdict {
+                Verdict::Success => {
+                    *trusted = untrusted;
+                    pending.pop();
+                }
+                Verdict::NotEnoughTrust(tally) => {
+                    let pivot = current + (height - current) / 2;
+                    if pivot == current {
+                        return Err(LightClientError::HeaderVerificationFailed {
+                            height,
+                            reason: format!("not enough trust: {}", tally),
+                        });
+                    }
+                    pending.push(pivot);
+                }
+                Verdict::Invalid(detail) => {
+                    return Err(LightClientError::HeaderVerificationFailed {
+                        height,
+                        reason: detail.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_height(height: u64) -> Result<Height, LightClientError> {
+    Height::try_from(height).map_err(|_e| LightClientError::InvalidHeight(height))
+}
+
+/// send the Tendermint JSON-RPC request (blocking)
+fn rpc_call<R: tendermint_rpc::Request>(
+    tendermint_rpc_url: &str,
+    request: R,
+) -> Result<R::Response, LightClientError> {
+    reqwest::blocking::Client::new()
+        .post(tendermint_rpc_url)
+        .json(&request::Wrapper::new(request))
+        .send()
+        .map_err(|e| LightClientError::RpcError(e.to_string()))?
+        .json::<response::Wrapper<R::Response>>()
+        .map_err(|e| LightClientError::RpcError(e.to_string()))?
+        .into_result()
+        .map_err(|e| LightClientError::RpcError(e.to_string()))
+}
+
+/// fetch all the validators at the given height
+fn fetch_validators(
+    tendermint_rpc_url: &str,
+    height: u64,
+) -> Result<validator::Set, LightClientError> {
+    let mut validators = vec![];
+    let mut page = 1;
+    loop {
+        let request = validators::Request::new(
+            Some(to_height(height)?),
+            Some(PageNumber::from(page)),
+            Some(PerPage::from(VALIDATORS_PER_PAGE)),
+        );
+        let response = rpc_call(tendermint_rpc_url, request)?;
+        let received = response.validators.len();
+        validators.extend(response.validators);
+        if received == 0 || validators.len() >= response.total as usize {
+            break;
+        }
+        page += 1;
+    }
+    Ok(validator::Set::without_proposer(validators))
+}
+
+/// fetch the signed header with its current and next validator sets
+/// and check they match the hashes in the header
+fn fetch_light_block(
+    tendermint_rpc_url: &str,
+    height: u64,
+) -> Result<LightBlock, LightClientError> {
+    let signed_header =
+        rpc_call(tendermint_rpc_url, commit::Request::new(to_height(height)?))?.signed_header;
+    let validators = fetch_validators(tendermint_rpc_url, height)?;
+    let next_validators = fetch_validators(tendermint_rpc_url, height + 1)?;
+    if validators.hash() != signed_header.header.validators_hash
+        || next_validators.hash() != signed_header.header.next_validators_hash
+    {
+        return Err(LightClientError::ValidatorSetMismatch(height));
+    }
+    Ok(LightBlock {
+        signed_header,
+        validators,
+        next_validators,
+    })
+}
+
+fn address_bytes(address: &str) -> Result<Vec<u8>, LightClientError> {
+    AccountId::from_str(address)
+        .map(|account| account.to_bytes())
+        .map_err(|e| LightClientError::InvalidAddress(e.to_string()))
+}
+
+/// `0x02 | len(address) | address | denom` (Cosmos SDK 0.43+)
+fn balance_store_key(address: &str, denom: &str) -> Result<Vec<u8>, LightClientError> {
+    let address = address_bytes(address)?;
+    let mut key = Vec::with_capacity(2 + address.len() + denom.len());
+    key.push(BALANCES_PREFIX);
+    key.push(address.len() as u8);
+    key.extend_from_slice(&address);
+    key.extend_from_slice(denom.as_bytes());
+    Ok(key)
+}
+
+/// `0x01 | address`
+fn account_store_key(address: &str) -> Result<Vec<u8>, LightClientError> {
+    let mut key = vec![ACCOUNTS_PREFIX];
+    key.extend(address_bytes(address)?);
+    Ok(key)
+}
+
+fn decode_commitment_proof(data: &[u8]) -> Result<CommitmentProof, LightClientError> {
+    CommitmentProof::decode(data).map_err(|e| LightClientError::InvalidProof(e.to_string()))
+}
+
+/// verify the two-level proof: the IAVL proof of the key in the module store
+/// and the simple Merkle proof of the store root in the app hash
+fn verify_store_proof(
+    proof: &ProofOps,
+    app_hash: &[u8],
+    height: u64,
+    store: &str,
+    key: &[u8],
+    value: &[u8],
+) -> Result<(), LightClientError> {
+    let (store_op, root_op) = match proof.ops.as_slice() {
+        [store_op, root_op] => (store_op, root_op),
+        _ => {
+            return Err(LightClientError::InvalidProof(format!(
+                "expected 2 proof ops, got {}",
+                proof.ops.len()
+            )))
+        }
+    };
+    if store_op.key != key || root_op.key != store.as_bytes() {
+        return Err(LightClientError::InvalidProof(
+            "proof keys don't match the query".to_owned(),
+        ));
+    }
+
+    let store_proof = decode_commitment_proof(&store_op.data)?;
+    let existence = match &store_proof.proof {
+        Some(Proof::Exist(existence)) => Some(existence),
+        Some(Proof::Nonexist(nonexistence)) => {
+            nonexistence.left.as_ref().or(nonexistence.right.as_ref())
+        }
+        _ => None,
+    }
+    .ok_or_else(|| LightClientError::InvalidProof("unsupported proof type".to_owned()))?;
+    let store_root = ics23::calculate_existence_root::<HostFunctionsManager>(existence)
+        .map_err(|e| LightClientError::InvalidProof(e.to_string()))?;
+
+    let store_verified = if value.is_empty() {
+        ics23::verify_non_membership::<HostFunctionsManager>(
+            &store_proof,
+            &ics23::iavl_spec(),
+            &store_root,
+            key,
+        )
+    } else {
+        ics23::verify_membership::<HostFunctionsManager>(
+            &store_proof,
+            &ics23::iavl_spec(),
+            &store_root,
+            key,
+            value,
+        )
+    };
+    if !store_verified {
+        return Err(LightClientError::InvalidProof(format!(
+            "the {} store proof doesn't match the value",
+            store
+        )));
+    }
+
+    let root_proof = decode_commitment_proof(&root_op.data)?;
+    let root_verified = ics23::verify_membership::<HostFunctionsManager>(
+        &root_proof,
+        &ics23::tendermint_spec(),
+        &app_hash.to_vec(),
+        store.as_bytes(),
+        &store_root,
+    );
+    if !root_verified {
+        return Err(LightClientError::AppHashMismatch(height));
+    }
+    Ok(())
+}
+
+/// the balance is stored as `Coin` before Cosmos SDK 0.47 and as the decimal amount since then
+fn decode_balance(value: Option<Vec<u8>>, denom: &str) -> Result<RawRpcBalance, LightClientError> {
+    let amount = match value {
+        None => "0".to_owned(),
+        Some(value) if !value.is_empty() && value.iter().all(u8::is_ascii_digit) => {
+            String::from_utf8(value).map_err(|e| LightClientError::DecodeError(e.to_string()))?
+        }
+        Some(value) => {
+            Coin::decode(value.as_slice())
+                .map_err(|e| LightClientError::DecodeError(e.to_string()))?
+                .amount
+        }
+    };
+    Ok(RawRpcBalance {
+        denom: denom.to_owned(),
+        amount,
+    })
+}
+
+fn decode_account(value: &[u8]) -> Result<RawRpcAccountStatus, LightClientError> {
+    let account = Any::decode(value).map_err(|e| LightClientError::DecodeError(e.to_string()))?;
+    decode_account_any(account).map_err(LightClientError::DecodeError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tendermint::merkle::proof::ProofOp;
+
+    const ADDRESS: &str = "cro1u08u5dvtnpmlpdq333uj9tcj75yceggszxpnsy";
+
+    // The fixed vectors have the layout of the Cosmos SDK `abci_query` responses (`prove=true`):
+    // the IAVL (v0.19) proof of the key in the module store and the simple Merkle proof
+    // of the store root in the app hash. They were computed with a separate IAVL/ICS-23
+    // implementation for bank and auth stores holding a few other accounts next to `ADDRESS`.
+
+    /// the app hash of the multistore (acc, bank, distribution, staking and upgrade stores)
+    const APP_HASH: &str = "b4dc478fcba877e0681bdb181d5ed81090908f0d00e598d9b68df262e16c99a3";
+    /// the `Coin` of 1234567basecro of `ADDRESS`
+    const BALANCE_VALUE: &str = "0a076261736563726f120731323334353637";
+    /// the existence proof of the basecro balance of `ADDRESS` in the bank store
+    const BALANCE_PROOF: &str = "0ac3010a1d0214e3cfca358b9877f0b4118c7922af12f5098ca1106261736563726f12120a076261736563726f1207313233343536371a0b0801180120012a03000202222908011225020402208b700f3de71a18b4a0df8cc9d27c01a49d0150d49e180a14dea0076b5aa9cef020222b08011204040602201a21206af8c62612c4d073a54690fb77724f90f3feab4ad93f7692eb28f84b1882d68f222908011225060c0220369ce14f07212f783175cc40b62bff82c88de47dd2a174cad6d12454cf7acac120";
+    /// the existence proof of the bank store root in the app hash
+    const BANK_ROOT_PROOF: &str = "0aac010a0462616e6b1220665b9f906395f59fa1d72e2e9457372eb90da3e1afa8f6fde8f03799e81c8b571a090801180120012a01002225080112210151aa002039cd92602a7dbffbf3174d9a49fd0eea1024115bb81b49a463c6e187222708011201011a20fd5dbc284abbf4c525ffb8e8c8f66d5cd5f76726ad390ed4bc41606c1a246a60222708011201011a20c79c51f67d450a8b08ce5dd411b713a88a556c7204e94d2aa866e157dfda0ea5";
+    /// the non-existence proof of the basetcro balance of `ADDRESS`
+    const MISSING_BALANCE_PROOF: &str = "12f4030a1e0214e3cfca358b9877f0b4118c7922af12f5098ca110626173657463726f12c3010a1d0214e3cfca358b9877f0b4118c7922af12f5098ca1106261736563726f12120a076261736563726f1207313233343536371a0b0801180120012a03000202222908011225020402208b700f3de71a18b4a0df8cc9d27c01a49d0150d49e180a14dea0076b5aa9cef020222b08011204040602201a21206af8c62612c4d073a54690fb77724f90f3feab4ad93f7692eb28f84b1882d68f222908011225060c0220369ce14f07212f783175cc40b62bff82c88de47dd2a174cad6d12454cf7acac1201a8b020a5a0214e3cfca358b9877f0b4118c7922af12f5098ca1106962632f32373339344642303932443245434344353631323343373446333645344331463932363030314345414441394341393745413632324232354634314535454232124a0a446962632f32373339344642303932443245434344353631323343373446333645344331463932363030314345414441394341393745413632324232354634314535454232120234321a0b0801180120012a0300020222290801122504060220422241c72663f17babcd81f230e22c8941cd871a6b6b2c7088c6f4f9153a31c220222908011225060c0220369ce14f07212f783175cc40b62bff82c88de47dd2a174cad6d12454cf7acac120";
+    /// the `BaseAccount` (as `Any`) of `ADDRESS` with the account number 12 and sequence 3
+    const ACCOUNT_VALUE: &str = "0a202f636f736d6f732e617574682e763162657461312e426173654163636f756e7412300a2a63726f3175303875356476746e706d6c706471333333756a3974636a37357963656767737a78706e7379180c2003";
+    /// the existence proof of the account of `ADDRESS` in the auth store
+    const ACCOUNT_PROOF: &str = "0ad0010a1501e3cfca358b9877f0b4118c7922af12f5098ca11012540a202f636f736d6f732e617574682e763162657461312e426173654163636f756e7412300a2a63726f3175303875356476746e706d6c706471333333756a3974636a37357963656767737a78706e7379180c20031a0b0801180120012a0300020222290801122502040220bea065c0a5c36fee16459ce29ced227880db765674ced9219cc840ca2e5cb6c620222908011225060a022081f8b616f462e14c2253685c9845b7b83a21a97adb3d12f988354741051467e920";
+    /// the existence proof of the auth store root in the app hash
+    const ACC_ROOT_PROOF: &str = "0aad010a03616363122059096b937ce34a4b6b0bf994b14ff4b47e4960978c3b5b8b11696623e2850fa71a090801180120012a0100222708011201011a2049cb1468b661e0dbc2d95603bd80b03e01d6feef355dcacf857cb059ea75345b222708011201011a20fd5dbc284abbf4c525ffb8e8c8f66d5cd5f76726ad390ed4bc41606c1a246a60222708011201011a20c79c51f67d450a8b08ce5dd411b713a88a556c7204e94d2aa866e157dfda0ea5";
+
+    #[test]
+    fn store_keys_are_length_prefixed() {
+        let address = address_bytes(ADDRESS).unwrap();
+        let balance_key = balance_store_key(ADDRESS, "basecro").unwrap();
+        assert_eq!(balance_key[0], BALANCES_PREFIX);
+        assert_eq!(balance_key[1] as usize, address.len());
+        assert_eq!(&balance_key[2..2 + address.len()], address.as_slice());
+        assert_eq!(&balance_key[2 + address.len()..], b"basecro");
+
+        let account_key = account_store_key(ADDRESS).unwrap();
+        assert_eq!(account_key[0], ACCOUNTS_PREFIX);
+        assert_eq!(&account_key[1..], address.as_slice());
+    }
+
+    #[test]
+    fn invalid_address_is_rejected() {
+        assert!(matches!(
+            account_store_key("invalid"),
+            Err(LightClientError::InvalidAddress(_))
+        ));
+    }
+
+    #[test]
+    fn balance_is_decoded_from_both_formats() {
+        let coin = Coin {
+            denom: "basecro".to_owned(),
+            amount: "1000".to_owned(),
+        };
+        let old = decode_balance(Some(coin.encode_to_vec()), "basecro").unwrap();
+        assert_eq!(old.amount, "1000");
+        let new = decode_balance(Some(b"2000".to_vec()), "basecro").unwrap();
+        assert_eq!(new.amount, "2000");
+        let missing = decode_balance(None, "basecro").unwrap();
+        assert_eq!(missing.amount, "0");
+    }
+
+    #[test]
+    fn malformed_proof_is_rejected() {
+        let proof = ProofOps { ops: vec![] };
+        assert!(matches!(
+            verify_store_proof(&proof, &[0; 32], 1, BANK_STORE, b"key", b"value"),
+            Err(LightClientError::InvalidProof(_))
+        ));
+    }
+
+    fn proof_ops(store: &str, key: &[u8], store_proof: &str, root_proof: &str) -> ProofOps {
+        ProofOps {
+            ops: vec![
+                ProofOp {
+                    field_type: "ics23:iavl".to_owned(),
+                    key: key.to_vec(),
+                    data: hex::decode(store_proof).unwrap(),
+                },
+                ProofOp {
+                    field_type: "ics23:simple".to_owned(),
+                    key: store.as_bytes().to_vec(),
+                    data: hex::decode(root_proof).unwrap(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn balance_proof_is_verified() {
+        let app_hash = hex::decode(APP_HASH).unwrap();
+        let key = balance_store_key(ADDRESS, "basecro").unwrap();
+        let value = hex::decode(BALANCE_VALUE).unwrap();
+        let proof = proof_ops(BANK_STORE, &key, BALANCE_PROOF, BANK_ROOT_PROOF);
+        verify_store_proof(&proof, &app_hash, 10, BANK_STORE, &key, &value).unwrap();
+        assert_eq!(
+            decode_balance(Some(value.clone()), "basecro")
+                .unwrap()
+                .amount,
+            "1234567"
+        );
+
+        // another value or app hash is rejected
+        let mut other_value = value;
+        *other_value.last_mut().unwrap() = b'8';
+        assert!(matches!(
+            verify_store_proof(&proof, &app_hash, 10, BANK_STORE, &key, &other_value),
+            Err(LightClientError::InvalidProof(_))
+        ));
+        let mut other_app_hash = app_hash;
+        other_app_hash[0] ^= 1;
+        assert!(matches!(
+            verify_store_proof(
+                &proof,
+                &other_app_hash,
+                10,
+                BANK_STORE,
+                &key,
+                &hex::decode(BALANCE_VALUE).unwrap()
+            ),
+            Err(LightClientError::AppHashMismatch(10))
+        ));
+    }
+
+    #[test]
+    fn missing_balance_proof_is_verified() {
+        let app_hash = hex::decode(APP_HASH).unwrap();
+        let key = balance_store_key(ADDRESS, "basetcro").unwrap();
+        let proof = proof_ops(BANK_STORE, &key, MISSING_BALANCE_PROOF, BANK_ROOT_PROOF);
+        verify_store_proof(&proof, &app_hash, 10, BANK_STORE, &key, &[]).unwrap();
+
+        // the non-existence proof doesn't prove a value, nor the absence of the existing key
+        assert!(verify_store_proof(&proof, &app_hash, 10, BANK_STORE, &key, b"1").is_err());
+        let existing_key = balance_store_key(ADDRESS, "basecro").unwrap();
+        let proof = proof_ops(
+            BANK_STORE,
+            &existing_key,
+            MISSING_BALANCE_PROOF,
+            BANK_ROOT_PROOF,
+        );
+        assert!(matches!(
+            verify_store_proof(&proof, &app_hash, 10, BANK_STORE, &existing_key, &[]),
+            Err(LightClientError::InvalidProof(_))
+        ));
+    }
+
+    #[test]
+    fn account_proof_is_verified() {
+        let app_hash = hex::decode(APP_HASH).unwrap();
+        let key = account_store_key(ADDRESS).unwrap();
+        let value = hex::decode(ACCOUNT_VALUE).unwrap();
+        let proof = proof_ops(AUTH_STORE, &key, ACCOUNT_PROOF, ACC_ROOT_PROOF);
+        verify_store_proof(&proof, &app_hash, 10, AUTH_STORE, &key, &value).unwrap();
+        let account = decode_account(&value).unwrap();
+        assert_eq!(account.address, ADDRESS);
+        assert_eq!(account.account_number, 12);
+        assert_eq!(account.sequence, 3);
+
+        // the proof of the auth store root doesn't prove the bank store
+        let proof = proof_ops(BANK_STORE, &key, ACCOUNT_PROOF, ACC_ROOT_PROOF);
+        assert!(matches!(
+            verify_store_proof(&proof, &app_hash, 10, BANK_STORE, &key, &value),
+            Err(LightClientError::AppHashMismatch(10))
+        ));
+    }
+}
@@ -14,4 +14,45 @@ pub enum RestError {
     GRPCError(tonic::Status),
     #[error("ErrorReport")]
     ErrorReport,
+    #[error("Decode error: {0}")]
+    DecodeError(String),
+    #[error("Invalid IBC timeout: {0}")]
+    InvalidIbcTimeout(String),
+    #[error("Unsupported transport: {0}")]
+    UnsupportedTransport(String),
+    #[error("Light client verification error: {0}")]
+    VerificationError(LightClientError),
+    #[error("The cache lock is poisoned")]
+    CacheLockPoisoned,
+}
+
+/// errors from the light client verification of the query results
+#[derive(Debug, thiserror::Error)]
+pub enum LightClientError {
+    #[error("Tendermint JSON-RPC error: {0}")]
+    RpcError(String),
+    #[error("Invalid trusted hash: {0}")]
+    InvalidTrustedHash(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid height: {0}")]
+    InvalidHeight(u64),
+    #[error("Trusted header hash mismatch: expected {expected}, got {actual}")]
+    TrustedHashMismatch { expected: String, actual: String },
+    #[error("Validator set hash mismatch at height {0}")]
+    ValidatorSetMismatch(u64),
+    #[error("Header verification failed at height {height}: {reason}")]
+    HeaderVerificationFailed { height: u64, reason: String },
+    #[error("Target height {target} is below the trusted height {trusted}")]
+    TargetBelowTrusted { target: u64, trusted: u64 },
+    #[error("ABCI query failed with code {code}: {log}")]
+    AbciQueryFailed { code: u32, log: String },
+    #[error("Missing Merkle proof in the ABCI query response")]
+    MissingProof,
+    #[error("Invalid Merkle proof: {0}")]
+    InvalidProof(String),
+    #[error("Merkle proof does not match the app hash at height {0}")]
+    AppHashMismatch(u64),
+    #[error("Failed to decode the verified value: {0}")]
+    DecodeError(String),
 }
@@ -1,10 +1,31 @@
 pub mod abi;
+pub mod eip1193;
+pub mod eip1271;
 pub mod eip712;
+pub mod ens;
 pub mod erc1155;
 pub mod erc20;
 #[cfg(feature = "erc4907")]
 pub mod erc4907;
 pub mod erc721;
+pub mod fee;
+pub mod multicall;
+pub mod nonce;
+pub mod permit;
 pub mod provider;
+pub mod replacement;
+pub mod transfer_history;
 pub mod utils;
+#[cfg(feature = "abi-contract")]
+pub use eip1193::*;
+pub use eip1271::*;
+#[cfg(feature = "abi-contract")]
+pub use eip712::{Eip712EncodedType, Eip712FieldPreview, Eip712TypedDataPreview};
+pub use ens::*;
+pub use fee::*;
+pub use multicall::*;
+pub use nonce::*;
+pub use permit::*;
+pub use replacement::*;
+pub use transfer_history::*;
 pub use utils::*;
@@ -1,7 +1,7 @@
 #![cfg(feature = "abi-contract")]
 
 use crate::EthError;
-use ethers::prelude::abi::{Error, ParamType, Token};
+use ethers::prelude::abi::{Abi, AbiError, Constructor, Error, Event, Function, ParamType, Token};
 use ethers::prelude::{Address, H160, U256};
 use pest::Parser;
 use serde::{Deserialize, Serialize};
@@ -36,11 +36,14 @@ pub enum EthAbiParamType {
 
 mod parser {
     use crate::EthError;
-    use ethers::prelude::abi;
-    use pest::iterators::Pairs;
+    use ethers::prelude::abi::{
+        self, AbiError, Constructor, Event, EventParam, Function, Param, ParamType, StateMutability,
+    };
+    use pest::iterators::{Pair, Pairs};
+    use pest::Parser;
     use pest_derive::Parser;
 
-    use super::EthAbiParamType;
+    use super::{EthAbiFragment, EthAbiParamType};
 
     #[derive(Parser)]
     #[grammar_inline = r#"
@@ -56,6 +59,26 @@ mod parser {
     base_param = { (non_recur_param | tuple_param) ~ array_size_param* }
     tuple_param = { "()" | "(" ~ (abi_param ~ ("," ~ abi_param)*)? ~ ")" }
     abi_param = _{ (base_param | tuple_param ) }
+
+    // human-readable ABI fragments, e.g. "function transfer(address to, uint256 amount) returns (bool)"
+    ws = _{ (" " | "\t" | "\r" | "\n")+ }
+    ident_char = _{ ASCII_ALPHANUMERIC | "_" | "$" }
+    identifier = @{ (ASCII_ALPHA | "_" | "$") ~ ident_char* }
+    indexed = { "indexed" ~ !ident_char }
+    data_location = _{ ("memory" | "calldata" | "storage") ~ !ident_char }
+    named_tuple_param = { "tuple"? ~ "(" ~ ws? ~ (named_param ~ (ws? ~ "," ~ ws? ~ named_param)*)? ~ ws? ~ ")" }
+    named_type = { (non_recur_param | named_tuple_param) ~ array_size_param* }
+    named_param = { named_type ~ (ws ~ indexed)? ~ (ws ~ data_location)? ~ (ws ~ identifier)? }
+    param_list = { "(" ~ ws? ~ (named_param ~ (ws? ~ "," ~ ws? ~ named_param)*)? ~ ws? ~ ")" }
+    state_mutability = { ("pure" | "view" | "payable" | "nonpayable") ~ !ident_char }
+    modifier = _{ state_mutability | (("external" | "public" | "virtual" | "override") ~ !ident_char) }
+    returns = { "returns" ~ ws? ~ param_list }
+    anonymous = { "anonymous" ~ !ident_char }
+    function_fragment = { "function" ~ ws ~ identifier ~ ws? ~ param_list ~ (ws ~ modifier)* ~ (ws? ~ returns)? }
+    event_fragment = { "event" ~ ws ~ identifier ~ ws? ~ param_list ~ (ws ~ anonymous)? }
+    error_fragment = { "error" ~ ws ~ identifier ~ ws? ~ param_list }
+    constructor_fragment = { "constructor" ~ ws? ~ param_list ~ (ws ~ modifier)* }
+    abi_fragment = _{ SOI ~ ws? ~ (function_fragment | event_fragment | error_fragment | constructor_fragment) ~ ws? ~ ";"? ~ ws? ~ EOI }
     "#]
     pub struct AbiParser;
 
@@ -87,7 +110,24 @@ mod parser {
             | Rule::array_size_param
             | Rule::m_param
             | Rule::m_short_param
-            | Rule::array_size_no => Err(EthError::AbiError(abi::Error::Other(
+            | Rule::array_size_no
+            | Rule::ws
+            | Rule::ident_char
+            | Rule::identifier
+            | Rule::indexed
+            | Rule::data_location
+            | Rule::named_param
+            | Rule::param_list
+            | Rule::state_mutability
+            | Rule::modifier
+            | Rule::returns
+            | Rule::anonymous
+            | Rule::function_fragment
+            | Rule::event_fragment
+            | Rule::error_fragment
+            | Rule::constructor_fragment
+            | Rule::abi_fragment
+            | Rule::EOI => Err(EthError::AbiError(abi::Error::Other(
                 "Unexpected parse tree item".into(),
             ))),
 
@@ -145,8 +185,141 @@ mod parser {
                     .collect();
                 Ok(EthAbiParamType::Tuple(internals))
             }
+            Rule::named_type => {
+                let mut inner_iter = first.into_inner();
+                maybe_array(&mut inner_iter)
+            }
+            Rule::named_tuple_param => {
+                let internals = first
+                    .into_inner()
+                    .map(|param| named_param(param).map(|(param_type, _, _)| param_type))
+                    .collect::<Result<_, _>>()?;
+                Ok(EthAbiParamType::Tuple(internals))
+            }
         }
     }
+
+    /// the type, the indexed flag and the name (can be empty) of the named parameter
+    fn named_param(param: Pair<Rule>) -> Result<(EthAbiParamType, bool, String), EthError> {
+        let mut param_type = None;
+        let mut indexed = false;
+        let mut name = String::new();
+        for inner in param.into_inner() {
+            match inner.as_rule() {
+                Rule::named_type => param_type = Some(maybe_array(&mut inner.into_inner())?),
+                Rule::indexed => indexed = true,
+                Rule::identifier => name = inner.as_str().to_owned(),
+                _ => {}
+            }
+        }
+        let param_type = param_type.ok_or(EthError::AbiError(abi::Error::InvalidData))?;
+        Ok((param_type, indexed, name))
+    }
+
+    /// the parameters of the parameter list
+    fn param_list(list: Pair<Rule>) -> Result<Vec<(ParamType, bool, String)>, EthError> {
+        list.into_inner()
+            .map(|param| {
+                let (param_type, indexed, name) = named_param(param)?;
+                Ok((ParamType::try_from(&param_type)?, indexed, name))
+            })
+            .collect()
+    }
+
+    /// the parameters of a non-event parameter list (`indexed` is only valid in event inputs)
+    fn params(list: Pair<Rule>) -> Result<Vec<Param>, EthError> {
+        param_list(list)?
+            .into_iter()
+            .map(|(kind, indexed, name)| {
+                if indexed {
+                    return Err(EthError::AbiError(abi::Error::Other(
+                        "`indexed` is only allowed in event parameters".into(),
+                    )));
+                }
+                Ok(Param {
+                    name,
+                    kind,
+                    internal_type: None,
+                })
+            })
+            .collect()
+    }
+
+    /// parse the human-readable ABI fragment
+    pub(crate) fn parse_fragment(fragment: &str) -> Result<EthAbiFragment, EthError> {
+        let fragment = AbiParser::parse(Rule::abi_fragment, fragment)
+            .map_err(|_e| EthError::AbiError(abi::Error::InvalidData))?
+            .next()
+            .ok_or(EthError::AbiError(abi::Error::InvalidData))?;
+        let rule = fragment.as_rule();
+        let mut inner = fragment.into_inner();
+        let mut next = || {
+            inner
+                .next()
+                .ok_or(EthError::AbiError(abi::Error::InvalidData))
+        };
+        Ok(match rule {
+            Rule::function_fragment => {
+                let name = next()?.as_str().to_owned();
+                let inputs = params(next()?)?;
+                let mut outputs = vec![];
+                let mut state_mutability = StateMutability::NonPayable;
+                for item in inner {
+                    match item.as_rule() {
+                        Rule::state_mutability => {
+                            state_mutability = match item.as_str() {
+                                "pure" => StateMutability::Pure,
+                                "view" => StateMutability::View,
+                                "payable" => StateMutability::Payable,
+                                _ => StateMutability::NonPayable,
+                            }
+                        }
+                        Rule::returns => {
+                            if let Some(list) = item.into_inner().next() {
+                                outputs = params(list)?;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                #[allow(deprecated)]
+                EthAbiFragment::Function(Function {
+                    name,
+                    inputs,
+                    outputs,
+                    constant: None,
+                    state_mutability,
+                })
+            }
+            Rule::event_fragment => {
+                let name = next()?.as_str().to_owned();
+                let inputs = param_list(next()?)?
+                    .into_iter()
+                    .map(|(kind, indexed, name)| EventParam {
+                        name,
+                        kind,
+                        indexed,
+                    })
+                    .collect();
+                let anonymous = inner.next().is_some();
+                EthAbiFragment::Event(Event {
+                    name,
+                    inputs,
+                    anonymous,
+                })
+            }
+            Rule::error_fragment => {
+                let name = next()?.as_str().to_owned();
+                let inputs = params(next()?)?;
+                EthAbiFragment::Error(AbiError { name, inputs })
+            }
+            Rule::constructor_fragment => {
+                let inputs = params(next()?)?;
+                EthAbiFragment::Constructor(Constructor { inputs })
+            }
+            _ => return Err(EthError::AbiError(abi::Error::InvalidData)),
+        })
+    }
 }
 
 impl EthAbiParamType {
@@ -249,6 +422,56 @@ impl FromStr for EthAbiParamType {
     }
 }
 
+/// A human-readable ABI fragment, e.g.
+/// `function transfer(address to, uint256 amount) returns (bool)` or
+/// `event Transfer(address indexed from, address indexed to, uint256 value)`
+#[derive(Debug, Clone, PartialEq)]
+pub enum EthAbiFragment {
+    Function(Function),
+    Event(Event),
+    Error(AbiError),
+    Constructor(Constructor),
+}
+
+impl FromStr for EthAbiFragment {
+    type Err = EthError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        pest::set_call_limit(Some(NonZeroUsize::new(10000).unwrap()));
+        parser::parse_fragment(s)
+    }
+}
+
+/// Parse the human-readable ABI fragments (the empty ones are skipped) into the contract ABI
+pub fn parse_human_readable_abi<S: AsRef<str>>(fragments: &[S]) -> Result<Abi, EthError> {
+    let mut abi = Abi::default();
+    for fragment in fragments {
+        let fragment = fragment.as_ref().trim();
+        if fragment.is_empty() {
+            continue;
+        }
+        match fragment.parse()? {
+            EthAbiFragment::Function(function) => abi
+                .functions
+                .entry(function.name.clone())
+                .or_default()
+                .push(function),
+            EthAbiFragment::Event(event) => abi
+                .events
+                .entry(event.name.clone())
+                .or_default()
+                .push(event),
+            EthAbiFragment::Error(error) => abi
+                .errors
+                .entry(error.name.clone())
+                .or_default()
+                .push(error),
+            EthAbiFragment::Constructor(constructor) => abi.constructor = Some(constructor),
+        }
+    }
+    Ok(abi)
+}
+
 impl TryFrom<&EthAbiParamType> for ParamType {
     type Error = EthError;
 
@@ -440,4 +663,86 @@ mod test {
             ])))
         );
     }
+
+    #[test]
+    pub fn test_human_readable_abi() {
+        use crate::abi::{parse_human_readable_abi, EthAbiFragment};
+        use ethers::prelude::abi::{ParamType, StateMutability, Token};
+        use ethers::prelude::{Address, U256};
+
+        let abi = parse_human_readable_abi(&[
+            "function transfer(address to, uint256 amount) returns (bool)",
+            "function balanceOf(address owner) external view returns (uint256 balance)",
+            "",
+            "event Transfer(address indexed from, address indexed to, uint256 value)",
+            "error InsufficientBalance(uint256 available, uint256 required);",
+        ])
+        .unwrap();
+
+        let transfer = abi.function("transfer").unwrap();
+        assert_eq!(transfer.inputs[0].name, "to");
+        assert_eq!(transfer.inputs[1].kind, ParamType::Uint(256));
+        assert_eq!(transfer.outputs[0].kind, ParamType::Bool);
+        assert_eq!(transfer.state_mutability, StateMutability::NonPayable);
+        let encoded = transfer
+            .encode_input(&[
+                Token::Address(
+                    "0x2c600e0a72b3ae39e9b27d2e310b180abe779368"
+                        .parse::<Address>()
+                        .unwrap(),
+                ),
+                Token::Uint(U256::from(100)),
+            ])
+            .unwrap();
+        assert_eq!(
+            ethers::utils::hex::encode(encoded),
+            "a9059cbb0000000000000000000000002c600e0a72b3ae39e9b27d2e310b180abe7793680000000000000000000000000000000000000000000000000000000000000064"
+        );
+
+        let balance_of = abi.function("balanceOf").unwrap();
+        assert_eq!(balance_of.state_mutability, StateMutability::View);
+        assert_eq!(balance_of.outputs[0].name, "balance");
+
+        let event = abi.event("Transfer").unwrap();
+        assert!(event.inputs[0].indexed && event.inputs[1].indexed && !event.inputs[2].indexed);
+        assert_eq!(
+            ethers::utils::hex::encode(event.signature()),
+            "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+        assert_eq!(abi.errors().next().unwrap().name, "InsufficientBalance");
+
+        let fragment: EthAbiFragment =
+            "function swap(tuple(address token, uint256[] amounts)[] calldata orders, bytes memory data) payable"
+                .parse()
+                .unwrap();
+        match fragment {
+            EthAbiFragment::Function(function) => {
+                assert_eq!(function.state_mutability, StateMutability::Payable);
+                assert_eq!(
+                    function.inputs[0].kind,
+                    ParamType::Array(Box::new(ParamType::Tuple(vec![
+                        ParamType::Address,
+                        ParamType::Array(Box::new(ParamType::Uint(256)))
+                    ])))
+                );
+                assert_eq!(function.inputs[1].name, "data");
+            }
+            _ => panic!("unexpected fragment"),
+        }
+
+        assert!("function (address)".parse::<EthAbiFragment>().is_err());
+        assert!("event Foo(uint 256 bar)".parse::<EthAbiFragment>().is_err());
+        assert!("function foo(address indexed to)"
+            .parse::<EthAbiFragment>()
+            .is_err());
+        assert!("function foo() returns (uint256 indexed bar)"
+            .parse::<EthAbiFragment>()
+            .is_err());
+        assert!("error Foo(uint256 indexed bar)"
+            .parse::<EthAbiFragment>()
+            .is_err());
+        assert!("constructor(address indexed owner)"
+            .parse::<EthAbiFragment>()
+            .is_err());
+    }
 }
@@ -0,0 +1,696 @@
+#![cfg(feature = "abi-contract")]
+
+use crate::node::ethereum::nonce::NonceTracker;
+use crate::provider::get_ethers_provider;
+use crate::{DynamicTransactionRequest, EthError, EthSigner, SecretKey};
+use ethers::prelude::{Address, LocalWallet, Middleware, ProviderError, Signer, SignerMiddleware};
+use ethers::providers::RpcError;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::{hex, to_checksum};
+use futures::future::LocalBoxFuture;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// the user rejected the request
+pub const EIP1193_USER_REJECTED: i64 = 4001;
+/// the requested account or method has not been authorized by the user
+pub const EIP1193_UNAUTHORIZED: i64 = 4100;
+/// the provider does not support the method
+pub const EIP1193_UNSUPPORTED_METHOD: i64 = 4200;
+/// the provider is disconnected from all chains
+pub const EIP1193_DISCONNECTED: i64 = 4900;
+/// the chain has not been added (EIP-3326)
+pub const EIP1193_UNRECOGNIZED_CHAIN: i64 = 4902;
+/// the JSON-RPC error of invalid parameters
+pub const EIP1193_INVALID_PARAMS: i64 = -32602;
+/// the JSON-RPC internal error
+pub const EIP1193_INTERNAL_ERROR: i64 = -32603;
+
+/// the read-only methods which are forwarded to the web3 API of the current chain;
+/// the other methods (e.g. the deprecated signing or the node account methods) are not supported
+const FORWARDED_METHODS: [&str; 21] = [
+    "eth_blockNumber",
+    "eth_call",
+    "eth_estimateGas",
+    "eth_feeHistory",
+    "eth_gasPrice",
+    "eth_getBalance",
+    "eth_getBlockByHash",
+    "eth_getBlockByNumber",
+    "eth_getBlockTransactionCountByHash",
+    "eth_getBlockTransactionCountByNumber",
+    "eth_getCode",
+    "eth_getLogs",
+    "eth_getProof",
+    "eth_getStorageAt",
+    "eth_getTransactionByBlockHashAndIndex",
+    "eth_getTransactionByBlockNumberAndIndex",
+    "eth_getTransactionByHash",
+    "eth_getTransactionCount",
+    "eth_getTransactionReceipt",
+    "eth_maxPriorityFeePerGas",
+    "eth_sendRawTransaction",
+];
+
+/// The error of EIP-1193 requests (`ProviderRpcError`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[error("{message} (code: {code})")]
+pub struct Eip1193Error {
+    pub code: i64,
+    pub message: String,
+}
+
+impl Eip1193Error {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(EIP1193_INVALID_PARAMS, message)
+    }
+
+    fn user_rejected() -> Self {
+        Self::new(EIP1193_USER_REJECTED, "User rejected the request.")
+    }
+
+    fn unauthorized() -> Self {
+        Self::new(
+            EIP1193_UNAUTHORIZED,
+            "The requested account has not been authorized by the user.",
+        )
+    }
+}
+
+impl From<EthError> for Eip1193Error {
+    fn from(error: EthError) -> Self {
+        Self::new(EIP1193_INTERNAL_ERROR, error.to_string())
+    }
+}
+
+impl From<ProviderError> for Eip1193Error {
+    fn from(error: ProviderError) -> Self {
+        // the errors returned by the node are passed as they are
+        match error.as_error_response() {
+            Some(response) => Self::new(response.code, response.message.clone()),
+            None => Self::new(EIP1193_INTERNAL_ERROR, error.to_string()),
+        }
+    }
+}
+
+/// The arguments of EIP-1193 `request`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Eip1193Request {
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// A chain the dApps can switch to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Eip1193Chain {
+    pub chain_id: u64,
+    pub chain_name: String,
+    /// the web3 API URL which the read-only calls are forwarded to
+    pub rpc_url: String,
+    /// if the chain only accepts legacy transactions
+    #[serde(default)]
+    pub legacy: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_currency_symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_explorer_url: Option<String>,
+}
+
+/// The requests to be confirmed by the user
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Eip1193Approval {
+    /// `eth_requestAccounts`: connect the accounts to the dApp
+    RequestAccounts { accounts: Vec<String> },
+    /// `personal_sign`: the message is the UTF-8 text if it's valid, or else the hex string
+    PersonalSign { address: String, message: String },
+    /// `eth_signTypedData_v4`
+    SignTypedData { address: String, typed_data: String },
+    /// `eth_sendTransaction`: the transaction object as sent by the dApp
+    SendTransaction { chain_id: u64, transaction: Value },
+    /// `wallet_switchEthereumChain`
+    SwitchChain { chain_id: u64 },
+    /// `wallet_addEthereumChain`
+    AddChain { chain: Eip1193Chain },
+}
+
+/// The user confirmation of the requests (e.g. a popup of the browser extension);
+/// the future resolves to `true` if the user approved the request
+pub trait Eip1193Approver {
+    fn approve<'a>(&'a self, approval: &'a Eip1193Approval) -> LocalBoxFuture<'a, bool>;
+}
+
+struct Eip1193State {
+    chains: Vec<Eip1193Chain>,
+    chain_id: u64,
+    connected: bool,
+}
+
+impl Eip1193State {
+    fn chain(&self) -> Result<Eip1193Chain, Eip1193Error> {
+        self.chains
+            .iter()
+            .find(|chain| chain.chain_id == self.chain_id)
+            .cloned()
+            .ok_or_else(|| Eip1193Error::new(EIP1193_UNRECOGNIZED_CHAIN, "Unrecognized chain."))
+    }
+}
+
+/// The parameter of `wallet_addEthereumChain` (EIP-3085)
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddEthereumChainParameter {
+    chain_id: String,
+    chain_name: String,
+    rpc_urls: Vec<String>,
+    #[serde(default)]
+    native_currency: Option<NativeCurrency>,
+    #[serde(default)]
+    block_explorer_urls: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct NativeCurrency {
+    symbol: String,
+}
+
+/// The parameter of `wallet_switchEthereumChain` (EIP-3326)
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SwitchEthereumChainParameter {
+    chain_id: String,
+}
+
+/// the chain ID from its hex string (e.g. "0x19")
+fn parse_chain_id(chain_id: &str) -> Result<u64, Eip1193Error> {
+    chain_id
+        .strip_prefix("0x")
+        .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+        .ok_or_else(|| Eip1193Error::invalid_params(format!("invalid chain ID: {chain_id}")))
+}
+
+/// the parameter at the index
+fn param<T: DeserializeOwned>(params: &Value, index: usize) -> Result<T, Eip1193Error> {
+    let value = params
+        .get(index)
+        .cloned()
+        .ok_or_else(|| Eip1193Error::invalid_params(format!("missing parameter {index}")))?;
+    serde_json::from_value(value).map_err(|e| Eip1193Error::invalid_params(e.to_string()))
+}
+
+/// The dispatcher of the EIP-1193 requests of dApps: the account and signing methods
+/// are handled with the key after the user confirmation, and the read-only calls
+/// are forwarded to the web3 API of the current chain.
+/// One dispatcher should be created per dApp origin, as it tracks the connection.
+pub struct Eip1193Dispatcher<A> {
+    secret_key: Arc<SecretKey>,
+    address: Address,
+    approver: A,
+    state: Mutex<Eip1193State>,
+}
+
+impl<A: Eip1193Approver> Eip1193Dispatcher<A> {
+    /// Create the dispatcher of the account of the key on the chains,
+    /// starting on the chain of `chain_id` (which must be one of them)
+    pub fn new(
+        secret_key: Arc<SecretKey>,
+        chains: Vec<Eip1193Chain>,
+        chain_id: u64,
+        approver: A,
+    ) -> Result<Self, EthError> {
+        if !chains.iter().any(|chain| chain.chain_id == chain_id) {
+            return Err(EthError::IncorrectChainidError(format!(
+                "the chain {chain_id} is not one of the chains"
+            )));
+        }
+        let address = LocalWallet::from(
+            secret_key
+                .get_eth_signing_key()
+                .map_err(EthError::HdWrapError)?,
+        )
+        .address();
+        Ok(Self {
+            secret_key,
+            address,
+            approver,
+            state: Mutex::new(Eip1193State {
+                chains,
+                chain_id,
+                connected: false,
+            }),
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, Eip1193State> {
+        // the state stays consistent even if a holder of the lock panicked
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// the current chain ID
+    pub fn chain_id(&self) -> u64 {
+        self.state().chain_id
+    }
+
+    /// if the dApp is connected to the account
+    pub fn is_connected(&self) -> bool {
+        self.state().connected
+    }
+
+    /// disconnect the dApp (it has to call `eth_requestAccounts` again)
+    pub fn disconnect(&self) {
+        self.state().connected = false;
+    }
+
+    /// the checksummed addresses exposed to the dApp (`eth_accounts`),
+    /// which are empty if it's not connected
+    pub fn accounts(&self) -> Vec<String> {
+        if self.is_connected() {
+            vec![self.checksummed_address()]
+        } else {
+            vec![]
+        }
+    }
+
+    fn checksummed_address(&self) -> String {
+        to_checksum(&self.address, None)
+    }
+
+    /// check if the dApp is connected and the address is the account
+    fn check_account(&self, address: &str) -> Result<(), Eip1193Error> {
+        let address = Address::from_str(address)
+            .map_err(|_| Eip1193Error::invalid_params(format!("invalid address: {address}")))?;
+        if !self.is_connected() || address != self.address {
+            return Err(Eip1193Error::unauthorized());
+        }
+        Ok(())
+    }
+
+    async fn approve(&self, approval: Eip1193Approval) -> Result<(), Eip1193Error> {
+        if self.approver.approve(&approval).await {
+            Ok(())
+        } else {
+            Err(Eip1193Error::user_rejected())
+        }
+    }
+
+    /// Handle the request (`{ method, params }`) of the dApp
+    pub async fn request(&self, request: Eip1193Request) -> Result<Value, Eip1193Error> {
+        let params = &request.params;
+        match request.method.as_str() {
+            "eth_chainId" => Ok(json!(format!("{:#x}", self.chain_id()))),
+            "net_version" => Ok(json!(self.chain_id().to_string())),
+            "eth_accounts" => Ok(json!(self.accounts())),
+            "eth_requestAccounts" => {
+                if !self.is_connected() {
+                    self.approve(Eip1193Approval::RequestAccounts {
+                        accounts: vec![self.checksummed_address()],
+                    })
+                    .await?;
+                    self.state().connected = true;
+                }
+                Ok(json!([self.checksummed_address()]))
+            }
+            "personal_sign" => self.personal_sign(params).await,
+            "eth_signTypedData_v4" => self.sign_typed_data(params).await,
+            "eth_sendTransaction" => self.send_transaction(params).await,
+            "wallet_switchEthereumChain" => self.switch_chain(params).await,
+            "wallet_addEthereumChain" => self.add_chain(params).await,
+            method if FORWARDED_METHODS.contains(&method) => {
+                let chain = self.state().chain()?;
+                let provider = get_ethers_provider(&chain.rpc_url).await?;
+                // some nodes reject the requests with null parameters
+                let params = match params {
+                    Value::Null => json!([]),
+                    params => params.clone(),
+                };
+                Ok(provider.request(method, params).await?)
+            }
+            method => Err(Eip1193Error::new(
+                EIP1193_UNSUPPORTED_METHOD,
+                format!("Unsupported method: {method}"),
+            )),
+        }
+    }
+
+    /// `personal_sign` with the parameters `[message, address]`
+    /// (the message is a hex string, or a text sent by some dApps)
+    async fn personal_sign(&self, params: &Value) -> Result<Value, Eip1193Error> {
+        let message: String = param(params, 0)?;
+        let address: String = param(params, 1)?;
+        self.check_account(&address)?;
+        let bytes = match message.strip_prefix("0x").map(hex::decode) {
+            Some(Ok(bytes)) => bytes,
+            _ => message.as_bytes().to_vec(),
+        };
+        let message = String::from_utf8(bytes.clone()).unwrap_or(message);
+        self.approve(Eip1193Approval::PersonalSign {
+            address: self.checksummed_address(),
+            message,
+        })
+        .await?;
+        let signature = self
+            .secret_key
+            .eth_sign(&bytes, self.chain_id())
+            .map_err(EthError::HdWrapError)?;
+        Ok(json!(format!("0x{signature}")))
+    }
+
+    /// `eth_signTypedData_v4` with the parameters `[address, typedData]`
+    /// (the typed data is a JSON string or object)
+    async fn sign_typed_data(&self, params: &Value) -> Result<Value, Eip1193Error> {
+        let address: String = param(params, 0)?;
+        self.check_account(&address)?;
+        let typed_data = match params.get(1) {
+            Some(Value::String(json)) => json.clone(),
+            Some(value @ Value::Object(_)) => value.to_string(),
+            _ => return Err(Eip1193Error::invalid_params("invalid typed data")),
+        };
+        // the typed data of another chain must not be signed
+        let domain_chain_id = serde_json::from_str::<Value>(&typed_data)
+            .map_err(|e| Eip1193Error::invalid_params(e.to_string()))?
+            .pointer("/domain/chainId")
+            .cloned();
+        let chain_id = self.chain_id();
+        let matches_chain = match &domain_chain_id {
+            None => true,
+            Some(Value::Number(n)) => n.as_u64() == Some(chain_id),
+            Some(Value::String(s)) => {
+                parse_chain_id(s).ok().or_else(|| s.parse().ok()) == Some(chain_id)
+            }
+            Some(_) => false,
+        };
+        if !matches_chain {
+            return Err(Eip1193Error::invalid_params(format!(
+                "the chain ID of the domain must be {chain_id}"
+            )));
+        }
+        self.approve(Eip1193Approval::SignTypedData {
+            address: self.checksummed_address(),
+            typed_data: typed_data.clone(),
+        })
+        .await?;
+        let signature = EthSigner::new(self.secret_key.clone()).sign_typed_data(&typed_data)?;
+        Ok(json!(signature))
+    }
+
+    /// `eth_sendTransaction` with the parameters `[transaction]`:
+    /// the missing nonce, gas limit and fees are filled before it's signed and sent
+    async fn send_transaction(&self, params: &Value) -> Result<Value, Eip1193Error> {
+        let transaction: Value = param(params, 0)?;
+        let mut tx: DynamicTransactionRequest = serde_json::from_value(transaction.clone())
+            .map_err(|e| Eip1193Error::invalid_params(e.to_string()))?;
+        let from = tx.from.unwrap_or(self.address);
+        self.check_account(&format!("{from:?}"))?;
+        let chain = self.state().chain()?;
+        self.approve(Eip1193Approval::SendTransaction {
+            chain_id: chain.chain_id,
+            transaction,
+        })
+        .await?;
+
+        tx.from = Some(self.address);
+        tx.chain_id = Some(chain.chain_id.into());
+        let mut tx = if !chain.legacy && tx.gas_price.is_none() && tx.access_list.0.is_empty() {
+            TypedTransaction::Eip1559(tx.to_eip1559_tx())
+        } else if chain.legacy {
+            TypedTransaction::Legacy(tx.to_legacy_tx())
+        } else {
+            tx.to_type_tx()
+        };
+        let provider = get_ethers_provider(&chain.rpc_url).await?;
+        let wallet = LocalWallet::from(
+            self.secret_key
+                .get_eth_signing_key()
+                .map_err(EthError::HdWrapError)?,
+        )
+        .with_chain_id(chain.chain_id);
+        let nonce_tracker = NonceTracker::new(provider.clone(), chain.chain_id, self.address);
+        let nonce = match tx.nonce() {
+            Some(nonce) => *nonce,
+            None => {
+                let nonce = nonce_tracker.next().await?;
+                tx.set_nonce(nonce);
+                nonce
+            }
+        };
+        let client = SignerMiddleware::new(provider, wallet);
+        match client.send_transaction(tx, None).await {
+            Ok(pending_tx) => Ok(json!(format!("{:?}", pending_tx.tx_hash()))),
+            Err(e) => {
+                nonce_tracker.release(nonce, &e.to_string());
+                Err(Eip1193Error::from(EthError::SendTxFail(e)))
+            }
+        }
+    }
+
+    /// `wallet_switchEthereumChain` with the parameters `[{ chainId }]`
+    async fn switch_chain(&self, params: &Value) -> Result<Value, Eip1193Error> {
+        let parameter: SwitchEthereumChainParameter = param(params, 0)?;
+        let chain_id = parse_chain_id(&parameter.chain_id)?;
+        {
+            let state = self.state();
+            if state.chain_id == chain_id {
+                return Ok(Value::Null);
+            }
+            if !state.chains.iter().any(|chain| chain.chain_id == chain_id) {
+                return Err(Eip1193Error::new(
+                    EIP1193_UNRECOGNIZED_CHAIN,
+                    format!("Unrecognized chain ID {:#x}.", chain_id),
+                ));
+            }
+        }
+        self.approve(Eip1193Approval::SwitchChain { chain_id })
+            .await?;
+        self.state().chain_id = chain_id;
+        Ok(Value::Null)
+    }
+
+    /// `wallet_addEthereumChain` with the parameters `[{ chainId, chainName, rpcUrls, ... }]`;
+    /// the chain is added but not switched to, and known chains are not changed
+    async fn add_chain(&self, params: &Value) -> Result<Value, Eip1193Error> {
+        let parameter: AddEthereumChainParameter = param(params, 0)?;
+        let chain_id = parse_chain_id(&parameter.chain_id)?;
+        if self
+            .state()
+            .chains
+            .iter()
+            .any(|chain| chain.chain_id == chain_id)
+        {
+            return Ok(Value::Null);
+        }
+        let rpc_url = parameter
+            .rpc_urls
+            .into_iter()
+            .find(|url| url.starts_with("https://"))
+            .ok_or_else(|| Eip1193Error::invalid_params("an HTTPS RPC URL is required"))?;
+        let chain = Eip1193Chain {
+            chain_id,
+            chain_name: parameter.chain_name,
+            rpc_url,
+            legacy: false,
+            native_currency_symbol: parameter.native_currency.map(|currency| currency.symbol),
+            block_explorer_url: parameter
+                .block_explorer_urls
+                .and_then(|urls| urls.into_iter().next()),
+        };
+        self.approve(Eip1193Approval::AddChain {
+            chain: chain.clone(),
+        })
+        .await?;
+        let mut state = self.state();
+        if !state.chains.iter().any(|chain| chain.chain_id == chain_id) {
+            state.chains.push(chain);
+        }
+        Ok(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{recover_eth_personal_signer, HDWallet};
+    use futures::executor::block_on;
+    use std::cell::RefCell;
+
+    const MNEMONIC: &str = "apple elegant knife hawk there screen vehicle lounge tube sun engage bus custom market pioneer casual wink present cat metal ride shallow fork brief";
+
+    /// approves the requests if `approved`, and records them
+    struct TestApprover {
+        approved: bool,
+        approvals: RefCell<Vec<Eip1193Approval>>,
+    }
+
+    impl Eip1193Approver for TestApprover {
+        fn approve<'a>(&'a self, approval: &'a Eip1193Approval) -> LocalBoxFuture<'a, bool> {
+            self.approvals.borrow_mut().push(approval.clone());
+            Box::pin(async move { self.approved })
+        }
+    }
+
+    fn dispatcher(approved: bool) -> Eip1193Dispatcher<TestApprover> {
+        let wallet = HDWallet::recover_wallet(MNEMONIC.to_string(), None).unwrap();
+        let secret_key = wallet.get_key("m/44'/60'/0'/0/0".to_string()).unwrap();
+        let chains = vec![Eip1193Chain {
+            chain_id: 25,
+            chain_name: "Cronos".to_owned(),
+            rpc_url: "https://evm.cronos.org".to_owned(),
+            legacy: false,
+            native_currency_symbol: Some("CRO".to_owned()),
+            block_explorer_url: None,
+        }];
+        let approver = TestApprover {
+            approved,
+            approvals: RefCell::new(vec![]),
+        };
+        Eip1193Dispatcher::new(secret_key, chains, 25, approver).unwrap()
+    }
+
+    fn request(
+        dispatcher: &Eip1193Dispatcher<TestApprover>,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, Eip1193Error> {
+        block_on(dispatcher.request(Eip1193Request {
+            method: method.to_owned(),
+            params,
+        }))
+    }
+
+    #[test]
+    fn test_accounts_and_signing() {
+        let dispatcher = dispatcher(true);
+        let address = dispatcher.checksummed_address();
+        assert_eq!(
+            request(&dispatcher, "eth_chainId", Value::Null).unwrap(),
+            json!("0x19")
+        );
+        assert_eq!(
+            request(&dispatcher, "eth_accounts", Value::Null).unwrap(),
+            json!([])
+        );
+        // signing requires the connection
+        assert_eq!(
+            request(
+                &dispatcher,
+                "personal_sign",
+                json!(["0x68656c6c6f", address])
+            )
+            .unwrap_err()
+            .code,
+            EIP1193_UNAUTHORIZED
+        );
+        assert_eq!(
+            request(&dispatcher, "eth_requestAccounts", Value::Null).unwrap(),
+            json!([address])
+        );
+        assert_eq!(
+            request(&dispatcher, "eth_accounts", Value::Null).unwrap(),
+            json!([address])
+        );
+
+        let signature = request(
+            &dispatcher,
+            "personal_sign",
+            json!(["0x68656c6c6f", address]),
+        )
+        .unwrap();
+        assert_eq!(
+            recover_eth_personal_signer("hello", signature.as_str().unwrap()).unwrap(),
+            address
+        );
+        assert_eq!(
+            dispatcher.approver.approvals.borrow().last(),
+            Some(&Eip1193Approval::PersonalSign {
+                address: address.clone(),
+                message: "hello".to_owned(),
+            })
+        );
+        assert_eq!(
+            request(&dispatcher, "eth_sign", json!([address, "0x00"]))
+                .unwrap_err()
+                .code,
+            EIP1193_UNSUPPORTED_METHOD
+        );
+        // only the read-only methods are forwarded to the node
+        for method in [
+            "personal_unlockAccount",
+            "eth_sendTransactionAsync",
+            "debug_traceCall",
+        ] {
+            assert_eq!(
+                request(&dispatcher, method, Value::Null).unwrap_err().code,
+                EIP1193_UNSUPPORTED_METHOD
+            );
+        }
+    }
+
+    #[test]
+    fn test_chains_and_rejection() {
+        let dispatcher = dispatcher(true);
+        assert_eq!(
+            request(
+                &dispatcher,
+                "wallet_switchEthereumChain",
+                json!([{ "chainId": "0x152" }])
+            )
+            .unwrap_err()
+            .code,
+            EIP1193_UNRECOGNIZED_CHAIN
+        );
+        let chain = json!([{
+            "chainId": "0x152",
+            "chainName": "Cronos Testnet",
+            "rpcUrls": ["https://evm-t3.cronos.org"],
+            "nativeCurrency": { "name": "TCRO", "symbol": "TCRO", "decimals": 18 },
+        }]);
+        assert_eq!(
+            request(&dispatcher, "wallet_addEthereumChain", chain).unwrap(),
+            Value::Null
+        );
+        assert_eq!(dispatcher.chain_id(), 25);
+        // the RPC URLs of the new chains must be HTTPS
+        let insecure_chain = json!([{
+            "chainId": "0x1",
+            "chainName": "Ethereum",
+            "rpcUrls": ["http://mainnet.example.com"],
+        }]);
+        assert_eq!(
+            request(&dispatcher, "wallet_addEthereumChain", insecure_chain)
+                .unwrap_err()
+                .code,
+            EIP1193_INVALID_PARAMS
+        );
+        request(
+            &dispatcher,
+            "wallet_switchEthereumChain",
+            json!([{ "chainId": "0x152" }]),
+        )
+        .unwrap();
+        assert_eq!(dispatcher.chain_id(), 338);
+        assert_eq!(
+            request(&dispatcher, "net_version", Value::Null).unwrap(),
+            json!("338")
+        );
+
+        let dispatcher = self::dispatcher(false);
+        assert_eq!(
+            request(&dispatcher, "eth_requestAccounts", Value::Null)
+                .unwrap_err()
+                .code,
+            EIP1193_USER_REJECTED
+        );
+        assert!(!dispatcher.is_connected());
+    }
+}
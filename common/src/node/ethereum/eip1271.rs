@@ -0,0 +1,204 @@
+use crate::provider::get_ethers_provider;
+use crate::utils::{hash_from_str, hex_decode};
+use crate::{address_from_str, parse_eth_signature, EthError};
+use ethers::abi::{encode, Token};
+use ethers::prelude::{Address, Middleware, TransactionRequest, H256};
+use ethers::providers::{JsonRpcError, RpcError};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::hash_message;
+
+/// the value returned by `isValidSignature(bytes32,bytes)` of a valid signature,
+/// i.e. its function selector as per [EIP-1271](https://eips.ethereum.org/EIPS/eip-1271)
+pub const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+/// if the call failed as the contract reverted it (the error code 3 of geth,
+/// or the "execution reverted" message of the other nodes)
+fn is_execution_reverted(error: &JsonRpcError) -> bool {
+    error.code == 3 || error.message.contains("execution reverted")
+}
+
+/// call `isValidSignature(bytes32,bytes)` of the contract wallet;
+/// the signature is passed as it is, as contract wallets define its format
+/// (e.g. the concatenated signatures of the owners of a multisig)
+async fn is_valid_contract_signature(
+    contract: Address,
+    hash: H256,
+    signature: Vec<u8>,
+    web3api_url: &str,
+) -> Result<bool, EthError> {
+    let provider = get_ethers_provider(web3api_url).await?;
+    let mut data = EIP1271_MAGIC_VALUE.to_vec();
+    data.extend(encode(&[
+        Token::FixedBytes(hash.as_bytes().to_vec()),
+        Token::Bytes(signature),
+    ]));
+    let tx: TypedTransaction = TransactionRequest::new().to(contract).data(data).into();
+    match provider.call(&tx, None).await {
+        // the returned `bytes4` is left-aligned in the 32-byte word
+        Ok(output) => Ok(output.len() >= 4 && output[..4] == EIP1271_MAGIC_VALUE),
+        // the contract wallet may revert instead of returning another value,
+        // but the other errors (e.g. of the node) don't mean the signature is invalid
+        Err(e) if e.as_error_response().is_some_and(is_execution_reverted) => Ok(false),
+        Err(e) => Err(EthError::ContractCallError(format!(
+            "isValidSignature: {e}"
+        ))),
+    }
+}
+
+/// Check if the contract wallet accepts the signature of the hash value
+/// via its `isValidSignature` as per [EIP-1271](https://eips.ethereum.org/EIPS/eip-1271).
+/// Argument `hash` must be a hex value of 32 bytes (H256).
+pub async fn is_valid_eip1271_signature(
+    contract_address: &str,
+    hash: &str,
+    signature: &str,
+    web3api_url: &str,
+) -> Result<bool, EthError> {
+    let contract = address_from_str(contract_address)?;
+    is_valid_contract_signature(
+        contract,
+        hash_from_str(hash).map_err(|_| EthError::HexConversion)?,
+        hex_decode(signature).map_err(|_| EthError::HexConversion)?,
+        web3api_url,
+    )
+    .await
+}
+
+/// the signature of the hash value is verified with EIP-1271 if the account is a contract,
+/// or else recovered from the signature (which then must be a 65-byte or compact one)
+async fn verify_account_signature(
+    address: Address,
+    hash: H256,
+    signature: &str,
+    web3api_url: &str,
+) -> Result<bool, EthError> {
+    let provider = get_ethers_provider(web3api_url).await?;
+    let code = provider
+        .get_code(address, None)
+        .await
+        .map_err(|e| EthError::ContractCallError(e.to_string()))?;
+    if code.is_empty() {
+        let signer = parse_eth_signature(signature)?
+            .recover(hash)
+            .map_err(|_| EthError::SignatureError)?;
+        Ok(signer == address)
+    } else {
+        is_valid_contract_signature(
+            address,
+            hash,
+            hex_decode(signature).map_err(|_| EthError::HexConversion)?,
+            web3api_url,
+        )
+        .await
+    }
+}
+
+/// Check if the hash value was signed (`eth_sign`) by the account, which is either
+/// an externally owned account or a contract wallet supporting EIP-1271.
+/// Argument `hash` must be a hex value of 32 bytes (H256).
+pub async fn verify_eth_account_signature(
+    address: &str,
+    hash: &str,
+    signature: &str,
+    web3api_url: &str,
+) -> Result<bool, EthError> {
+    let address = address_from_str(address)?;
+    verify_account_signature(
+        address,
+        hash_from_str(hash).map_err(|_| EthError::HexConversion)?,
+        signature,
+        web3api_url,
+    )
+    .await
+}
+
+/// Check if the message was signed as per EIP-191 (`personal_sign`) by the account,
+/// which is either an externally owned account or a contract wallet supporting EIP-1271
+/// (which is given the EIP-191 hash of the message).
+pub async fn verify_eth_account_personal_signature(
+    address: &str,
+    message: &str,
+    signature: &str,
+    web3api_url: &str,
+) -> Result<bool, EthError> {
+    let address = address_from_str(address)?;
+    verify_account_signature(address, hash_message(message), signature, web3api_url).await
+}
+
+/// Check if the contract wallet accepts the signature of the hash value
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn is_valid_eip1271_signature_blocking(
+    contract_address: &str,
+    hash: &str,
+    signature: &str,
+    web3api_url: &str,
+) -> Result<bool, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(is_valid_eip1271_signature(
+        contract_address,
+        hash,
+        signature,
+        web3api_url,
+    ))
+}
+
+/// Check if the hash value was signed by the account (EOA or contract wallet)
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn verify_eth_account_signature_blocking(
+    address: &str,
+    hash: &str,
+    signature: &str,
+    web3api_url: &str,
+) -> Result<bool, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(verify_eth_account_signature(
+        address,
+        hash,
+        signature,
+        web3api_url,
+    ))
+}
+
+/// Check if the message was signed as per EIP-191 by the account (EOA or contract wallet)
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn verify_eth_account_personal_signature_blocking(
+    address: &str,
+    message: &str,
+    signature: &str,
+    web3api_url: &str,
+) -> Result<bool, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(verify_eth_account_personal_signature(
+        address,
+        message,
+        signature,
+        web3api_url,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_execution_reverted() {
+        let error = |code, message: &str| JsonRpcError {
+            code,
+            message: message.to_owned(),
+            data: None,
+        };
+        assert!(is_execution_reverted(&error(3, "execution reverted")));
+        assert!(is_execution_reverted(&error(
+            -32000,
+            "execution reverted: invalid signature"
+        )));
+        assert!(!is_execution_reverted(&error(
+            -32005,
+            "rate limit exceeded"
+        )));
+        assert!(!is_execution_reverted(&error(-32601, "method not found")));
+    }
+}
@@ -1,10 +1,12 @@
 #![cfg(feature = "abi-contract")]
 
+use crate::contract::format_token;
 use crate::node::ethereum::abi::{EthAbiParamType, EthAbiToken};
 use crate::transaction::{Eip712Error, EthError};
-use ethers::prelude::{abi, U256};
+use ethers::prelude::{abi, Address, H256, U256};
 use ethers::types::transaction::eip712::encode_eip712_type;
-use ethers::utils::keccak256;
+use ethers::utils::{hex, keccak256};
+use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 use std::str::FromStr;
 use std::{
@@ -74,6 +76,8 @@ const EIP712_DOMAIN_TYPE_NAME: &str = "EIP712Domain";
 pub struct Eip712TypedData {
     domain: HashMap<Eip712FieldName, Eip712FieldValue>,
     primary_type: Eip712StructName,
+    /// the resolved `encodeType` strings (with the referenced structs appended)
+    encoded_types: HashMap<Eip712StructName, String>,
     type_hashes: HashMap<Eip712StructName, U256>,
     types: HashMap<Eip712StructName, Eip712Struct>,
     values: HashMap<Eip712FieldName, Eip712FieldValue>,
@@ -136,13 +140,161 @@ impl Eip712TypedData {
 
     /// Encode the typed data.
     pub fn encode(&self) -> Result<Vec<u8>> {
-        let domain_separator = self.build_struct_hash(EIP712_DOMAIN_TYPE_NAME, &self.domain)?;
+        let domain_separator = self.domain_separator()?;
         let struct_hash = self.build_struct_hash(&self.primary_type, &self.values)?;
         let digest_input = [&[0x19, 0x01], &domain_separator[..], &struct_hash[..]].concat();
 
         Ok(keccak256(digest_input).to_vec())
     }
 
+    /// Hash of the domain, as returned by `DOMAIN_SEPARATOR()` of the verifying contract.
+    pub fn domain_separator(&self) -> Result<[u8; 32]> {
+        self.build_struct_hash(EIP712_DOMAIN_TYPE_NAME, &self.domain)
+    }
+
+    /// Hash of the message (the primary struct).
+    pub fn struct_hash(&self) -> Result<[u8; 32]> {
+        self.build_struct_hash(&self.primary_type, &self.values)
+    }
+
+    /// The primary type name.
+    pub fn primary_type(&self) -> &str {
+        &self.primary_type
+    }
+
+    /// The resolved `encodeType` string of the struct, e.g.
+    /// `Mail(Person from,Person to,string contents)Person(string name,address wallet)`.
+    pub fn encoded_type(&self, struct_name: &str) -> Result<&str> {
+        self.encoded_types
+            .get(struct_name)
+            .map(String::as_str)
+            .ok_or_else(|| Eip712Error::MissingTypeError(struct_name.to_owned()).into())
+    }
+
+    /// The resolved `encodeType` strings of all the struct types (sorted by the struct name).
+    pub fn encoded_types(&self) -> Vec<Eip712EncodedType> {
+        let mut encoded_types: Vec<_> = self
+            .encoded_types
+            .iter()
+            .map(|(name, encoded_type)| Eip712EncodedType {
+                name: name.clone(),
+                encoded_type: encoded_type.clone(),
+            })
+            .collect();
+        encoded_types.sort_by(|a, b| a.name.cmp(&b.name));
+        encoded_types
+    }
+
+    /// The flattened fields of the domain for display.
+    pub fn preview_domain(&self) -> Result<Vec<Eip712FieldPreview>> {
+        let mut fields = vec![];
+        self.preview_struct(EIP712_DOMAIN_TYPE_NAME, &self.domain, "", 0, &mut fields)?;
+        Ok(fields)
+    }
+
+    /// The flattened fields of the message for display: each struct or array field
+    /// is followed by its nested fields (one level deeper).
+    pub fn preview_message(&self) -> Result<Vec<Eip712FieldPreview>> {
+        let mut fields = vec![];
+        self.preview_struct(&self.primary_type, &self.values, "", 0, &mut fields)?;
+        Ok(fields)
+    }
+
+    /// The hashes, the `encodeType` strings and the flattened fields of the typed data.
+    pub fn preview(&self) -> Result<Eip712TypedDataPreview> {
+        Ok(Eip712TypedDataPreview {
+            primary_type: self.primary_type.clone(),
+            domain_separator: format!("0x{}", hex::encode(self.domain_separator()?)),
+            struct_hash: format!("0x{}", hex::encode(self.struct_hash()?)),
+            hash: format!("0x{}", hex::encode(self.encode()?)),
+            encoded_types: self.encoded_types(),
+            domain: self.preview_domain()?,
+            message: self.preview_message()?,
+        })
+    }
+
+    /// Recover the address of the signer of the typed data from the signature
+    /// (65 bytes or EIP-2098 compact in hex).
+    pub fn recover_signer(&self, signature: &str) -> Result<Address> {
+        let signature = crate::parse_eth_signature(signature)?;
+        let hash = H256::from_slice(&self.encode()?);
+        signature
+            .recover(hash)
+            .map_err(|_| EthError::SignatureError)
+    }
+
+    /// Flatten the fields of the struct in their declaration order.
+    fn preview_struct(
+        &self,
+        struct_name: &str,
+        values: &HashMap<Eip712FieldName, Eip712FieldValue>,
+        path: &str,
+        depth: u32,
+        fields: &mut Vec<Eip712FieldPreview>,
+    ) -> Result<()> {
+        for field in &self.get_struct(struct_name)?.fields {
+            let value = values
+                .get(&field.name)
+                .ok_or_else(|| Eip712Error::MissingFieldError(field.name.clone()))?;
+            self.preview_value(
+                &field.name,
+                field.r#type.as_ref(),
+                value,
+                &join_path(path, &field.name),
+                depth,
+                fields,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Flatten the value: a struct or an array is followed by its items.
+    fn preview_value(
+        &self,
+        name: &str,
+        field_type: &EthAbiParamType,
+        value: &Eip712FieldValue,
+        path: &str,
+        depth: u32,
+        fields: &mut Vec<Eip712FieldPreview>,
+    ) -> Result<()> {
+        let mut preview = Eip712FieldPreview {
+            path: path.to_owned(),
+            name: name.to_owned(),
+            field_type: field_type.to_string(),
+            value: None,
+            depth,
+        };
+        match (field_type, value) {
+            (EthAbiParamType::Struct(_), Eip712FieldValue::Struct(sub_name, sub_values)) => {
+                fields.push(preview);
+                self.preview_struct(sub_name, sub_values, path, depth + 1, fields)
+            }
+            (
+                EthAbiParamType::Array(item_type) | EthAbiParamType::FixedArray(item_type, _),
+                Eip712FieldValue::Array(items) | Eip712FieldValue::FixedArray(items),
+            ) => {
+                fields.push(preview);
+                for (i, item) in items.iter().enumerate() {
+                    let item_name = format!("{name}[{i}]");
+                    let item_path = format!("{path}[{i}]");
+                    self.preview_value(&item_name, item_type, item, &item_path, depth + 1, fields)?;
+                }
+                Ok(())
+            }
+            (_, Eip712FieldValue::String(value)) => {
+                preview.value = Some(value.clone());
+                fields.push(preview);
+                Ok(())
+            }
+            _ => {
+                preview.value = Some(format_token(&value.try_into()?));
+                fields.push(preview);
+                Ok(())
+            }
+        }
+    }
+
     /// Build hashes of the all associating struct types when constructing. Since these type hashes
     /// could be reused when encoding primary struct and other referenced sub-structs.
     fn build_all_type_hashes(&mut self) -> Result<()> {
@@ -179,11 +331,12 @@ impl Eip712TypedData {
             }
 
             // Hash encoded data.
-            let hash = keccak256(encoded_data);
+            let hash = keccak256(&encoded_data);
 
             // Save typed hash of this struct.
             self.type_hashes
                 .insert(struct_name.clone(), U256::from(&hash[..]));
+            self.encoded_types.insert(struct_name.clone(), encoded_data);
         }
 
         Ok(())
@@ -266,6 +419,51 @@ impl Eip712TypedData {
     }
 }
 
+/// the path of the nested field, e.g. `from.wallet`
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+/// The resolved `encodeType` string of an EIP-712 struct type
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Eip712EncodedType {
+    pub name: String,
+    pub encoded_type: String,
+}
+
+/// A field of the EIP-712 typed data flattened for display
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Eip712FieldPreview {
+    /// the dotted path of the field, e.g. `from.wallet` or `details[0].token`
+    pub path: String,
+    pub name: String,
+    /// the EIP-712 type, e.g. `address`, `Person` or `PermitDetails[]`
+    pub field_type: String,
+    /// the readable value (none for structs and arrays, whose fields follow)
+    pub value: Option<String>,
+    /// the nesting level (0 for the fields of the primary type)
+    pub depth: u32,
+}
+
+/// The inspection of the EIP-712 typed data for approval screens
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Eip712TypedDataPreview {
+    pub primary_type: String,
+    /// the domain separator as hex with prefix `0x`
+    pub domain_separator: String,
+    /// the hash of the message as hex with prefix `0x`
+    pub struct_hash: String,
+    /// the hash to be signed as hex with prefix `0x`
+    pub hash: String,
+    pub encoded_types: Vec<Eip712EncodedType>,
+    pub domain: Vec<Eip712FieldPreview>,
+    pub message: Vec<Eip712FieldPreview>,
+}
+
 /// EIP-712 struct type
 #[derive(Debug, Eq, PartialEq)]
 struct Eip712Struct {
@@ -445,6 +643,66 @@ mod eip712_encoding_tests {
         );
     }
 
+    #[test]
+    fn test_eip712_typed_data_preview() {
+        let typed_data = Eip712TypedData::new(RECURSIVELY_NESTED_JSON_TYPED_DATA).unwrap();
+        assert_eq!(
+            typed_data.encoded_type("Transaction").unwrap(),
+            "Transaction(string DAS_MESSAGE,string inputsCapacity,string outputsCapacity,string fee,Action action,Cell[] inputs,Cell[] outputs,bytes32 digest)Action(string action,string params)Cell(string capacity,string lock,string type,string data,string extraData)"
+        );
+
+        let preview = typed_data.preview().unwrap();
+        assert_eq!(preview.primary_type, "Transaction");
+        assert_eq!(
+            preview.hash,
+            "0x42b1aca82bb6900ff75e90a136de550a58f1a220a071704088eabd5e6ce20446"
+        );
+        assert_eq!(preview.encoded_types.len(), 4);
+        let action = &preview.message[4];
+        assert_eq!(
+            (
+                action.path.as_str(),
+                action.field_type.as_str(),
+                action.depth
+            ),
+            ("action", "Action", 0)
+        );
+        assert!(action.value.is_none());
+        let params = &preview.message[6];
+        assert_eq!(params.path, "action.params");
+        assert_eq!(params.value.as_deref(), Some("0x00"));
+        assert_eq!(params.depth, 1);
+        let output = preview
+            .message
+            .iter()
+            .find(|field| field.path == "outputs[1].capacity")
+            .unwrap();
+        assert_eq!(output.value.as_deref(), Some("201 CKB"));
+        assert_eq!(output.depth, 2);
+        assert_eq!(
+            preview.message.last().unwrap().value.as_deref(),
+            Some("0x53a6c0f19ec281604607f5d6817e442082ad1882bef0df64d84d3810dae561eb")
+        );
+    }
+
+    #[test]
+    fn test_eip712_typed_data_signer_recovery() {
+        use ethers::prelude::{LocalWallet, Signer};
+
+        let typed_data = Eip712TypedData::new(SIMPLE_JSON_TYPED_DATA).unwrap();
+        let wallet = LocalWallet::from(crate::SecretKey::new().get_eth_signing_key().unwrap());
+        let signature = wallet
+            .sign_hash(H256::from_slice(&typed_data.encode().unwrap()))
+            .unwrap();
+        assert_eq!(
+            typed_data
+                .recover_signer(&format!("0x{signature}"))
+                .unwrap(),
+            wallet.address()
+        );
+        assert!(typed_data.recover_signer("0x1234").is_err());
+    }
+
     #[test]
     fn test_eip712_typed_data_recursively_nested_encoding() {
         let typed_data = Eip712TypedData::new(RECURSIVELY_NESTED_JSON_TYPED_DATA).unwrap();
@@ -270,10 +270,10 @@ fn json_to_string(json_value: &serde_json::Value) -> Option<Eip712FieldValue> {
 fn json_to_uint(json_value: &serde_json::Value) -> Option<Eip712FieldValue> {
     match json_value {
         serde_json::Value::Number(u) => u.as_u64().map(Into::into),
-        serde_json::Value::String(s) => s
-            .parse::<u128>()
+        // a decimal string (e.g. the max uint256 amount), or a hex one
+        serde_json::Value::String(s) => U256::from_dec_str(s)
             .ok()
-            .map_or_else(|| U256::from_str(s).ok(), |u| Some(u.into())),
+            .or_else(|| U256::from_str(s.strip_prefix("0x").unwrap_or(s)).ok()),
         _ => None,
     }
     .map(Eip712FieldValue::Uint)
@@ -0,0 +1,245 @@
+use crate::provider::get_ethers_provider;
+use crate::{address_from_str, EthError};
+use ethers::prelude::{Address, Middleware, NameOrAddress};
+use ethers::providers::{ens::ENS_ADDRESS, Http, Provider, ProviderError};
+use ethers::utils::to_checksum;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+lazy_static::lazy_static! {
+    /// the name registries configured per chain id (in addition to the default ENS ones)
+    static ref NAME_REGISTRIES: RwLock<HashMap<u64, Address>> = RwLock::new(HashMap::new());
+}
+
+/// the ENS registry deployed on Ethereum mainnet and its testnets
+fn default_name_registry(chain_id: u64) -> Option<Address> {
+    match chain_id {
+        // mainnet, goerli, holesky and sepolia
+        1 | 5 | 17000 | 11155111 => Some(ENS_ADDRESS),
+        _ => None,
+    }
+}
+
+/// set the address of the ENS-compatible name registry of the chain,
+/// e.g. the Cronos ID registry on Cronos, or a locally deployed one on a devnet
+pub fn set_eth_name_registry(chain_id: u64, registry_address: &str) -> Result<(), EthError> {
+    let registry = address_from_str(registry_address)?;
+    NAME_REGISTRIES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(chain_id, registry);
+    Ok(())
+}
+
+/// the name registry of the chain (the configured one or the default ENS one)
+fn name_registry(chain_id: u64) -> Result<Address, EthError> {
+    NAME_REGISTRIES
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(&chain_id)
+        .copied()
+        .or_else(|| default_name_registry(chain_id))
+        .ok_or_else(|| {
+            EthError::NameResolutionError(format!(
+                "no name registry is set for the chain {chain_id}"
+            ))
+        })
+}
+
+/// if the string is a name (e.g. "vitalik.eth" or "alice.cro") rather than a hex address
+pub fn is_eth_name(name_or_address: &str) -> bool {
+    !name_or_address.starts_with("0x")
+        && name_or_address.contains('.')
+        && !name_or_address.starts_with('.')
+        && !name_or_address.ends_with('.')
+        && !name_or_address.chars().any(char::is_whitespace)
+}
+
+/// the normalized name (lowercased) to be hashed and resolved;
+/// the labels may only contain ASCII letters, digits, hyphens and leading underscores,
+/// as the full ENSIP-15 (Unicode) normalization isn't supported,
+/// so a confusable or non-normalized Unicode name is rejected instead of hashed to another node
+pub fn normalize_eth_name(name: &str) -> Result<String, EthError> {
+    let invalid = |reason: &str| EthError::NameResolutionError(format!("{name}: {reason}"));
+    if !name.is_ascii() {
+        return Err(invalid("non-ASCII names aren't supported"));
+    }
+    let name = name.to_ascii_lowercase();
+    for label in name.split('.') {
+        if label.is_empty() {
+            return Err(invalid("empty label"));
+        }
+        let unprefixed = label.trim_start_matches('_');
+        if !unprefixed
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(invalid("invalid character in the label"));
+        }
+        if label.get(2..4) == Some("--") {
+            return Err(invalid(
+                "hyphens in the third and fourth positions of the label",
+            ));
+        }
+    }
+    Ok(name)
+}
+
+/// the provider querying the name registry of the chain
+async fn name_provider(chain_id: u64, web3api_url: &str) -> Result<Provider<Http>, EthError> {
+    let registry = name_registry(chain_id)?;
+    Ok(get_ethers_provider(web3api_url).await?.ens(registry))
+}
+
+/// if the name (or its resolver) isn't registered
+fn is_unregistered_error(error: &ProviderError) -> bool {
+    matches!(
+        error,
+        ProviderError::EnsError(_) | ProviderError::EnsNotOwned(_)
+    )
+}
+
+/// resolve the name to its address with the name registry of the chain
+pub async fn resolve_eth_name(
+    name: &str,
+    chain_id: u64,
+    web3api_url: &str,
+) -> Result<Address, EthError> {
+    let name = normalize_eth_name(name)?;
+    let provider = name_provider(chain_id, web3api_url).await?;
+    let address = provider
+        .resolve_name(&name)
+        .await
+        .map_err(|e| EthError::NameResolutionError(format!("{name}: {e}")))?;
+    if address.is_zero() {
+        return Err(EthError::NameResolutionError(format!(
+            "{name}: no address is set"
+        )));
+    }
+    Ok(address)
+}
+
+/// the address of the hex address or the name
+pub async fn resolve_eth_address(
+    name_or_address: &str,
+    chain_id: u64,
+    web3api_url: &str,
+) -> Result<Address, EthError> {
+    if is_eth_name(name_or_address) {
+        resolve_eth_name(name_or_address, chain_id, web3api_url).await
+    } else {
+        address_from_str(name_or_address)
+    }
+}
+
+/// the address of the `NameOrAddress` value (e.g. the target of the EIP-681 request)
+pub async fn resolve_name_or_address(
+    name_or_address: &NameOrAddress,
+    chain_id: u64,
+    web3api_url: &str,
+) -> Result<Address, EthError> {
+    match name_or_address {
+        NameOrAddress::Address(address) => Ok(*address),
+        NameOrAddress::Name(name) => resolve_eth_name(name, chain_id, web3api_url).await,
+    }
+}
+
+/// the primary name of the address from its reverse record,
+/// only if the name resolves back to the address (so it's safe to be displayed);
+/// none if the address has no (or a mismatching) reverse record
+pub async fn lookup_eth_name(
+    address: &str,
+    chain_id: u64,
+    web3api_url: &str,
+) -> Result<Option<String>, EthError> {
+    let address = address_from_str(address)?;
+    let provider = name_provider(chain_id, web3api_url).await?;
+    let name = match provider.lookup_address(address).await {
+        // only the normalized names are displayed
+        Ok(name) if is_eth_name(&name) && normalize_eth_name(&name).ok() == Some(name.clone()) => {
+            name
+        }
+        Ok(_) => return Ok(None),
+        Err(e) if is_unregistered_error(&e) => return Ok(None),
+        Err(e) => return Err(EthError::NameResolutionError(e.to_string())),
+    };
+    match provider.resolve_name(&name).await {
+        Ok(resolved) if resolved == address => Ok(Some(name)),
+        Ok(_) => Ok(None),
+        Err(e) if is_unregistered_error(&e) => Ok(None),
+        Err(e) => Err(EthError::NameResolutionError(format!("{name}: {e}"))),
+    }
+}
+
+/// resolve the name to its checksummed address
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn resolve_eth_name_blocking(
+    name: &str,
+    chain_id: u64,
+    web3api_url: &str,
+) -> Result<String, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    let address = rt.block_on(resolve_eth_name(name, chain_id, web3api_url))?;
+    Ok(to_checksum(&address, None))
+}
+
+/// the verified primary name of the address
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn lookup_eth_name_blocking(
+    address: &str,
+    chain_id: u64,
+    web3api_url: &str,
+) -> Result<Option<String>, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(lookup_eth_name(address, chain_id, web3api_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_told_apart_from_addresses() {
+        assert!(is_eth_name("vitalik.eth"));
+        assert!(is_eth_name("alice.cro"));
+        assert!(is_eth_name("pay.alice.cro"));
+        assert!(!is_eth_name("0x2c600e0a72b3ae39e9b27d2e310b180abe779368"));
+        assert!(!is_eth_name("alice"));
+        assert!(!is_eth_name("alice."));
+        assert!(!is_eth_name("ali ce.eth"));
+    }
+
+    #[test]
+    fn names_are_normalized_or_rejected() {
+        assert_eq!(normalize_eth_name("Alice.CRO").unwrap(), "alice.cro");
+        assert_eq!(
+            normalize_eth_name("pay.alice.eth").unwrap(),
+            "pay.alice.eth"
+        );
+        assert_eq!(
+            normalize_eth_name("_pay.my-name.eth").unwrap(),
+            "_pay.my-name.eth"
+        );
+        // Cyrillic "а" confusable with the Latin "a"
+        assert!(normalize_eth_name("\u{430}lice.eth").is_err());
+        assert!(normalize_eth_name("ALİCE.eth").is_err());
+        assert!(normalize_eth_name("alice..eth").is_err());
+        assert!(normalize_eth_name("ali_ce.eth").is_err());
+        assert!(normalize_eth_name("ali$ce.eth").is_err());
+        assert!(normalize_eth_name("xn--lice-s8a.eth").is_err());
+    }
+
+    #[test]
+    fn name_registry_is_configurable_per_chain() {
+        assert_eq!(name_registry(1).unwrap(), ENS_ADDRESS);
+        assert!(name_registry(777).is_err());
+        set_eth_name_registry(777, "0x5FbDB2315678afecb367f032d93F642f64180aa3").unwrap();
+        assert_eq!(
+            name_registry(777).unwrap(),
+            address_from_str("0x5FbDB2315678afecb367f032d93F642f64180aa3").unwrap()
+        );
+        assert!(set_eth_name_registry(778, "alice.cro").is_err());
+    }
+}
@@ -0,0 +1,332 @@
+use crate::provider::get_ethers_provider;
+use crate::{u256_from_dec_str, EthError, EthNetwork};
+use ethers::prelude::Middleware;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{BlockNumber, FeeHistory, U256};
+use ethers::utils::format_ether;
+use serde::{Deserialize, Serialize};
+
+/// the number of recent blocks to sample in `eth_feeHistory`
+const FEE_HISTORY_BLOCKS: u64 = 10;
+/// the priority fee reward percentiles of the slow, normal and fast tiers
+const REWARD_PERCENTILES: [f64; 3] = [10.0, 50.0, 90.0];
+/// the priority fee used when the sampled blocks were empty (1 gwei)
+const DEFAULT_PRIORITY_FEE: u64 = 1_000_000_000;
+/// the gas price multipliers (in percent) of the slow, normal and fast tiers on legacy chains
+const LEGACY_MULTIPLIERS: [u64; 3] = [100, 110, 125];
+
+/// the speed tier of the fee estimation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EthFeeSpeed {
+    Slow,
+    Normal,
+    Fast,
+}
+
+/// the suggested fees of a speed tier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthFeeTier {
+    /// the max fee per gas in wei (the gas price on legacy chains)
+    pub max_fee_per_gas: String,
+    /// the max priority fee per gas in wei (the gas price on legacy chains)
+    pub max_priority_fee_per_gas: String,
+    /// the expected cost of the gas limit in ether / native token decimals
+    pub estimated_cost: String,
+    /// the cost of the gas limit at the max fee per gas in ether / native token decimals
+    pub max_cost: String,
+}
+
+/// the fee estimation with the slow, normal and fast tiers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthFeeEstimate {
+    /// if the fees are from `eth_gasPrice` (the legacy transaction request)
+    pub legacy: bool,
+    /// the base fee per gas of the next block in wei ("0" on legacy chains)
+    pub base_fee_per_gas: String,
+    pub slow: EthFeeTier,
+    pub normal: EthFeeTier,
+    pub fast: EthFeeTier,
+}
+
+impl EthFeeEstimate {
+    /// return the suggested fees of the speed tier
+    pub fn tier(&self, speed: EthFeeSpeed) -> &EthFeeTier {
+        match speed {
+            EthFeeSpeed::Slow => &self.slow,
+            EthFeeSpeed::Normal => &self.normal,
+            EthFeeSpeed::Fast => &self.fast,
+        }
+    }
+}
+
+impl EthFeeTier {
+    fn new(
+        max_fee: U256,
+        priority_fee: U256,
+        expected_fee: U256,
+        gas_limit: U256,
+    ) -> Result<Self, EthError> {
+        let estimated_cost = expected_fee
+            .checked_mul(gas_limit)
+            .ok_or(EthError::Overflow)?;
+        let max_cost = max_fee.checked_mul(gas_limit).ok_or(EthError::Overflow)?;
+        Ok(Self {
+            max_fee_per_gas: max_fee.to_string(),
+            max_priority_fee_per_gas: priority_fee.to_string(),
+            estimated_cost: format_ether(estimated_cost),
+            max_cost: format_ether(max_cost),
+        })
+    }
+
+    /// set the fees of the transaction request:
+    /// the max fees for EIP-1559 and the gas price (max fee per gas) for the others
+    pub fn apply_to(&self, tx: &mut TypedTransaction) -> Result<(), EthError> {
+        let max_fee = u256_from_dec_str(&self.max_fee_per_gas)?;
+        match tx {
+            TypedTransaction::Eip1559(inner) => {
+                inner.max_fee_per_gas = Some(max_fee);
+                inner.max_priority_fee_per_gas =
+                    Some(u256_from_dec_str(&self.max_priority_fee_per_gas)?);
+            }
+            _ => {
+                tx.set_gas_price(max_fee);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// the base fee increased by the maximum 12.5% per block for the given number of blocks
+fn max_base_fee_after(base_fee: U256, blocks: usize) -> Result<U256, EthError> {
+    (0..blocks).try_fold(base_fee, |fee, _| {
+        fee.checked_mul(U256::from(9))
+            .map(|fee| (fee + 7) / 8)
+            .ok_or(EthError::Overflow)
+    })
+}
+
+/// the median of the rewards at the percentile index in the non-empty blocks
+fn median_reward(history: &FeeHistory, index: usize) -> Option<U256> {
+    let mut rewards: Vec<U256> = history
+        .reward
+        .iter()
+        .zip(history.gas_used_ratio.iter())
+        .filter(|(_, ratio)| **ratio > 0.0)
+        .filter_map(|(rewards, _)| rewards.get(index).copied())
+        .collect();
+    if rewards.is_empty() {
+        return None;
+    }
+    rewards.sort();
+    Some(rewards[rewards.len() / 2])
+}
+
+/// compute the EIP-1559 tiers from `eth_feeHistory`;
+/// `None` if the chain doesn't report base fees
+pub fn estimate_from_fee_history(
+    history: &FeeHistory,
+    gas_limit: U256,
+) -> Result<Option<EthFeeEstimate>, EthError> {
+    let (next_base_fee, previous) = match history.base_fee_per_gas.split_last() {
+        Some((next, previous)) if !next.is_zero() => (*next, previous),
+        _ => return Ok(None),
+    };
+    // if the base fee is trending up, leave room for one more block of increases
+    let previous_sum = previous
+        .iter()
+        .try_fold(U256::zero(), |sum, fee| sum.checked_add(*fee))
+        .ok_or(EthError::Overflow)?;
+    let rising = !previous.is_empty()
+        && next_base_fee
+            .checked_mul(U256::from(previous.len()))
+            .ok_or(EthError::Overflow)?
+            > previous_sum;
+    // the faster tiers never tip less than the slower ones
+    let mut priority_fees = [U256::zero(); REWARD_PERCENTILES.len()];
+    for index in 0..REWARD_PERCENTILES.len() {
+        let previous_fee = index.checked_sub(1).map(|i| priority_fees[i]);
+        priority_fees[index] = median_reward(history, index)
+            .unwrap_or_else(|| U256::from(DEFAULT_PRIORITY_FEE))
+            .max(previous_fee.unwrap_or_default());
+    }
+    let tier = |index: usize| {
+        let priority_fee = priority_fees[index];
+        let blocks = index + 1 + usize::from(rising);
+        let max_fee = max_base_fee_after(next_base_fee, blocks)?
+            .checked_add(priority_fee)
+            .ok_or(EthError::Overflow)?;
+        let expected_fee = next_base_fee
+            .checked_add(priority_fee)
+            .ok_or(EthError::Overflow)?;
+        EthFeeTier::new(max_fee, priority_fee, expected_fee, gas_limit)
+    };
+    Ok(Some(EthFeeEstimate {
+        legacy: false,
+        base_fee_per_gas: next_base_fee.to_string(),
+        slow: tier(0)?,
+        normal: tier(1)?,
+        fast: tier(2)?,
+    }))
+}
+
+/// compute the legacy tiers from `eth_gasPrice`
+pub fn estimate_from_gas_price(
+    gas_price: U256,
+    gas_limit: U256,
+) -> Result<EthFeeEstimate, EthError> {
+    let tier = |percent: u64| {
+        let price = gas_price
+            .checked_mul(U256::from(percent))
+            .ok_or(EthError::Overflow)?
+            / 100;
+        EthFeeTier::new(price, price, price, gas_limit)
+    };
+    Ok(EthFeeEstimate {
+        legacy: true,
+        base_fee_per_gas: "0".to_owned(),
+        slow: tier(LEGACY_MULTIPLIERS[0])?,
+        normal: tier(LEGACY_MULTIPLIERS[1])?,
+        fast: tier(LEGACY_MULTIPLIERS[2])?,
+    })
+}
+
+/// estimate the slow, normal and fast fees for a transaction with the given gas limit (decimal):
+/// from the recent `eth_feeHistory` rewards and base fee trend,
+/// or from `eth_gasPrice` on legacy chains (and nodes without `eth_feeHistory`)
+pub async fn estimate_eth_fees(
+    web3api_url: &str,
+    network: EthNetwork,
+    gas_limit: &str,
+) -> Result<EthFeeEstimate, EthError> {
+    let (_, legacy) = network.to_chain_params()?;
+    let gas_limit = u256_from_dec_str(gas_limit)?;
+    let client = get_ethers_provider(web3api_url).await?;
+    if !legacy {
+        if let Ok(history) = client
+            .fee_history(FEE_HISTORY_BLOCKS, BlockNumber::Latest, &REWARD_PERCENTILES)
+            .await
+        {
+            if let Some(estimate) = estimate_from_fee_history(&history, gas_limit)? {
+                return Ok(estimate);
+            }
+        }
+    }
+    let gas_price = client
+        .get_gas_price()
+        .await
+        .map_err(EthError::FeeEstimationError)?;
+    estimate_from_gas_price(gas_price, gas_limit)
+}
+
+/// estimate the slow, normal and fast fees for a transaction with the given gas limit
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn estimate_eth_fees_blocking(
+    web3api_url: &str,
+    network: EthNetwork,
+    gas_limit: &str,
+) -> Result<EthFeeEstimate, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(estimate_eth_fees(web3api_url, network, gas_limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u64 = 1_000_000_000;
+
+    fn fee_history(base_fees: &[u64], rewards: &[[u64; 3]], ratios: &[f64]) -> FeeHistory {
+        FeeHistory {
+            base_fee_per_gas: base_fees.iter().map(|fee| U256::from(fee * GWEI)).collect(),
+            gas_used_ratio: ratios.to_vec(),
+            oldest_block: U256::from(100),
+            reward: rewards
+                .iter()
+                .map(|reward| reward.iter().map(|fee| U256::from(fee * GWEI)).collect())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn tiers_are_computed_from_fee_history() {
+        let history = fee_history(
+            &[10, 10, 10, 10],
+            &[[1, 2, 3], [1, 2, 5], [1, 3, 4]],
+            &[0.5, 0.5, 0.5],
+        );
+        let estimate = estimate_from_fee_history(&history, U256::from(21000))
+            .unwrap()
+            .unwrap();
+        assert!(!estimate.legacy);
+        assert_eq!(estimate.base_fee_per_gas, (10 * GWEI).to_string());
+        assert_eq!(estimate.slow.max_priority_fee_per_gas, GWEI.to_string());
+        assert_eq!(
+            estimate.normal.max_priority_fee_per_gas,
+            (2 * GWEI).to_string()
+        );
+        assert_eq!(
+            estimate.fast.max_priority_fee_per_gas,
+            (4 * GWEI).to_string()
+        );
+        // one block of the maximum base fee increase
+        assert_eq!(
+            estimate.slow.max_fee_per_gas,
+            (12_250_000_000u64).to_string()
+        );
+        // 21000 * (10 + 1) gwei
+        assert_eq!(estimate.slow.estimated_cost, "0.000231000000000000");
+        let slow = u256_from_dec_str(&estimate.slow.max_fee_per_gas).unwrap();
+        let normal = u256_from_dec_str(&estimate.normal.max_fee_per_gas).unwrap();
+        let fast = u256_from_dec_str(&estimate.fast.max_fee_per_gas).unwrap();
+        assert!(slow < normal && normal < fast);
+    }
+
+    #[test]
+    fn rising_base_fee_adds_headroom() {
+        let stable = fee_history(&[10, 10, 10], &[[1, 2, 3], [1, 2, 3]], &[0.5, 0.5]);
+        let rising = fee_history(&[8, 9, 10], &[[1, 2, 3], [1, 2, 3]], &[1.0, 1.0]);
+        let stable = estimate_from_fee_history(&stable, U256::from(21000))
+            .unwrap()
+            .unwrap();
+        let rising = estimate_from_fee_history(&rising, U256::from(21000))
+            .unwrap()
+            .unwrap();
+        assert_eq!(stable.base_fee_per_gas, rising.base_fee_per_gas);
+        assert!(
+            u256_from_dec_str(&rising.normal.max_fee_per_gas).unwrap()
+                > u256_from_dec_str(&stable.normal.max_fee_per_gas).unwrap()
+        );
+    }
+
+    #[test]
+    fn empty_blocks_use_default_priority_fee() {
+        let history = fee_history(&[10, 10], &[[0, 0, 0]], &[0.0]);
+        let estimate = estimate_from_fee_history(&history, U256::from(21000))
+            .unwrap()
+            .unwrap();
+        assert_eq!(estimate.fast.max_priority_fee_per_gas, GWEI.to_string());
+    }
+
+    #[test]
+    fn missing_base_fee_is_not_eip1559() {
+        let history = fee_history(&[0, 0], &[[1, 2, 3]], &[0.5]);
+        assert!(estimate_from_fee_history(&history, U256::from(21000))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn legacy_tiers_are_computed_from_gas_price() {
+        let estimate = estimate_from_gas_price(U256::from(100 * GWEI), U256::from(21000)).unwrap();
+        assert!(estimate.legacy);
+        assert_eq!(estimate.slow.max_fee_per_gas, (100 * GWEI).to_string());
+        assert_eq!(estimate.normal.max_fee_per_gas, (110 * GWEI).to_string());
+        assert_eq!(estimate.fast.max_fee_per_gas, (125 * GWEI).to_string());
+        assert_eq!(
+            estimate.tier(EthFeeSpeed::Fast).max_priority_fee_per_gas,
+            estimate.fast.max_fee_per_gas
+        );
+        assert_eq!(estimate.slow.max_cost, "0.002100000000000000");
+    }
+}
@@ -0,0 +1,485 @@
+use crate::contract::decode_revert_reason;
+#[cfg(feature = "abi-contract")]
+use crate::node::ethereum::abi::EthAbiToken;
+use crate::provider::{get_ethers_provider, get_http_client};
+#[cfg(feature = "abi-contract")]
+use crate::EthAbiContract;
+use crate::{address_from_str, u256_from_str, ContractBalance, EthError};
+use ethers::abi::{decode, encode, ParamType, Token};
+use ethers::prelude::{Address, Bytes, Middleware, TransactionRequest, U256};
+use ethers::providers::{Http, Provider};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::id;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// the address of the Multicall3 contract (the same on all chains where it's deployed)
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+/// the Multicall3 function allowing the failure of each call
+const AGGREGATE3_FUNCTION: &str = "aggregate3((address,bool,bytes)[])";
+/// the default max number of calls per batch
+const DEFAULT_MAX_CALLS: usize = 100;
+/// the default max total calldata size per batch (in bytes)
+const DEFAULT_MAX_CALLDATA_BYTES: usize = 64 * 1024;
+
+/// how the calls are batched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EthBatchMode {
+    /// Multicall3 if it's deployed on the chain, otherwise JSON-RPC batch requests
+    Auto,
+    /// `aggregate3` calls of the Multicall3 contract
+    Multicall3,
+    /// JSON-RPC batch requests of `eth_call`
+    JsonRpcBatch,
+}
+
+/// the result of a call in the batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthMulticallResult {
+    pub success: bool,
+    /// the output data, or the revert data of the failed call
+    pub return_data: Vec<u8>,
+    /// the revert reason or the node error of the failed call
+    pub error: Option<String>,
+}
+
+/// the result of a balance query in the batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthBalanceResult {
+    /// the balance as a decimal string (none if the query failed)
+    pub balance: Option<String>,
+    pub error: Option<String>,
+}
+
+impl From<EthMulticallResult> for EthBalanceResult {
+    fn from(result: EthMulticallResult) -> Self {
+        match result {
+            // a call to an account without code succeeds with no output
+            EthMulticallResult {
+                success: true,
+                return_data,
+                ..
+            } if return_data.len() < 32 => Self {
+                balance: None,
+                error: Some("invalid balance output".to_owned()),
+            },
+            EthMulticallResult {
+                success: true,
+                return_data,
+                ..
+            } => Self {
+                balance: Some(U256::from_big_endian(&return_data[..32]).to_string()),
+                error: None,
+            },
+            EthMulticallResult { error, .. } => Self {
+                balance: None,
+                error,
+            },
+        }
+    }
+}
+
+/// a read-only contract call
+#[derive(Debug, Clone)]
+struct EthCallRequest {
+    target: Address,
+    data: Vec<u8>,
+}
+
+/// Batched read-only contract calls, sent in size-bounded batches
+/// with the Multicall3 contract or with JSON-RPC batch requests;
+/// each call can fail without failing the others
+#[derive(Debug, Clone)]
+pub struct EthMulticall {
+    calls: Vec<EthCallRequest>,
+    max_calls: usize,
+    max_calldata_bytes: usize,
+    mode: EthBatchMode,
+}
+
+impl Default for EthMulticall {
+    fn default() -> Self {
+        Self {
+            calls: vec![],
+            max_calls: DEFAULT_MAX_CALLS,
+            max_calldata_bytes: DEFAULT_MAX_CALLDATA_BYTES,
+            mode: EthBatchMode::Auto,
+        }
+    }
+}
+
+impl EthMulticall {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// set the max number of calls per batch
+    pub fn max_calls(mut self, max_calls: usize) -> Self {
+        self.max_calls = max_calls.max(1);
+        self
+    }
+
+    /// set the max total calldata size per batch (in bytes)
+    pub fn max_calldata_bytes(mut self, max_calldata_bytes: usize) -> Self {
+        self.max_calldata_bytes = max_calldata_bytes;
+        self
+    }
+
+    /// set how the calls are batched
+    pub fn mode(mut self, mode: EthBatchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// add the call of the contract with the encoded calldata;
+    /// it returns the index of its result
+    pub fn add_call(&mut self, contract_address: &str, data: Vec<u8>) -> Result<usize, EthError> {
+        self.calls.push(EthCallRequest {
+            target: address_from_str(contract_address)?,
+            data,
+        });
+        Ok(self.calls.len() - 1)
+    }
+
+    /// add the balance query of the account (decoded with `EthBalanceResult::from`);
+    /// it returns the index of its result
+    pub fn add_balance_query(
+        &mut self,
+        account_address: &str,
+        contract_details: &ContractBalance,
+    ) -> Result<usize, EthError> {
+        let account = Token::Address(address_from_str(account_address)?);
+        let (contract_address, data) = match contract_details {
+            ContractBalance::Erc20 { contract_address }
+            | ContractBalance::Erc721 { contract_address } => {
+                let mut data = id("balanceOf(address)").to_vec();
+                data.extend(encode(&[account]));
+                (contract_address, data)
+            }
+            ContractBalance::Erc1155 {
+                contract_address,
+                token_id,
+            } => {
+                let mut data = id("balanceOf(address,uint256)").to_vec();
+                data.extend(encode(&[account, Token::Uint(u256_from_str(token_id)?)]));
+                (contract_address, data)
+            }
+        };
+        self.add_call(contract_address, data)
+    }
+
+    /// add the call of the contract function (its output can be decoded
+    /// with `EthAbiContract::decode_output`); it returns the index of its result
+    #[cfg(feature = "abi-contract")]
+    pub fn add_abi_call(
+        &mut self,
+        contract_address: &str,
+        abi_contract: &EthAbiContract,
+        function_name: &str,
+        tokens: Vec<EthAbiToken>,
+    ) -> Result<usize, EthError> {
+        let data = abi_contract.encode(function_name, tokens)?;
+        self.add_call(contract_address, data)
+    }
+
+    /// the calls split into batches bounded by the number of calls and the calldata size
+    /// (a call larger than the calldata bound is sent in its own batch)
+    fn batches(&self) -> Vec<&[EthCallRequest]> {
+        let mut batches = vec![];
+        let mut start = 0;
+        let mut calldata_bytes = 0;
+        for (i, call) in self.calls.iter().enumerate() {
+            let count = i - start;
+            if count > 0
+                && (count == self.max_calls
+                    || calldata_bytes + call.data.len() > self.max_calldata_bytes)
+            {
+                batches.push(&self.calls[start..i]);
+                start = i;
+                calldata_bytes = 0;
+            }
+            calldata_bytes += call.data.len();
+        }
+        if start < self.calls.len() {
+            batches.push(&self.calls[start..]);
+        }
+        batches
+    }
+
+    /// send the calls; the results are in the order the calls were added
+    pub async fn execute(&self, web3api_url: &str) -> Result<Vec<EthMulticallResult>, EthError> {
+        if self.calls.is_empty() {
+            return Ok(vec![]);
+        }
+        let provider = get_ethers_provider(web3api_url).await?;
+        let multicall = address_from_str(MULTICALL3_ADDRESS)?;
+        let use_multicall = match self.mode {
+            EthBatchMode::Multicall3 => true,
+            EthBatchMode::JsonRpcBatch => false,
+            EthBatchMode::Auto => !provider
+                .get_code(multicall, None)
+                .await
+                .map_err(|e| EthError::MulticallError(e.to_string()))?
+                .is_empty(),
+        };
+        let mut results = Vec::with_capacity(self.calls.len());
+        for batch in self.batches() {
+            if use_multicall {
+                results.extend(aggregate3(&provider, multicall, batch).await?);
+            } else {
+                results.extend(json_rpc_batch(web3api_url, batch).await?);
+            }
+        }
+        Ok(results)
+    }
+}
+
+/// the result of the call given its success and output (or revert data)
+fn call_result(success: bool, return_data: Vec<u8>) -> EthMulticallResult {
+    let error = (!success).then(|| match decode_revert_reason(&return_data, None) {
+        Some(reason) => format!("execution reverted: {reason}"),
+        None => "execution reverted".to_owned(),
+    });
+    EthMulticallResult {
+        success,
+        return_data,
+        error,
+    }
+}
+
+/// send the calls with `aggregate3` of the Multicall3 contract
+async fn aggregate3(
+    provider: &Provider<Http>,
+    multicall: Address,
+    calls: &[EthCallRequest],
+) -> Result<Vec<EthMulticallResult>, EthError> {
+    let calls_token = Token::Array(
+        calls
+            .iter()
+            .map(|call| {
+                Token::Tuple(vec![
+                    Token::Address(call.target),
+                    Token::Bool(true),
+                    Token::Bytes(call.data.clone()),
+                ])
+            })
+            .collect(),
+    );
+    let mut data = id(AGGREGATE3_FUNCTION).to_vec();
+    data.extend(encode(&[calls_token]));
+    let tx: TypedTransaction = TransactionRequest::new().to(multicall).data(data).into();
+    let output = provider
+        .call(&tx, None)
+        .await
+        .map_err(|e| EthError::MulticallError(e.to_string()))?;
+    decode_aggregate3_output(&output, calls.len())
+}
+
+/// decode the `(bool success, bytes returnData)[]` output of `aggregate3`
+fn decode_aggregate3_output(
+    output: &[u8],
+    count: usize,
+) -> Result<Vec<EthMulticallResult>, EthError> {
+    let kind = ParamType::Array(Box::new(ParamType::Tuple(vec![
+        ParamType::Bool,
+        ParamType::Bytes,
+    ])));
+    let results = match decode(&[kind], output)?.pop() {
+        Some(Token::Array(results)) if results.len() == count => results,
+        _ => {
+            return Err(EthError::MulticallError(
+                "unexpected aggregate3 output".to_owned(),
+            ))
+        }
+    };
+    results
+        .into_iter()
+        .map(|result| match result {
+            Token::Tuple(mut result) => match (result.pop(), result.pop()) {
+                (Some(Token::Bytes(return_data)), Some(Token::Bool(success))) => {
+                    Ok(call_result(success, return_data))
+                }
+                _ => Err(EthError::MulticallError(
+                    "unexpected aggregate3 output".to_owned(),
+                )),
+            },
+            _ => Err(EthError::MulticallError(
+                "unexpected aggregate3 output".to_owned(),
+            )),
+        })
+        .collect()
+}
+
+/// send the calls with a JSON-RPC batch request of `eth_call`
+async fn json_rpc_batch(
+    web3api_url: &str,
+    calls: &[EthCallRequest],
+) -> Result<Vec<EthMulticallResult>, EthError> {
+    let requests: Vec<Value> = calls
+        .iter()
+        .enumerate()
+        .map(|(i, call)| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": i,
+                "method": "eth_call",
+                "params": [{"to": call.target, "data": Bytes::from(call.data.clone())}, "latest"],
+            })
+        })
+        .collect();
+    let responses: Value = get_http_client()?
+        .post(web3api_url)
+        .json(&requests)
+        .send()
+        .await
+        .map_err(EthError::ClientError)?
+        .json()
+        .await
+        .map_err(EthError::ClientError)?;
+    parse_json_rpc_batch_responses(responses, calls.len())
+}
+
+/// match the batch responses (in any order) to the requests by their ids
+fn parse_json_rpc_batch_responses(
+    responses: Value,
+    count: usize,
+) -> Result<Vec<EthMulticallResult>, EthError> {
+    let responses = match responses {
+        Value::Array(responses) => responses,
+        _ => {
+            return Err(EthError::MulticallError(
+                "the node doesn't support JSON-RPC batch requests".to_owned(),
+            ))
+        }
+    };
+    let mut results = vec![None; count];
+    for response in responses {
+        let index = match response["id"].as_u64() {
+            Some(index) if (index as usize) < count => index as usize,
+            _ => continue,
+        };
+        let result = match response["result"].as_str() {
+            Some(output) => {
+                let output = output
+                    .parse::<Bytes>()
+                    .map_err(|e| EthError::MulticallError(e.to_string()))?;
+                call_result(true, output.to_vec())
+            }
+            None => {
+                let return_data = response["error"]["data"]
+                    .as_str()
+                    .and_then(|data| data.parse::<Bytes>().ok())
+                    .map(|data| data.to_vec())
+                    .unwrap_or_default();
+                let mut result = call_result(false, return_data);
+                if result.return_data.is_empty() {
+                    result.error = response["error"]["message"].as_str().map(str::to_owned);
+                }
+                result
+            }
+        };
+        results[index] = Some(result);
+    }
+    results
+        .into_iter()
+        .map(|result| {
+            result
+                .ok_or_else(|| EthError::MulticallError("missing response in the batch".to_owned()))
+        })
+        .collect()
+}
+
+/// query the balances of the account in the token contracts with batched calls;
+/// a failed query doesn't fail the others
+pub async fn get_contract_balances(
+    account_address: &str,
+    contract_details: Vec<ContractBalance>,
+    web3api_url: &str,
+) -> Result<Vec<EthBalanceResult>, EthError> {
+    let mut multicall = EthMulticall::new();
+    for details in contract_details.iter() {
+        multicall.add_balance_query(account_address, details)?;
+    }
+    Ok(multicall
+        .execute(web3api_url)
+        .await?
+        .into_iter()
+        .map(EthBalanceResult::from)
+        .collect())
+}
+
+/// query the balances of the account in the token contracts with batched calls
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn get_contract_balances_blocking(
+    account_address: &str,
+    contract_details: Vec<ContractBalance>,
+    web3api_url: &str,
+) -> Result<Vec<EthBalanceResult>, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(get_contract_balances(
+        account_address,
+        contract_details,
+        web3api_url,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_are_split_into_bounded_batches() {
+        let mut multicall = EthMulticall::new().max_calls(3).max_calldata_bytes(100);
+        for len in [10, 10, 10, 10, 90, 200, 10] {
+            multicall
+                .add_call(MULTICALL3_ADDRESS, vec![0; len])
+                .unwrap();
+        }
+        let sizes: Vec<usize> = multicall
+            .batches()
+            .iter()
+            .map(|batch| batch.len())
+            .collect();
+        assert_eq!(sizes, vec![3, 2, 1, 1]);
+    }
+
+    #[test]
+    fn aggregate3_output_is_decoded() {
+        let mut revert_data = vec![0x08, 0xc3, 0x79, 0xa0];
+        revert_data.extend(encode(&[Token::String("paused".to_owned())]));
+        let output = encode(&[Token::Array(vec![
+            Token::Tuple(vec![
+                Token::Bool(true),
+                Token::Bytes(encode(&[Token::Uint(U256::from(42))])),
+            ]),
+            Token::Tuple(vec![Token::Bool(false), Token::Bytes(revert_data)]),
+            Token::Tuple(vec![Token::Bool(true), Token::Bytes(vec![])]),
+        ])]);
+        let results = decode_aggregate3_output(&output, 3).unwrap();
+        let balances: Vec<EthBalanceResult> = results.into_iter().map(Into::into).collect();
+        assert_eq!(balances[0].balance.as_deref(), Some("42"));
+        assert_eq!(
+            balances[1].error.as_deref(),
+            Some("execution reverted: paused")
+        );
+        assert!(balances[2].balance.is_none());
+        assert!(decode_aggregate3_output(&output, 2).is_err());
+    }
+
+    #[test]
+    fn json_rpc_batch_responses_are_matched_by_id() {
+        let responses = json!([
+            {"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "execution reverted"}},
+            {"jsonrpc": "2.0", "id": 0, "result": "0x2a"},
+        ]);
+        let results = parse_json_rpc_batch_responses(responses, 2).unwrap();
+        assert!(results[0].success);
+        assert_eq!(results[0].return_data, vec![0x2a]);
+        assert!(!results[1].success);
+        assert_eq!(results[1].error.as_deref(), Some("execution reverted"));
+
+        assert!(parse_json_rpc_batch_responses(json!([]), 1).is_err());
+        assert!(parse_json_rpc_batch_responses(json!({"error": {}}), 1).is_err());
+    }
+}
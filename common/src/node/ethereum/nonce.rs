@@ -0,0 +1,216 @@
+use crate::{address_from_str, EthError, EthNetwork};
+use ethers::prelude::{Address, BlockNumber, Middleware, U256};
+use ethers::providers::{Http, Provider};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Mutex, MutexGuard};
+
+lazy_static::lazy_static! {
+    /// the nonces tracked by this process, shared by all the broadcasting functions
+    static ref NONCE_MANAGER: NonceManager = NonceManager::default();
+}
+
+/// the locally tracked nonces of an account on a chain
+#[derive(Default, Debug)]
+struct AccountNonces {
+    /// the nonce after the highest reserved one
+    next: u64,
+    /// the reserved nonces not yet included in the node's pending transaction count
+    reserved: BTreeSet<u64>,
+}
+
+/// the nonces of all accounts (per chain id and address)
+#[derive(Default)]
+struct NonceManager {
+    accounts: Mutex<HashMap<(u64, Address), AccountNonces>>,
+}
+
+impl NonceManager {
+    fn accounts(&self) -> MutexGuard<'_, HashMap<(u64, Address), AccountNonces>> {
+        // the map stays consistent even if a holder of the lock panicked
+        self.accounts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// reserve the nonce given the node's pending transaction count:
+    /// the first gap left by a failed transaction, otherwise the next tracked one
+    /// (or the node's one if transactions were sent elsewhere)
+    fn reserve(&self, chain_id: u64, address: Address, pending_count: u64) -> u64 {
+        let mut accounts = self.accounts();
+        let account = accounts.entry((chain_id, address)).or_default();
+        account.reserved = account.reserved.split_off(&pending_count);
+        if account.next < pending_count {
+            account.next = pending_count;
+        }
+        let nonce = (pending_count..account.next)
+            .find(|nonce| !account.reserved.contains(nonce))
+            .unwrap_or(account.next);
+        if nonce == account.next {
+            account.next += 1;
+        }
+        account.reserved.insert(nonce);
+        nonce
+    }
+
+    /// release the nonce of the transaction that failed to be sent:
+    /// it's reused by the next transaction, or all tracked nonces are dropped
+    /// if the node rejected it because of its nonce
+    fn release(&self, chain_id: u64, address: Address, nonce: u64, resync: bool) {
+        let mut accounts = self.accounts();
+        if resync {
+            accounts.remove(&(chain_id, address));
+        } else if let Some(account) = accounts.get_mut(&(chain_id, address)) {
+            account.reserved.remove(&nonce);
+            if nonce + 1 == account.next {
+                account.next = nonce;
+            }
+        }
+    }
+
+    /// the gaps (nonces to be reused) in the tracked nonces
+    fn gaps(&self, chain_id: u64, address: Address, pending_count: u64) -> Vec<u64> {
+        self.accounts()
+            .get(&(chain_id, address))
+            .map(|account| {
+                (pending_count..account.next)
+                    .filter(|nonce| !account.reserved.contains(nonce))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// if the node rejected the transaction because of its nonce
+/// (so the tracked nonces are out of sync with the node);
+/// the other errors mentioning the nonce (e.g. "nonce too high") don't mean it's already used
+fn is_nonce_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("nonce too low")
+        || error.contains("already known")
+        || error.contains("replacement transaction underpriced")
+}
+
+/// The handle of the locally tracked nonces of an account on a chain;
+/// transactions sent back-to-back get consecutive nonces
+/// even before the node includes them in its pending transaction count
+#[derive(Clone, Debug)]
+pub struct NonceTracker {
+    provider: Provider<Http>,
+    chain_id: u64,
+    address: Address,
+}
+
+impl NonceTracker {
+    pub fn new(provider: Provider<Http>, chain_id: u64, address: Address) -> Self {
+        Self {
+            provider,
+            chain_id,
+            address,
+        }
+    }
+
+    /// the pending transaction count of the account from the node
+    async fn pending_count(&self) -> Result<u64, EthError> {
+        let count = self
+            .provider
+            .get_transaction_count(self.address, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(EthError::NonceError)?;
+        Ok(count.low_u64())
+    }
+
+    /// reserve the nonce for the next transaction
+    pub async fn next(&self) -> Result<U256, EthError> {
+        let pending_count = self.pending_count().await?;
+        Ok(NONCE_MANAGER
+            .reserve(self.chain_id, self.address, pending_count)
+            .into())
+    }
+
+    /// release the nonce of the transaction that failed to be sent with the given error
+    pub fn release(&self, nonce: U256, error: &str) {
+        NONCE_MANAGER.release(
+            self.chain_id,
+            self.address,
+            nonce.low_u64(),
+            is_nonce_error(error),
+        );
+    }
+
+    /// the nonces that were released or skipped, i.e. the ones that will be reused
+    pub async fn gaps(&self) -> Result<Vec<U256>, EthError> {
+        let pending_count = self.pending_count().await?;
+        Ok(NONCE_MANAGER
+            .gaps(self.chain_id, self.address, pending_count)
+            .into_iter()
+            .map(U256::from)
+            .collect())
+    }
+}
+
+/// drop the locally tracked nonces of the address,
+/// e.g. after its pending transactions were dropped from the mempool
+pub fn reset_eth_nonces(network: EthNetwork, address: &str) -> Result<(), EthError> {
+    let (chain_id, _) = network.to_chain_params()?;
+    let address = address_from_str(address)?;
+    NONCE_MANAGER.accounts().remove(&(chain_id, address));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nonces_are_reserved_back_to_back() {
+        let manager = NonceManager::default();
+        let address = Address::from_low_u64_be(1);
+        // the node doesn't see the sent transactions yet
+        assert_eq!(manager.reserve(25, address, 3), 3);
+        assert_eq!(manager.reserve(25, address, 3), 4);
+        assert_eq!(manager.reserve(25, address, 4), 5);
+        // the same address on another chain
+        assert_eq!(manager.reserve(338, address, 0), 0);
+        // transactions were sent elsewhere
+        assert_eq!(manager.reserve(25, address, 10), 10);
+    }
+
+    #[test]
+    fn released_nonce_is_reused() {
+        let manager = NonceManager::default();
+        let address = Address::from_low_u64_be(1);
+        for nonce in 0..3 {
+            assert_eq!(manager.reserve(25, address, 0), nonce);
+        }
+        manager.release(25, address, 1, false);
+        assert_eq!(manager.gaps(25, address, 0), vec![1]);
+        assert_eq!(manager.reserve(25, address, 0), 1);
+        assert_eq!(manager.reserve(25, address, 0), 3);
+
+        // the last one is released
+        manager.release(25, address, 3, false);
+        assert!(manager.gaps(25, address, 0).is_empty());
+        assert_eq!(manager.reserve(25, address, 0), 3);
+    }
+
+    #[test]
+    fn nonce_error_resyncs_with_node() {
+        let manager = NonceManager::default();
+        let address = Address::from_low_u64_be(1);
+        assert_eq!(manager.reserve(25, address, 5), 5);
+        assert_eq!(manager.reserve(25, address, 5), 6);
+        assert!(is_nonce_error("nonce too low"));
+        assert!(is_nonce_error("Nonce too low: next nonce 7, tx nonce 6"));
+        assert!(is_nonce_error("already known"));
+        assert!(is_nonce_error("replacement transaction underpriced"));
+        assert!(!is_nonce_error(
+            "insufficient funds for gas * price + value"
+        ));
+        assert!(!is_nonce_error("nonce too high"));
+        assert!(!is_nonce_error(
+            "failed to get the nonce: connection refused"
+        ));
+        manager.release(25, address, 6, true);
+        assert_eq!(manager.reserve(25, address, 5), 5);
+    }
+}
@@ -0,0 +1,779 @@
+#![cfg(feature = "abi-contract")]
+
+use crate::node::ethereum::eip712::Eip712TypedData;
+use crate::provider::get_ethers_provider;
+use crate::{address_from_str, u256_from_dec_str, EthError, EthNetwork, EthSigner, SecretKey};
+use ethers::abi::{decode, encode, ParamType, Token};
+use ethers::prelude::{Address, LocalWallet, Middleware, Signer, TransactionRequest, U256};
+use ethers::providers::{Http, Provider};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::{hex, id, to_checksum};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// the address of the Uniswap Permit2 contract (the same on all chains where it's deployed)
+pub const PERMIT2_ADDRESS: &str = "0x000000000022D473030F116dDEE9F6B43aC78BA3";
+
+/// the EIP-712 domain versions tried if the token's one isn't given
+/// (most tokens use "1", USDC uses "2")
+const DEFAULT_PERMIT_VERSIONS: [&str; 2] = ["1", "2"];
+
+/// the token allowance of Permit2 in `PermitSingle` / `PermitBatch`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EthPermit2Details {
+    pub token_address: String,
+    /// the amount as a decimal string (uint160)
+    pub amount: String,
+    /// the expiration timestamp of the allowance (uint48)
+    pub expiration: u64,
+    /// the Permit2 nonce of the allowance (queried from Permit2 if not set)
+    pub nonce: Option<u64>,
+}
+
+/// Information needed for building the permit signatures of different standards.
+/// Amounts are decimal strings; deadlines are unix timestamps.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum EthPermitRequest {
+    /// EIP-2612 `Permit`
+    Erc2612 {
+        token_address: String,
+        owner: String,
+        spender: String,
+        value: String,
+        deadline: u64,
+        /// the EIP-712 domain version (found with the token's `DOMAIN_SEPARATOR` if not set)
+        version: Option<String>,
+        /// the token nonce of the owner (queried with `nonces` if not set)
+        nonce: Option<String>,
+    },
+    /// DAI-style `Permit` approving all or nothing
+    Dai {
+        token_address: String,
+        holder: String,
+        spender: String,
+        expiry: u64,
+        allowed: bool,
+        /// the token nonce of the holder (queried with `nonces` if not set)
+        nonce: Option<String>,
+    },
+    /// Permit2 `PermitSingle` (allowance transfer)
+    Permit2Single {
+        owner: String,
+        details: EthPermit2Details,
+        spender: String,
+        sig_deadline: u64,
+    },
+    /// Permit2 `PermitBatch` (allowance transfer of several tokens)
+    Permit2Batch {
+        owner: String,
+        details: Vec<EthPermit2Details>,
+        spender: String,
+        sig_deadline: u64,
+    },
+    /// Permit2 `PermitTransferFrom` (signature transfer)
+    Permit2TransferFrom {
+        token_address: String,
+        amount: String,
+        spender: String,
+        /// the unordered nonce (any unused one)
+        nonce: String,
+        deadline: u64,
+    },
+}
+
+impl EthPermitRequest {
+    /// the owner of the tokens who has to sign the permit (none if any account can)
+    fn owner(&self) -> Option<&str> {
+        match self {
+            EthPermitRequest::Erc2612 { owner, .. }
+            | EthPermitRequest::Permit2Single { owner, .. }
+            | EthPermitRequest::Permit2Batch { owner, .. } => Some(owner),
+            EthPermitRequest::Dai { holder, .. } => Some(holder),
+            EthPermitRequest::Permit2TransferFrom { .. } => None,
+        }
+    }
+}
+
+/// The signed permit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EthPermitSignature {
+    /// the signed EIP-712 typed data (JSON)
+    pub typed_data: String,
+    /// the 65-byte signature as hex with prefix `0x`
+    pub signature: String,
+    /// the signature split for the `permit(..., v, r, s)` calls
+    pub v: u8,
+    pub r: String,
+    pub s: String,
+}
+
+/// the json type fields from the (name, type) pairs
+fn type_fields(fields: &[(&str, &str)]) -> Value {
+    Value::Array(
+        fields
+            .iter()
+            .map(|(name, field_type)| json!({ "name": name, "type": field_type }))
+            .collect(),
+    )
+}
+
+/// call the view function of the contract and decode its output
+async fn call_view(
+    provider: &Provider<Http>,
+    contract: Address,
+    signature: &str,
+    args: &[Token],
+    outputs: &[ParamType],
+) -> Result<Vec<Token>, EthError> {
+    let mut data = id(signature).to_vec();
+    data.extend(encode(args));
+    let tx: TypedTransaction = TransactionRequest::new().to(contract).data(data).into();
+    let output = provider
+        .call(&tx, None)
+        .await
+        .map_err(|e| EthError::ContractCallError(format!("{signature}: {e}")))?;
+    Ok(decode(outputs, &output)?)
+}
+
+/// the token nonce of the owner (`nonces(address)`)
+async fn query_nonce(
+    provider: &Provider<Http>,
+    token: Address,
+    owner: Address,
+) -> Result<U256, EthError> {
+    match call_view(
+        provider,
+        token,
+        "nonces(address)",
+        &[Token::Address(owner)],
+        &[ParamType::Uint(256)],
+    )
+    .await?
+    .pop()
+    {
+        Some(Token::Uint(nonce)) => Ok(nonce),
+        _ => Err(EthError::PermitError("invalid nonces output".to_owned())),
+    }
+}
+
+/// the token name used in its EIP-712 domain (`name()`)
+async fn query_name(provider: &Provider<Http>, token: Address) -> Result<String, EthError> {
+    match call_view(provider, token, "name()", &[], &[ParamType::String])
+        .await?
+        .pop()
+    {
+        Some(Token::String(name)) => Ok(name),
+        _ => Err(EthError::PermitError("invalid name output".to_owned())),
+    }
+}
+
+/// the token's `DOMAIN_SEPARATOR()` (none if it doesn't implement it)
+async fn query_domain_separator(provider: &Provider<Http>, token: Address) -> Option<Vec<u8>> {
+    match call_view(
+        provider,
+        token,
+        "DOMAIN_SEPARATOR()",
+        &[],
+        &[ParamType::FixedBytes(32)],
+    )
+    .await
+    .ok()?
+    .pop()?
+    {
+        Token::FixedBytes(separator) => Some(separator),
+        _ => None,
+    }
+}
+
+/// the Permit2 nonce of the allowance (`allowance(owner, token, spender)`)
+async fn query_permit2_nonce(
+    provider: &Provider<Http>,
+    owner: Address,
+    token: Address,
+    spender: Address,
+) -> Result<u64, EthError> {
+    let outputs = call_view(
+        provider,
+        address_from_str(PERMIT2_ADDRESS)?,
+        "allowance(address,address,address)",
+        &[
+            Token::Address(owner),
+            Token::Address(token),
+            Token::Address(spender),
+        ],
+        &[
+            ParamType::Uint(160),
+            ParamType::Uint(48),
+            ParamType::Uint(48),
+        ],
+    )
+    .await?;
+    match outputs.get(2) {
+        Some(Token::Uint(nonce)) => Ok(nonce.low_u64()),
+        _ => Err(EthError::PermitError("invalid allowance output".to_owned())),
+    }
+}
+
+/// the token nonce given in the request, or the queried one
+async fn nonce_or_query(
+    nonce: &Option<String>,
+    provider: &Provider<Http>,
+    token: Address,
+    owner: Address,
+) -> Result<U256, EthError> {
+    match nonce {
+        Some(nonce) => u256_from_dec_str(nonce),
+        None => query_nonce(provider, token, owner).await,
+    }
+}
+
+/// the json of the token permit (EIP-2612 or DAI) with the given domain version
+fn token_permit_json(
+    request: &EthPermitRequest,
+    chain_id: u64,
+    name: &str,
+    version: &str,
+    nonce: U256,
+) -> Result<Value, EthError> {
+    let domain_fields = type_fields(&[
+        ("name", "string"),
+        ("version", "string"),
+        ("chainId", "uint256"),
+        ("verifyingContract", "address"),
+    ]);
+    match request {
+        EthPermitRequest::Erc2612 {
+            token_address,
+            owner,
+            spender,
+            value,
+            deadline,
+            ..
+        } => Ok(json!({
+            "types": {
+                "EIP712Domain": domain_fields,
+                "Permit": type_fields(&[
+                    ("owner", "address"),
+                    ("spender", "address"),
+                    ("value", "uint256"),
+                    ("nonce", "uint256"),
+                    ("deadline", "uint256"),
+                ]),
+            },
+            "primaryType": "Permit",
+            "domain": {
+                "name": name,
+                "version": version,
+                "chainId": chain_id,
+                "verifyingContract": to_checksum(&address_from_str(token_address)?, None),
+            },
+            "message": {
+                "owner": to_checksum(&address_from_str(owner)?, None),
+                "spender": to_checksum(&address_from_str(spender)?, None),
+                "value": u256_from_dec_str(value)?.to_string(),
+                "nonce": nonce.to_string(),
+                "deadline": deadline.to_string(),
+            },
+        })),
+        EthPermitRequest::Dai {
+            token_address,
+            holder,
+            spender,
+            expiry,
+            allowed,
+            ..
+        } => Ok(json!({
+            "types": {
+                "EIP712Domain": domain_fields,
+                "Permit": type_fields(&[
+                    ("holder", "address"),
+                    ("spender", "address"),
+                    ("nonce", "uint256"),
+                    ("expiry", "uint256"),
+                    ("allowed", "bool"),
+                ]),
+            },
+            "primaryType": "Permit",
+            "domain": {
+                "name": name,
+                "version": version,
+                "chainId": chain_id,
+                "verifyingContract": to_checksum(&address_from_str(token_address)?, None),
+            },
+            "message": {
+                "holder": to_checksum(&address_from_str(holder)?, None),
+                "spender": to_checksum(&address_from_str(spender)?, None),
+                "nonce": nonce.to_string(),
+                "expiry": expiry.to_string(),
+                "allowed": allowed,
+            },
+        })),
+        _ => Err(EthError::PermitError(
+            "not an EIP-2612 or DAI permit".to_owned(),
+        )),
+    }
+}
+
+/// Build the EIP-712 typed data (JSON) of the token permit (EIP-2612 or DAI)
+/// from the token name, the owner's nonce and the token's `DOMAIN_SEPARATOR`:
+/// the domain version is the first one (of the request or the defaults; "1" for DAI)
+/// matching the separator, or the first one if the token doesn't implement it
+fn token_permit_typed_data(
+    request: &EthPermitRequest,
+    chain_id: u64,
+    name: &str,
+    nonce: U256,
+    domain_separator: Option<&[u8]>,
+) -> Result<String, EthError> {
+    let versions = match request {
+        EthPermitRequest::Erc2612 {
+            version: Some(version),
+            ..
+        } => vec![version.as_str()],
+        EthPermitRequest::Erc2612 { version: None, .. } => DEFAULT_PERMIT_VERSIONS.to_vec(),
+        _ => vec!["1"],
+    };
+    let typed_data = versions
+        .iter()
+        .map(|version| {
+            token_permit_json(request, chain_id, name, version, nonce).map(|json| json.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let domain_separator = match domain_separator {
+        Some(domain_separator) => domain_separator,
+        None => return Ok(typed_data[0].clone()),
+    };
+    for typed_data in typed_data {
+        if Eip712TypedData::new(&typed_data)?.domain_separator()?[..] == domain_separator[..] {
+            return Ok(typed_data);
+        }
+    }
+    Err(EthError::PermitError(format!(
+        "the domain doesn't match the DOMAIN_SEPARATOR of the token (tried the versions {})",
+        versions.join(", ")
+    )))
+}
+
+/// the json of the Permit2 allowance details, querying the missing nonce
+async fn permit2_details(
+    provider: &Provider<Http>,
+    owner: Address,
+    spender: Address,
+    details: &EthPermit2Details,
+) -> Result<Value, EthError> {
+    let token = address_from_str(&details.token_address)?;
+    let nonce = match details.nonce {
+        Some(nonce) => nonce,
+        None => query_permit2_nonce(provider, owner, token, spender).await?,
+    };
+    Ok(json!({
+        "token": to_checksum(&token, None),
+        "amount": u256_from_dec_str(&details.amount)?.to_string(),
+        "expiration": details.expiration.to_string(),
+        "nonce": nonce.to_string(),
+    }))
+}
+
+/// the EIP-712 domain of Permit2
+fn permit2_domain(chain_id: u64) -> Value {
+    json!({
+        "name": "Permit2",
+        "chainId": chain_id,
+        "verifyingContract": PERMIT2_ADDRESS,
+    })
+}
+
+const PERMIT_DETAILS_FIELDS: [(&str, &str); 4] = [
+    ("token", "address"),
+    ("amount", "uint160"),
+    ("expiration", "uint48"),
+    ("nonce", "uint48"),
+];
+
+/// Build the EIP-712 typed data (JSON) of the permit,
+/// querying the token nonce, name and domain version where they aren't given
+pub async fn build_eth_permit(
+    request: EthPermitRequest,
+    network: EthNetwork,
+    web3api_url: &str,
+) -> Result<String, EthError> {
+    let (chain_id, _) = network.to_chain_params()?;
+    let provider = get_ethers_provider(web3api_url).await?;
+    let permit2_domain_fields = type_fields(&[
+        ("name", "string"),
+        ("chainId", "uint256"),
+        ("verifyingContract", "address"),
+    ]);
+
+    match request {
+        EthPermitRequest::Erc2612 {
+            ref token_address,
+            ref owner,
+            ref nonce,
+            ..
+        }
+        | EthPermitRequest::Dai {
+            ref token_address,
+            holder: ref owner,
+            ref nonce,
+            ..
+        } => {
+            let token = address_from_str(token_address)?;
+            let owner = address_from_str(owner)?;
+            let nonce = nonce_or_query(nonce, &provider, token, owner).await?;
+            let name = query_name(&provider, token).await?;
+            let domain_separator = query_domain_separator(&provider, token).await;
+            token_permit_typed_data(
+                &request,
+                chain_id,
+                &name,
+                nonce,
+                domain_separator.as_deref(),
+            )
+        }
+        EthPermitRequest::Permit2Single {
+            owner,
+            details,
+            spender,
+            sig_deadline,
+        } => {
+            let owner = address_from_str(&owner)?;
+            let spender = address_from_str(&spender)?;
+            let details = permit2_details(&provider, owner, spender, &details).await?;
+            Ok(json!({
+                "types": {
+                    "EIP712Domain": permit2_domain_fields,
+                    "PermitSingle": type_fields(&[
+                        ("details", "PermitDetails"),
+                        ("spender", "address"),
+                        ("sigDeadline", "uint256"),
+                    ]),
+                    "PermitDetails": type_fields(&PERMIT_DETAILS_FIELDS),
+                },
+                "primaryType": "PermitSingle",
+                "domain": permit2_domain(chain_id),
+                "message": {
+                    "details": details,
+                    "spender": to_checksum(&spender, None),
+                    "sigDeadline": sig_deadline.to_string(),
+                },
+            })
+            .to_string())
+        }
+        EthPermitRequest::Permit2Batch {
+            owner,
+            details,
+            spender,
+            sig_deadline,
+        } => {
+            let owner = address_from_str(&owner)?;
+            let spender = address_from_str(&spender)?;
+            let mut details_json = Vec::with_capacity(details.len());
+            for details in details.iter() {
+                details_json.push(permit2_details(&provider, owner, spender, details).await?);
+            }
+            Ok(json!({
+                "types": {
+                    "EIP712Domain": permit2_domain_fields,
+                    "PermitBatch": type_fields(&[
+                        ("details", "PermitDetails[]"),
+                        ("spender", "address"),
+                        ("sigDeadline", "uint256"),
+                    ]),
+                    "PermitDetails": type_fields(&PERMIT_DETAILS_FIELDS),
+                },
+                "primaryType": "PermitBatch",
+                "domain": permit2_domain(chain_id),
+                "message": {
+                    "details": details_json,
+                    "spender": to_checksum(&spender, None),
+                    "sigDeadline": sig_deadline.to_string(),
+                },
+            })
+            .to_string())
+        }
+        EthPermitRequest::Permit2TransferFrom {
+            token_address,
+            amount,
+            spender,
+            nonce,
+            deadline,
+        } => {
+            let token = address_from_str(&token_address)?;
+            let spender = address_from_str(&spender)?;
+            Ok(json!({
+                "types": {
+                    "EIP712Domain": permit2_domain_fields,
+                    "PermitTransferFrom": type_fields(&[
+                        ("permitted", "TokenPermissions"),
+                        ("spender", "address"),
+                        ("nonce", "uint256"),
+                        ("deadline", "uint256"),
+                    ]),
+                    "TokenPermissions": type_fields(&[
+                        ("token", "address"),
+                        ("amount", "uint256"),
+                    ]),
+                },
+                "primaryType": "PermitTransferFrom",
+                "domain": permit2_domain(chain_id),
+                "message": {
+                    "permitted": {
+                        "token": to_checksum(&token, None),
+                        "amount": u256_from_dec_str(&amount)?.to_string(),
+                    },
+                    "spender": to_checksum(&spender, None),
+                    "nonce": u256_from_dec_str(&nonce)?.to_string(),
+                    "deadline": deadline.to_string(),
+                },
+            })
+            .to_string())
+        }
+    }
+}
+
+/// Split the signature (hex, 65 bytes or EIP-2098 compact) into the v, r and s parameters
+pub fn split_eth_signature(signature: &str) -> Result<(u8, String, String), EthError> {
+    let signature = crate::parse_eth_signature(signature)?;
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    signature.r.to_big_endian(&mut r);
+    signature.s.to_big_endian(&mut s);
+    let v = u8::try_from(signature.v).map_err(|_| EthError::SignatureError)?;
+    Ok((
+        v,
+        format!("0x{}", hex::encode(r)),
+        format!("0x{}", hex::encode(s)),
+    ))
+}
+
+/// Sign the EIP-712 typed data of the permit (e.g. built with `build_eth_permit`)
+pub fn sign_eth_permit_typed_data(
+    typed_data: String,
+    secret_key: Arc<SecretKey>,
+) -> Result<EthPermitSignature, EthError> {
+    let signature = EthSigner::new(secret_key).sign_typed_data(&typed_data)?;
+    let (v, r, s) = split_eth_signature(&signature)?;
+    Ok(EthPermitSignature {
+        typed_data,
+        signature,
+        v,
+        r,
+        s,
+    })
+}
+
+/// Build and sign the permit; the secret key has to be the one of the token owner
+pub async fn sign_eth_permit(
+    request: EthPermitRequest,
+    network: EthNetwork,
+    secret_key: Arc<SecretKey>,
+    web3api_url: &str,
+) -> Result<EthPermitSignature, EthError> {
+    if let Some(owner) = request.owner() {
+        let signer_address = LocalWallet::from(
+            secret_key
+                .get_eth_signing_key()
+                .map_err(|_| EthError::SignatureError)?,
+        )
+        .address();
+        if address_from_str(owner)? != signer_address {
+            return Err(EthError::PermitError(format!(
+                "the permit of {owner} can't be signed by {}",
+                to_checksum(&signer_address, None)
+            )));
+        }
+    }
+    let typed_data = build_eth_permit(request, network, web3api_url).await?;
+    sign_eth_permit_typed_data(typed_data, secret_key)
+}
+
+/// Build the EIP-712 typed data (JSON) of the permit
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn build_eth_permit_blocking(
+    request: EthPermitRequest,
+    network: EthNetwork,
+    web3api_url: &str,
+) -> Result<String, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(build_eth_permit(request, network, web3api_url))
+}
+
+/// Build and sign the permit
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn sign_eth_permit_blocking(
+    request: EthPermitRequest,
+    network: EthNetwork,
+    secret_key: Arc<SecretKey>,
+    web3api_url: &str,
+) -> Result<EthPermitSignature, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(sign_eth_permit(request, network, secret_key, web3api_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::prelude::Signature;
+    use std::str::FromStr;
+
+    const USDC_ADDRESS: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+    /// the `DOMAIN_SEPARATOR()` of USDC on Ethereum mainnet
+    const USDC_DOMAIN_SEPARATOR: &str =
+        "06c37168a7db5138defc7866392bb87a741f9b3d104deb5094588ce041cae335";
+    const DAI_ADDRESS: &str = "0x6B175474E89094C44Da98b954EedeAC495271d0F";
+    /// the `DOMAIN_SEPARATOR()` of DAI on Ethereum mainnet
+    const DAI_DOMAIN_SEPARATOR: &str =
+        "dbb8cf42e1ecb028be3f3dbc922e1d878b963f411dc388ced501601c60f7c6f7";
+    const SPENDER: &str = "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB";
+    const OWNER: &str = "0xaAaAaAaaAaAaAaaAaAAAAAAAAaaaAaAaAaaAaaAa";
+
+    fn usdc_permit(version: Option<String>) -> EthPermitRequest {
+        EthPermitRequest::Erc2612 {
+            token_address: USDC_ADDRESS.to_owned(),
+            owner: OWNER.to_owned(),
+            spender: SPENDER.to_owned(),
+            value: "1000000".to_owned(),
+            deadline: 1700000000,
+            version,
+            nonce: None,
+        }
+    }
+
+    #[test]
+    fn erc2612_permit_matches_the_token_domain() {
+        let separator = hex::decode(USDC_DOMAIN_SEPARATOR).unwrap();
+        // USDC uses the version "2", found among the default versions
+        let typed_data = token_permit_typed_data(
+            &usdc_permit(None),
+            1,
+            "USD Coin",
+            3.into(),
+            Some(&separator),
+        )
+        .unwrap();
+        let eip712 = Eip712TypedData::new(&typed_data).unwrap();
+        assert_eq!(eip712.domain_separator().unwrap()[..], separator[..]);
+        assert_eq!(
+            eip712.encoded_type("Permit").unwrap(),
+            "Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)"
+        );
+        let json: Value = serde_json::from_str(&typed_data).unwrap();
+        assert_eq!(json["domain"]["version"], "2");
+        assert_eq!(json["message"]["owner"], OWNER);
+        assert_eq!(json["message"]["value"], "1000000");
+        assert_eq!(json["message"]["nonce"], "3");
+        assert_eq!(json["message"]["deadline"], "1700000000");
+
+        // the given version has to match
+        assert!(token_permit_typed_data(
+            &usdc_permit(Some("1".to_owned())),
+            1,
+            "USD Coin",
+            3.into(),
+            Some(&separator)
+        )
+        .is_err());
+        // so does the chain id
+        assert!(token_permit_typed_data(
+            &usdc_permit(None),
+            5,
+            "USD Coin",
+            3.into(),
+            Some(&separator)
+        )
+        .is_err());
+        // the first version is used if the token doesn't implement `DOMAIN_SEPARATOR`
+        let typed_data =
+            token_permit_typed_data(&usdc_permit(None), 1, "USD Coin", 3.into(), None).unwrap();
+        let json: Value = serde_json::from_str(&typed_data).unwrap();
+        assert_eq!(json["domain"]["version"], "1");
+    }
+
+    #[test]
+    fn dai_permit_matches_the_token_domain() {
+        let separator = hex::decode(DAI_DOMAIN_SEPARATOR).unwrap();
+        let request = EthPermitRequest::Dai {
+            token_address: DAI_ADDRESS.to_owned(),
+            holder: OWNER.to_owned(),
+            spender: SPENDER.to_owned(),
+            expiry: 1700000000,
+            allowed: true,
+            nonce: None,
+        };
+        let typed_data =
+            token_permit_typed_data(&request, 1, "Dai Stablecoin", 0.into(), Some(&separator))
+                .unwrap();
+        let eip712 = Eip712TypedData::new(&typed_data).unwrap();
+        assert_eq!(eip712.domain_separator().unwrap()[..], separator[..]);
+        assert_eq!(
+            eip712.encoded_type("Permit").unwrap(),
+            "Permit(address holder,address spender,uint256 nonce,uint256 expiry,bool allowed)"
+        );
+        let json: Value = serde_json::from_str(&typed_data).unwrap();
+        assert_eq!(json["message"]["holder"], OWNER);
+        assert_eq!(json["message"]["allowed"], true);
+
+        // another token name doesn't match the separator
+        assert!(token_permit_typed_data(&request, 1, "Dai", 0.into(), Some(&separator)).is_err());
+    }
+
+    #[test]
+    fn signature_is_split_into_v_r_s() {
+        let signature = format!("0x{}{}1c", "11".repeat(32), "22".repeat(32));
+        let (v, r, s) = split_eth_signature(&signature).unwrap();
+        assert_eq!(v, 28);
+        assert_eq!(r, format!("0x{}", "11".repeat(32)));
+        assert_eq!(s, format!("0x{}", "22".repeat(32)));
+        assert!(split_eth_signature("0x1234").is_err());
+    }
+
+    #[test]
+    fn signed_permit2_transfer_matches_its_signature() {
+        let typed_data = json!({
+            "types": {
+                "EIP712Domain": type_fields(&[
+                    ("name", "string"),
+                    ("chainId", "uint256"),
+                    ("verifyingContract", "address"),
+                ]),
+                "PermitTransferFrom": type_fields(&[
+                    ("permitted", "TokenPermissions"),
+                    ("spender", "address"),
+                    ("nonce", "uint256"),
+                    ("deadline", "uint256"),
+                ]),
+                "TokenPermissions": type_fields(&[("token", "address"), ("amount", "uint256")]),
+            },
+            "primaryType": "PermitTransferFrom",
+            "domain": permit2_domain(1),
+            "message": {
+                "permitted": {
+                    "token": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+                    // the max uint256 amount
+                    "amount": U256::MAX.to_string(),
+                },
+                "spender": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB",
+                "nonce": "0",
+                "deadline": "1700000000",
+            },
+        })
+        .to_string();
+        let secret_key = Arc::new(SecretKey::new());
+        let owner = LocalWallet::from(secret_key.get_eth_signing_key().unwrap()).address();
+        let permit = sign_eth_permit_typed_data(typed_data.clone(), secret_key).unwrap();
+
+        let hash = Eip712TypedData::new(&typed_data).unwrap().encode().unwrap();
+        let signature = Signature::from_str(&permit.signature).unwrap();
+        assert_eq!(
+            signature
+                .recover(ethers::types::H256::from_slice(&hash))
+                .unwrap(),
+            owner
+        );
+        assert!(permit.v == 27 || permit.v == 28);
+    }
+}
@@ -20,11 +20,8 @@ pub fn set_ethers_httpagent(agent: &str) -> Result<(), EthError> {
     Err(EthError::HttpAgentError)
 }
 
-// urlinfo: url string of the node to connect to, "http://mynode:8545"
-// agentinfo: agent string for http header
-pub async fn get_ethers_provider(urlinfo: &str) -> Result<Provider<Http>, EthError> {
-    let url = Url::parse(urlinfo).map_err(EthError::NodeUrl)?;
-
+/// the http client used to connect to the nodes
+pub(crate) fn get_http_client() -> Result<reqwest::Client, EthError> {
     #[cfg(target_arch = "wasm32")]
     let client = reqwest::Client::builder()
         .build()
@@ -48,6 +45,14 @@ pub async fn get_ethers_provider(urlinfo: &str) -> Result<Provider<Http>, EthErr
                 .map_err(EthError::ClientError)?,
         }
     };
+    Ok(client)
+}
+
+// urlinfo: url string of the node to connect to, "http://mynode:8545"
+// agentinfo: agent string for http header
+pub async fn get_ethers_provider(urlinfo: &str) -> Result<Provider<Http>, EthError> {
+    let url = Url::parse(urlinfo).map_err(EthError::NodeUrl)?;
+    let client = get_http_client()?;
 
     let httpprovider = Http::new_with_client(url, client);
     let finalprovider = Provider::new(httpprovider);
@@ -0,0 +1,382 @@
+use crate::provider::get_ethers_provider;
+use crate::{
+    estimate_eth_fees, u256_from_dec_str, EthError, EthFeeSpeed, EthFeeTier, EthNetwork, SecretKey,
+};
+use ethers::prelude::{
+    Address, Eip1559TransactionRequest, Eip2930TransactionRequest, LocalWallet, Middleware, Signer,
+    TransactionRequest, TxHash, U256,
+};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip2930::AccessList;
+use std::{str::FromStr, sync::Arc};
+
+/// the minimum fee increase (in percent) accepted by the nodes for a replacement transaction
+const REPLACEMENT_BUMP_PERCENT: u64 = 10;
+/// the gas limit of a plain transfer
+const TRANSFER_GAS_LIMIT: u64 = 21000;
+
+/// increase the fee by at least 10% (rounded up, and at least by 1 wei)
+fn bump_fee(fee: U256) -> Result<U256, EthError> {
+    let increase = fee
+        .checked_mul(U256::from(REPLACEMENT_BUMP_PERCENT))
+        .ok_or(EthError::Overflow)?
+        .checked_add(U256::from(99))
+        .ok_or(EthError::Overflow)?
+        / 100;
+    fee.checked_add(increase.max(U256::one()))
+        .ok_or(EthError::Overflow)
+}
+
+/// bump the fees of the transaction for the node replacement rules (+10% on all fee fields);
+/// if the current network fees (`floor`) are higher, they are used instead
+pub fn bump_replacement_fees(
+    tx: &mut TypedTransaction,
+    floor: Option<&EthFeeTier>,
+) -> Result<(), EthError> {
+    let (floor_max_fee, floor_priority_fee) = match floor {
+        Some(floor) => (
+            u256_from_dec_str(&floor.max_fee_per_gas)?,
+            u256_from_dec_str(&floor.max_priority_fee_per_gas)?,
+        ),
+        None => (U256::zero(), U256::zero()),
+    };
+    match tx {
+        TypedTransaction::Eip1559(inner) => {
+            let priority_fee = inner
+                .max_priority_fee_per_gas
+                .ok_or_else(|| EthError::ReplacementError("missing max priority fee".to_owned()))?;
+            let max_fee = inner
+                .max_fee_per_gas
+                .ok_or_else(|| EthError::ReplacementError("missing max fee".to_owned()))?;
+            let priority_fee = bump_fee(priority_fee)?.max(floor_priority_fee);
+            inner.max_priority_fee_per_gas = Some(priority_fee);
+            inner.max_fee_per_gas = Some(bump_fee(max_fee)?.max(floor_max_fee).max(priority_fee));
+        }
+        _ => {
+            let gas_price = tx
+                .gas_price()
+                .ok_or_else(|| EthError::ReplacementError("missing gas price".to_owned()))?;
+            tx.set_gas_price(bump_fee(gas_price)?.max(floor_max_fee));
+        }
+    }
+    Ok(())
+}
+
+/// build the zero-value self-transfer with the same nonce and type as the pending transaction
+/// (the fees are not bumped)
+pub fn build_cancel_tx(tx: &TypedTransaction, from: Address) -> Result<TypedTransaction, EthError> {
+    let nonce = *tx
+        .nonce()
+        .ok_or_else(|| EthError::ReplacementError("missing nonce".to_owned()))?;
+    let chain_id = tx
+        .chain_id()
+        .ok_or_else(|| EthError::ReplacementError("missing chain id".to_owned()))?;
+    let legacy = TransactionRequest::new()
+        .from(from)
+        .to(from)
+        .value(U256::zero())
+        .nonce(nonce)
+        .gas(TRANSFER_GAS_LIMIT)
+        .chain_id(chain_id);
+    Ok(match tx {
+        TypedTransaction::Legacy(inner) => {
+            let mut cancel = legacy;
+            cancel.gas_price = inner.gas_price;
+            cancel.into()
+        }
+        TypedTransaction::Eip2930(inner) => {
+            let mut cancel = legacy;
+            cancel.gas_price = inner.tx.gas_price;
+            Eip2930TransactionRequest::new(cancel, AccessList::default()).into()
+        }
+        TypedTransaction::Eip1559(inner) => {
+            let mut cancel = Eip1559TransactionRequest::new()
+                .from(from)
+                .to(from)
+                .value(U256::zero())
+                .nonce(nonce)
+                .gas(TRANSFER_GAS_LIMIT)
+                .chain_id(chain_id);
+            cancel.max_fee_per_gas = inner.max_fee_per_gas;
+            cancel.max_priority_fee_per_gas = inner.max_priority_fee_per_gas;
+            cancel.into()
+        }
+    })
+}
+
+fn signing_wallet(tx: &TypedTransaction, secret_key: &SecretKey) -> Result<LocalWallet, EthError> {
+    let chain_id = tx
+        .chain_id()
+        .ok_or_else(|| EthError::ReplacementError("missing chain id".to_owned()))?;
+    let wallet = LocalWallet::from(
+        secret_key
+            .get_eth_signing_key()
+            .map_err(|_| EthError::SignatureError)?,
+    )
+    .with_chain_id(chain_id.as_u64());
+    if let Some(from) = tx.from() {
+        if *from != wallet.address() {
+            return Err(EthError::ReplacementError(format!(
+                "the transaction is sent from {:?}, not from the signing key",
+                from
+            )));
+        }
+    }
+    Ok(wallet)
+}
+
+fn sign_replacement(tx: &TypedTransaction, wallet: &LocalWallet) -> Result<Vec<u8>, EthError> {
+    if tx.nonce().is_none() {
+        return Err(EthError::ReplacementError("missing nonce".to_owned()));
+    }
+    let sig = wallet
+        .sign_transaction_sync(tx)
+        .map_err(|_| EthError::SignatureError)?;
+    Ok(tx.rlp_signed(&sig).to_vec())
+}
+
+/// re-sign the pending transaction with the same nonce and the fees bumped
+/// for the node replacement rules; it returns the new signed raw transaction
+pub fn speed_up_eth_tx(
+    mut tx: TypedTransaction,
+    floor: Option<&EthFeeTier>,
+    secret_key: Arc<SecretKey>,
+) -> Result<Vec<u8>, EthError> {
+    let wallet = signing_wallet(&tx, &secret_key)?;
+    bump_replacement_fees(&mut tx, floor)?;
+    sign_replacement(&tx, &wallet)
+}
+
+/// sign a zero-value self-transfer with the same nonce as the pending transaction
+/// and the fees bumped for the node replacement rules;
+/// it returns the new signed raw transaction
+pub fn cancel_eth_tx(
+    tx: &TypedTransaction,
+    floor: Option<&EthFeeTier>,
+    secret_key: Arc<SecretKey>,
+) -> Result<Vec<u8>, EthError> {
+    let wallet = signing_wallet(tx, &secret_key)?;
+    let mut cancel = build_cancel_tx(tx, wallet.address())?;
+    bump_replacement_fees(&mut cancel, floor)?;
+    sign_replacement(&cancel, &wallet)
+}
+
+/// fetch the pending transaction and the current network fees of its type
+async fn get_pending_tx(
+    tx_hash: &str,
+    web3api_url: &str,
+) -> Result<(TypedTransaction, Option<EthFeeTier>), EthError> {
+    let hash = TxHash::from_str(tx_hash).map_err(|_| EthError::InvalidTxHash)?;
+    let client = get_ethers_provider(web3api_url).await?;
+    let pending = client
+        .get_transaction(hash)
+        .await
+        .map_err(|e| EthError::GetTransactionError(e.to_string()))?
+        .ok_or_else(|| EthError::GetTransactionError(format!("{:?} not found", hash)))?;
+    if pending.block_number.is_some() {
+        return Err(EthError::ReplacementError(format!(
+            "{:?} is already included in a block",
+            hash
+        )));
+    }
+    let mut tx = TypedTransaction::from(&pending);
+    if tx.chain_id().is_none() {
+        let chain_id = client
+            .get_chainid()
+            .await
+            .map_err(|e| EthError::GetTransactionError(e.to_string()))?;
+        tx.set_chain_id(chain_id.as_u64());
+    }
+    let network = EthNetwork::Custom {
+        chain_id: tx.chain_id().map(|id| id.as_u64()).unwrap_or_default(),
+        legacy: !matches!(tx, TypedTransaction::Eip1559(_)),
+    };
+    // the bumped fees alone satisfy the replacement rules if the estimation fails
+    let floor = estimate_eth_fees(web3api_url, network, &TRANSFER_GAS_LIMIT.to_string())
+        .await
+        .ok()
+        .map(|estimate| estimate.tier(EthFeeSpeed::Normal).clone());
+    Ok((tx, floor))
+}
+
+/// speed up the pending transaction by its hash:
+/// re-sign it with the same nonce and the fees bumped by at least 10%
+/// (or to the current network fees if higher); it returns the new signed raw transaction
+pub async fn speed_up_eth_tx_by_hash(
+    tx_hash: &str,
+    web3api_url: &str,
+    secret_key: Arc<SecretKey>,
+) -> Result<Vec<u8>, EthError> {
+    let (tx, floor) = get_pending_tx(tx_hash, web3api_url).await?;
+    speed_up_eth_tx(tx, floor.as_ref(), secret_key)
+}
+
+/// cancel the pending transaction by its hash:
+/// sign a zero-value self-transfer with the same nonce and the fees bumped by at least 10%
+/// (or to the current network fees if higher); it returns the new signed raw transaction
+pub async fn cancel_eth_tx_by_hash(
+    tx_hash: &str,
+    web3api_url: &str,
+    secret_key: Arc<SecretKey>,
+) -> Result<Vec<u8>, EthError> {
+    let (tx, floor) = get_pending_tx(tx_hash, web3api_url).await?;
+    cancel_eth_tx(&tx, floor.as_ref(), secret_key)
+}
+
+/// speed up the pending transaction by its hash
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn speed_up_eth_tx_by_hash_blocking(
+    tx_hash: &str,
+    web3api_url: &str,
+    secret_key: Arc<SecretKey>,
+) -> Result<Vec<u8>, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(speed_up_eth_tx_by_hash(tx_hash, web3api_url, secret_key))
+}
+
+/// cancel the pending transaction by its hash
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn cancel_eth_tx_by_hash_blocking(
+    tx_hash: &str,
+    web3api_url: &str,
+    secret_key: Arc<SecretKey>,
+) -> Result<Vec<u8>, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(cancel_eth_tx_by_hash(tx_hash, web3api_url, secret_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::utils::rlp::Rlp;
+
+    const GWEI: u64 = 1_000_000_000;
+
+    fn secret_key() -> Arc<SecretKey> {
+        Arc::new(
+            SecretKey::from_hex(
+                "24e585759e492f5e810607c82c202476c22c5876b10247ebf8b2bb7f75dbed2e".to_owned(),
+            )
+            .unwrap(),
+        )
+    }
+
+    fn sender() -> Address {
+        LocalWallet::from(secret_key().get_eth_signing_key().unwrap()).address()
+    }
+
+    fn eip1559_tx() -> TypedTransaction {
+        Eip1559TransactionRequest::new()
+            .from(sender())
+            .to(Address::repeat_byte(1))
+            .value(1000)
+            .nonce(7)
+            .gas(50000)
+            .max_fee_per_gas(100 * GWEI)
+            .max_priority_fee_per_gas(2 * GWEI)
+            .chain_id(1)
+            .into()
+    }
+
+    fn legacy_request() -> TransactionRequest {
+        TransactionRequest::new()
+            .from(sender())
+            .to(Address::repeat_byte(1))
+            .value(1000)
+            .nonce(7)
+            .gas(50000)
+            .gas_price(5000 * GWEI)
+            .chain_id(25)
+    }
+
+    fn legacy_tx() -> TypedTransaction {
+        legacy_request().into()
+    }
+
+    #[test]
+    fn fees_are_bumped_by_ten_percent() {
+        let mut tx = eip1559_tx();
+        bump_replacement_fees(&mut tx, None).unwrap();
+        match tx {
+            TypedTransaction::Eip1559(inner) => {
+                assert_eq!(inner.max_fee_per_gas, Some(U256::from(110 * GWEI)));
+                assert_eq!(
+                    inner.max_priority_fee_per_gas,
+                    Some(U256::from(2_200_000_000u64))
+                );
+            }
+            _ => unreachable!(),
+        }
+
+        let mut tx = legacy_tx();
+        bump_replacement_fees(&mut tx, None).unwrap();
+        assert_eq!(tx.gas_price(), Some(U256::from(5500 * GWEI)));
+
+        let mut tx: TypedTransaction =
+            Eip2930TransactionRequest::new(legacy_request(), AccessList::default()).into();
+        bump_replacement_fees(&mut tx, None).unwrap();
+        assert_eq!(tx.gas_price(), Some(U256::from(5500 * GWEI)));
+
+        // rounded up and at least 1 wei
+        assert_eq!(bump_fee(U256::from(15)).unwrap(), U256::from(17));
+        assert_eq!(bump_fee(U256::zero()).unwrap(), U256::one());
+    }
+
+    #[test]
+    fn higher_network_fees_are_used() {
+        let floor = EthFeeTier {
+            max_fee_per_gas: (200 * GWEI).to_string(),
+            max_priority_fee_per_gas: (3 * GWEI).to_string(),
+            estimated_cost: "0".to_owned(),
+            max_cost: "0".to_owned(),
+        };
+        let mut tx = eip1559_tx();
+        bump_replacement_fees(&mut tx, Some(&floor)).unwrap();
+        match tx {
+            TypedTransaction::Eip1559(inner) => {
+                assert_eq!(inner.max_fee_per_gas, Some(U256::from(200 * GWEI)));
+                assert_eq!(inner.max_priority_fee_per_gas, Some(U256::from(3 * GWEI)));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn cancel_is_zero_value_self_transfer() {
+        let cancel = build_cancel_tx(&legacy_tx(), sender()).unwrap();
+        assert!(matches!(cancel, TypedTransaction::Legacy(_)));
+        assert_eq!(cancel.to_addr(), Some(&sender()));
+        assert_eq!(cancel.value(), Some(&U256::zero()));
+        assert_eq!(cancel.nonce(), Some(&U256::from(7)));
+        assert_eq!(cancel.gas(), Some(&U256::from(TRANSFER_GAS_LIMIT)));
+        assert!(cancel.data().is_none());
+
+        let raw = cancel_eth_tx(&eip1559_tx(), None, secret_key()).unwrap();
+        // the EIP-1559 type is kept
+        assert_eq!(raw[0], 2);
+        let (decoded, _) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        assert_eq!(decoded.nonce(), Some(&U256::from(7)));
+        assert_eq!(decoded.value(), Some(&U256::zero()));
+    }
+
+    #[test]
+    fn speed_up_keeps_nonce_and_payload() {
+        let raw = speed_up_eth_tx(legacy_tx(), None, secret_key()).unwrap();
+        let (decoded, _) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        assert_eq!(decoded.nonce(), Some(&U256::from(7)));
+        assert_eq!(decoded.value(), Some(&U256::from(1000)));
+        assert_eq!(decoded.gas_price(), Some(U256::from(5500 * GWEI)));
+    }
+
+    #[test]
+    fn other_sender_is_rejected() {
+        let mut tx = legacy_tx();
+        tx.set_from(Address::repeat_byte(2));
+        assert!(matches!(
+            speed_up_eth_tx(tx, None, secret_key()),
+            Err(EthError::ReplacementError(_))
+        ));
+    }
+}
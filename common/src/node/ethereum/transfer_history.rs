@@ -0,0 +1,471 @@
+use crate::provider::get_ethers_provider;
+use crate::{address_from_str, EthError};
+use ethers::abi::{decode, ParamType, Token};
+use ethers::prelude::{Middleware, ValueOrArray};
+use ethers::providers::{Http, JsonRpcError, Provider, ProviderError, RpcError};
+use ethers::types::{Address, Filter, Log, H256, U256};
+use ethers::utils::{keccak256, to_checksum};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// the initial number of blocks per `eth_getLogs` request
+const INITIAL_CHUNK_BLOCKS: u64 = 2_000;
+/// the max number of blocks per `eth_getLogs` request
+const MAX_CHUNK_BLOCKS: u64 = 10_000;
+/// the ERC-20 / ERC-721 `Transfer` event signature
+const TRANSFER_EVENT: &str = "Transfer(address,address,uint256)";
+/// the ERC-1155 `TransferSingle` event signature
+const TRANSFER_SINGLE_EVENT: &str = "TransferSingle(address,address,address,uint256,uint256)";
+/// the ERC-1155 `TransferBatch` event signature
+const TRANSFER_BATCH_EVENT: &str = "TransferBatch(address,address,address,uint256[],uint256[])";
+/// the messages of the nodes rejecting `eth_getLogs` because of the block range
+/// or the number of results (unlike the rate limits, a smaller range then succeeds)
+const RANGE_LIMIT_MESSAGES: [&str; 6] = [
+    // geth, Erigon and Infura (-32005)
+    "query returned more than",
+    // Alchemy
+    "log response size exceeded",
+    // QuickNode, Ankr, BlastAPI and others
+    "block range",
+    "blocks range",
+    // Ethermint (e.g. Cronos)
+    "blocks distance",
+    // Nethermind
+    "too many blocks",
+];
+
+/// the direction of the transfer relative to the queried address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EthTransferDirection {
+    Incoming,
+    Outgoing,
+    /// the address sent the tokens to itself
+    SelfTransfer,
+}
+
+/// the token standard of the transferred token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EthTokenStandard {
+    Erc20,
+    Erc721,
+    Erc1155,
+}
+
+/// a token transfer of the queried address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthTokenTransfer {
+    pub direction: EthTransferDirection,
+    pub standard: EthTokenStandard,
+    pub contract_address: String,
+    pub from: String,
+    pub to: String,
+    /// the token id as a decimal string (none for ERC-20)
+    pub token_id: Option<String>,
+    /// the amount in base units as a decimal string ("1" for ERC-721)
+    pub amount: String,
+    pub block_number: u64,
+    pub tx_hash: String,
+    pub log_index: u64,
+}
+
+/// the token transfers found in the scanned blocks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthTokenTransferHistory {
+    /// the transfers ordered by block number and log index
+    pub transfers: Vec<EthTokenTransfer>,
+    /// the block to resume the scanning from (the last scanned one + 1)
+    pub next_block: u64,
+}
+
+fn event_topic(signature: &str) -> H256 {
+    H256::from(keccak256(signature))
+}
+
+/// if the node rejected `eth_getLogs` because of the block range or the response size
+fn is_range_limit_error(error: &JsonRpcError) -> bool {
+    let message = error.message.to_lowercase();
+    RANGE_LIMIT_MESSAGES
+        .iter()
+        .any(|limit| message.contains(limit))
+}
+
+/// the filters of the logs sent or received by the address (the topics of a filter
+/// match any of their values, so the events with the address in the same topic are merged):
+/// `Transfer` with the address in `from` (topic 1),
+/// `Transfer` with the address in `to` and `TransferSingle` / `TransferBatch`
+/// with the address in `from` (topic 2),
+/// and `TransferSingle` / `TransferBatch` with the address in `to` (topic 3)
+fn transfer_filters(address: Address, contract_address: Option<Address>) -> Vec<Filter> {
+    let address = H256::from(address);
+    let filter = match contract_address {
+        Some(contract_address) => Filter::new().address(contract_address),
+        None => Filter::new(),
+    };
+    vec![
+        filter
+            .clone()
+            .topic0(event_topic(TRANSFER_EVENT))
+            .topic1(address),
+        filter
+            .clone()
+            .topic0(ValueOrArray::Array(vec![
+                Some(event_topic(TRANSFER_EVENT)),
+                Some(event_topic(TRANSFER_SINGLE_EVENT)),
+                Some(event_topic(TRANSFER_BATCH_EVENT)),
+            ]))
+            .topic2(address),
+        filter
+            .topic0(ValueOrArray::Array(vec![
+                Some(event_topic(TRANSFER_SINGLE_EVENT)),
+                Some(event_topic(TRANSFER_BATCH_EVENT)),
+            ]))
+            .topic3(address),
+    ]
+}
+
+fn decode_uints(kinds: &[ParamType], data: &[u8]) -> Option<Vec<Token>> {
+    decode(kinds, data).ok()
+}
+
+/// decode the `Transfer`, `TransferSingle` or `TransferBatch` log
+/// into the transfers of the address (none if the log isn't one of them)
+fn decode_transfer_log(log: &Log, address: Address) -> Vec<EthTokenTransfer> {
+    let topic = match log.topics.first() {
+        Some(topic) => *topic,
+        None => return vec![],
+    };
+    let topic_address = |index: usize| log.topics.get(index).map(|topic| Address::from(*topic));
+    // (standard, from, to, [(token id, amount)])
+    let decoded = if topic == event_topic(TRANSFER_EVENT) {
+        match (log.topics.len(), topic_address(1), topic_address(2)) {
+            // ERC-20: the amount in the data
+            (3, Some(from), Some(to)) if log.data.len() == 32 => Some((
+                EthTokenStandard::Erc20,
+                from,
+                to,
+                vec![(None, U256::from_big_endian(&log.data))],
+            )),
+            // ERC-721: the token id in the topics
+            (4, Some(from), Some(to)) => Some((
+                EthTokenStandard::Erc721,
+                from,
+                to,
+                vec![(
+                    Some(U256::from_big_endian(log.topics[3].as_bytes())),
+                    U256::one(),
+                )],
+            )),
+            _ => None,
+        }
+    } else if topic == event_topic(TRANSFER_SINGLE_EVENT) {
+        match (topic_address(2), topic_address(3)) {
+            (Some(from), Some(to)) => {
+                decode_uints(&[ParamType::Uint(256), ParamType::Uint(256)], &log.data).and_then(
+                    |tokens| match tokens.as_slice() {
+                        [Token::Uint(id), Token::Uint(amount)] => Some((
+                            EthTokenStandard::Erc1155,
+                            from,
+                            to,
+                            vec![(Some(*id), *amount)],
+                        )),
+                        _ => None,
+                    },
+                )
+            }
+            _ => None,
+        }
+    } else if topic == event_topic(TRANSFER_BATCH_EVENT) {
+        let uint_array = || ParamType::Array(Box::new(ParamType::Uint(256)));
+        match (topic_address(2), topic_address(3)) {
+            (Some(from), Some(to)) => decode_uints(&[uint_array(), uint_array()], &log.data)
+                .and_then(|tokens| match tokens.as_slice() {
+                    [Token::Array(ids), Token::Array(amounts)] if ids.len() == amounts.len() => {
+                        let transfers = ids
+                            .iter()
+                            .zip(amounts)
+                            .map(|(id, amount)| {
+                                (id.clone().into_uint(), amount.clone().into_uint())
+                            })
+                            .map(|(id, amount)| Some((Some(id?), amount?)))
+                            .collect::<Option<Vec<_>>>()?;
+                        Some((EthTokenStandard::Erc1155, from, to, transfers))
+                    }
+                    _ => None,
+                }),
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    let (standard, from, to, transfers) = match decoded {
+        Some(decoded) => decoded,
+        None => return vec![],
+    };
+    let direction = match (from == address, to == address) {
+        (true, true) => EthTransferDirection::SelfTransfer,
+        (true, false) => EthTransferDirection::Outgoing,
+        (false, true) => EthTransferDirection::Incoming,
+        (false, false) => return vec![],
+    };
+    transfers
+        .into_iter()
+        .map(|(token_id, amount)| EthTokenTransfer {
+            direction,
+            standard,
+            contract_address: to_checksum(&log.address, None),
+            from: to_checksum(&from, None),
+            to: to_checksum(&to, None),
+            token_id: token_id.map(|token_id| token_id.to_string()),
+            amount: amount.to_string(),
+            block_number: log.block_number.unwrap_or_default().as_u64(),
+            tx_hash: format!("{:?}", log.transaction_hash.unwrap_or_default()),
+            log_index: log.log_index.unwrap_or_default().as_u64(),
+        })
+        .collect()
+}
+
+/// get the logs of all the filters in the block range
+async fn get_transfer_logs(
+    provider: &Provider<Http>,
+    filters: &[Filter],
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<Log>, ProviderError> {
+    let mut logs = vec![];
+    for filter in filters {
+        let filter = filter.clone().from_block(from_block).to_block(to_block);
+        logs.extend(provider.get_logs(&filter).await?);
+    }
+    Ok(logs)
+}
+
+/// scan the logs for the ERC-20, ERC-721 and ERC-1155 transfers from or to the address
+/// (optionally only of the given contract) from the checkpoint block
+/// to the given block (the latest one if not set).
+/// The block range of `eth_getLogs` is halved when the node rejects it
+/// and doubled again after successful requests.
+pub async fn get_eth_token_transfers(
+    web3api_url: &str,
+    address: &str,
+    contract_address: Option<String>,
+    from_block: u64,
+    to_block: Option<u64>,
+) -> Result<EthTokenTransferHistory, EthError> {
+    let address = address_from_str(address)?;
+    let contract_address = contract_address
+        .as_deref()
+        .map(address_from_str)
+        .transpose()?;
+    let provider = get_ethers_provider(web3api_url).await?;
+    let to_block = match to_block {
+        Some(to_block) => to_block,
+        None => provider
+            .get_block_number()
+            .await
+            .map_err(EthError::GetBlockNumberError)?
+            .as_u64(),
+    };
+    let filters = transfer_filters(address, contract_address);
+
+    // the transfers keyed by the block number, log index and token id (also removes the
+    // duplicates of self-transfers returned by both the `from` and `to` filters)
+    let mut transfers = BTreeMap::new();
+    let mut start = from_block;
+    let mut chunk = INITIAL_CHUNK_BLOCKS;
+    while start <= to_block {
+        let end = to_block.min(start.saturating_add(chunk - 1));
+        match get_transfer_logs(&provider, &filters, start, end).await {
+            Ok(logs) => {
+                for transfer in logs
+                    .iter()
+                    .flat_map(|log| decode_transfer_log(log, address))
+                {
+                    let key = (
+                        transfer.block_number,
+                        transfer.log_index,
+                        transfer.token_id.clone(),
+                    );
+                    transfers.insert(key, transfer);
+                }
+                start = end + 1;
+                chunk = (chunk * 2).min(MAX_CHUNK_BLOCKS);
+            }
+            Err(e) if chunk > 1 && e.as_error_response().is_some_and(is_range_limit_error) => {
+                chunk /= 2
+            }
+            Err(e) => return Err(EthError::GetLogsError(e)),
+        }
+    }
+    Ok(EthTokenTransferHistory {
+        transfers: transfers.into_values().collect(),
+        next_block: start.max(from_block),
+    })
+}
+
+/// scan the logs for the ERC-20, ERC-721 and ERC-1155 transfers from or to the address
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn get_eth_token_transfers_blocking(
+    web3api_url: &str,
+    address: &str,
+    contract_address: Option<String>,
+    from_block: u64,
+    to_block: Option<u64>,
+) -> Result<EthTokenTransferHistory, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(get_eth_token_transfers(
+        web3api_url,
+        address,
+        contract_address,
+        from_block,
+        to_block,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+    use ethers::types::{Bytes, U64};
+
+    fn log(topics: Vec<H256>, data: Vec<u8>) -> Log {
+        Log {
+            address: Address::from_low_u64_be(0xc0),
+            topics,
+            data: Bytes::from(data),
+            block_number: Some(U64::from(100)),
+            transaction_hash: Some(H256::from_low_u64_be(1)),
+            log_index: Some(U256::from(3)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fungible_and_nft_transfers_are_decoded() {
+        let me = Address::from_low_u64_be(1);
+        let other = Address::from_low_u64_be(2);
+        let erc20 = log(
+            vec![event_topic(TRANSFER_EVENT), me.into(), other.into()],
+            encode(&[Token::Uint(U256::from(500))]),
+        );
+        let transfers = decode_transfer_log(&erc20, me);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].standard, EthTokenStandard::Erc20);
+        assert_eq!(transfers[0].direction, EthTransferDirection::Outgoing);
+        assert_eq!(transfers[0].amount, "500");
+        assert_eq!(transfers[0].token_id, None);
+        assert_eq!(transfers[0].block_number, 100);
+        assert_eq!(transfers[0].log_index, 3);
+
+        let erc721 = log(
+            vec![
+                event_topic(TRANSFER_EVENT),
+                other.into(),
+                me.into(),
+                H256::from_low_u64_be(42),
+            ],
+            vec![],
+        );
+        let transfers = decode_transfer_log(&erc721, me);
+        assert_eq!(transfers[0].standard, EthTokenStandard::Erc721);
+        assert_eq!(transfers[0].direction, EthTransferDirection::Incoming);
+        assert_eq!(transfers[0].token_id.as_deref(), Some("42"));
+        assert_eq!(transfers[0].amount, "1");
+
+        // not the queried address
+        assert!(decode_transfer_log(&erc721, Address::from_low_u64_be(3)).is_empty());
+    }
+
+    #[test]
+    fn erc1155_transfers_are_decoded() {
+        let me = Address::from_low_u64_be(1);
+        let operator = Address::from_low_u64_be(9);
+        let single = log(
+            vec![
+                event_topic(TRANSFER_SINGLE_EVENT),
+                operator.into(),
+                me.into(),
+                me.into(),
+            ],
+            encode(&[Token::Uint(U256::from(7)), Token::Uint(U256::from(2))]),
+        );
+        let transfers = decode_transfer_log(&single, me);
+        assert_eq!(transfers[0].direction, EthTransferDirection::SelfTransfer);
+        assert_eq!(transfers[0].token_id.as_deref(), Some("7"));
+        assert_eq!(transfers[0].amount, "2");
+
+        let batch = log(
+            vec![
+                event_topic(TRANSFER_BATCH_EVENT),
+                operator.into(),
+                Address::zero().into(),
+                me.into(),
+            ],
+            encode(&[
+                Token::Array(vec![Token::Uint(U256::from(1)), Token::Uint(U256::from(2))]),
+                Token::Array(vec![
+                    Token::Uint(U256::from(10)),
+                    Token::Uint(U256::from(20)),
+                ]),
+            ]),
+        );
+        let transfers = decode_transfer_log(&batch, me);
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[1].standard, EthTokenStandard::Erc1155);
+        assert_eq!(transfers[1].direction, EthTransferDirection::Incoming);
+        assert_eq!(transfers[1].token_id.as_deref(), Some("2"));
+        assert_eq!(transfers[1].amount, "20");
+    }
+
+    #[test]
+    fn range_limit_errors_are_detected() {
+        let error = |code, message: &str| JsonRpcError {
+            code,
+            message: message.to_owned(),
+            data: None,
+        };
+        assert!(is_range_limit_error(&error(
+            -32005,
+            "query returned more than 10000 results"
+        )));
+        assert!(is_range_limit_error(&error(
+            -32602,
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range"
+        )));
+        assert!(is_range_limit_error(&error(
+            -32000,
+            "maximum [from, to] blocks distance: 10000"
+        )));
+        assert!(is_range_limit_error(&error(
+            -32614,
+            "eth_getLogs is limited to a 10,000 blocks range"
+        )));
+        // the rate limits are not fixed by a smaller range
+        assert!(!is_range_limit_error(&error(
+            -32005,
+            "daily request count exceeded, request rate limited"
+        )));
+        assert!(!is_range_limit_error(&error(429, "Too many requests")));
+        assert!(!is_range_limit_error(&error(-32603, "internal error")));
+    }
+
+    #[test]
+    fn transfer_filters_cover_all_topics() {
+        let me = Address::from_low_u64_be(0xaa);
+        let filters = transfer_filters(me, None);
+        assert_eq!(filters.len(), 3);
+        let topic = H256::from(me);
+        assert_eq!(filters[0].topics[1], Some(topic.into()));
+        assert_eq!(filters[1].topics[2], Some(topic.into()));
+        assert_eq!(filters[2].topics[3], Some(topic.into()));
+        assert_eq!(
+            filters[1].topics[0],
+            Some(ValueOrArray::Array(vec![
+                Some(event_topic(TRANSFER_EVENT)),
+                Some(event_topic(TRANSFER_SINGLE_EVENT)),
+                Some(event_topic(TRANSFER_BATCH_EVENT)),
+            ]))
+        );
+    }
+}
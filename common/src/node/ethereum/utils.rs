@@ -11,6 +11,7 @@ use std::{str::FromStr, sync::Arc, time::Duration};
 use ethers::utils::hex::ToHex;
 
 use crate::contract::{Contract, ContractCall};
+use crate::node::ethereum::{ens::resolve_eth_address, nonce::NonceTracker};
 
 use ethers::prelude::TransactionReceipt as EthersTransactionReceipt;
 
@@ -124,7 +125,7 @@ pub enum ContractApproval {
 /// Information needed for querying balance on different common contract types.
 /// The balance in the case of ERC721 returns the number of non-fungible tokens
 /// of the same type the account holds (e.g. the number of cryptokitties).
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum ContractBalance {
     Erc20 {
         contract_address: String,
@@ -138,7 +139,9 @@ pub enum ContractBalance {
     },
 }
 
-/// Information needed for transferring tokens on different common contract types
+/// Information needed for transferring tokens on different common contract types.
+/// The `to_address` can also be a name (e.g. ENS or Cronos ID),
+/// resolved with the name registry of the chain
 #[derive(Serialize, Deserialize)]
 pub enum ContractTransfer {
     Erc20Transfer {
@@ -474,19 +477,21 @@ pub async fn get_contract_balance(
     ContractCall::from(call).call().await
 }
 
+/// the signing client and the locally tracked nonces of its account
 fn create_localwallet_client(
     polling_interval_ms: u64,
     key: Arc<SecretKey>,
     chain_id: u64,
     client: Provider<Http>,
-) -> Result<SignerMiddleware<Provider<Http>, LocalWallet>, EthError> {
+) -> Result<(SignerMiddleware<Provider<Http>, LocalWallet>, NonceTracker), EthError> {
     let provider = client.interval(Duration::from_millis(polling_interval_ms));
     let ethers_key = key
         .get_eth_signing_key()
         .map_err(|_| EthError::SignatureError)?;
     let wallet = LocalWallet::from(ethers_key).with_chain_id(chain_id);
+    let nonce_tracker = NonceTracker::new(provider.clone(), chain_id, wallet.address());
     let client = SignerMiddleware::new(provider, wallet);
-    Ok(client)
+    Ok((client, nonce_tracker))
 }
 
 async fn broadcast_contract_approval_tx_common(
@@ -507,10 +512,15 @@ async fn broadcast_contract_approval_tx_common(
             let client = get_ethers_provider(web3api_url).await?;
             let amount = u256_from_dec_str(&amount)?;
             if let Some(key) = secret_key {
-                let client = create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
+                let (client, nonce_tracker) =
+                    create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
                 let contract = Contract::new_erc20(&contract_address, client)?;
                 let call = contract.approve(approved_address, amount);
-                let receipt = ContractCall::from(call).legacy(legacy).send().await?;
+                let receipt = ContractCall::from(call)
+                    .legacy(legacy)
+                    .nonce_tracker(nonce_tracker)
+                    .send()
+                    .await?;
                 Ok((Some(receipt), None))
             } else {
                 let contract = Contract::new_erc20(&contract_address, client)?;
@@ -528,10 +538,15 @@ async fn broadcast_contract_approval_tx_common(
             let token_id = u256_from_str(&token_id)?;
             let client = get_ethers_provider(web3api_url).await?;
             if let Some(key) = secret_key {
-                let client = create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
+                let (client, nonce_tracker) =
+                    create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
                 let contract = Contract::new_erc721(&contract_address, client)?;
                 let call = contract.approve(approved_address, token_id);
-                let receipt = ContractCall::from(call).legacy(legacy).send().await?;
+                let receipt = ContractCall::from(call)
+                    .legacy(legacy)
+                    .nonce_tracker(nonce_tracker)
+                    .send()
+                    .await?;
                 Ok((Some(receipt), None))
             } else {
                 let contract = Contract::new_erc721(&contract_address, client)?;
@@ -548,11 +563,16 @@ async fn broadcast_contract_approval_tx_common(
             let approved_address = address_from_str(&approved_address)?;
             let client = get_ethers_provider(web3api_url).await?;
             if let Some(key) = secret_key {
-                let client = create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
+                let (client, nonce_tracker) =
+                    create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
 
                 let contract = Contract::new_erc721(&contract_address, client)?;
                 let call = contract.set_approval_for_all(approved_address, approved);
-                let receipt = ContractCall::from(call).legacy(legacy).send().await?;
+                let receipt = ContractCall::from(call)
+                    .legacy(legacy)
+                    .nonce_tracker(nonce_tracker)
+                    .send()
+                    .await?;
                 Ok((Some(receipt), None))
             } else {
                 let contract = Contract::new_erc721(&contract_address, client)?;
@@ -569,10 +589,15 @@ async fn broadcast_contract_approval_tx_common(
             let approved_address = address_from_str(&approved_address)?;
             let client = get_ethers_provider(web3api_url).await?;
             if let Some(key) = secret_key {
-                let client = create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
+                let (client, nonce_tracker) =
+                    create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
                 let contract = Contract::new_erc1155(&contract_address, client)?;
                 let call = contract.set_approval_for_all(approved_address, approved);
-                let receipt = ContractCall::from(call).legacy(legacy).send().await?;
+                let receipt = ContractCall::from(call)
+                    .legacy(legacy)
+                    .nonce_tracker(nonce_tracker)
+                    .send()
+                    .await?;
                 Ok((Some(receipt), None))
             } else {
                 let contract = Contract::new_erc1155(&contract_address, client)?;
@@ -634,15 +659,20 @@ async fn broadcast_contract_transfer_tx_common(
             to_address,
             amount,
         } => {
-            let to_address = address_from_str(&to_address)?;
+            let to_address = resolve_eth_address(&to_address, chain_id, web3api_url).await?;
             let amount = u256_from_dec_str(&amount)?;
             let client = get_ethers_provider(web3api_url).await?;
             if let Some(key) = secret_key {
-                let client = create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
+                let (client, nonce_tracker) =
+                    create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
 
                 let contract = Contract::new_erc20(&contract_address, client)?;
                 let call = contract.transfer(to_address, amount);
-                let receipt = ContractCall::from(call).legacy(legacy).send().await?;
+                let receipt = ContractCall::from(call)
+                    .legacy(legacy)
+                    .nonce_tracker(nonce_tracker)
+                    .send()
+                    .await?;
                 Ok((Some(receipt), None))
             } else {
                 let contract = Contract::new_erc20(&contract_address, client)?;
@@ -658,14 +688,19 @@ async fn broadcast_contract_transfer_tx_common(
             amount,
         } => {
             let from_address = address_from_str(&from_address)?;
-            let to_address = address_from_str(&to_address)?;
+            let to_address = resolve_eth_address(&to_address, chain_id, web3api_url).await?;
             let amount = u256_from_dec_str(&amount)?;
             let client = get_ethers_provider(web3api_url).await?;
             if let Some(key) = secret_key {
-                let client = create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
+                let (client, nonce_tracker) =
+                    create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
                 let contract = Contract::new_erc20(&contract_address, client)?;
                 let call = contract.transfer_from(from_address, to_address, amount);
-                let receipt = ContractCall::from(call).legacy(legacy).send().await?;
+                let receipt = ContractCall::from(call)
+                    .legacy(legacy)
+                    .nonce_tracker(nonce_tracker)
+                    .send()
+                    .await?;
                 Ok((Some(receipt), None))
             } else {
                 let contract = Contract::new_erc20(&contract_address, client)?;
@@ -681,16 +716,21 @@ async fn broadcast_contract_transfer_tx_common(
             token_id,
         } => {
             let token_id = u256_from_str(&token_id)?;
-            let to_address = address_from_str(&to_address)?;
+            let to_address = resolve_eth_address(&to_address, chain_id, web3api_url).await?;
             let from_address = address_from_str(&from_address)?;
             let client = get_ethers_provider(web3api_url).await?;
 
             if let Some(key) = secret_key {
-                let client = create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
+                let (client, nonce_tracker) =
+                    create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
 
                 let contract = Contract::new_erc721(&contract_address, client)?;
                 let call = contract.transfer_from(from_address, to_address, token_id);
-                let receipt = ContractCall::from(call).legacy(legacy).send().await?;
+                let receipt = ContractCall::from(call)
+                    .legacy(legacy)
+                    .nonce_tracker(nonce_tracker)
+                    .send()
+                    .await?;
                 Ok((Some(receipt), None))
             } else {
                 let contract = Contract::new_erc721(&contract_address, client)?;
@@ -706,16 +746,21 @@ async fn broadcast_contract_transfer_tx_common(
             token_id,
         } => {
             let token_id = u256_from_str(&token_id)?;
-            let to_address = address_from_str(&to_address)?;
+            let to_address = resolve_eth_address(&to_address, chain_id, web3api_url).await?;
             let from_address = address_from_str(&from_address)?;
             let client = get_ethers_provider(web3api_url).await?;
 
             if let Some(key) = secret_key {
-                let client = create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
+                let (client, nonce_tracker) =
+                    create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
 
                 let contract = Contract::new_erc721(&contract_address, client)?;
                 let call = contract.safe_transfer_from(from_address, to_address, token_id);
-                let receipt = ContractCall::from(call).legacy(legacy).send().await?;
+                let receipt = ContractCall::from(call)
+                    .legacy(legacy)
+                    .nonce_tracker(nonce_tracker)
+                    .send()
+                    .await?;
                 Ok((Some(receipt), None))
             } else {
                 let contract = Contract::new_erc721(&contract_address, client)?;
@@ -732,11 +777,12 @@ async fn broadcast_contract_transfer_tx_common(
             additional_data,
         } => {
             let token_id = u256_from_str(&token_id)?;
-            let to_address = address_from_str(&to_address)?;
+            let to_address = resolve_eth_address(&to_address, chain_id, web3api_url).await?;
             let from_address = address_from_str(&from_address)?;
             let client = get_ethers_provider(web3api_url).await?;
             if let Some(key) = secret_key {
-                let client = create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
+                let (client, nonce_tracker) =
+                    create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
 
                 let contract = Contract::new_erc721(&contract_address, client)?;
                 let call = contract.safe_transfer_from_with_from_and_to_and_data(
@@ -745,7 +791,11 @@ async fn broadcast_contract_transfer_tx_common(
                     token_id,
                     additional_data.into(),
                 );
-                let receipt = ContractCall::from(call).legacy(legacy).send().await?;
+                let receipt = ContractCall::from(call)
+                    .legacy(legacy)
+                    .nonce_tracker(nonce_tracker)
+                    .send()
+                    .await?;
                 Ok((Some(receipt), None))
             } else {
                 let contract = Contract::new_erc721(&contract_address, client)?;
@@ -769,11 +819,12 @@ async fn broadcast_contract_transfer_tx_common(
         } => {
             let token_id = u256_from_str(&token_id)?;
             let amount = u256_from_dec_str(&amount)?;
-            let to_address = address_from_str(&to_address)?;
+            let to_address = resolve_eth_address(&to_address, chain_id, web3api_url).await?;
             let from_address = address_from_str(&from_address)?;
             let client = get_ethers_provider(web3api_url).await?;
             if let Some(key) = secret_key {
-                let client = create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
+                let (client, nonce_tracker) =
+                    create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
 
                 let contract = Contract::new_erc1155(&contract_address, client)?;
                 let call = contract.safe_transfer_from(
@@ -783,7 +834,11 @@ async fn broadcast_contract_transfer_tx_common(
                     amount,
                     additional_data.into(),
                 );
-                let receipt = ContractCall::from(call).legacy(legacy).send().await?;
+                let receipt = ContractCall::from(call)
+                    .legacy(legacy)
+                    .nonce_tracker(nonce_tracker)
+                    .send()
+                    .await?;
                 Ok((Some(receipt), None))
             } else {
                 let contract = Contract::new_erc1155(&contract_address, client)?;
@@ -853,7 +908,7 @@ async fn broadcast_contract_batch_transfer_tx_common(
             amounts,
             additional_data,
         } => {
-            let to_address = address_from_str(&to_address)?;
+            let to_address = resolve_eth_address(&to_address, chain_id, web3api_url).await?;
             let from_address = address_from_str(&from_address)?;
             let token_ids = token_ids
                 .iter()
@@ -866,7 +921,8 @@ async fn broadcast_contract_batch_transfer_tx_common(
             let client = get_ethers_provider(web3api_url).await?;
 
             if let Some(key) = secret_key {
-                let client = create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
+                let (client, nonce_tracker) =
+                    create_localwallet_client(polling_interval_ms, key, chain_id, client)?;
 
                 let contract = Contract::new_erc1155(&contract_address, client)?;
 
@@ -877,7 +933,11 @@ async fn broadcast_contract_batch_transfer_tx_common(
                     amounts,
                     additional_data.into(),
                 );
-                let receipt = ContractCall::from(call).legacy(legacy).send().await?;
+                let receipt = ContractCall::from(call)
+                    .legacy(legacy)
+                    .nonce_tracker(nonce_tracker)
+                    .send()
+                    .await?;
                 Ok((Some(receipt), None))
             } else {
                 let contract = Contract::new_erc1155(&contract_address, client)?;
@@ -931,7 +991,8 @@ pub async fn construct_contract_batch_transfer_tx(
 }
 
 /// given the plain transfer details, it'll construct, sign and broadcast
-/// a corresponding transaction.
+/// a corresponding transaction (`to_hex` can also be a name resolved
+/// with the name registry of the chain).
 /// If successful, it returns the transaction receipt.
 pub async fn broadcast_sign_eth_tx(
     to_hex: &str,
@@ -950,15 +1011,28 @@ pub async fn broadcast_sign_eth_tx(
     }
     .derive_address(secret_key.as_ref())
     .map_err(EthError::HdWrapError)?;
-    let tx = construct_simple_eth_transfer_tx(&from_address, to_hex, amount, legacy, chain_id)?;
+    let to_address = resolve_eth_address(to_hex, chain_id, web3api_url).await?;
+    let mut tx = construct_simple_eth_transfer_tx(
+        &from_address,
+        &format!("{to_address:?}"),
+        amount,
+        legacy,
+        chain_id,
+    )?;
     let client = get_ethers_provider(web3api_url).await?;
 
-    let client = create_localwallet_client(polling_interval_ms, secret_key, chain_id, client)?;
+    let (client, nonce_tracker) =
+        create_localwallet_client(polling_interval_ms, secret_key, chain_id, client)?;
+    let nonce = nonce_tracker.next().await?;
+    tx.set_nonce(nonce);
 
-    let pending_tx = client
-        .send_transaction(tx, None)
-        .await
-        .map_err(EthError::SendTxFail)?;
+    let pending_tx = match client.send_transaction(tx, None).await {
+        Ok(pending_tx) => pending_tx,
+        Err(e) => {
+            nonce_tracker.release(nonce, &e.to_string());
+            return Err(EthError::SendTxFail(e));
+        }
+    };
     let tx_receipt = pending_tx
         .await
         .map_err(EthError::BroadcastTxFail)?
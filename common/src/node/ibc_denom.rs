@@ -0,0 +1,475 @@
+#[cfg(not(target_arch = "wasm32"))]
+use super::cosmos_sdk::{get_denom_metadata_blocking, DenomMetadata};
+use super::error::RestError;
+use crate::{CosmosSDKMsg, IbcTimeout, SingleCoin};
+use cosmos_sdk_proto::ibc::applications::transfer::v1::{
+    query_client::QueryClient, DenomTrace as RawDenomTrace, QueryDenomTraceRequest,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::RwLock;
+
+/// the prefix of the IBC vouchers' denominations
+const IBC_DENOM_PREFIX: &str = "ibc/";
+/// the prefix of the channel identifiers
+const CHANNEL_PREFIX: &str = "channel-";
+
+/// one hop of the ICS-20 trace (the port and channel on the receiving chain)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IbcHop {
+    /// the port identifier (usually "transfer")
+    pub port: String,
+    /// the channel identifier (e.g. "channel-0")
+    pub channel: String,
+}
+
+/// the ICS-20 denomination trace of an IBC voucher
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DenomTrace {
+    /// the chain of port/channel identifiers the tokens were sent through
+    /// (the most recent hop first); empty for native tokens
+    pub path: String,
+    /// the base denomination on the origin chain
+    pub base_denom: String,
+}
+
+impl From<RawDenomTrace> for DenomTrace {
+    fn from(trace: RawDenomTrace) -> Self {
+        Self {
+            path: trace.path,
+            base_denom: trace.base_denom,
+        }
+    }
+}
+
+impl fmt::Display for DenomTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.base_denom)
+        } else {
+            write!(f, "{}/{}", self.path, self.base_denom)
+        }
+    }
+}
+
+/// parses the full denomination path, e.g. "transfer/channel-0/uatom"
+/// (the base denomination may contain '/', e.g. "gamm/pool/1")
+impl FromStr for DenomTrace {
+    type Err = eyre::Report;
+
+    fn from_str(full_denom: &str) -> Result<Self, Self::Err> {
+        if full_denom.is_empty() {
+            return Err(eyre::eyre!("empty denomination"));
+        }
+        if full_denom.starts_with(IBC_DENOM_PREFIX) {
+            return Err(eyre::eyre!(
+                "{} is a hashed denomination, the trace needs to be queried",
+                full_denom
+            ));
+        }
+        let parts: Vec<&str> = full_denom.split('/').collect();
+        let mut path_len = 0;
+        while path_len + 2 < parts.len() && is_channel_id(parts[path_len + 1]) {
+            path_len += 2;
+        }
+        Ok(Self {
+            path: parts[..path_len].join("/"),
+            base_denom: parts[path_len..].join("/"),
+        })
+    }
+}
+
+fn is_channel_id(id: &str) -> bool {
+    id.strip_prefix(CHANNEL_PREFIX)
+        .map(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or(false)
+}
+
+/// the uppercase hexadecimal SHA-256 hash of the full denomination path
+pub fn get_ibc_denom_hash(full_denom: &str) -> String {
+    hex::encode_upper(Sha256::digest(full_denom.as_bytes()))
+}
+
+/// the on-chain denomination of the tokens with the given trace path and base denomination
+/// ("ibc/{hash}", or the base denomination if the path is empty)
+pub fn get_ibc_denom(path: &str, base_denom: &str) -> String {
+    DenomTrace {
+        path: path.to_owned(),
+        base_denom: base_denom.to_owned(),
+    }
+    .ibc_denom()
+}
+
+impl DenomTrace {
+    /// whether the tokens are native to the current chain
+    pub fn is_native(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    /// the on-chain denomination ("ibc/{hash}" for vouchers)
+    pub fn ibc_denom(&self) -> String {
+        if self.is_native() {
+            self.base_denom.clone()
+        } else {
+            format!(
+                "{}{}",
+                IBC_DENOM_PREFIX,
+                get_ibc_denom_hash(&self.to_string())
+            )
+        }
+    }
+
+    /// the port/channel pairs of the path (the most recent hop first)
+    pub fn hops(&self) -> eyre::Result<Vec<IbcHop>> {
+        if self.is_native() {
+            return Ok(vec![]);
+        }
+        let parts: Vec<&str> = self.path.split('/').collect();
+        if parts.len() % 2 != 0 {
+            return Err(eyre::eyre!("invalid trace path: {}", self.path));
+        }
+        parts
+            .chunks(2)
+            .map(|pair| {
+                if pair[0].is_empty() || !is_channel_id(pair[1]) {
+                    Err(eyre::eyre!("invalid trace path: {}", self.path))
+                } else {
+                    Ok(IbcHop {
+                        port: pair[0].to_owned(),
+                        channel: pair[1].to_owned(),
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// build the ICS-20 transfers that send the tokens back to their origin chain
+    /// by unwinding the trace: the n-th message is to be signed and broadcasted
+    /// on the n-th chain of the route (starting from the current one),
+    /// `receivers` are the recipient addresses on each next chain
+    /// (the last one being on the origin chain), and `timeouts` are the timeouts
+    /// of each hop on its next chain (the heights of different chains are unrelated,
+    /// so the timeout heights may be 0 to only use the timestamps).
+    pub fn unwind_to_origin(
+        &self,
+        amount: &str,
+        receivers: Vec<String>,
+        timeouts: Vec<IbcTimeout>,
+    ) -> eyre::Result<Vec<CosmosSDKMsg>> {
+        let hops = self.hops()?;
+        if hops.is_empty() {
+            return Err(eyre::eyre!("{} is native to the current chain", self));
+        }
+        if hops.len() != receivers.len() {
+            return Err(eyre::eyre!(
+                "expected {} receivers, got {}",
+                hops.len(),
+                receivers.len()
+            ));
+        }
+        if hops.len() != timeouts.len() {
+            return Err(eyre::eyre!(
+                "expected {} timeouts, got {}",
+                hops.len(),
+                timeouts.len()
+            ));
+        }
+        for timeout in timeouts.iter() {
+            timeout.validate(None)?;
+        }
+        let msgs = hops
+            .iter()
+            .zip(receivers.into_iter().zip(timeouts))
+            .enumerate()
+            .map(|(index, (hop, (receiver, timeout)))| {
+                let remaining = DenomTrace {
+                    path: hops[index..]
+                        .iter()
+                        .map(|hop| format!("{}/{}", hop.port, hop.channel))
+                        .collect::<Vec<_>>()
+                        .join("/"),
+                    base_denom: self.base_denom.clone(),
+                };
+                timeout.to_ibc_transfer(
+                    receiver,
+                    hop.port.clone(),
+                    hop.channel.clone(),
+                    SingleCoin::Other {
+                        amount: amount.to_owned(),
+                        denom: remaining.ibc_denom(),
+                    },
+                )
+            })
+            .collect();
+        Ok(msgs)
+    }
+}
+
+/// strips the "ibc/" prefix if present
+fn denom_hash(denom: &str) -> &str {
+    denom.strip_prefix(IBC_DENOM_PREFIX).unwrap_or(denom)
+}
+
+/// return the denomination trace of the IBC voucher (async for JS/WASM)
+#[cfg(target_arch = "wasm32")]
+pub async fn get_denom_trace(grpc_web_url: &str, denom: &str) -> Result<DenomTrace, RestError> {
+    let mut client = QueryClient::new(tonic_web_wasm_client::Client::new(grpc_web_url.to_string()));
+    let request = QueryDenomTraceRequest {
+        hash: denom_hash(denom).to_owned(),
+    };
+    client
+        .denom_trace(request)
+        .await
+        .map_err(RestError::GRPCError)?
+        .into_inner()
+        .denom_trace
+        .map(Into::into)
+        .ok_or(RestError::MissingResult)
+}
+
+/// return the denomination trace of the IBC voucher (blocking for other platforms;
+/// platform-guarded as JS/WASM doesn't support the reqwest blocking)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn get_denom_trace_blocking(grpc_url: &str, denom: &str) -> Result<DenomTrace, RestError> {
+    tokio::runtime::Runtime::new()
+        .map_err(|_err| RestError::AsyncRuntimeError)?
+        .block_on(async move {
+            let mut client = QueryClient::connect(grpc_url.to_string())
+                .await
+                .map_err(RestError::GRPCTransportError)?;
+            let request = QueryDenomTraceRequest {
+                hash: denom_hash(denom).to_owned(),
+            };
+            client
+                .denom_trace(request)
+                .await
+                .map_err(RestError::GRPCError)?
+                .into_inner()
+                .denom_trace
+                .map(Into::into)
+                .ok_or(RestError::MissingResult)
+        })
+}
+
+/// the resolved denomination with its trace and display metadata
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct IbcDenomInfo {
+    /// the on-chain denomination
+    pub denom: String,
+    /// the denomination trace (empty path for native tokens)
+    pub trace: DenomTrace,
+    /// the denomination metadata registered on the current chain, if any
+    pub metadata: Option<DenomMetadata>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IbcDenomInfo {
+    /// the suggested denomination to display
+    /// (from the metadata if registered, otherwise the base denomination)
+    pub fn display_denom(&self) -> String {
+        match &self.metadata {
+            Some(metadata) if !metadata.display.is_empty() => metadata.display.clone(),
+            _ => self.trace.base_denom.clone(),
+        }
+    }
+}
+
+/// resolves the IBC vouchers' denominations and caches their traces
+/// (the trace of a given hash never changes)
+#[cfg(not(target_arch = "wasm32"))]
+pub struct IbcDenomResolver {
+    /// the Cosmos gRPC (usually on 9090)
+    grpc_url: String,
+    /// the cached traces keyed by the "ibc/{hash}" denomination
+    traces: RwLock<HashMap<String, DenomTrace>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IbcDenomResolver {
+    /// a new resolver using the gRPC URL
+    pub fn new(grpc_url: String) -> Self {
+        Self {
+            grpc_url,
+            traces: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// return the denomination trace (blocking);
+    /// native denominations are returned with an empty path without querying
+    pub fn get_denom_trace(&self, denom: &str) -> Result<DenomTrace, RestError> {
+        if !denom.starts_with(IBC_DENOM_PREFIX) {
+            return Ok(DenomTrace {
+                path: String::new(),
+                base_denom: denom.to_owned(),
+            });
+        }
+        let key = format!("{}{}", IBC_DENOM_PREFIX, denom_hash(denom).to_uppercase());
+        let cached = self
+            .traces
+            .read()
+            .map_err(|_| RestError::CacheLockPoisoned)?
+            .get(&key)
+            .cloned();
+        if let Some(trace) = cached {
+            return Ok(trace);
+        }
+        let trace = get_denom_trace_blocking(&self.grpc_url, &key)?;
+        self.traces
+            .write()
+            .map_err(|_| RestError::CacheLockPoisoned)?
+            .insert(key, trace.clone());
+        Ok(trace)
+    }
+
+    /// return the denomination trace and its display metadata (blocking)
+    pub fn resolve(&self, denom: &str) -> Result<IbcDenomInfo, RestError> {
+        let trace = self.get_denom_trace(denom)?;
+        let metadata = match get_denom_metadata_blocking(&self.grpc_url, denom.to_owned()) {
+            Ok(metadata) => Some(metadata),
+            Err(RestError::GRPCError(status)) if status.code() == tonic::Code::NotFound => None,
+            Err(RestError::MissingResult) => None,
+            Err(e) => return Err(e),
+        };
+        Ok(IbcDenomInfo {
+            denom: denom.to_owned(),
+            trace,
+            metadata,
+        })
+    }
+
+    /// remove the cached traces
+    pub fn clear_cache(&self) -> Result<(), RestError> {
+        self.traces
+            .write()
+            .map_err(|_| RestError::CacheLockPoisoned)?
+            .clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Height;
+
+    #[test]
+    fn ibc_denom_hash_matches_ibc_go() {
+        assert_eq!(
+            get_ibc_denom("transfer/channel-0", "uatom"),
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+        assert_eq!(get_ibc_denom("", "basecro"), "basecro");
+    }
+
+    #[test]
+    fn full_denom_is_parsed() {
+        let trace: DenomTrace = "transfer/channel-5/transfer/channel-0/gamm/pool/1"
+            .parse()
+            .unwrap();
+        assert_eq!(trace.path, "transfer/channel-5/transfer/channel-0");
+        assert_eq!(trace.base_denom, "gamm/pool/1");
+        assert_eq!(
+            trace.to_string(),
+            "transfer/channel-5/transfer/channel-0/gamm/pool/1"
+        );
+
+        let native: DenomTrace = "basecro".parse().unwrap();
+        assert!(native.is_native());
+        assert!("ibc/27394FB0".parse::<DenomTrace>().is_err());
+    }
+
+    #[test]
+    fn trace_is_unwound_to_origin() {
+        let trace = DenomTrace {
+            path: "transfer/channel-5/transfer/channel-0".to_owned(),
+            base_denom: "uatom".to_owned(),
+        };
+        // the first hop times out at a height of Osmosis, the second one only at a time
+        let timeouts = vec![
+            IbcTimeout {
+                timeout_height: Height {
+                    revision_number: 1,
+                    revision_height: 4_000_000,
+                },
+                timeout_timestamp: 0,
+            },
+            IbcTimeout {
+                timeout_height: Height {
+                    revision_number: 0,
+                    revision_height: 0,
+                },
+                timeout_timestamp: 1645800000000000000,
+            },
+        ];
+        let msgs = trace
+            .unwind_to_origin(
+                "100",
+                vec!["osmo1receiver".to_owned(), "cosmos1receiver".to_owned()],
+                timeouts.clone(),
+            )
+            .unwrap();
+        assert_eq!(msgs.len(), 2);
+        match &msgs[0] {
+            CosmosSDKMsg::IbcTransfer {
+                source_channel,
+                token,
+                receiver,
+                timeout_height,
+                timeout_timestamp,
+                ..
+            } => {
+                assert_eq!(source_channel, "channel-5");
+                assert_eq!(receiver, "osmo1receiver");
+                assert_eq!(timeout_height.revision_height, 4_000_000);
+                assert_eq!(*timeout_timestamp, 0);
+                assert_eq!(
+                    token,
+                    &SingleCoin::Other {
+                        amount: "100".to_owned(),
+                        denom: trace.ibc_denom(),
+                    }
+                );
+            }
+            _ => panic!("unexpected message"),
+        }
+        match &msgs[1] {
+            CosmosSDKMsg::IbcTransfer {
+                source_channel,
+                token,
+                timeout_height,
+                timeout_timestamp,
+                ..
+            } => {
+                assert_eq!(source_channel, "channel-0");
+                assert_eq!(timeout_height.revision_height, 0);
+                assert_eq!(*timeout_timestamp, 1645800000000000000);
+                assert_eq!(
+                    token,
+                    &SingleCoin::Other {
+                        amount: "100".to_owned(),
+                        denom: get_ibc_denom("transfer/channel-0", "uatom"),
+                    }
+                );
+            }
+            _ => panic!("unexpected message"),
+        }
+
+        let receivers = vec!["osmo1receiver".to_owned(), "cosmos1receiver".to_owned()];
+        assert!(trace
+            .unwind_to_origin("100", receivers[..1].to_vec(), timeouts.clone())
+            .is_err());
+        assert!(trace
+            .unwind_to_origin("100", receivers.clone(), timeouts[..1].to_vec())
+            .is_err());
+        // a hop without any timeout
+        let mut unset = timeouts;
+        unset[1].timeout_timestamp = 0;
+        assert!(trace.unwind_to_origin("100", receivers, unset).is_err());
+    }
+}
@@ -0,0 +1,489 @@
+use super::error::RestError;
+use crate::{validate_ibc_timeout, CosmosSDKMsg, Height, SingleCoin};
+use cosmos_sdk_proto::ibc::core::channel::v1::{
+    query_client::QueryClient, QueryChannelClientStateRequest, QueryChannelConsensusStateRequest,
+};
+use ibc_proto::ibc::lightclients::tendermint::v1::{ClientState, ConsensusState};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tendermint_rpc::{endpoint::status, request, response};
+
+/// the number of nanoseconds in a second
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// the latest state of the counterparty chain: the channel's light client state
+/// advanced to the latest block of the counterparty node
+/// (the light client alone lags behind if the relayers haven't updated it recently)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CounterpartyState {
+    /// the client identifier on the source chain
+    pub client_id: String,
+    /// the chain id of the counterparty chain
+    pub chain_id: String,
+    /// the latest height of the counterparty chain
+    pub latest_height: Height,
+    /// the block time (in nanoseconds since the Unix epoch) at the latest height
+    pub latest_timestamp: u64,
+}
+
+impl CounterpartyState {
+    /// advance the light client state to the latest block reported by the counterparty node;
+    /// the revision number is kept, so the node must be on the client's chain id
+    pub fn update_latest_block(
+        &mut self,
+        chain_id: &str,
+        height: u64,
+        timestamp: u64,
+    ) -> eyre::Result<()> {
+        if chain_id != self.chain_id {
+            return Err(eyre::eyre!(
+                "the counterparty node is on {} instead of {}",
+                chain_id,
+                self.chain_id
+            ));
+        }
+        self.latest_height.revision_height = self.latest_height.revision_height.max(height);
+        self.latest_timestamp = self.latest_timestamp.max(timestamp);
+        Ok(())
+    }
+}
+
+/// the timeout relative to the counterparty chain's latest state;
+/// an offset is disabled when set to 0, but not both
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelativeIbcTimeout {
+    /// the number of blocks after the latest height
+    pub height_offset: u64,
+    /// the number of seconds after the latest block time (or the local time if it's later)
+    pub timestamp_offset_secs: u64,
+}
+
+/// the absolute timeout of the ICS-20 transfer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IbcTimeout {
+    /// the timeout height on the counterparty chain (disabled when 0)
+    pub timeout_height: Height,
+    /// the timeout timestamp (in nanoseconds) on the counterparty chain (disabled when 0)
+    pub timeout_timestamp: u64,
+}
+
+impl RelativeIbcTimeout {
+    /// compute the absolute timeout from the counterparty chain's latest state;
+    /// the state must be advanced to the counterparty node's latest block
+    /// (as done by `get_counterparty_state`), otherwise the timeout height may
+    /// already have passed when the light client is stale;
+    /// the timestamp offset is added to the later of the latest block time and
+    /// `local_timestamp` (in nanoseconds since the Unix epoch), as the consensus state
+    /// of the light client may be stale if the relayers haven't updated it recently
+    pub fn to_absolute(
+        &self,
+        state: &CounterpartyState,
+        local_timestamp: u64,
+    ) -> eyre::Result<IbcTimeout> {
+        let timeout_height = if self.height_offset == 0 {
+            Height {
+                revision_number: 0,
+                revision_height: 0,
+            }
+        } else {
+            Height {
+                revision_number: state.latest_height.revision_number,
+                revision_height: state
+                    .latest_height
+                    .revision_height
+                    .checked_add(self.height_offset)
+                    .ok_or_else(|| eyre::eyre!("timeout height overflow"))?,
+            }
+        };
+        let timeout_timestamp = if self.timestamp_offset_secs == 0 {
+            0
+        } else {
+            self.timestamp_offset_secs
+                .checked_mul(NANOS_PER_SEC)
+                .and_then(|offset| {
+                    state
+                        .latest_timestamp
+                        .max(local_timestamp)
+                        .checked_add(offset)
+                })
+                .ok_or_else(|| eyre::eyre!("timeout timestamp overflow"))?
+        };
+        let timeout = IbcTimeout {
+            timeout_height,
+            timeout_timestamp,
+        };
+        timeout.validate(Some(state))?;
+        Ok(timeout)
+    }
+}
+
+impl IbcTimeout {
+    /// check that the timeout is set and, if the counterparty state is known,
+    /// that it hasn't already passed
+    pub fn validate(&self, state: Option<&CounterpartyState>) -> eyre::Result<()> {
+        validate_ibc_timeout(&self.timeout_height, self.timeout_timestamp)?;
+        if let Some(state) = state {
+            if self.timeout_height.revision_height != 0 {
+                if self.timeout_height.revision_number != state.latest_height.revision_number {
+                    return Err(eyre::eyre!(
+                        "timeout revision number {} doesn't match the counterparty revision {}",
+                        self.timeout_height.revision_number,
+                        state.latest_height.revision_number
+                    ));
+                }
+                if self.timeout_height.revision_height <= state.latest_height.revision_height {
+                    return Err(eyre::eyre!(
+                        "timeout height {} has already passed on {} (latest height {})",
+                        self.timeout_height.revision_height,
+                        state.chain_id,
+                        state.latest_height.revision_height
+                    ));
+                }
+            }
+            if self.timeout_timestamp != 0 && self.timeout_timestamp <= state.latest_timestamp {
+                return Err(eyre::eyre!(
+                    "timeout timestamp {} has already passed on {} (latest block time {})",
+                    self.timeout_timestamp,
+                    state.chain_id,
+                    state.latest_timestamp
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// build the ICS-20 transfer message with this timeout
+    pub fn to_ibc_transfer(
+        &self,
+        receiver: String,
+        source_port: String,
+        source_channel: String,
+        token: SingleCoin,
+    ) -> CosmosSDKMsg {
+        CosmosSDKMsg::IbcTransfer {
+            receiver,
+            source_port,
+            source_channel,
+            token,
+            timeout_height: self.timeout_height.clone(),
+            timeout_timestamp: self.timeout_timestamp,
+        }
+    }
+}
+
+fn decode_client_state(
+    client_id: String,
+    client_state: Option<cosmrs::Any>,
+) -> Result<(String, String, Height), RestError> {
+    let client_state = client_state.ok_or(RestError::MissingResult)?;
+    let client_state = ClientState::decode(client_state.value.as_slice())
+        .map_err(|e| RestError::DecodeError(e.to_string()))?;
+    let latest_height = client_state.latest_height.ok_or(RestError::MissingResult)?;
+    Ok((client_id, client_state.chain_id, latest_height))
+}
+
+fn decode_consensus_timestamp(consensus_state: Option<cosmrs::Any>) -> Result<u64, RestError> {
+    let consensus_state = consensus_state.ok_or(RestError::MissingResult)?;
+    let timestamp = ConsensusState::decode(consensus_state.value.as_slice())
+        .map_err(|e| RestError::DecodeError(e.to_string()))?
+        .timestamp
+        .ok_or(RestError::MissingResult)?;
+    let seconds =
+        u64::try_from(timestamp.seconds).map_err(|e| RestError::DecodeError(e.to_string()))?;
+    let nanos =
+        u64::try_from(timestamp.nanos).map_err(|e| RestError::DecodeError(e.to_string()))?;
+    Ok(seconds * NANOS_PER_SEC + nanos)
+}
+
+/// query the latest block (chain id, height and time in nanoseconds) of the counterparty node
+async fn query_latest_block(
+    counterparty_tendermint_rpc_url: &str,
+) -> Result<(String, u64, u64), RestError> {
+    let status = reqwest::Client::new()
+        .post(counterparty_tendermint_rpc_url)
+        .json(&request::Wrapper::new(status::Request))
+        .send()
+        .await
+        .map_err(RestError::RequestError)?
+        .json::<response::Wrapper<status::Response>>()
+        .await
+        .map_err(RestError::RequestError)?
+        .into_result()
+        .map_err(|_e| RestError::MissingResult)?;
+    let timestamp = u64::try_from(status.sync_info.latest_block_time.unix_timestamp_nanos())
+        .map_err(|e| RestError::DecodeError(e.to_string()))?;
+    Ok((
+        status.node_info.network.to_string(),
+        status.sync_info.latest_block_height.value(),
+        timestamp,
+    ))
+}
+
+/// query the counterparty chain's latest state: the state known by the client of the channel,
+/// advanced to the latest block of the counterparty node (async for JS/WASM)
+#[cfg(target_arch = "wasm32")]
+pub async fn get_counterparty_state(
+    grpc_web_url: &str,
+    port_id: &str,
+    channel_id: &str,
+    counterparty_tendermint_rpc_url: &str,
+) -> Result<CounterpartyState, RestError> {
+    let mut client = QueryClient::new(tonic_web_wasm_client::Client::new(grpc_web_url.to_string()));
+    let identified = client
+        .channel_client_state(QueryChannelClientStateRequest {
+            port_id: port_id.to_owned(),
+            channel_id: channel_id.to_owned(),
+        })
+        .await
+        .map_err(RestError::GRPCError)?
+        .into_inner()
+        .identified_client_state
+        .ok_or(RestError::MissingResult)?;
+    let (client_id, chain_id, latest_height) =
+        decode_client_state(identified.client_id, identified.client_state)?;
+    let consensus_state = client
+        .channel_consensus_state(QueryChannelConsensusStateRequest {
+            port_id: port_id.to_owned(),
+            channel_id: channel_id.to_owned(),
+            revision_number: latest_height.revision_number,
+            revision_height: latest_height.revision_height,
+        })
+        .await
+        .map_err(RestError::GRPCError)?
+        .into_inner()
+        .consensus_state;
+    let mut state = CounterpartyState {
+        client_id,
+        chain_id,
+        latest_height,
+        latest_timestamp: decode_consensus_timestamp(consensus_state)?,
+    };
+    let (chain_id, height, timestamp) = query_latest_block(counterparty_tendermint_rpc_url).await?;
+    state
+        .update_latest_block(&chain_id, height, timestamp)
+        .map_err(|e| RestError::InvalidIbcTimeout(e.to_string()))?;
+    Ok(state)
+}
+
+/// query the counterparty chain's latest state: the state known by the client of the channel,
+/// advanced to the latest block of the counterparty node (blocking for other platforms)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn get_counterparty_state_blocking(
+    grpc_url: &str,
+    port_id: &str,
+    channel_id: &str,
+    counterparty_tendermint_rpc_url: &str,
+) -> Result<CounterpartyState, RestError> {
+    tokio::runtime::Runtime::new()
+        .map_err(|_err| RestError::AsyncRuntimeError)?
+        .block_on(async move {
+            let mut client = QueryClient::connect(grpc_url.to_string())
+                .await
+                .map_err(RestError::GRPCTransportError)?;
+            let identified = client
+                .channel_client_state(QueryChannelClientStateRequest {
+                    port_id: port_id.to_owned(),
+                    channel_id: channel_id.to_owned(),
+                })
+                .await
+                .map_err(RestError::GRPCError)?
+                .into_inner()
+                .identified_client_state
+                .ok_or(RestError::MissingResult)?;
+            let (client_id, chain_id, latest_height) =
+                decode_client_state(identified.client_id, identified.client_state)?;
+            let consensus_state = client
+                .channel_consensus_state(QueryChannelConsensusStateRequest {
+                    port_id: port_id.to_owned(),
+                    channel_id: channel_id.to_owned(),
+                    revision_number: latest_height.revision_number,
+                    revision_height: latest_height.revision_height,
+                })
+                .await
+                .map_err(RestError::GRPCError)?
+                .into_inner()
+                .consensus_state;
+            let mut state = CounterpartyState {
+                client_id,
+                chain_id,
+                latest_height,
+                latest_timestamp: decode_consensus_timestamp(consensus_state)?,
+            };
+            let (chain_id, height, timestamp) =
+                query_latest_block(counterparty_tendermint_rpc_url).await?;
+            state
+                .update_latest_block(&chain_id, height, timestamp)
+                .map_err(|e| RestError::InvalidIbcTimeout(e.to_string()))?;
+            Ok(state)
+        })
+}
+
+/// compute the absolute timeout of a transfer over the channel
+/// from the counterparty chain's latest state (blocking)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn get_ibc_timeout_blocking(
+    grpc_url: &str,
+    port_id: &str,
+    channel_id: &str,
+    counterparty_tendermint_rpc_url: &str,
+    relative: RelativeIbcTimeout,
+) -> Result<IbcTimeout, RestError> {
+    let state = get_counterparty_state_blocking(
+        grpc_url,
+        port_id,
+        channel_id,
+        counterparty_tendermint_rpc_url,
+    )?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| RestError::InvalidIbcTimeout(e.to_string()))?;
+    let now =
+        u64::try_from(now.as_nanos()).map_err(|e| RestError::InvalidIbcTimeout(e.to_string()))?;
+    relative
+        .to_absolute(&state, now)
+        .map_err(|e| RestError::InvalidIbcTimeout(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counterparty_state() -> CounterpartyState {
+        CounterpartyState {
+            client_id: "07-tendermint-0".to_owned(),
+            chain_id: "cosmoshub-4".to_owned(),
+            latest_height: Height {
+                revision_number: 4,
+                revision_height: 1000,
+            },
+            latest_timestamp: 1645800000000000000,
+        }
+    }
+
+    #[test]
+    fn relative_timeout_is_converted() {
+        let timeout = RelativeIbcTimeout {
+            height_offset: 100,
+            timestamp_offset_secs: 600,
+        }
+        .to_absolute(&counterparty_state(), 0)
+        .unwrap();
+        assert_eq!(
+            timeout.timeout_height,
+            Height {
+                revision_number: 4,
+                revision_height: 1100,
+            }
+        );
+        assert_eq!(timeout.timeout_timestamp, 1645800600000000000);
+
+        let timeout = RelativeIbcTimeout {
+            height_offset: 0,
+            timestamp_offset_secs: 600,
+        }
+        .to_absolute(&counterparty_state(), 0)
+        .unwrap();
+        assert_eq!(timeout.timeout_height.revision_height, 0);
+    }
+
+    #[test]
+    fn stale_state_uses_local_time() {
+        // the consensus state was updated an hour before the local time
+        let now = 1645803600000000000;
+        let timeout = RelativeIbcTimeout {
+            height_offset: 100,
+            timestamp_offset_secs: 600,
+        }
+        .to_absolute(&counterparty_state(), now)
+        .unwrap();
+        assert_eq!(timeout.timeout_timestamp, 1645804200000000000);
+        assert_eq!(timeout.timeout_height.revision_height, 1100);
+    }
+
+    #[test]
+    fn stale_client_is_advanced_to_the_counterparty_node() {
+        // the relayers haven't updated the client for 5000 blocks
+        let mut state = counterparty_state();
+        state
+            .update_latest_block("cosmoshub-4", 6000, 1645830000000000000)
+            .unwrap();
+        assert_eq!(
+            state.latest_height,
+            Height {
+                revision_number: 4,
+                revision_height: 6000,
+            }
+        );
+        let timeout = RelativeIbcTimeout {
+            height_offset: 100,
+            timestamp_offset_secs: 600,
+        }
+        .to_absolute(&state, 0)
+        .unwrap();
+        assert_eq!(timeout.timeout_height.revision_height, 6100);
+        assert_eq!(timeout.timeout_timestamp, 1645830600000000000);
+
+        // the timeout computed from the stale client has already passed on the counterparty chain
+        let stale = RelativeIbcTimeout {
+            height_offset: 100,
+            timestamp_offset_secs: 0,
+        }
+        .to_absolute(&counterparty_state(), 0)
+        .unwrap();
+        assert!(stale.validate(Some(&state)).is_err());
+
+        // a node behind the client doesn't move the state back
+        let mut state = counterparty_state();
+        state
+            .update_latest_block("cosmoshub-4", 900, 1645700000000000000)
+            .unwrap();
+        assert_eq!(state, counterparty_state());
+
+        // a node of another chain is rejected
+        assert!(counterparty_state()
+            .update_latest_block("cosmoshub-5", 6000, 1645830000000000000)
+            .is_err());
+    }
+
+    #[test]
+    fn disabled_timeout_is_rejected() {
+        assert!(RelativeIbcTimeout {
+            height_offset: 0,
+            timestamp_offset_secs: 0,
+        }
+        .to_absolute(&counterparty_state(), 0)
+        .is_err());
+    }
+
+    #[test]
+    fn expired_timeout_is_rejected() {
+        let state = counterparty_state();
+        let expired_height = IbcTimeout {
+            timeout_height: Height {
+                revision_number: 4,
+                revision_height: 1000,
+            },
+            timeout_timestamp: 0,
+        };
+        assert!(expired_height.validate(Some(&state)).is_err());
+
+        let wrong_revision = IbcTimeout {
+            timeout_height: Height {
+                revision_number: 3,
+                revision_height: 2000,
+            },
+            timeout_timestamp: 0,
+        };
+        assert!(wrong_revision.validate(Some(&state)).is_err());
+
+        let expired_timestamp = IbcTimeout {
+            timeout_height: Height {
+                revision_number: 0,
+                revision_height: 0,
+            },
+            timeout_timestamp: 1645799999000000000,
+        };
+        assert!(expired_timestamp.validate(Some(&state)).is_err());
+        assert!(expired_timestamp.validate(None).is_ok());
+    }
+}
@@ -4,12 +4,15 @@
 #![allow(clippy::await_holding_lock)]
 
 use super::error::RestError;
+use super::transport::{pagination_query, rest_get, QueryTransport};
 use crate::proto;
 use proto::chainmain::nft::v1::{
     query_client::QueryClient, BaseNft, Collection, Denom, Owner, QueryCollectionRequest,
     QueryDenomByNameRequest, QueryDenomRequest, QueryDenomsRequest, QueryNftRequest,
     QueryOwnerRequest, QuerySupplyRequest,
 };
+use serde::Deserialize;
+use serde_with::{serde_as, DisplayFromStr};
 
 use crate::PageRequest;
 
@@ -24,14 +27,33 @@ pub struct Client {
     pub client: RwLock<QueryClient<tonic::transport::Channel>>,
     #[cfg(not(target_arch = "wasm32"))]
     pub rt: tokio::runtime::Runtime,
+    /// the LCD REST API URL which the queries are sent to instead of `client`
+    rest_api_url: Option<String>,
 }
 
 impl Client {
     #[cfg(target_arch = "wasm32")]
     pub fn new(grpc_web_url: String) -> Self {
         let client = QueryClient::new(tonic_web_wasm_client::Client::new(grpc_web_url));
-        Self { client }
+        Self {
+            client,
+            rest_api_url: None,
+        }
     }
+
+    /// the queries are sent over gRPC-web or the LCD REST API
+    #[cfg(target_arch = "wasm32")]
+    pub fn from_transport(transport: QueryTransport) -> Result<Self, RestError> {
+        match transport {
+            QueryTransport::Rest(api_url) => Ok(Self {
+                // the gRPC-web client is lazy and not used
+                client: QueryClient::new(tonic_web_wasm_client::Client::new(api_url.clone())),
+                rest_api_url: Some(api_url),
+            }),
+            transport => Ok(Self::new(transport.grpc_url()?.to_owned())),
+        }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn new_blocking(grpc_url: String) -> Result<Self, RestError> {
         let rt = tokio::runtime::Runtime::new().map_err(|_err| RestError::AsyncRuntimeError)?;
@@ -44,12 +66,37 @@ impl Client {
         Ok(Self {
             client: RwLock::new(client?),
             rt,
+            rest_api_url: None,
         })
     }
 
+    /// the queries are sent over gRPC or the LCD REST API
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_transport(transport: QueryTransport) -> Result<Self, RestError> {
+        match transport {
+            QueryTransport::Rest(api_url) => {
+                let rt =
+                    tokio::runtime::Runtime::new().map_err(|_err| RestError::AsyncRuntimeError)?;
+                // the gRPC channel is lazy and not used
+                let channel = tonic::transport::Channel::from_shared(api_url.clone())
+                    .map_err(|e| RestError::UnsupportedTransport(e.to_string()))?
+                    .connect_lazy();
+                Ok(Self {
+                    client: RwLock::new(QueryClient::new(channel)),
+                    rt,
+                    rest_api_url: Some(api_url),
+                })
+            }
+            transport => Self::new_blocking(transport.grpc_url()?.to_owned()),
+        }
+    }
+
     #[cfg(target_arch = "wasm32")]
     /// Supply queries the total supply of a given denom or owner
     pub async fn supply(&mut self, denom_id: String, owner: String) -> Result<u64, RestError> {
+        if let Some(api_url) = &self.rest_api_url {
+            return rest_supply(api_url, denom_id, owner).await;
+        }
         let request = QuerySupplyRequest { denom_id, owner };
         let res = self
             .client
@@ -64,6 +111,9 @@ impl Client {
     /// Supply queries the total supply of a given denom or owner
     pub fn supply_blocking(&self, denom_id: String, owner: String) -> Result<u64, RestError> {
         self.rt.block_on(async move {
+            if let Some(api_url) = &self.rest_api_url {
+                return rest_supply(api_url, denom_id, owner).await;
+            }
             let mut client = self.client.write().unwrap();
             let request = QuerySupplyRequest { denom_id, owner };
             let res = (*client)
@@ -83,6 +133,9 @@ impl Client {
         owner: String,
         pagination: Option<PageRequest>,
     ) -> Result<Option<Owner>, RestError> {
+        if let Some(api_url) = &self.rest_api_url {
+            return rest_owner(api_url, denom_id, owner, pagination).await;
+        }
         let request = QueryOwnerRequest {
             denom_id,
             owner,
@@ -106,6 +159,9 @@ impl Client {
         pagination: Option<PageRequest>,
     ) -> Result<Option<Owner>, RestError> {
         self.rt.block_on(async move {
+            if let Some(api_url) = &self.rest_api_url {
+                return rest_owner(api_url, denom_id, owner, pagination).await;
+            }
             let mut client = self.client.write().unwrap();
             let request = QueryOwnerRequest {
                 denom_id,
@@ -128,6 +184,9 @@ impl Client {
         denom_id: String,
         pagination: Option<PageRequest>,
     ) -> Result<Option<Collection>, RestError> {
+        if let Some(api_url) = &self.rest_api_url {
+            return rest_collection(api_url, denom_id, pagination).await;
+        }
         let request = QueryCollectionRequest {
             denom_id,
             pagination,
@@ -149,6 +208,9 @@ impl Client {
         pagination: Option<PageRequest>,
     ) -> Result<Option<Collection>, RestError> {
         self.rt.block_on(async move {
+            if let Some(api_url) = &self.rest_api_url {
+                return rest_collection(api_url, denom_id, pagination).await;
+            }
             let mut client = self.client.write().unwrap();
             let request = QueryCollectionRequest {
                 denom_id,
@@ -166,6 +228,9 @@ impl Client {
     #[cfg(target_arch = "wasm32")]
     /// Denom queries the definition of a given denom
     pub async fn denom(&mut self, denom_id: String) -> Result<Option<Denom>, RestError> {
+        if let Some(api_url) = &self.rest_api_url {
+            return rest_denom(api_url, denom_id).await;
+        }
         let request = QueryDenomRequest { denom_id };
         let res = self
             .client
@@ -180,6 +245,9 @@ impl Client {
     /// Denom queries the definition of a given denom
     pub fn denom_blocking(&self, denom_id: String) -> Result<Option<Denom>, RestError> {
         self.rt.block_on(async move {
+            if let Some(api_url) = &self.rest_api_url {
+                return rest_denom(api_url, denom_id).await;
+            }
             let mut client = self.client.write().unwrap();
             let request = QueryDenomRequest { denom_id };
             let res = (*client)
@@ -194,6 +262,9 @@ impl Client {
     #[cfg(target_arch = "wasm32")]
     /// DenomByName queries the definition of a given denom by name
     pub async fn denom_by_name(&mut self, denom_name: String) -> Result<Option<Denom>, RestError> {
+        if let Some(api_url) = &self.rest_api_url {
+            return rest_denom_by_name(api_url, denom_name).await;
+        }
         let request = QueryDenomByNameRequest { denom_name };
         let res = self
             .client
@@ -208,6 +279,9 @@ impl Client {
     /// DenomByName queries the definition of a given denom by name
     pub fn denom_by_name_blocking(&self, denom_name: String) -> Result<Option<Denom>, RestError> {
         self.rt.block_on(async move {
+            if let Some(api_url) = &self.rest_api_url {
+                return rest_denom_by_name(api_url, denom_name).await;
+            }
             let mut client = self.client.write().unwrap();
             let request = QueryDenomByNameRequest { denom_name };
             let res = (*client)
@@ -225,6 +299,9 @@ impl Client {
         &mut self,
         pagination: Option<PageRequest>,
     ) -> Result<Vec<Denom>, RestError> {
+        if let Some(api_url) = &self.rest_api_url {
+            return rest_denoms(api_url, pagination).await;
+        }
         let request = QueryDenomsRequest { pagination };
         let res = self
             .client
@@ -242,6 +319,9 @@ impl Client {
         pagination: Option<PageRequest>,
     ) -> Result<Vec<Denom>, RestError> {
         self.rt.block_on(async move {
+            if let Some(api_url) = &self.rest_api_url {
+                return rest_denoms(api_url, pagination).await;
+            }
             let mut client = self.client.write().unwrap();
             let request = QueryDenomsRequest { pagination };
             let res = (*client)
@@ -260,6 +340,9 @@ impl Client {
         denom_id: String,
         token_id: String,
     ) -> Result<Option<BaseNft>, RestError> {
+        if let Some(api_url) = &self.rest_api_url {
+            return rest_nft(api_url, denom_id, token_id).await;
+        }
         let request = QueryNftRequest { denom_id, token_id };
         let res = self
             .client
@@ -278,6 +361,9 @@ impl Client {
         token_id: String,
     ) -> Result<Option<BaseNft>, RestError> {
         self.rt.block_on(async move {
+            if let Some(api_url) = &self.rest_api_url {
+                return rest_nft(api_url, denom_id, token_id).await;
+            }
             let mut client = self.client.write().unwrap();
             let request = QueryNftRequest { denom_id, token_id };
             let res = (*client)
@@ -289,3 +375,125 @@ impl Client {
         })
     }
 }
+
+/// the supply response of the REST API
+#[serde_as]
+#[derive(Deserialize)]
+struct RestSupplyResponse {
+    #[serde_as(as = "DisplayFromStr")]
+    amount: u64,
+}
+
+/// the owner response of the REST API
+#[derive(Deserialize)]
+struct RestOwnerResponse {
+    owner: Option<Owner>,
+}
+
+/// the collection response of the REST API
+#[derive(Deserialize)]
+struct RestCollectionResponse {
+    collection: Option<Collection>,
+}
+
+/// the denom (or denom by name) response of the REST API
+#[derive(Deserialize)]
+struct RestDenomResponse {
+    denom: Option<Denom>,
+}
+
+/// the denoms response of the REST API
+#[derive(Deserialize)]
+struct RestDenomsResponse {
+    #[serde(default)]
+    denoms: Vec<Denom>,
+}
+
+/// the NFT response of the REST API
+#[derive(Deserialize)]
+struct RestNftResponse {
+    nft: Option<BaseNft>,
+}
+
+async fn rest_supply(api_url: &str, denom_id: String, owner: String) -> Result<u64, RestError> {
+    let response: RestSupplyResponse = rest_get(
+        api_url,
+        &format!("/chainmain/nft/v1/collections/{}/supply", denom_id),
+        &[("owner", owner)],
+    )
+    .await?;
+    Ok(response.amount)
+}
+
+async fn rest_owner(
+    api_url: &str,
+    denom_id: String,
+    owner: String,
+    pagination: Option<PageRequest>,
+) -> Result<Option<Owner>, RestError> {
+    let mut query = vec![("denom_id", denom_id), ("owner", owner)];
+    query.extend(pagination_query(pagination));
+    let response: RestOwnerResponse = rest_get(api_url, "/chainmain/nft/v1/nfts", &query).await?;
+    Ok(response.owner)
+}
+
+async fn rest_collection(
+    api_url: &str,
+    denom_id: String,
+    pagination: Option<PageRequest>,
+) -> Result<Option<Collection>, RestError> {
+    let response: RestCollectionResponse = rest_get(
+        api_url,
+        &format!("/chainmain/nft/v1/collections/{}", denom_id),
+        &pagination_query(pagination),
+    )
+    .await?;
+    Ok(response.collection)
+}
+
+async fn rest_denom(api_url: &str, denom_id: String) -> Result<Option<Denom>, RestError> {
+    let response: RestDenomResponse = rest_get(
+        api_url,
+        &format!("/chainmain/nft/v1/denoms/{}", denom_id),
+        &[],
+    )
+    .await?;
+    Ok(response.denom)
+}
+
+async fn rest_denom_by_name(api_url: &str, denom_name: String) -> Result<Option<Denom>, RestError> {
+    let response: RestDenomResponse = rest_get(
+        api_url,
+        &format!("/chainmain/nft/v1/denoms/name/{}", denom_name),
+        &[],
+    )
+    .await?;
+    Ok(response.denom)
+}
+
+async fn rest_denoms(
+    api_url: &str,
+    pagination: Option<PageRequest>,
+) -> Result<Vec<Denom>, RestError> {
+    let response: RestDenomsResponse = rest_get(
+        api_url,
+        "/chainmain/nft/v1/denoms",
+        &pagination_query(pagination),
+    )
+    .await?;
+    Ok(response.denoms)
+}
+
+async fn rest_nft(
+    api_url: &str,
+    denom_id: String,
+    token_id: String,
+) -> Result<Option<BaseNft>, RestError> {
+    let response: RestNftResponse = rest_get(
+        api_url,
+        &format!("/chainmain/nft/v1/nfts/{}/{}", denom_id, token_id),
+        &[],
+    )
+    .await?;
+    Ok(response.nft)
+}
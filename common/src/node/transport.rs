@@ -0,0 +1,210 @@
+use super::error::RestError;
+use crate::PageRequest;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// The transport of the Cosmos SDK queries
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QueryTransport {
+    /// gRPC (usually on 9090; not available in JS/WASM)
+    Grpc(String),
+    /// gRPC-web (usually on 9091; only available in JS/WASM)
+    GrpcWeb(String),
+    /// LCD REST JSON API (usually on 1317)
+    Rest(String),
+}
+
+impl QueryTransport {
+    /// the gRPC flavour available on the current platform (the default transport):
+    /// gRPC-web in JS/WASM, otherwise gRPC
+    pub fn grpc(url: &str) -> Self {
+        if cfg!(target_arch = "wasm32") {
+            QueryTransport::GrpcWeb(url.to_owned())
+        } else {
+            QueryTransport::Grpc(url.to_owned())
+        }
+    }
+
+    /// select the transport from the configured URLs:
+    /// the gRPC(-web) URL if not empty, otherwise the REST API URL
+    pub fn select(grpc_url: &str, api_url: &str) -> Result<Self, RestError> {
+        if !grpc_url.is_empty() {
+            Ok(QueryTransport::grpc(grpc_url))
+        } else if !api_url.is_empty() {
+            Ok(QueryTransport::Rest(api_url.to_owned()))
+        } else {
+            Err(RestError::UnsupportedTransport(
+                "no gRPC or REST API URL configured".to_owned(),
+            ))
+        }
+    }
+
+    /// the gRPC(-web) URL if the transport is supported on the current platform
+    pub(crate) fn grpc_url(&self) -> Result<&str, RestError> {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            QueryTransport::Grpc(url) => Ok(url),
+            #[cfg(target_arch = "wasm32")]
+            QueryTransport::GrpcWeb(url) => Ok(url),
+            _ => Err(RestError::UnsupportedTransport(format!(
+                "{:?} is not available on this platform",
+                self
+            ))),
+        }
+    }
+}
+
+/// run the query on a new async runtime (for the blocking functions)
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn block_on<T>(
+    query: impl std::future::Future<Output = Result<T, RestError>>,
+) -> Result<T, RestError> {
+    tokio::runtime::Runtime::new()
+        .map_err(|_err| RestError::AsyncRuntimeError)?
+        .block_on(query)
+}
+
+/// connect the gRPC (or gRPC-web in JS/WASM) query client
+macro_rules! grpc_client {
+    ($client:ident, $url:expr) => {{
+        #[cfg(target_arch = "wasm32")]
+        let client = $client::new(tonic_web_wasm_client::Client::new($url.to_owned()));
+        #[cfg(not(target_arch = "wasm32"))]
+        let client = $client::connect($url.to_owned())
+            .await
+            .map_err(RestError::GRPCTransportError)?;
+        client
+    }};
+}
+
+/// the error returned by the LCD REST API (the gRPC status in JSON)
+#[derive(Deserialize)]
+struct RestErrorResponse {
+    code: i32,
+    message: String,
+}
+
+/// map the unsuccessful LCD REST API response to the same error as the gRPC one
+async fn check_rest_response(response: reqwest::Response) -> Result<reqwest::Response, RestError> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let error = response
+        .json::<RestErrorResponse>()
+        .await
+        .map_err(RestError::RequestError)?;
+    Err(RestError::GRPCError(tonic::Status::new(
+        tonic::Code::from_i32(error.code),
+        error.message,
+    )))
+}
+
+/// send the GET request to the LCD REST API and decode the JSON response
+pub(crate) async fn rest_get<T: DeserializeOwned>(
+    api_url: &str,
+    path: &str,
+    query: &[(&str, String)],
+) -> Result<T, RestError> {
+    let response = reqwest::Client::new()
+        .get(format!("{}{}", api_url.trim_end_matches('/'), path))
+        .query(query)
+        .send()
+        .await
+        .map_err(RestError::RequestError)?;
+    check_rest_response(response)
+        .await?
+        .json::<T>()
+        .await
+        .map_err(RestError::RequestError)
+}
+
+/// send the POST request to the LCD REST API and decode the JSON response
+pub(crate) async fn rest_post<B: Serialize, T: DeserializeOwned>(
+    api_url: &str,
+    path: &str,
+    body: &B,
+) -> Result<T, RestError> {
+    let response = reqwest::Client::new()
+        .post(format!("{}{}", api_url.trim_end_matches('/'), path))
+        .json(body)
+        .send()
+        .await
+        .map_err(RestError::RequestError)?;
+    check_rest_response(response)
+        .await?
+        .json::<T>()
+        .await
+        .map_err(RestError::RequestError)
+}
+
+/// the query parameters of the pagination in the LCD REST API
+pub(crate) fn pagination_query(pagination: Option<PageRequest>) -> Vec<(&'static str, String)> {
+    match pagination {
+        None => vec![],
+        Some(pagination) => {
+            let mut query = vec![];
+            if !pagination.key.is_empty() {
+                query.push(("pagination.key", STANDARD.encode(pagination.key)));
+            }
+            if pagination.offset != 0 {
+                query.push(("pagination.offset", pagination.offset.to_string()));
+            }
+            if pagination.limit != 0 {
+                query.push(("pagination.limit", pagination.limit.to_string()));
+            }
+            if pagination.count_total {
+                query.push(("pagination.count_total", "true".to_owned()));
+            }
+            if pagination.reverse {
+                query.push(("pagination.reverse", "true".to_owned()));
+            }
+            query
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transport_is_selected_from_urls() {
+        // gRPC is the default, whatever the URL looks like
+        assert_eq!(
+            QueryTransport::grpc("https://rest.mainnet.crypto.org:1317"),
+            QueryTransport::Grpc("https://rest.mainnet.crypto.org:1317".to_owned())
+        );
+        assert_eq!(
+            QueryTransport::select("http://127.0.0.1:9090", "http://127.0.0.1:1317").unwrap(),
+            QueryTransport::Grpc("http://127.0.0.1:9090".to_owned())
+        );
+        assert_eq!(
+            QueryTransport::select("", "http://127.0.0.1:1317").unwrap(),
+            QueryTransport::Rest("http://127.0.0.1:1317".to_owned())
+        );
+        assert!(QueryTransport::select("", "").is_err());
+        assert!(QueryTransport::GrpcWeb("http://127.0.0.1:9091".to_owned())
+            .grpc_url()
+            .is_err());
+    }
+
+    #[test]
+    fn pagination_is_converted_to_query() {
+        assert!(pagination_query(None).is_empty());
+        let query = pagination_query(Some(PageRequest {
+            key: vec![1, 2],
+            offset: 0,
+            limit: 10,
+            count_total: true,
+            reverse: false,
+        }));
+        assert_eq!(
+            query,
+            vec![
+                ("pagination.key", "AQI=".to_owned()),
+                ("pagination.limit", "10".to_owned()),
+                ("pagination.count_total", "true".to_owned()),
+            ]
+        );
+    }
+}
@@ -1,20 +1,35 @@
 use std::{fmt::Display, num::ParseIntError, str::FromStr};
 
+mod air_gap;
+mod render;
+
+pub use air_gap::*;
+pub use render::*;
+
 use ethers::{
-    abi::{ethereum_types::FromDecStrErr, ParamType},
-    prelude::{Address, NameOrAddress, U256},
+    abi::{encode, ethereum_types::FromDecStrErr, ParamType, Token},
+    prelude::{Address, Middleware, NameOrAddress, I256, U256},
+    types::transaction::eip2718::TypedTransaction,
+    utils::{hex, id},
 };
 use regex::Regex;
 
-use crate::{abi::EthAbiParamType, EthAmount, EthError};
+use crate::provider::get_ethers_provider;
+use crate::{
+    abi::EthAbiParamType, is_eth_name, resolve_name_or_address, DynamicTransactionRequest,
+    EthAmount, EthError, EthNetwork,
+};
 
 /// Parameter value types in EIP681 requests
-/// TODO: negative/signed numbers?
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Value {
     Address(NameOrAddress),
     String(String),
     Number(U256),
+    /// signed numbers (of `int<M>` parameters)
+    SignedNumber(I256),
+    /// hex values (of `bytes` and `bytes<M>` parameters)
+    Bytes(Vec<u8>),
 }
 
 impl Display for Value {
@@ -24,10 +39,66 @@ impl Display for Value {
             Value::Address(NameOrAddress::Name(address)) => write!(f, "{}", address),
             Value::String(string) => write!(f, "{}", string),
             Value::Number(number) => write!(f, "{}", number),
+            Value::SignedNumber(number) => write!(f, "{}", number),
+            Value::Bytes(bytes) => write!(f, "0x{}", hex::encode(bytes)),
         }
     }
 }
 
+/// the error of the value which cannot be encoded as the ABI type
+fn invalid_value(param_type: &ParamType, value: &Value) -> EthError {
+    EthError::PaymentRequestError(format!("invalid {param_type} value: {value}"))
+}
+
+impl Value {
+    /// the ABI token of the value of the parameter type;
+    /// the names must be resolved before
+    fn to_token(&self, param_type: &ParamType) -> Result<Token, EthError> {
+        let token = match (param_type, self) {
+            (ParamType::Address, Value::Address(NameOrAddress::Address(address))) => {
+                Token::Address(*address)
+            }
+            (ParamType::Address, Value::Address(NameOrAddress::Name(name))) => {
+                return Err(EthError::PaymentRequestError(format!(
+                    "the name {name} is not resolved"
+                )))
+            }
+            (ParamType::Uint(size), Value::Number(number)) if number.bits() <= *size => {
+                Token::Uint(*number)
+            }
+            (ParamType::Int(size), Value::SignedNumber(number)) if fits_int(*number, *size) => {
+                Token::Int(number.into_raw())
+            }
+            (ParamType::Int(size), Value::Number(number)) => match I256::try_from(*number) {
+                Ok(number) if fits_int(number, *size) => Token::Int(number.into_raw()),
+                _ => return Err(invalid_value(param_type, self)),
+            },
+            (ParamType::Bytes, Value::Bytes(bytes)) => Token::Bytes(bytes.clone()),
+            (ParamType::FixedBytes(size), Value::Bytes(bytes)) if bytes.len() == *size => {
+                Token::FixedBytes(bytes.clone())
+            }
+            (ParamType::String, Value::String(string)) => Token::String(string.clone()),
+            (ParamType::Bool, Value::String(string)) => match string.as_str() {
+                "true" => Token::Bool(true),
+                "false" => Token::Bool(false),
+                _ => return Err(invalid_value(param_type, self)),
+            },
+            _ => return Err(invalid_value(param_type, self)),
+        };
+        Ok(token)
+    }
+}
+
+/// if the signed number fits in the two's complement of the bit size
+fn fits_int(number: I256, size: usize) -> bool {
+    let magnitude = if number.is_negative() {
+        !number.into_raw()
+    } else {
+        number.into_raw()
+    };
+    magnitude.bits() < size
+}
+
 /// Possible parameters in EIP681 requests
 #[derive(Debug, Clone, PartialEq)]
 pub enum Parameter {
@@ -101,6 +172,175 @@ impl EIP681Request {
     }
 }
 
+impl EIP681Request {
+    /// Resolve the names of the target address and the address parameters
+    /// with the name registry of the request's chain (or of the node's chain if not set)
+    pub async fn resolve_names(&mut self, web3api_url: &str) -> Result<(), EthError> {
+        if !self.has_names() {
+            return Ok(());
+        }
+        let chain_id = match self.chain_id {
+            Some(chain_id) => chain_id,
+            None => get_ethers_provider(web3api_url)
+                .await?
+                .get_chainid()
+                .await
+                .map_err(|e| EthError::NameResolutionError(e.to_string()))?
+                .as_u64(),
+        };
+        self.resolve_names_on_chain(chain_id, web3api_url).await
+    }
+
+    /// if the target address or an address parameter is a name
+    pub fn has_names(&self) -> bool {
+        matches!(self.target_address, NameOrAddress::Name(_))
+            || self.parameters.iter().any(|parameter| {
+                matches!(
+                    parameter,
+                    Parameter::Other(_, Value::Address(NameOrAddress::Name(_)))
+                )
+            })
+    }
+
+    /// Resolve the names with the name registry of the chain
+    async fn resolve_names_on_chain(
+        &mut self,
+        chain_id: u64,
+        web3api_url: &str,
+    ) -> Result<(), EthError> {
+        let address = resolve_name_or_address(&self.target_address, chain_id, web3api_url).await?;
+        self.target_address = NameOrAddress::Address(address);
+        for parameter in self.parameters.iter_mut() {
+            if let Parameter::Other(_, Value::Address(name_or_address)) = parameter {
+                let address =
+                    resolve_name_or_address(name_or_address, chain_id, web3api_url).await?;
+                *name_or_address = NameOrAddress::Address(address);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the request (if it has a chain id) is on the chain
+    pub fn check_chain_id(&self, chain_id: u64) -> Result<(), EthError> {
+        match self.chain_id {
+            Some(request_chain_id) if request_chain_id != chain_id => {
+                Err(EthError::PaymentRequestError(format!(
+                    "the request is on the chain {request_chain_id}, not on the chain {chain_id}"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl EIP681Request {
+    /// the ABI-encoded call of the function with the typed parameters
+    /// (none if it's a payment in the native token);
+    /// the names must be resolved before (`resolve_names`)
+    pub fn encode_function_call(&self) -> Result<Option<Vec<u8>>, EthError> {
+        let function_name = match &self.function_name {
+            Some(function_name) => function_name,
+            None => return Ok(None),
+        };
+        let mut param_types = Vec::new();
+        let mut tokens = Vec::new();
+        for parameter in self.parameters.iter() {
+            if let Parameter::Other(param_type, value) = parameter {
+                tokens.push(value.to_token(param_type)?);
+                param_types.push(param_type.to_string());
+            }
+        }
+        let mut data = id(format!("{function_name}({})", param_types.join(","))).to_vec();
+        data.extend(encode(&tokens));
+        Ok(Some(data))
+    }
+
+    /// Convert the request to the transaction request on the network:
+    /// the payment of `value` to the target address, or the call of the function
+    /// of the target contract with the ABI-encoded parameters (and `value` if it's payable),
+    /// with the suggested gas limit and gas price;
+    /// the names must be resolved before (`resolve_names`)
+    pub fn to_transaction_request(
+        &self,
+        network: EthNetwork,
+    ) -> Result<DynamicTransactionRequest, EthError> {
+        let (chain_id, _) = network.to_chain_params()?;
+        self.check_chain_id(chain_id)?;
+        let to = match &self.target_address {
+            NameOrAddress::Address(address) => *address,
+            NameOrAddress::Name(name) => {
+                return Err(EthError::PaymentRequestError(format!(
+                    "the name {name} is not resolved"
+                )))
+            }
+        };
+        let mut request = DynamicTransactionRequest {
+            to: Some(NameOrAddress::Address(to)),
+            data: self.encode_function_call()?.map(Into::into),
+            chain_id: Some(chain_id.into()),
+            ..Default::default()
+        };
+        for parameter in self.parameters.iter() {
+            match parameter {
+                Parameter::Value(amount) => {
+                    let value: U256 = amount.clone().try_into().map_err(EthError::ParseError)?;
+                    request.value = Some(value);
+                }
+                Parameter::GasLimit(gas_limit) => request.gas = Some((*gas_limit).into()),
+                Parameter::GasPrice(gas_price) => request.gas_price = Some((*gas_price).into()),
+                Parameter::Other(..) => {}
+            }
+        }
+        Ok(request)
+    }
+
+    /// Convert the request to the transaction on the network
+    /// (a legacy one if the network needs it, or else an EIP-1559 one
+    /// with the suggested gas price as the max fee per gas)
+    pub fn to_typed_transaction(&self, network: EthNetwork) -> Result<TypedTransaction, EthError> {
+        let (_, legacy) = network.clone().to_chain_params()?;
+        let mut request = self.to_transaction_request(network)?;
+        if legacy {
+            Ok(TypedTransaction::Legacy(request.to_legacy_tx()))
+        } else {
+            request.max_fee_per_gas = request.gas_price.take();
+            Ok(TypedTransaction::Eip1559(request.to_eip1559_tx()))
+        }
+    }
+}
+
+/// Parse the EIP681 request (e.g. scanned from a QR code), check it's on the network,
+/// resolve its names (if any) with the name registry of the network
+/// and convert it to the transaction on the network
+pub async fn build_eip681_transaction(
+    request: &str,
+    network: EthNetwork,
+    web3api_url: &str,
+) -> Result<TypedTransaction, EthError> {
+    let mut request = EIP681Request::from_str(request)
+        .map_err(|e| EthError::PaymentRequestError(e.to_string()))?;
+    let (chain_id, _) = network.clone().to_chain_params()?;
+    request.check_chain_id(chain_id)?;
+    if request.has_names() {
+        request
+            .resolve_names_on_chain(chain_id, web3api_url)
+            .await?;
+    }
+    request.to_typed_transaction(network)
+}
+
+/// Parse the EIP681 request, resolve its names and convert it to the transaction
+/// (blocking; not compiled to wasm).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn build_eip681_transaction_blocking(
+    request: &str,
+    network: EthNetwork,
+    web3api_url: &str,
+) -> Result<TypedTransaction, EthError> {
+    let rt = tokio::runtime::Runtime::new().map_err(|_err| EthError::AsyncRuntimeError)?;
+    rt.block_on(build_eip681_transaction(request, network, web3api_url))
+}
+
 impl Display for EIP681Request {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "ethereum:")?;
@@ -163,6 +403,20 @@ pub enum EIP681ParseError {
     InvalidKey(EthError),
     #[error("Invalid number: {0}")]
     InvalidNumberValue(FromDecStrErr),
+    #[error("Invalid signed number: {0}")]
+    InvalidSignedNumberValue(String),
+    #[error("Invalid bytes value: {0}")]
+    InvalidBytesValue(String),
+}
+
+/// parse the hex address or the ENS name
+fn parse_name_or_address(value: &str) -> Result<NameOrAddress, EIP681ParseError> {
+    if is_eth_name(value) {
+        Ok(NameOrAddress::Name(value.to_string()))
+    } else {
+        let address = Address::from_str(value).map_err(EIP681ParseError::InvalidAddress)?;
+        Ok(NameOrAddress::Address(address))
+    }
 }
 
 impl FromStr for EIP681Request {
@@ -183,16 +437,14 @@ impl FromStr for EIP681Request {
             value                   = number / ethereum_address / STRING
             number                  = [ "-" / "+" ] *DIGIT [ "." 1*DIGIT ] [ ( "e" / "E" ) [ 1*DIGIT ] ]
             */
-            // FIXME: ENS_NAME and some parameter values are not supported yet
-            static ref EIP681_EXP: Regex = Regex::new(r"^ethereum:(pay-)?(0x[[:xdigit:]]{40})(@\d+)?(/\w+)?(\?\w+=[\w|\.]+(&\w+=[\w|\.]+)*)?$").expect("EIP681_EXP regex should compile");
+            // FIXME: some parameter values are not supported yet
+            static ref EIP681_EXP: Regex = Regex::new(r"^ethereum:(pay-)?(0x[[:xdigit:]]{40}|[[:alnum:]\-]+(?:\.[[:alnum:]\-]+)+)(@\d+)?(/\w+)?(\?\w+=[\w|\.\-+]+(&\w+=[\w|\.\-+]+)*)?$").expect("EIP681_EXP regex should compile");
         }
         let captures = EIP681_EXP
             .captures(s)
             .ok_or(EIP681ParseError::InvalidRequest)?;
         let has_pay_tag = captures.get(1).is_some();
-        let etherum_address =
-            Address::from_str(&captures[2]).map_err(EIP681ParseError::InvalidAddress)?;
-        let target_address = NameOrAddress::Address(etherum_address);
+        let target_address = parse_name_or_address(&captures[2])?;
         let chain_id = match captures.get(3) {
             Some(capture) => Some(
                 capture
@@ -256,16 +508,32 @@ impl FromStr for EIP681Request {
                         )
                         .map_err(EIP681ParseError::InvalidKey)?;
                         let value = match param_type {
-                            ParamType::Uint(_) | ParamType::Int(_) => {
-                                let value = U256::from_dec_str(value)
+                            ParamType::Uint(_) => {
+                                let value = U256::from_dec_str(value.trim_start_matches('+'))
                                     .map_err(EIP681ParseError::InvalidNumberValue)?;
                                 Value::Number(value)
                             }
-                            ParamType::Address => {
-                                let value = Address::from_str(value)
-                                    .map_err(EIP681ParseError::InvalidAddress)?;
-                                Value::Address(NameOrAddress::Address(value))
+                            ParamType::Int(_) => {
+                                let value = I256::from_dec_str(value.trim_start_matches('+'))
+                                    .map_err(|e| {
+                                        EIP681ParseError::InvalidSignedNumberValue(e.to_string())
+                                    })?;
+                                Value::SignedNumber(value)
+                            }
+                            ParamType::Bytes | ParamType::FixedBytes(_) => {
+                                let bytes = value
+                                    .strip_prefix("0x")
+                                    .ok_or_else(|| {
+                                        EIP681ParseError::InvalidBytesValue(value.to_string())
+                                    })
+                                    .and_then(|hex_value| {
+                                        hex::decode(hex_value).map_err(|e| {
+                                            EIP681ParseError::InvalidBytesValue(e.to_string())
+                                        })
+                                    })?;
+                                Value::Bytes(bytes)
                             }
+                            ParamType::Address => Value::Address(parse_name_or_address(value)?),
                             _ => Value::String(value.to_string()),
                         };
                         let param = Parameter::Other(param_type, value);
@@ -294,9 +562,12 @@ mod test {
     };
 
     use crate::{
-        qr_code::{EIP681Request, Parameter, Value},
-        EthAmount,
+        qr_code::{build_eip681_transaction, EIP681Request, Parameter, Value},
+        EthAmount, EthError, EthNetwork,
     };
+    use ethers::prelude::I256;
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::utils::hex;
 
     #[test]
     pub fn test_parse() {
@@ -349,6 +620,142 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_parse_names() {
+        let request = EIP681Request::from_str(
+            "ethereum:pay-usdc.alice.cro@25/transfer?address=bob.eth&uint256=1",
+        )
+        .unwrap();
+        assert!(request.has_pay_tag);
+        assert_eq!(
+            request.target_address,
+            NameOrAddress::Name("usdc.alice.cro".to_string())
+        );
+        assert_eq!(request.chain_id, Some(25));
+        assert_eq!(
+            request.parameters[0],
+            Parameter::Other(
+                ParamType::Address,
+                Value::Address(NameOrAddress::Name("bob.eth".to_string()))
+            )
+        );
+        assert_eq!(
+            request.to_string(),
+            "ethereum:pay-usdc.alice.cro@25/transfer?address=bob.eth&uint256=1"
+        );
+        assert!(EIP681Request::from_str("ethereum:alice").is_err());
+    }
+
+    #[test]
+    pub fn test_parse_signed_numbers_and_bytes() {
+        let request = EIP681Request::from_str(
+            "ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7/adjust?int8=-128&bytes=0x1234&bytes2=0xabcd&int256=+5",
+        )
+        .unwrap();
+        assert_eq!(
+            request.parameters[0],
+            Parameter::Other(ParamType::Int(8), Value::SignedNumber(I256::from(-128)))
+        );
+        assert_eq!(
+            request.parameters[1],
+            Parameter::Other(ParamType::Bytes, Value::Bytes(vec![0x12, 0x34]))
+        );
+        assert_eq!(
+            request.parameters[3],
+            Parameter::Other(ParamType::Int(256), Value::SignedNumber(I256::from(5)))
+        );
+        assert_eq!(
+            request.to_string(),
+            "ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7/adjust?int8=-128&bytes=0x1234&bytes2=0xabcd&int256=5"
+        );
+        let data = request.encode_function_call().unwrap().unwrap();
+        assert_eq!(data.len(), 4 + 32 * 6);
+        assert!(EIP681Request::from_str(
+            "ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7/f?bytes=1234"
+        )
+        .is_err());
+
+        // out of the range of int8
+        let request = EIP681Request::from_str(
+            "ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7/adjust?int8=-129",
+        )
+        .unwrap();
+        assert!(request.encode_function_call().is_err());
+        let request = EIP681Request::from_str(
+            "ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7/adjust?bytes2=0x12",
+        )
+        .unwrap();
+        assert!(request.encode_function_call().is_err());
+    }
+
+    #[test]
+    pub fn test_to_transaction() {
+        let request = EIP681Request::from_str("ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7@25/transfer?address=0x8e23ee67d1332ad560396262c48ffbb01f93d052&uint256=1&gasLimit=100000&gasPrice=5000000000000").unwrap();
+        let tx = request
+            .to_typed_transaction(EthNetwork::Custom {
+                chain_id: 25,
+                legacy: true,
+            })
+            .unwrap();
+        assert!(matches!(tx, TypedTransaction::Legacy(_)));
+        assert_eq!(
+            tx.to_addr(),
+            Some(&Address::from_str("0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7").unwrap())
+        );
+        assert_eq!(
+            hex::encode(tx.data().unwrap()),
+            "a9059cbb0000000000000000000000008e23ee67d1332ad560396262c48ffbb01f93d0520000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(tx.gas(), Some(&U256::from(100000)));
+        assert_eq!(tx.gas_price(), Some(U256::from(5000000000000u64)));
+        assert_eq!(tx.chain_id(), Some(25.into()));
+
+        let approve = EIP681Request::from_str("ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7/approve?address=0x8e23ee67d1332ad560396262c48ffbb01f93d052&uint256=1").unwrap();
+        let tx = approve.to_typed_transaction(EthNetwork::Mainnet).unwrap();
+        assert!(matches!(tx, TypedTransaction::Eip1559(_)));
+        assert!(hex::encode(tx.data().unwrap()).starts_with("095ea7b3"));
+
+        let payment = EIP681Request::from_str(
+            "ethereum:0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359@1?value=2.014e18",
+        )
+        .unwrap();
+        let tx = payment.to_typed_transaction(EthNetwork::Mainnet).unwrap();
+        assert_eq!(tx.value(), Some(&U256::from(2014000000000000000u64)));
+        assert!(tx.data().is_none());
+        // the chain id mismatch
+        assert!(payment.to_typed_transaction(EthNetwork::Cronos).is_err());
+
+        // the names are not resolved
+        let request = EIP681Request::from_str("ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7/transfer?address=bob.eth&uint256=1").unwrap();
+        assert!(request.to_typed_transaction(EthNetwork::Mainnet).is_err());
+    }
+
+    #[tokio::test]
+    pub async fn test_build_transaction() {
+        // no node is queried without names
+        let tx = build_eip681_transaction(
+            "ethereum:0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359@25?value=1e18",
+            EthNetwork::Custom {
+                chain_id: 25,
+                legacy: false,
+            },
+            "http://127.0.0.1:1",
+        )
+        .await
+        .unwrap();
+        assert_eq!(tx.chain_id(), Some(25.into()));
+        assert_eq!(tx.value(), Some(&U256::exp10(18)));
+
+        // the chain is checked before the names are resolved
+        let result = build_eip681_transaction(
+            "ethereum:alice.cro@25?value=1",
+            EthNetwork::Mainnet,
+            "http://127.0.0.1:1",
+        )
+        .await;
+        assert!(matches!(result, Err(EthError::PaymentRequestError(_))));
+    }
+
     #[test]
     pub fn test_display() {
         let info = EIP681Request {
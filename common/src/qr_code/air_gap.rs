@@ -0,0 +1,1260 @@
+use std::{collections::BTreeSet, fmt::Display, str::FromStr, sync::Arc};
+
+use ciborium::value::Value;
+use cosmrs::crypto::{secp256k1::SigningKey, PublicKey};
+use cosmrs::proto::cosmos::crypto::secp256k1::PubKey;
+use cosmrs::proto::cosmos::tx::v1beta1::{AuthInfo, SignDoc, TxRaw};
+use cosmrs::AccountId;
+use ethers::{
+    core::k256::ecdsa::{signature::Verifier, Signature as EcdsaSignature, VerifyingKey},
+    prelude::{Address, LocalWallet, Signature, Signer, H256, U256},
+    types::transaction::eip2718::TypedTransaction,
+    utils::{hash_message, hex, keccak256, rlp},
+};
+use prost::Message;
+use rand_core::{OsRng, RngCore};
+
+use crate::{
+    get_msg_sign_payload, node::ethereum::eip712::Eip712TypedData, CosmosError, CosmosSDKMsg,
+    CosmosSDKTxInfo, DynamicTransactionRequest, EthError, HDWallet, HdWrapError,
+    PublicKeyBytesWrapper, SecretKey,
+};
+
+/// the UR type of the Ethereum sign requests as per
+/// [ERC-4527](https://eips.ethereum.org/EIPS/eip-4527)
+pub const ETH_SIGN_REQUEST_UR_TYPE: &str = "eth-sign-request";
+/// the UR type of the Ethereum signatures as per ERC-4527
+pub const ETH_SIGNATURE_UR_TYPE: &str = "eth-signature";
+/// the UR type of the Cosmos SDK sign requests
+pub const COSMOS_SIGN_REQUEST_UR_TYPE: &str = "cosmos-sign-request";
+/// the UR type of the Cosmos SDK signatures
+pub const COSMOS_SIGNATURE_UR_TYPE: &str = "cosmos-signature";
+/// the default maximum length of the fragments of the multi-part URs,
+/// which keeps each frame readable in an animated QR code
+pub const DEFAULT_UR_FRAGMENT_LENGTH: usize = 200;
+
+/// the CBOR tag of UUIDs (the request IDs)
+const UUID_TAG: u64 = 37;
+/// the CBOR tag of `crypto-keypath` (the derivation paths)
+const CRYPTO_KEYPATH_TAG: u64 = 304;
+
+/// Errors of the air-gapped signing payloads
+#[derive(Debug, thiserror::Error)]
+pub enum AirGapError {
+    #[error("UR error: {0}")]
+    UrError(String),
+    #[error("CBOR error: {0}")]
+    CborError(String),
+    #[error("Unexpected UR type: {0}")]
+    UnexpectedUrType(String),
+    #[error("Invalid payload: {0}")]
+    InvalidPayload(String),
+    #[error("Invalid derivation path: {0}")]
+    InvalidDerivationPath(String),
+    #[error("The request ID of the signature does not match")]
+    RequestIdMismatch,
+    #[error("The derived address {derived} does not match the requested {requested}")]
+    AddressMismatch { requested: String, derived: String },
+    #[error("The signer {signer} does not match the requested {requested}")]
+    SignerMismatch { requested: String, signer: String },
+    #[error("The signature is invalid for the sign data")]
+    InvalidSignature,
+    #[error("HD wallet error: {0}")]
+    WalletError(#[from] HdWrapError),
+    #[error("Ethereum error: {0}")]
+    EthError(#[from] EthError),
+    #[error("Cosmos error: {0}")]
+    CosmosError(#[from] CosmosError),
+    #[error("Signing error: {0}")]
+    SigningError(String),
+}
+
+fn invalid_payload(reason: &str) -> AirGapError {
+    AirGapError::InvalidPayload(reason.to_owned())
+}
+
+fn ur_error(error: ur::Error) -> AirGapError {
+    AirGapError::UrError(error.to_string())
+}
+
+/// a new random request ID (UUID v4)
+fn new_request_id() -> [u8; 16] {
+    let mut id = [0u8; 16];
+    OsRng.fill_bytes(&mut id);
+    id[6] = (id[6] & 0x0f) | 0x40;
+    id[8] = (id[8] & 0x3f) | 0x80;
+    id
+}
+
+fn uint(n: u64) -> Value {
+    Value::Integer(n.into())
+}
+
+fn as_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Integer(n) => u64::try_from(*n).ok(),
+        _ => None,
+    }
+}
+
+fn as_bytes(value: &Value) -> Option<&[u8]> {
+    match value {
+        Value::Bytes(bytes) => Some(bytes),
+        _ => None,
+    }
+}
+
+fn as_text(value: &Value) -> Option<&str> {
+    match value {
+        Value::Text(text) => Some(text),
+        _ => None,
+    }
+}
+
+/// the value without its tag if it has the expected one
+fn untagged(value: &Value, tag: u64) -> &Value {
+    match value {
+        Value::Tag(t, inner) if *t == tag => inner,
+        _ => value,
+    }
+}
+
+/// the entries of the CBOR map with the unsigned integer keys
+struct CborMap<'a>(&'a [(Value, Value)]);
+
+impl<'a> CborMap<'a> {
+    fn from_value(value: &'a Value) -> Result<Self, AirGapError> {
+        match value {
+            Value::Map(entries) => Ok(Self(entries)),
+            _ => Err(invalid_payload("a CBOR map is expected")),
+        }
+    }
+
+    fn get(&self, key: u64) -> Option<&'a Value> {
+        self.0
+            .iter()
+            .find(|(k, _)| as_u64(k) == Some(key))
+            .map(|(_, v)| v)
+    }
+
+    fn required(&self, key: u64, name: &str) -> Result<&'a Value, AirGapError> {
+        self.get(key)
+            .ok_or_else(|| AirGapError::InvalidPayload(format!("missing {name}")))
+    }
+
+    fn request_id(&self, key: u64) -> Result<Option<[u8; 16]>, AirGapError> {
+        self.get(key)
+            .map(|value| {
+                as_bytes(untagged(value, UUID_TAG))
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| invalid_payload("the request ID must be a UUID"))
+            })
+            .transpose()
+    }
+
+    fn text(&self, key: u64) -> Result<Option<String>, AirGapError> {
+        self.get(key)
+            .map(|value| {
+                as_text(value)
+                    .map(str::to_owned)
+                    .ok_or_else(|| AirGapError::InvalidPayload(format!("text is expected: {key}")))
+            })
+            .transpose()
+    }
+}
+
+fn uuid_value(request_id: &[u8; 16]) -> Value {
+    Value::Tag(UUID_TAG, Box::new(Value::Bytes(request_id.to_vec())))
+}
+
+/// A component of the derivation path in the `crypto-keypath` of the UR registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UrPathComponent {
+    pub index: u32,
+    pub hardened: bool,
+}
+
+/// A derivation path (e.g. "m/44'/60'/0'/0/0") as `crypto-keypath` of the UR registry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrKeyPath {
+    pub components: Vec<UrPathComponent>,
+    /// the fingerprint of the master key (if the signer should check it)
+    pub source_fingerprint: Option<u32>,
+}
+
+impl UrKeyPath {
+    fn to_cbor_value(&self) -> Value {
+        let components = self
+            .components
+            .iter()
+            .flat_map(|c| [uint(c.index as u64), Value::Bool(c.hardened)])
+            .collect();
+        let mut entries = vec![(uint(1), Value::Array(components))];
+        if let Some(fingerprint) = self.source_fingerprint {
+            entries.push((uint(2), uint(fingerprint as u64)));
+        }
+        entries.push((uint(3), uint(self.components.len() as u64)));
+        Value::Tag(CRYPTO_KEYPATH_TAG, Box::new(Value::Map(entries)))
+    }
+
+    fn from_cbor_value(value: &Value) -> Result<Self, AirGapError> {
+        let map = CborMap::from_value(untagged(value, CRYPTO_KEYPATH_TAG))?;
+        let components = match map.required(1, "the path components")? {
+            Value::Array(values) if values.len() % 2 == 0 => values
+                .chunks(2)
+                .map(|pair| match (as_u64(&pair[0]), &pair[1]) {
+                    (Some(index), Value::Bool(hardened)) if index < 1 << 31 => {
+                        Ok(UrPathComponent {
+                            index: index as u32,
+                            hardened: *hardened,
+                        })
+                    }
+                    _ => Err(invalid_payload("invalid path component")),
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return Err(invalid_payload("invalid path components")),
+        };
+        let source_fingerprint = map
+            .get(2)
+            .map(|value| {
+                as_u64(value)
+                    .and_then(|n| u32::try_from(n).ok())
+                    .ok_or_else(|| invalid_payload("invalid source fingerprint"))
+            })
+            .transpose()?;
+        Ok(Self {
+            components,
+            source_fingerprint,
+        })
+    }
+}
+
+impl FromStr for UrKeyPath {
+    type Err = AirGapError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let invalid = || AirGapError::InvalidDerivationPath(path.to_owned());
+        let mut parts = path.split('/');
+        if parts.next() != Some("m") {
+            return Err(invalid());
+        }
+        let components = parts
+            .map(|part| {
+                let (index, hardened) = match part.strip_suffix(|c| c == '\'' || c == 'h') {
+                    Some(index) => (index, true),
+                    None => (part, false),
+                };
+                let index: u32 = index.parse().map_err(|_| invalid())?;
+                if index >= 1 << 31 {
+                    return Err(invalid());
+                }
+                Ok(UrPathComponent { index, hardened })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            components,
+            source_fingerprint: None,
+        })
+    }
+}
+
+impl Display for UrKeyPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "m")?;
+        for component in self.components.iter() {
+            write!(f, "/{}", component.index)?;
+            if component.hardened {
+                write!(f, "'")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A payload of the UR registry, transported as (animated) QR codes
+pub trait UrPayload: Sized {
+    /// the UR type, e.g. "eth-sign-request"
+    const UR_TYPE: &'static str;
+
+    /// the CBOR data item of the payload
+    fn to_cbor_value(&self) -> Value;
+
+    /// the payload from its CBOR data item
+    fn from_cbor_value(value: &Value) -> Result<Self, AirGapError>;
+
+    /// the CBOR encoding of the payload
+    fn to_cbor(&self) -> Result<Vec<u8>, AirGapError> {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&self.to_cbor_value(), &mut bytes)
+            .map_err(|e| AirGapError::CborError(e.to_string()))?;
+        Ok(bytes)
+    }
+
+    /// the payload from its CBOR encoding
+    fn from_cbor(bytes: &[u8]) -> Result<Self, AirGapError> {
+        let value: Value =
+            ciborium::de::from_reader(bytes).map_err(|e| AirGapError::CborError(e.to_string()))?;
+        Self::from_cbor_value(&value)
+    }
+
+    /// the single-part UR (e.g. "ur:eth-signature/...") for a static QR code
+    fn to_ur(&self) -> Result<String, AirGapError> {
+        Ok(ur::encode(&self.to_cbor()?, Self::UR_TYPE))
+    }
+
+    /// the encoder of the fountain-coded frames for an animated QR code
+    fn to_ur_frames(&self, max_fragment_length: usize) -> Result<UrFrameEncoder, AirGapError> {
+        UrFrameEncoder::new(Self::UR_TYPE, self.to_cbor()?, max_fragment_length)
+    }
+
+    /// the payload from its single-part UR
+    fn from_ur(value: &str) -> Result<Self, AirGapError> {
+        let mut decoder = UrFrameDecoder::default();
+        decoder.receive(value)?;
+        decoder
+            .payload()?
+            .ok_or_else(|| invalid_payload("a single-part UR is expected"))
+    }
+}
+
+/// The encoder of the frames of a UR: the payload is sent as it is if it fits
+/// in one frame, or else as fountain-coded parts which can be displayed in a loop
+/// (after the first `frame_count` parts, the parts mix the fragments,
+/// so the scanner can recover the payload from any sufficient subset)
+pub struct UrFrameEncoder {
+    ur_type: String,
+    message: Vec<u8>,
+    encoder: Option<ur::Encoder>,
+}
+
+impl UrFrameEncoder {
+    /// Create the encoder of the CBOR message with the UR type
+    pub fn new(
+        ur_type: &str,
+        message: Vec<u8>,
+        max_fragment_length: usize,
+    ) -> Result<Self, AirGapError> {
+        if max_fragment_length == 0 {
+            return Err(AirGapError::UrError(
+                "the fragment length must be positive".to_owned(),
+            ));
+        }
+        let encoder = if message.len() > max_fragment_length {
+            Some(ur::Encoder::new(&message, max_fragment_length, ur_type).map_err(ur_error)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            ur_type: ur_type.to_owned(),
+            message,
+            encoder,
+        })
+    }
+
+    /// the number of the distinct fragments of the payload
+    pub fn frame_count(&self) -> usize {
+        self.encoder
+            .as_ref()
+            .map_or(1, |encoder| encoder.fragment_count())
+    }
+
+    /// if the payload fits in one frame
+    pub fn is_single_part(&self) -> bool {
+        self.encoder.is_none()
+    }
+
+    /// the next frame to display (the lowercase UR string)
+    pub fn next_frame(&mut self) -> Result<String, AirGapError> {
+        match self.encoder.as_mut() {
+            Some(encoder) => encoder.next_part().map_err(ur_error),
+            None => Ok(ur::encode(&self.message, self.ur_type.clone())),
+        }
+    }
+}
+
+/// The payloads of the air-gapped signing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AirGapPayload {
+    EthSignRequest(EthSignRequest),
+    EthSignature(EthSignature),
+    CosmosSignRequest(CosmosSignRequest),
+    CosmosSignature(CosmosSignature),
+}
+
+impl AirGapPayload {
+    /// the payload from the decoded UR type and CBOR message
+    pub fn from_cbor(ur_type: &str, message: &[u8]) -> Result<Self, AirGapError> {
+        match ur_type {
+            ETH_SIGN_REQUEST_UR_TYPE => {
+                Ok(Self::EthSignRequest(EthSignRequest::from_cbor(message)?))
+            }
+            ETH_SIGNATURE_UR_TYPE => Ok(Self::EthSignature(EthSignature::from_cbor(message)?)),
+            COSMOS_SIGN_REQUEST_UR_TYPE => Ok(Self::CosmosSignRequest(
+                CosmosSignRequest::from_cbor(message)?,
+            )),
+            COSMOS_SIGNATURE_UR_TYPE => {
+                Ok(Self::CosmosSignature(CosmosSignature::from_cbor(message)?))
+            }
+            _ => Err(AirGapError::UnexpectedUrType(ur_type.to_owned())),
+        }
+    }
+}
+
+/// The decoder which reassembles the frames of a UR scanned in any order
+/// (single-part URs are complete after one frame)
+#[derive(Default)]
+pub struct UrFrameDecoder {
+    ur_type: Option<String>,
+    decoder: ur::Decoder,
+    message: Option<Vec<u8>>,
+    frame_count: Option<usize>,
+    received_fragments: BTreeSet<usize>,
+}
+
+impl UrFrameDecoder {
+    /// Receive a scanned frame (as QR codes in the alphanumeric mode are uppercase,
+    /// the case is ignored).
+    /// Return if the payload is complete; frames are ignored after that.
+    pub fn receive(&mut self, frame: &str) -> Result<bool, AirGapError> {
+        let frame = frame.trim().to_lowercase();
+        let rest = frame
+            .strip_prefix("ur:")
+            .ok_or_else(|| AirGapError::UrError("the frame must start with \"ur:\"".to_owned()))?;
+        let (ur_type, rest) = rest
+            .split_once('/')
+            .ok_or_else(|| AirGapError::UrError("the frame has no payload".to_owned()))?;
+        match &self.ur_type {
+            Some(expected) if expected != ur_type => {
+                return Err(AirGapError::UnexpectedUrType(ur_type.to_owned()))
+            }
+            Some(_) => {}
+            None => self.ur_type = Some(ur_type.to_owned()),
+        }
+        if self.message.is_some() {
+            return Ok(true);
+        }
+        match rest.split_once('/') {
+            // a multi-part frame: "ur:<type>/<seq>-<count>/<fragment>"
+            Some((sequence, _)) => {
+                let (index, count) = sequence
+                    .split_once('-')
+                    .and_then(|(i, c)| Some((i.parse::<usize>().ok()?, c.parse::<usize>().ok()?)))
+                    .ok_or_else(|| AirGapError::UrError(format!("invalid sequence: {sequence}")))?;
+                self.decoder.receive(&frame).map_err(ur_error)?;
+                self.frame_count = Some(count);
+                if index <= count {
+                    self.received_fragments.insert(index);
+                }
+                if self.decoder.complete() {
+                    self.message = self.decoder.message().map_err(ur_error)?;
+                }
+            }
+            None => {
+                let (_, message) = ur::decode(&frame).map_err(ur_error)?;
+                self.message = Some(message);
+            }
+        }
+        Ok(self.message.is_some())
+    }
+
+    /// if the payload is complete
+    pub fn is_complete(&self) -> bool {
+        self.message.is_some()
+    }
+
+    /// the UR type of the received frames
+    pub fn ur_type(&self) -> Option<&str> {
+        self.ur_type.as_deref()
+    }
+
+    /// the estimated progress between 0 and 1 (from the distinct simple fragments;
+    /// the mixed ones may complete the payload earlier)
+    pub fn progress(&self) -> f64 {
+        match (&self.message, self.frame_count) {
+            (Some(_), _) => 1.0,
+            (None, Some(count)) if count > 0 => {
+                (self.received_fragments.len() as f64 / count as f64).min(0.99)
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// the reassembled CBOR message
+    pub fn message(&self) -> Option<&[u8]> {
+        self.message.as_deref()
+    }
+
+    /// the reassembled payload of the expected type
+    pub fn payload<T: UrPayload>(&self) -> Result<Option<T>, AirGapError> {
+        match (&self.ur_type, &self.message) {
+            (Some(ur_type), Some(message)) => {
+                if ur_type != T::UR_TYPE {
+                    return Err(AirGapError::UnexpectedUrType(ur_type.clone()));
+                }
+                T::from_cbor(message).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// the reassembled payload of any supported type
+    pub fn decode(&self) -> Result<Option<AirGapPayload>, AirGapError> {
+        match (&self.ur_type, &self.message) {
+            (Some(ur_type), Some(message)) => AirGapPayload::from_cbor(ur_type, message).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The type of the data of the Ethereum sign requests (ERC-4527)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EthSignDataType {
+    /// the RLP encoding of an unsigned legacy transaction
+    Transaction = 1,
+    /// the JSON of EIP-712 typed data
+    TypedData = 2,
+    /// a message signed as per EIP-191 (`personal_sign`)
+    PersonalMessage = 3,
+    /// the encoding of an unsigned typed transaction (EIP-2718), e.g. EIP-1559
+    TypedTransaction = 4,
+}
+
+impl TryFrom<u64> for EthSignDataType {
+    type Error = AirGapError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Transaction),
+            2 => Ok(Self::TypedData),
+            3 => Ok(Self::PersonalMessage),
+            4 => Ok(Self::TypedTransaction),
+            _ => Err(AirGapError::InvalidPayload(format!(
+                "unknown data type: {value}"
+            ))),
+        }
+    }
+}
+
+/// The signing key of the derivation path in the HD wallet
+fn derive_key(wallet: &HDWallet, path: &UrKeyPath) -> Result<Arc<SecretKey>, AirGapError> {
+    Ok(wallet.get_key(path.to_string())?)
+}
+
+/// An Ethereum sign request (`eth-sign-request` of ERC-4527),
+/// created on the online device and signed on the air-gapped one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthSignRequest {
+    pub request_id: [u8; 16],
+    pub sign_data: Vec<u8>,
+    pub data_type: EthSignDataType,
+    pub chain_id: Option<u64>,
+    pub derivation_path: UrKeyPath,
+    pub address: Option<Address>,
+    /// the origin of the request, e.g. the name of the wallet or dApp
+    pub origin: Option<String>,
+}
+
+impl EthSignRequest {
+    /// Create a sign request of the data with a random request ID
+    pub fn new(sign_data: Vec<u8>, data_type: EthSignDataType, derivation_path: UrKeyPath) -> Self {
+        Self {
+            request_id: new_request_id(),
+            sign_data,
+            data_type,
+            chain_id: None,
+            derivation_path,
+            address: None,
+            origin: None,
+        }
+    }
+
+    /// Create the sign request of the unsigned transaction on the chain;
+    /// the transaction is typed (EIP-2718) if it has EIP-1559 fees or an access list
+    pub fn from_transaction(
+        mut tx: DynamicTransactionRequest,
+        chain_id: u64,
+        derivation_path: UrKeyPath,
+    ) -> Self {
+        tx.chain_id = Some(chain_id.into());
+        let address = tx.from;
+        let tx = tx.to_type_tx();
+        let data_type = match tx {
+            TypedTransaction::Legacy(_) => EthSignDataType::Transaction,
+            _ => EthSignDataType::TypedTransaction,
+        };
+        let mut request = Self::new(tx.rlp().to_vec(), data_type, derivation_path);
+        request.chain_id = Some(chain_id);
+        request.address = address;
+        request
+    }
+
+    /// Create the sign request of the EIP-712 typed data (JSON)
+    pub fn from_typed_data(
+        json_typed_data: &str,
+        derivation_path: UrKeyPath,
+    ) -> Result<Self, AirGapError> {
+        // check the typed data before sending it to the signer
+        Eip712TypedData::new(json_typed_data)?.encode()?;
+        Ok(Self::new(
+            json_typed_data.as_bytes().to_vec(),
+            EthSignDataType::TypedData,
+            derivation_path,
+        ))
+    }
+
+    /// Create the sign request of the message (signed as per EIP-191)
+    pub fn from_personal_message(message: &[u8], derivation_path: UrKeyPath) -> Self {
+        Self::new(
+            message.to_vec(),
+            EthSignDataType::PersonalMessage,
+            derivation_path,
+        )
+    }
+
+    /// Set the chain ID
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Set the address which the signer must derive
+    pub fn address(mut self, address: Address) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// Set the origin of the request
+    pub fn origin(mut self, origin: impl Into<String>) -> Self {
+        self.origin = Some(origin.into());
+        self
+    }
+
+    /// Sign the request with the key of its derivation path on the air-gapped device.
+    /// The `v` of the signature is as per EIP-155 for legacy transactions,
+    /// the y parity (0 or 1) for typed transactions, and 27 or 28 otherwise.
+    pub fn sign(&self, wallet: &HDWallet) -> Result<EthSignature, AirGapError> {
+        let secret_key = derive_key(wallet, &self.derivation_path)?;
+        let signer = LocalWallet::from(secret_key.get_eth_signing_key()?);
+        if let Some(address) = self.address {
+            let derived = signer.address();
+            if derived != address {
+                return Err(AirGapError::AddressMismatch {
+                    requested: format!("{address:?}"),
+                    derived: format!("{derived:?}"),
+                });
+            }
+        }
+        let hash = match self.data_type {
+            EthSignDataType::Transaction | EthSignDataType::TypedTransaction => {
+                H256::from(keccak256(&self.sign_data))
+            }
+            EthSignDataType::TypedData => {
+                let json = std::str::from_utf8(&self.sign_data)
+                    .map_err(|_| invalid_payload("the typed data must be UTF-8 JSON"))?;
+                let hash = Eip712TypedData::new(json)?.encode()?;
+                H256::from_slice(&hash)
+            }
+            EthSignDataType::PersonalMessage => hash_message(&self.sign_data),
+        };
+        let mut signature = signer
+            .sign_hash(hash)
+            .map_err(|e| AirGapError::SigningError(e.to_string()))?;
+        let recovery_id = signature.v - 27;
+        match (self.data_type, self.chain_id) {
+            (EthSignDataType::Transaction, Some(chain_id)) => {
+                signature.v = recovery_id + 35 + chain_id * 2
+            }
+            (EthSignDataType::TypedTransaction, _) => signature.v = recovery_id,
+            _ => {}
+        }
+        Ok(EthSignature {
+            request_id: Some(self.request_id),
+            signature,
+            origin: None,
+        })
+    }
+
+    /// Assemble the signed transaction (raw bytes to broadcast) on the online device
+    /// from the signature returned by the air-gapped one,
+    /// after checking that it is the signature of the request by its address
+    /// (so the request must have the address, e.g. the `from` of the transaction)
+    pub fn build_signed_transaction(
+        &self,
+        signature: &EthSignature,
+    ) -> Result<Vec<u8>, AirGapError> {
+        if !matches!(
+            self.data_type,
+            EthSignDataType::Transaction | EthSignDataType::TypedTransaction
+        ) {
+            return Err(invalid_payload("the request is not a transaction"));
+        }
+        if signature.request_id != Some(self.request_id) {
+            return Err(AirGapError::RequestIdMismatch);
+        }
+        let address = self
+            .address
+            .ok_or_else(|| invalid_payload("missing the address of the signer"))?;
+        let signer = signature
+            .signature
+            .recover(H256::from(keccak256(&self.sign_data)))
+            .map_err(|_| AirGapError::InvalidSignature)?;
+        if signer != address {
+            return Err(AirGapError::SignerMismatch {
+                requested: format!("{address:?}"),
+                signer: format!("{signer:?}"),
+            });
+        }
+        let tx: TypedTransaction =
+            rlp::decode(&self.sign_data).map_err(|e| AirGapError::InvalidPayload(e.to_string()))?;
+        Ok(tx.rlp_signed(&signature.signature).to_vec())
+    }
+}
+
+impl UrPayload for EthSignRequest {
+    const UR_TYPE: &'static str = ETH_SIGN_REQUEST_UR_TYPE;
+
+    fn to_cbor_value(&self) -> Value {
+        let mut entries = vec![
+            (uint(1), uuid_value(&self.request_id)),
+            (uint(2), Value::Bytes(self.sign_data.clone())),
+            (uint(3), uint(self.data_type as u64)),
+        ];
+        if let Some(chain_id) = self.chain_id {
+            entries.push((uint(4), uint(chain_id)));
+        }
+        entries.push((uint(5), self.derivation_path.to_cbor_value()));
+        if let Some(address) = self.address {
+            entries.push((uint(6), Value::Bytes(address.as_bytes().to_vec())));
+        }
+        if let Some(origin) = &self.origin {
+            entries.push((uint(7), Value::Text(origin.clone())));
+        }
+        Value::Map(entries)
+    }
+
+    fn from_cbor_value(value: &Value) -> Result<Self, AirGapError> {
+        let map = CborMap::from_value(value)?;
+        let request_id = map
+            .request_id(1)?
+            .ok_or_else(|| invalid_payload("missing the request ID"))?;
+        let sign_data = as_bytes(map.required(2, "the sign data")?)
+            .ok_or_else(|| invalid_payload("the sign data must be bytes"))?
+            .to_vec();
+        let data_type = as_u64(map.required(3, "the data type")?)
+            .ok_or_else(|| invalid_payload("invalid data type"))?
+            .try_into()?;
+        let chain_id = map
+            .get(4)
+            .map(|value| as_u64(value).ok_or_else(|| invalid_payload("invalid chain ID")))
+            .transpose()?;
+        let derivation_path = UrKeyPath::from_cbor_value(map.required(5, "the derivation path")?)?;
+        let address = map
+            .get(6)
+            .map(|value| match as_bytes(value) {
+                Some(bytes) if bytes.len() == 20 => Ok(Address::from_slice(bytes)),
+                _ => Err(invalid_payload("the address must be 20 bytes")),
+            })
+            .transpose()?;
+        Ok(Self {
+            request_id,
+            sign_data,
+            data_type,
+            chain_id,
+            derivation_path,
+            address,
+            origin: map.text(7)?,
+        })
+    }
+}
+
+/// An Ethereum signature (`eth-signature` of ERC-4527) returned by the air-gapped device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthSignature {
+    pub request_id: Option<[u8; 16]>,
+    pub signature: Signature,
+    pub origin: Option<String>,
+}
+
+impl EthSignature {
+    /// the signature bytes: `r || s || v` with `v` in big-endian of the minimal length
+    /// (EIP-155 values may not fit in one byte)
+    pub fn signature_bytes(&self) -> Vec<u8> {
+        let mut bytes = [0u8; 64].to_vec();
+        self.signature.r.to_big_endian(&mut bytes[0..32]);
+        self.signature.s.to_big_endian(&mut bytes[32..64]);
+        let v = self.signature.v.to_be_bytes();
+        let start = v.iter().position(|b| *b != 0).unwrap_or(v.len() - 1);
+        bytes.extend_from_slice(&v[start..]);
+        bytes
+    }
+}
+
+impl UrPayload for EthSignature {
+    const UR_TYPE: &'static str = ETH_SIGNATURE_UR_TYPE;
+
+    fn to_cbor_value(&self) -> Value {
+        let mut entries = vec![];
+        if let Some(request_id) = &self.request_id {
+            entries.push((uint(1), uuid_value(request_id)));
+        }
+        entries.push((uint(2), Value::Bytes(self.signature_bytes())));
+        if let Some(origin) = &self.origin {
+            entries.push((uint(3), Value::Text(origin.clone())));
+        }
+        Value::Map(entries)
+    }
+
+    fn from_cbor_value(value: &Value) -> Result<Self, AirGapError> {
+        let map = CborMap::from_value(value)?;
+        let bytes = as_bytes(map.required(2, "the signature")?)
+            .filter(|bytes| bytes.len() > 64 && bytes.len() <= 72)
+            .ok_or_else(|| invalid_payload("invalid signature"))?;
+        let v = bytes[64..]
+            .iter()
+            .fold(0u64, |v, byte| (v << 8) | *byte as u64);
+        Ok(Self {
+            request_id: map.request_id(1)?,
+            signature: Signature {
+                r: U256::from_big_endian(&bytes[0..32]),
+                s: U256::from_big_endian(&bytes[32..64]),
+                v,
+            },
+            origin: map.text(3)?,
+        })
+    }
+}
+
+/// The type of the data of the Cosmos SDK sign requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CosmosSignDataType {
+    /// the JSON of `StdSignDoc` (`SIGN_MODE_LEGACY_AMINO_JSON`)
+    Amino = 1,
+    /// the protobuf bytes of `SignDoc` (`SIGN_MODE_DIRECT`)
+    Direct = 2,
+    /// `SIGN_MODE_TEXTUAL`
+    Textual = 3,
+    /// an arbitrary message (e.g. ADR-036)
+    Message = 4,
+}
+
+impl TryFrom<u64> for CosmosSignDataType {
+    type Error = AirGapError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Amino),
+            2 => Ok(Self::Direct),
+            3 => Ok(Self::Textual),
+            4 => Ok(Self::Message),
+            _ => Err(AirGapError::InvalidPayload(format!(
+                "unknown data type: {value}"
+            ))),
+        }
+    }
+}
+
+/// A Cosmos SDK sign request (`cosmos-sign-request`),
+/// created on the online device and signed on the air-gapped one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CosmosSignRequest {
+    pub request_id: [u8; 16],
+    pub sign_data: Vec<u8>,
+    pub data_type: CosmosSignDataType,
+    pub derivation_paths: Vec<UrKeyPath>,
+    /// the bech32 addresses of the signers,
+    /// which the keys of the derivation paths must match
+    pub addresses: Vec<String>,
+    pub origin: Option<String>,
+}
+
+impl CosmosSignRequest {
+    /// Create a sign request of the data with a random request ID
+    pub fn new(
+        sign_data: Vec<u8>,
+        data_type: CosmosSignDataType,
+        derivation_path: UrKeyPath,
+    ) -> Self {
+        Self {
+            request_id: new_request_id(),
+            sign_data,
+            data_type,
+            derivation_paths: vec![derivation_path],
+            addresses: vec![],
+            origin: None,
+        }
+    }
+
+    /// Create the sign request of the `SignDoc` of the messages
+    /// (the online device only needs the public key of the signer)
+    pub fn from_tx(
+        tx_info: CosmosSDKTxInfo,
+        msgs: Vec<CosmosSDKMsg>,
+        sender_pubkey: PublicKeyBytesWrapper,
+        derivation_path: UrKeyPath,
+    ) -> Result<Self, AirGapError> {
+        let sign_doc = get_msg_sign_payload(tx_info, msgs, sender_pubkey)?;
+        Ok(Self::new(
+            sign_doc,
+            CosmosSignDataType::Direct,
+            derivation_path,
+        ))
+    }
+
+    /// Add the address of the signer
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.addresses.push(address.into());
+        self
+    }
+
+    /// Set the origin of the request
+    pub fn origin(mut self, origin: impl Into<String>) -> Self {
+        self.origin = Some(origin.into());
+        self
+    }
+
+    /// Sign the request with the key of its (first) derivation path
+    /// on the air-gapped device; the key must match the (first) address if any
+    pub fn sign(&self, wallet: &HDWallet) -> Result<CosmosSignature, AirGapError> {
+        let path = self
+            .derivation_paths
+            .first()
+            .ok_or_else(|| invalid_payload("missing the derivation path"))?;
+        let secret_key = derive_key(wallet, path)?;
+        if let Some(address) = self.addresses.first() {
+            let requested = AccountId::from_str(address)
+                .map_err(|e| AirGapError::InvalidPayload(e.to_string()))?;
+            let derived = PublicKey::from(secret_key.get_signing_key().public_key())
+                .account_id(requested.prefix())
+                .map_err(|e| AirGapError::InvalidPayload(e.to_string()))?;
+            if derived != requested {
+                return Err(AirGapError::AddressMismatch {
+                    requested: requested.to_string(),
+                    derived: derived.to_string(),
+                });
+            }
+        }
+        let signing_key = SigningKey::new(Box::new(secret_key.get_signing_key()));
+        let signature = signing_key
+            .sign(&self.sign_data)
+            .map_err(|e| AirGapError::SigningError(e.to_string()))?;
+        Ok(CosmosSignature {
+            request_id: self.request_id,
+            signature: signature.to_vec(),
+            public_key: secret_key.get_public_key_bytes(),
+        })
+    }
+
+    /// Assemble the signed transaction (raw bytes to broadcast) of the `SignDoc`
+    /// on the online device from the signature returned by the air-gapped one,
+    /// after checking that it is the signature of the sign data by the signer of `AuthInfo`
+    pub fn build_signed_tx(&self, signature: &CosmosSignature) -> Result<Vec<u8>, AirGapError> {
+        if self.data_type != CosmosSignDataType::Direct {
+            return Err(invalid_payload("the request is not a SignDoc"));
+        }
+        if signature.request_id != self.request_id {
+            return Err(AirGapError::RequestIdMismatch);
+        }
+        let sign_doc = SignDoc::decode(self.sign_data.as_slice())
+            .map_err(|e| AirGapError::InvalidPayload(e.to_string()))?;
+        let auth_info = AuthInfo::decode(sign_doc.auth_info_bytes.as_slice())
+            .map_err(|e| AirGapError::InvalidPayload(e.to_string()))?;
+        let signer_key = auth_info
+            .signer_infos
+            .first()
+            .and_then(|signer_info| signer_info.public_key.as_ref())
+            .ok_or_else(|| invalid_payload("missing the public key of the signer"))
+            .and_then(|public_key| {
+                PubKey::decode(public_key.value.as_slice())
+                    .map_err(|e| AirGapError::InvalidPayload(e.to_string()))
+            })?
+            .key;
+        if signer_key != signature.public_key {
+            return Err(AirGapError::SignerMismatch {
+                requested: hex::encode(&signer_key),
+                signer: hex::encode(&signature.public_key),
+            });
+        }
+        let verifying_key = VerifyingKey::from_sec1_bytes(&signature.public_key)
+            .map_err(|_| AirGapError::InvalidSignature)?;
+        let ecdsa_signature = EcdsaSignature::from_slice(&signature.signature)
+            .map_err(|_| AirGapError::InvalidSignature)?;
+        verifying_key
+            .verify(&self.sign_data, &ecdsa_signature)
+            .map_err(|_| AirGapError::InvalidSignature)?;
+        Ok(TxRaw {
+            body_bytes: sign_doc.body_bytes,
+            auth_info_bytes: sign_doc.auth_info_bytes,
+            signatures: vec![signature.signature.clone()],
+        }
+        .encode_to_vec())
+    }
+}
+
+impl UrPayload for CosmosSignRequest {
+    const UR_TYPE: &'static str = COSMOS_SIGN_REQUEST_UR_TYPE;
+
+    fn to_cbor_value(&self) -> Value {
+        let mut entries = vec![
+            (uint(1), uuid_value(&self.request_id)),
+            (uint(2), Value::Bytes(self.sign_data.clone())),
+            (uint(3), uint(self.data_type as u64)),
+            (
+                uint(4),
+                Value::Array(
+                    self.derivation_paths
+                        .iter()
+                        .map(UrKeyPath::to_cbor_value)
+                        .collect(),
+                ),
+            ),
+        ];
+        if !self.addresses.is_empty() {
+            entries.push((
+                uint(5),
+                Value::Array(self.addresses.iter().cloned().map(Value::Text).collect()),
+            ));
+        }
+        if let Some(origin) = &self.origin {
+            entries.push((uint(6), Value::Text(origin.clone())));
+        }
+        Value::Map(entries)
+    }
+
+    fn from_cbor_value(value: &Value) -> Result<Self, AirGapError> {
+        let map = CborMap::from_value(value)?;
+        let request_id = map
+            .request_id(1)?
+            .ok_or_else(|| invalid_payload("missing the request ID"))?;
+        let sign_data = as_bytes(map.required(2, "the sign data")?)
+            .ok_or_else(|| invalid_payload("the sign data must be bytes"))?
+            .to_vec();
+        let data_type = as_u64(map.required(3, "the data type")?)
+            .ok_or_else(|| invalid_payload("invalid data type"))?
+            .try_into()?;
+        let derivation_paths = match map.required(4, "the derivation paths")? {
+            Value::Array(values) => values
+                .iter()
+                .map(UrKeyPath::from_cbor_value)
+                .collect::<Result<_, _>>()?,
+            _ => return Err(invalid_payload("invalid derivation paths")),
+        };
+        let addresses = match map.get(5) {
+            Some(Value::Array(values)) => values
+                .iter()
+                .map(|value| {
+                    as_text(value)
+                        .map(str::to_owned)
+                        .ok_or_else(|| invalid_payload("invalid address"))
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(invalid_payload("invalid addresses")),
+            None => vec![],
+        };
+        Ok(Self {
+            request_id,
+            sign_data,
+            data_type,
+            derivation_paths,
+            addresses,
+            origin: map.text(6)?,
+        })
+    }
+}
+
+/// A Cosmos SDK signature (`cosmos-signature`) returned by the air-gapped device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CosmosSignature {
+    pub request_id: [u8; 16],
+    /// the 64-byte `r || s` signature
+    pub signature: Vec<u8>,
+    /// the compressed public key of the signer
+    pub public_key: Vec<u8>,
+}
+
+impl UrPayload for CosmosSignature {
+    const UR_TYPE: &'static str = COSMOS_SIGNATURE_UR_TYPE;
+
+    fn to_cbor_value(&self) -> Value {
+        Value::Map(vec![
+            (uint(1), uuid_value(&self.request_id)),
+            (uint(2), Value::Bytes(self.signature.clone())),
+            (uint(3), Value::Bytes(self.public_key.clone())),
+        ])
+    }
+
+    fn from_cbor_value(value: &Value) -> Result<Self, AirGapError> {
+        let map = CborMap::from_value(value)?;
+        let request_id = map
+            .request_id(1)?
+            .ok_or_else(|| invalid_payload("missing the request ID"))?;
+        let signature = as_bytes(map.required(2, "the signature")?)
+            .filter(|bytes| bytes.len() == 64)
+            .ok_or_else(|| invalid_payload("the signature must be 64 bytes"))?
+            .to_vec();
+        let public_key = as_bytes(map.required(3, "the public key")?)
+            .ok_or_else(|| invalid_payload("the public key must be bytes"))?
+            .to_vec();
+        Ok(Self {
+            request_id,
+            signature,
+            public_key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{eth_sign_transaction_with_chainid, Network, SingleCoin, WalletCoin};
+
+    const MNEMONIC: &str = "apple elegant knife hawk there screen vehicle lounge tube sun engage bus custom market pioneer casual wink present cat metal ride shallow fork brief";
+
+    const TX: &str = r#"{
+        "to": "0x2c600e0a72b3ae39e9b27d2e310b180abe779368",
+        "gas": "0x5208",
+        "gasPrice": "0x12a05f200",
+        "nonce": "0x1",
+        "value": "0xde0b6b3a7640000",
+        "data": "0x"
+    }"#;
+
+    #[test]
+    pub fn test_eth_sign_request() {
+        let wallet = HDWallet::recover_wallet(MNEMONIC.to_string(), None).unwrap();
+        let path = UrKeyPath::from_str("m/44'/60'/0'/0/0").unwrap();
+        assert_eq!(path.to_string(), "m/44'/60'/0'/0/0");
+        assert!(UrKeyPath::from_str("44'/60'").is_err());
+
+        let secret_key = wallet.get_key("m/44'/60'/0'/0/0".to_string()).unwrap();
+        let address = LocalWallet::from(secret_key.get_eth_signing_key().unwrap()).address();
+        let tx: DynamicTransactionRequest = serde_json::from_str(TX).unwrap();
+        let request = EthSignRequest::from_transaction(tx, 25, path)
+            .origin("test")
+            .address(address);
+        assert_eq!(request.data_type, EthSignDataType::Transaction);
+
+        // the request is sent as frames in a shuffled order
+        let mut encoder = request.to_ur_frames(20).unwrap();
+        assert!(!encoder.is_single_part());
+        let mut frames: Vec<String> = (0..encoder.frame_count())
+            .map(|_| encoder.next_frame().unwrap())
+            .collect();
+        frames.reverse();
+        let mut decoder = UrFrameDecoder::default();
+        for frame in frames.iter() {
+            if decoder.receive(&frame.to_uppercase()).unwrap() {
+                break;
+            }
+        }
+        assert!(decoder.is_complete());
+        let scanned: EthSignRequest = decoder.payload().unwrap().unwrap();
+        assert_eq!(scanned, request);
+
+        // the air-gapped device signs it, and the online one assembles the transaction
+        let signature = scanned.sign(&wallet).unwrap();
+        let signature = EthSignature::from_ur(&signature.to_ur().unwrap()).unwrap();
+        let signed_tx = request.build_signed_transaction(&signature).unwrap();
+        assert_eq!(
+            signed_tx,
+            eth_sign_transaction_with_chainid(TX, secret_key, 25).unwrap()
+        );
+
+        let other =
+            EthSignRequest::from_personal_message(b"hello", request.derivation_path.clone());
+        assert!(matches!(
+            request.build_signed_transaction(&other.sign(&wallet).unwrap()),
+            Err(AirGapError::RequestIdMismatch)
+        ));
+        let mut unidentified = signature.clone();
+        unidentified.request_id = None;
+        assert!(matches!(
+            request.build_signed_transaction(&unidentified),
+            Err(AirGapError::RequestIdMismatch)
+        ));
+
+        // signed by another account of the wallet
+        let mut other = request.clone();
+        other.derivation_path = UrKeyPath::from_str("m/44'/60'/0'/0/1").unwrap();
+        assert!(matches!(
+            other.sign(&wallet),
+            Err(AirGapError::AddressMismatch { .. })
+        ));
+        other.address = None;
+        assert!(matches!(
+            request.build_signed_transaction(&other.sign(&wallet).unwrap()),
+            Err(AirGapError::SignerMismatch { .. })
+        ));
+    }
+
+    #[test]
+    pub fn test_cosmos_sign_request() {
+        let wallet = HDWallet::recover_wallet(MNEMONIC.to_string(), None).unwrap();
+        let secret_key = wallet.get_key("m/44'/118'/0'/0/0".to_string()).unwrap();
+        let tx_info = CosmosSDKTxInfo {
+            account_number: 1,
+            sequence_number: 0,
+            gas_limit: 100_000,
+            timeout_height: 9001,
+            fee_amount: SingleCoin::ATOM { amount: 1 },
+            memo_note: None,
+            network: Network::CosmosHub,
+        };
+        let msg = CosmosSDKMsg::BankSend {
+            recipient_address: "cosmos1l5s7tnj28a7zxeeckhgwlhjys8dlrrefgqr4pj".to_owned(),
+            amount: SingleCoin::ATOM { amount: 100 },
+        };
+        let request = CosmosSignRequest::from_tx(
+            tx_info,
+            vec![msg],
+            PublicKeyBytesWrapper(secret_key.get_public_key_bytes()),
+            UrKeyPath::from_str("m/44'/118'/0'/0/0").unwrap(),
+        )
+        .unwrap()
+        .address(
+            secret_key
+                .to_address(WalletCoin::CosmosSDK {
+                    network: Network::CosmosHub,
+                })
+                .unwrap(),
+        );
+        let scanned = CosmosSignRequest::from_ur(&request.to_ur().unwrap()).unwrap();
+        assert_eq!(scanned, request);
+
+        let signature = scanned.sign(&wallet).unwrap();
+        let mut decoder = UrFrameDecoder::default();
+        assert!(decoder.receive(&signature.to_ur().unwrap()).unwrap());
+        assert_eq!(
+            decoder.decode().unwrap(),
+            Some(AirGapPayload::CosmosSignature(signature.clone()))
+        );
+        assert!(matches!(
+            decoder.payload::<EthSignature>(),
+            Err(AirGapError::UnexpectedUrType(_))
+        ));
+
+        let signed_tx = request.build_signed_tx(&signature).unwrap();
+        let tx = TxRaw::decode(signed_tx.as_slice()).unwrap();
+        assert_eq!(tx.signatures, vec![signature.signature.clone()]);
+
+        // the key of the derivation path must match the requested address
+        let mut other = request.clone();
+        other.derivation_paths = vec![UrKeyPath::from_str("m/44'/118'/0'/0/1").unwrap()];
+        assert!(matches!(
+            other.sign(&wallet),
+            Err(AirGapError::AddressMismatch { .. })
+        ));
+
+        // the signature must be of the sign data by the signer of the `SignDoc`
+        other.addresses.clear();
+        assert!(matches!(
+            request.build_signed_tx(&other.sign(&wallet).unwrap()),
+            Err(AirGapError::SignerMismatch { .. })
+        ));
+        let mut tampered = signature;
+        tampered.signature[63] ^= 1;
+        assert!(matches!(
+            request.build_signed_tx(&tampered),
+            Err(AirGapError::InvalidSignature)
+        ));
+    }
+}
@@ -0,0 +1,410 @@
+use std::{fmt::Write, str::FromStr};
+
+use qrcode::{Color, EcLevel, QrCode};
+
+use super::EIP681Request;
+
+/// Error correction levels of QR codes (the share of the codewords which can be restored)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QrErrorCorrection {
+    /// 7%
+    Low,
+    /// 15%
+    Medium,
+    /// 25%
+    Quartile,
+    /// 30%
+    High,
+}
+
+impl FromStr for QrErrorCorrection {
+    type Err = QrCodeError;
+
+    /// parse the level from its letter ("L", "M", "Q" or "H")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "L" | "l" => Ok(QrErrorCorrection::Low),
+            "M" | "m" => Ok(QrErrorCorrection::Medium),
+            "Q" | "q" => Ok(QrErrorCorrection::Quartile),
+            "H" | "h" => Ok(QrErrorCorrection::High),
+            _ => Err(QrCodeError::InvalidOption(format!(
+                "unknown error correction level: {s}"
+            ))),
+        }
+    }
+}
+
+impl From<QrErrorCorrection> for EcLevel {
+    fn from(level: QrErrorCorrection) -> Self {
+        match level {
+            QrErrorCorrection::Low => EcLevel::L,
+            QrErrorCorrection::Medium => EcLevel::M,
+            QrErrorCorrection::Quartile => EcLevel::Q,
+            QrErrorCorrection::High => EcLevel::H,
+        }
+    }
+}
+
+/// the largest module size in pixels (PNG) or user units (SVG)
+pub const QR_MAX_MODULE_SIZE: usize = 64;
+/// the widest quiet zone in modules
+pub const QR_MAX_QUIET_ZONE: usize = 32;
+/// the largest width (and height) of the images in pixels or user units
+pub const QR_MAX_IMAGE_DIMENSION: usize = 8192;
+
+/// Options of the QR code rendering
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrRenderOptions {
+    /// the error correction level
+    pub error_correction: QrErrorCorrection,
+    /// the width of the light border in modules (the specification asks for 4),
+    /// up to `QR_MAX_QUIET_ZONE`
+    pub quiet_zone: usize,
+    /// the size of a module in pixels (PNG) or user units (SVG), from 1 to `QR_MAX_MODULE_SIZE`
+    pub module_size: usize,
+}
+
+impl Default for QrRenderOptions {
+    fn default() -> Self {
+        Self {
+            error_correction: QrErrorCorrection::Medium,
+            quiet_zone: 4,
+            module_size: 8,
+        }
+    }
+}
+
+/// QR code rendering and decoding errors
+#[derive(Debug, thiserror::Error)]
+pub enum QrCodeError {
+    #[error("Cannot encode the QR code: {0}")]
+    EncodingError(String),
+    #[error("Invalid rendering option: {0}")]
+    InvalidOption(String),
+    #[error("Cannot render the PNG image: {0}")]
+    PngError(String),
+    #[error("Invalid image: {0}")]
+    InvalidImage(String),
+    #[error("No QR code is found in the image")]
+    NotFound,
+    #[error("Cannot decode the QR code: {0}")]
+    DecodingError(String),
+}
+
+/// The modules of the QR code, including its quiet zone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrMatrix {
+    size: usize,
+    dark_modules: Vec<bool>,
+}
+
+impl QrMatrix {
+    /// Encode the payload (e.g. a payment request URI) with the error correction level
+    /// and surround it with the quiet zone (in modules)
+    pub fn encode(
+        payload: &str,
+        error_correction: QrErrorCorrection,
+        quiet_zone: usize,
+    ) -> Result<Self, QrCodeError> {
+        if quiet_zone > QR_MAX_QUIET_ZONE {
+            return Err(QrCodeError::InvalidOption(format!(
+                "the quiet zone must be at most {QR_MAX_QUIET_ZONE} modules"
+            )));
+        }
+        let code = QrCode::with_error_correction_level(payload, error_correction.into())
+            .map_err(|e| QrCodeError::EncodingError(e.to_string()))?;
+        let width = code.width();
+        let size = width + 2 * quiet_zone;
+        let colors = code.to_colors();
+        let mut dark_modules = vec![false; size * size];
+        for y in 0..width {
+            for x in 0..width {
+                dark_modules[(y + quiet_zone) * size + x + quiet_zone] =
+                    colors[y * width + x] == Color::Dark;
+            }
+        }
+        Ok(Self { size, dark_modules })
+    }
+
+    /// the number of modules per side (including the quiet zone)
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// if the module at the column `x` and the row `y` is dark
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.size && y < self.size && self.dark_modules[y * self.size + x]
+    }
+
+    /// the width (and height) of the image with the module size,
+    /// which must be from 1 to `QR_MAX_MODULE_SIZE`
+    fn image_dimension(&self, module_size: usize) -> Result<usize, QrCodeError> {
+        if module_size == 0 || module_size > QR_MAX_MODULE_SIZE {
+            return Err(QrCodeError::InvalidOption(format!(
+                "the module size must be from 1 to {QR_MAX_MODULE_SIZE}"
+            )));
+        }
+        self.size
+            .checked_mul(module_size)
+            .filter(|dimension| *dimension <= QR_MAX_IMAGE_DIMENSION)
+            .ok_or_else(|| {
+                QrCodeError::InvalidOption(format!(
+                    "the image must be at most {QR_MAX_IMAGE_DIMENSION} pixels wide"
+                ))
+            })
+    }
+
+    /// the SVG image with the module size in user units
+    pub fn to_svg(&self, module_size: usize) -> Result<String, QrCodeError> {
+        let dimension = self.image_dimension(module_size)?;
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.is_dark(x, y) {
+                    // writing to a string cannot fail
+                    let _ = write!(
+                        path,
+                        "M{},{}h{}v{}h-{}z",
+                        x * module_size,
+                        y * module_size,
+                        module_size,
+                        module_size,
+                        module_size
+                    );
+                }
+            }
+        }
+        Ok(format!(
+            concat!(
+                r#"<?xml version="1.0" standalone="yes"?>"#,
+                r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{dimension}" height="{dimension}" viewBox="0 0 {dimension} {dimension}" shape-rendering="crispEdges">"#,
+                r##"<rect x="0" y="0" width="{dimension}" height="{dimension}" fill="#ffffff"/>"##,
+                r##"<path fill="#000000" d="{path}"/>"##,
+                "</svg>"
+            ),
+            dimension = dimension,
+            path = path
+        ))
+    }
+
+    /// the 8-bit grayscale pixels (row by row) with the module size in pixels;
+    /// it returns the width (and height) of the image and its pixels
+    pub fn to_grayscale(&self, module_size: usize) -> Result<(usize, Vec<u8>), QrCodeError> {
+        let dimension = self.image_dimension(module_size)?;
+        let mut pixels = vec![0xffu8; dimension * dimension];
+        for y in 0..dimension {
+            for x in 0..dimension {
+                if self.is_dark(x / module_size, y / module_size) {
+                    pixels[y * dimension + x] = 0;
+                }
+            }
+        }
+        Ok((dimension, pixels))
+    }
+
+    /// the PNG image (8-bit grayscale) with the module size in pixels
+    pub fn to_png(&self, module_size: usize) -> Result<Vec<u8>, QrCodeError> {
+        let (dimension, pixels) = self.to_grayscale(module_size)?;
+        let dimension = u32::try_from(dimension)
+            .map_err(|_| QrCodeError::InvalidOption("the image is too large".to_owned()))?;
+        let mut png_bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png_bytes, dimension, dimension);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| QrCodeError::PngError(e.to_string()))?;
+        writer
+            .write_image_data(&pixels)
+            .map_err(|e| QrCodeError::PngError(e.to_string()))?;
+        writer
+            .finish()
+            .map_err(|e| QrCodeError::PngError(e.to_string()))?;
+        Ok(png_bytes)
+    }
+
+    /// the text with two rows of modules per line (with the Unicode half blocks);
+    /// the light modules are drawn, as terminals usually have a dark background
+    pub fn to_terminal_string(&self) -> String {
+        let mut text = String::new();
+        for y in (0..self.size).step_by(2) {
+            for x in 0..self.size {
+                let top = !self.is_dark(x, y);
+                // the row below the last one is dark, i.e. not drawn
+                let bottom = y + 1 < self.size && !self.is_dark(x, y + 1);
+                text.push(match (top, bottom) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// the matrix of the payload with the options, checking that its image isn't too large
+fn encode_with_options(payload: &str, options: &QrRenderOptions) -> Result<QrMatrix, QrCodeError> {
+    let matrix = QrMatrix::encode(payload, options.error_correction, options.quiet_zone)?;
+    matrix.image_dimension(options.module_size)?;
+    Ok(matrix)
+}
+
+/// Render the QR code of the payload (e.g. an EIP-681 or a Cosmos payment request URI)
+/// to the SVG image
+pub fn render_qr_svg(payload: &str, options: &QrRenderOptions) -> Result<String, QrCodeError> {
+    encode_with_options(payload, options)?.to_svg(options.module_size)
+}
+
+/// Render the QR code of the payload to the PNG image (8-bit grayscale)
+pub fn render_qr_png(payload: &str, options: &QrRenderOptions) -> Result<Vec<u8>, QrCodeError> {
+    encode_with_options(payload, options)?.to_png(options.module_size)
+}
+
+/// Render the QR code of the payload to the 8-bit grayscale pixels (row by row);
+/// it returns the width (and height) of the image and its pixels,
+/// which can be decoded with `decode_qr_grayscale`
+pub fn render_qr_grayscale(
+    payload: &str,
+    options: &QrRenderOptions,
+) -> Result<(usize, Vec<u8>), QrCodeError> {
+    encode_with_options(payload, options)?.to_grayscale(options.module_size)
+}
+
+/// Render the QR code of the payload to the text to be printed on a terminal
+/// (the module size is not used)
+pub fn render_qr_terminal(payload: &str, options: &QrRenderOptions) -> Result<String, QrCodeError> {
+    Ok(encode_with_options(payload, options)?.to_terminal_string())
+}
+
+/// Decode the QR code in the 8-bit grayscale image (row by row, `width * height` pixels)
+/// back into its payload (e.g. the payment request URI)
+pub fn decode_qr_grayscale(
+    width: usize,
+    height: usize,
+    pixels: &[u8],
+) -> Result<String, QrCodeError> {
+    if width == 0 || height == 0 || width.checked_mul(height) != Some(pixels.len()) {
+        return Err(QrCodeError::InvalidImage(format!(
+            "{} pixels for {width}x{height}",
+            pixels.len()
+        )));
+    }
+    let mut image =
+        rqrr::PreparedImage::prepare_from_greyscale(width, height, |x, y| pixels[y * width + x]);
+    let grids = image.detect_grids();
+    let grid = grids.first().ok_or(QrCodeError::NotFound)?;
+    let (_, payload) = grid
+        .decode()
+        .map_err(|e| QrCodeError::DecodingError(e.to_string()))?;
+    Ok(payload)
+}
+
+impl EIP681Request {
+    /// Render the QR code of the request to the SVG image
+    pub fn to_qr_svg(&self, options: &QrRenderOptions) -> Result<String, QrCodeError> {
+        render_qr_svg(&self.to_string(), options)
+    }
+
+    /// Render the QR code of the request to the PNG image
+    pub fn to_qr_png(&self, options: &QrRenderOptions) -> Result<Vec<u8>, QrCodeError> {
+        render_qr_png(&self.to_string(), options)
+    }
+
+    /// Render the QR code of the request to the text to be printed on a terminal
+    pub fn to_qr_terminal(&self, options: &QrRenderOptions) -> Result<String, QrCodeError> {
+        render_qr_terminal(&self.to_string(), options)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const REQUEST: &str = "ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7/transfer?address=0x8e23ee67d1332ad560396262c48ffbb01f93d052&uint256=1";
+
+    #[test]
+    pub fn test_render_and_decode() {
+        let options = QrRenderOptions::default();
+        let matrix =
+            QrMatrix::encode(REQUEST, options.error_correction, options.quiet_zone).unwrap();
+        // the quiet zone is light
+        assert!((0..matrix.size()).all(|i| !matrix.is_dark(i, 0) && !matrix.is_dark(0, i)));
+        // the top-left finder pattern
+        assert!(matrix.is_dark(4, 4));
+
+        let (dimension, pixels) = matrix.to_grayscale(4).unwrap();
+        assert_eq!(dimension, matrix.size() * 4);
+        assert_eq!(
+            decode_qr_grayscale(dimension, dimension, &pixels).unwrap(),
+            REQUEST
+        );
+
+        let request = EIP681Request::from_str(REQUEST).unwrap();
+        let png = request.to_qr_png(&options).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let svg = request.to_qr_svg(&options).unwrap();
+        assert!(svg.contains("<svg") && svg.contains("<path"));
+        let text = request.to_qr_terminal(&options).unwrap();
+        assert_eq!(text.lines().count(), (matrix.size() + 1) / 2);
+        let (dimension, pixels) = render_qr_grayscale(REQUEST, &options).unwrap();
+        assert_eq!(
+            decode_qr_grayscale(dimension, dimension, &pixels).unwrap(),
+            REQUEST
+        );
+
+        assert!(decode_qr_grayscale(dimension, dimension, &pixels[1..]).is_err());
+        assert!(matches!(
+            decode_qr_grayscale(8, 8, &[0xff; 64]),
+            Err(QrCodeError::NotFound)
+        ));
+    }
+
+    #[test]
+    pub fn test_options() {
+        assert_eq!(
+            QrErrorCorrection::from_str("H").unwrap(),
+            QrErrorCorrection::High
+        );
+        assert!(QrErrorCorrection::from_str("X").is_err());
+        let low = QrMatrix::encode(REQUEST, QrErrorCorrection::Low, 0).unwrap();
+        let high = QrMatrix::encode(REQUEST, QrErrorCorrection::High, 0).unwrap();
+        assert!(low.size() < high.size());
+        let options = QrRenderOptions {
+            module_size: 0,
+            ..Default::default()
+        };
+        assert!(render_qr_svg(REQUEST, &options).is_err());
+        for options in [
+            QrRenderOptions {
+                module_size: QR_MAX_MODULE_SIZE + 1,
+                ..Default::default()
+            },
+            QrRenderOptions {
+                module_size: usize::MAX,
+                ..Default::default()
+            },
+            QrRenderOptions {
+                quiet_zone: usize::MAX / 2,
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                render_qr_png(REQUEST, &options),
+                Err(QrCodeError::InvalidOption(_))
+            ));
+        }
+        // the largest modules of a large code make a too large image
+        let payload = "a".repeat(2000);
+        let options = QrRenderOptions {
+            module_size: QR_MAX_MODULE_SIZE,
+            ..Default::default()
+        };
+        assert!(matches!(
+            render_qr_svg(&payload, &options),
+            Err(QrCodeError::InvalidOption(_))
+        ));
+    }
+}
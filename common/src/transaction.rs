@@ -1,5 +1,7 @@
 /// wrapper and helpers for CosmRS
 mod cosmos_sdk;
+/// versioned envelopes of unsigned transactions for offline signing
+mod envelope;
 /// wrapper and helpers for ethers
 mod ethereum;
 /// wrapper and helpers for LunaClassic chain
@@ -12,6 +14,7 @@ mod uniffi_binding;
 mod wasm_binding;
 
 pub use cosmos_sdk::*;
+pub use envelope::*;
 pub use ethereum::*;
 pub use nft::*;
 #[cfg(feature = "uniffi-binding")]
@@ -22,9 +22,12 @@ use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use std::sync::Arc;
 
+mod ethermint;
 mod parser;
 mod signer;
 
+#[cfg(feature = "abi-contract")]
+pub use ethermint::*;
 pub use parser::*;
 pub use signer::*;
 
@@ -90,7 +93,7 @@ impl Network {
         }
     }
 
-    fn get_chain_id(&self) -> eyre::Result<tendermint::chain::Id> {
+    pub(crate) fn get_chain_id(&self) -> eyre::Result<tendermint::chain::Id> {
         let chain_id = match self {
             Network::CryptoOrgMainnet => CRYPTO_ORG_CHAIN_ID,
             Network::CronosMainnet => CRONOS_CHAIN_ID,
@@ -434,6 +437,17 @@ pub enum CosmosSDKMsg {
     Raw { raw_msg: CosmosRawMsg },
 }
 
+/// the timeout height and timestamp cannot both be 0 (the packet would never time out)
+pub fn validate_ibc_timeout(timeout_height: &Height, timeout_timestamp: u64) -> eyre::Result<()> {
+    if timeout_height.revision_height == 0 && timeout_timestamp == 0 {
+        Err(eyre!(
+            "timeout height and timeout timestamp cannot both be 0"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 impl CosmosSDKMsg {
     fn to_any(&self, sender_address: AccountId) -> eyre::Result<Any> {
         match self {
@@ -589,6 +603,7 @@ impl CosmosSDKMsg {
                 timeout_height,
                 timeout_timestamp,
             } => {
+                validate_ibc_timeout(timeout_height, *timeout_timestamp)?;
                 let any = MsgTransfer {
                     sender: Signer::from_str(sender_address.as_ref())
                         .map_err(|e| eyre::eyre!("{e}"))?,
@@ -597,7 +612,6 @@ impl CosmosSDKMsg {
                     chan_on_a: ChannelId::from_str(source_channel)
                         .map_err(|e| eyre::eyre!("{e}"))?,
                     token: token.try_into()?,
-                    // TODO: timeout_height and timeout_timestamp cannot both be 0.
                     timeout_height_on_b: TimeoutHeight::try_from(timeout_height.clone())
                         .map_err(|e| eyre::eyre!("{e}"))?,
                     timeout_timestamp_on_b: Timestamp::from_nanoseconds(*timeout_timestamp)
@@ -1374,6 +1388,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ibc_transfer_without_timeout_is_rejected() {
+        let wallet = HDWallet::recover_wallet(WORDS.to_string(), None).expect("wallet");
+        let private_key = wallet
+            .get_key("m/44'/118'/0'/0/0".to_string())
+            .expect("key");
+
+        let result = build_signed_single_msg_tx(
+            TX_INFO,
+            CosmosSDKMsg::IbcTransfer {
+                source_channel: "channel-3".to_string(),
+                source_port: "transfer".to_string(),
+                receiver: "cosmos19dyl0uyzes4k23lscla02n06fc22h4uqsdwq6z".to_string(),
+                token: SingleCoin::Other {
+                    amount: "100000000".to_string(),
+                    denom: "basetcro".to_string(),
+                },
+                timeout_height: Height {
+                    revision_number: 0,
+                    revision_height: 0,
+                },
+                timeout_timestamp: 0,
+            },
+            private_key,
+        );
+        assert!(result.is_err());
+    }
+
     #[test]
     fn message_check() {
         let amount = &SingleCoin::ATOM { amount: 1 };
@@ -0,0 +1,813 @@
+#![cfg(feature = "abi-contract")]
+
+use super::{
+    validate_ibc_timeout, CosmosError, CosmosSDKMsg, CosmosSDKTxInfo, PublicKeyBytesWrapper,
+    SingleCoin,
+};
+use crate::utils::hex_decode;
+use crate::wallet::SecretKey;
+use crate::EthSigner;
+use cosmrs::proto::cosmos::tx::v1beta1::TxRaw;
+use cosmrs::tx::{self, Fee, ModeInfo, SignMode, SignerInfo, SignerPublicKey};
+use cosmrs::{AccountId, Any};
+use ethers::core::k256::ecdsa::VerifyingKey;
+use ethers::utils::public_key_to_address;
+use eyre::{eyre, WrapErr};
+use ibc_proto::cosmos::base::v1beta1::Coin as IbcCoin;
+use prost::Message;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+/// type URL of the Ethermint extension option of the legacy EIP-712 signed transactions
+pub const ETHERMINT_WEB3_TX_TYPE_URL: &str = "/ethermint.types.v1.ExtensionOptionsWeb3Tx";
+/// type URL of the Ethermint `eth_secp256k1` public keys
+pub const ETHERMINT_PUBKEY_TYPE_URL: &str = "/ethermint.crypto.v1.ethsecp256k1.PubKey";
+
+/// `ExtensionOptionsWeb3Tx` of Ethermint
+#[derive(Clone, PartialEq, Message)]
+struct ExtensionOptionsWeb3Tx {
+    /// the EIP-155 chain ID of the EIP-712 domain
+    #[prost(uint64, tag = "1")]
+    typed_data_chain_id: u64,
+    /// the bech32 address of the fee payer (the signer)
+    #[prost(string, tag = "2")]
+    fee_payer: String,
+    /// the EIP-712 signature of the fee payer
+    #[prost(bytes = "vec", tag = "3")]
+    fee_payer_sig: Vec<u8>,
+}
+
+/// `eth_secp256k1` public key of Ethermint
+#[derive(Clone, PartialEq, Message)]
+struct EthSecp256k1PubKey {
+    /// the compressed public key
+    #[prost(bytes = "vec", tag = "1")]
+    key: Vec<u8>,
+}
+
+/// Parse the EIP-155 chain ID from an Ethermint chain ID `{identifier}_{EIP155}-{epoch}`,
+/// e.g. 25 from `cronosmainnet_25-1`.
+pub fn parse_ethermint_chain_id(chain_id: &str) -> eyre::Result<u64> {
+    chain_id
+        .rsplit_once('_')
+        .and_then(|(identifier, rest)| {
+            let (eip155_chain_id, epoch) = rest.split_once('-')?;
+            if identifier.is_empty() || epoch.parse::<u64>().is_err() {
+                return None;
+            }
+            eip155_chain_id.parse().ok()
+        })
+        .ok_or_else(|| eyre!("invalid Ethermint chain id: {chain_id}"))
+}
+
+/// a field value of the amino JSON of a message
+enum AminoValue {
+    String(String),
+    /// `uint64` encoded as a string
+    Uint64(u64),
+    Array(Vec<AminoValue>),
+    Struct(Vec<(&'static str, AminoValue)>),
+}
+
+impl AminoValue {
+    fn coin(coin: IbcCoin) -> Self {
+        AminoValue::Struct(vec![
+            ("denom", AminoValue::String(coin.denom)),
+            ("amount", AminoValue::String(coin.amount)),
+        ])
+    }
+
+    /// the empty values are omitted from the amino JSON (`omitempty`) and the EIP-712 types,
+    /// including the structs of only empty fields (e.g. a zero `timeout_height`)
+    fn is_empty(&self) -> bool {
+        match self {
+            AminoValue::String(value) => value.is_empty(),
+            AminoValue::Uint64(value) => *value == 0,
+            AminoValue::Array(values) => values.is_empty(),
+            AminoValue::Struct(fields) => fields.iter().all(|(_, value)| value.is_empty()),
+        }
+    }
+
+    /// Convert to the JSON value and add the EIP-712 types of the structs to `types`.
+    /// The struct of this value is named `type_name` and the ones of its fields are named
+    /// by `prefix` and their field names as Ethermint does (e.g. `TypeTimeoutHeight`).
+    /// Return the JSON value and its EIP-712 type.
+    fn encode(
+        self,
+        type_name: &str,
+        prefix: &str,
+        types: &mut Map<String, Value>,
+    ) -> (Value, String) {
+        match self {
+            AminoValue::String(value) => (Value::String(value), "string".to_owned()),
+            AminoValue::Uint64(value) => (Value::String(value.to_string()), "uint64".to_owned()),
+            AminoValue::Array(values) => {
+                // the empty arrays are omitted, so the element type is always known
+                let mut element_type = String::new();
+                let values = values
+                    .into_iter()
+                    .map(|value| {
+                        let (value, value_type) = value.encode(type_name, prefix, types);
+                        element_type = value_type;
+                        value
+                    })
+                    .collect();
+                (Value::Array(values), format!("{element_type}[]"))
+            }
+            AminoValue::Struct(fields) => {
+                let mut object = Map::new();
+                let mut field_types = vec![];
+                for (name, value) in fields {
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let field_type_name = format!("{prefix}{}", title_case(name));
+                    let (value, field_type) =
+                        value.encode(&field_type_name, &field_type_name, types);
+                    field_types.push(json!({ "name": name, "type": field_type }));
+                    object.insert(name.to_owned(), value);
+                }
+                types.insert(type_name.to_owned(), Value::Array(field_types));
+                (Value::Object(object), type_name.to_owned())
+            }
+        }
+    }
+}
+
+/// `timeout_height` -> `TimeoutHeight`
+fn title_case(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+impl CosmosSDKMsg {
+    /// the legacy amino type name and JSON value of the message
+    fn to_amino(&self, sender_address: &str) -> eyre::Result<(&'static str, AminoValue)> {
+        let sender_address = AminoValue::String(sender_address.to_owned());
+        let coin = |amount: &SingleCoin| -> eyre::Result<AminoValue> {
+            Ok(AminoValue::coin(IbcCoin::try_from(amount)?))
+        };
+        Ok(match self {
+            CosmosSDKMsg::BankSend {
+                recipient_address,
+                amount,
+            } => (
+                "cosmos-sdk/MsgSend",
+                AminoValue::Struct(vec![
+                    ("from_address", sender_address),
+                    ("to_address", AminoValue::String(recipient_address.clone())),
+                    ("amount", AminoValue::Array(vec![coin(amount)?])),
+                ]),
+            ),
+            CosmosSDKMsg::StakingDelegate {
+                validator_address,
+                amount,
+            } => (
+                "cosmos-sdk/MsgDelegate",
+                AminoValue::Struct(vec![
+                    ("delegator_address", sender_address),
+                    (
+                        "validator_address",
+                        AminoValue::String(validator_address.clone()),
+                    ),
+                    ("amount", coin(amount)?),
+                ]),
+            ),
+            CosmosSDKMsg::StakingUndelegate {
+                validator_address,
+                amount,
+            } => (
+                "cosmos-sdk/MsgUndelegate",
+                AminoValue::Struct(vec![
+                    ("delegator_address", sender_address),
+                    (
+                        "validator_address",
+                        AminoValue::String(validator_address.clone()),
+                    ),
+                    ("amount", coin(amount)?),
+                ]),
+            ),
+            CosmosSDKMsg::StakingBeginRedelegate {
+                validator_src_address,
+                validator_dst_address,
+                amount,
+            } => (
+                "cosmos-sdk/MsgBeginRedelegate",
+                AminoValue::Struct(vec![
+                    ("delegator_address", sender_address),
+                    (
+                        "validator_src_address",
+                        AminoValue::String(validator_src_address.clone()),
+                    ),
+                    (
+                        "validator_dst_address",
+                        AminoValue::String(validator_dst_address.clone()),
+                    ),
+                    ("amount", coin(amount)?),
+                ]),
+            ),
+            CosmosSDKMsg::DistributionSetWithdrawAddress { withdraw_address } => (
+                "cosmos-sdk/MsgModifyWithdrawAddress",
+                AminoValue::Struct(vec![
+                    ("delegator_address", sender_address),
+                    (
+                        "withdraw_address",
+                        AminoValue::String(withdraw_address.clone()),
+                    ),
+                ]),
+            ),
+            CosmosSDKMsg::DistributionWithdrawDelegatorReward { validator_address } => (
+                "cosmos-sdk/MsgWithdrawDelegationReward",
+                AminoValue::Struct(vec![
+                    ("delegator_address", sender_address),
+                    (
+                        "validator_address",
+                        AminoValue::String(validator_address.clone()),
+                    ),
+                ]),
+            ),
+            CosmosSDKMsg::IbcTransfer {
+                receiver,
+                source_port,
+                source_channel,
+                token,
+                timeout_height,
+                timeout_timestamp,
+            } => {
+                validate_ibc_timeout(timeout_height, *timeout_timestamp)?;
+                (
+                    "cosmos-sdk/MsgTransfer",
+                    AminoValue::Struct(vec![
+                        ("source_port", AminoValue::String(source_port.clone())),
+                        ("source_channel", AminoValue::String(source_channel.clone())),
+                        ("token", coin(token)?),
+                        ("sender", sender_address),
+                        ("receiver", AminoValue::String(receiver.clone())),
+                        (
+                            "timeout_height",
+                            AminoValue::Struct(vec![
+                                (
+                                    "revision_number",
+                                    AminoValue::Uint64(timeout_height.revision_number),
+                                ),
+                                (
+                                    "revision_height",
+                                    AminoValue::Uint64(timeout_height.revision_height),
+                                ),
+                            ]),
+                        ),
+                        ("timeout_timestamp", AminoValue::Uint64(*timeout_timestamp)),
+                    ]),
+                )
+            }
+            _ => {
+                return Err(eyre!(
+                    "the message is not supported by the Ethermint EIP-712 signing"
+                ))
+            }
+        })
+    }
+}
+
+/// Builder of the Cosmos SDK transactions signed with the legacy EIP-712 wrapper of
+/// Ethermint-based chains (e.g. Cronos), which lets Ethereum keys (and wallets like MetaMask)
+/// sign the Cosmos SDK messages. The amino JSON of the transaction is wrapped as
+/// the EIP-712 typed data and the signature is attached in `ExtensionOptionsWeb3Tx`.
+///
+/// Only the bank, staking, distribution and IBC transfer messages are supported, and all
+/// the messages must be of the same type.
+pub struct EthermintEip712TxBuilder {
+    tx_info: CosmosSDKTxInfo,
+    msgs: Vec<CosmosSDKMsg>,
+}
+
+impl EthermintEip712TxBuilder {
+    /// Create a builder of the messages.
+    pub fn new(tx_info: CosmosSDKTxInfo, msgs: Vec<CosmosSDKMsg>) -> Self {
+        Self { tx_info, msgs }
+    }
+
+    /// Add a message.
+    pub fn add_msg(mut self, msg: CosmosSDKMsg) -> Self {
+        self.msgs.push(msg);
+        self
+    }
+
+    /// the bech32 address (of the Ethereum address) of the signer
+    fn sender_account_id(&self, sender_pubkey: &[u8]) -> eyre::Result<AccountId> {
+        let verifying_key =
+            VerifyingKey::from_sec1_bytes(sender_pubkey).wrap_err("invalid public key")?;
+        let address = public_key_to_address(&verifying_key);
+        AccountId::new(self.tx_info.network.get_bech32_hrp(), address.as_bytes())
+    }
+
+    /// the EIP-712 typed data (JSON) to be signed by the sender of the bech32 address
+    fn eip712_typed_data(&self, sender_address: &str) -> eyre::Result<String> {
+        if self.msgs.is_empty() {
+            return Err(eyre!("no messages to sign"));
+        }
+        let chain_id = self.tx_info.network.get_chain_id()?;
+        let eip155_chain_id = parse_ethermint_chain_id(chain_id.as_str())?;
+
+        let mut types = Map::new();
+        let mut msgs = vec![];
+        let mut msg_type = None;
+        for msg in self.msgs.iter() {
+            let (amino_type, value) = msg.to_amino(sender_address)?;
+            if *msg_type.get_or_insert(amino_type) != amino_type {
+                return Err(eyre!("all the messages must be of the same type"));
+            }
+            let (value, _) = value.encode("MsgValue", "Type", &mut types);
+            msgs.push(json!({ "type": amino_type, "value": value }));
+        }
+        let fee_amount = IbcCoin::try_from(&self.tx_info.fee_amount)?;
+
+        types.insert(
+            "EIP712Domain".to_owned(),
+            json!([
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "string" },
+                { "name": "salt", "type": "string" }
+            ]),
+        );
+        types.insert(
+            "Tx".to_owned(),
+            json!([
+                { "name": "account_number", "type": "string" },
+                { "name": "chain_id", "type": "string" },
+                { "name": "fee", "type": "Fee" },
+                { "name": "memo", "type": "string" },
+                { "name": "msgs", "type": "Msg[]" },
+                { "name": "sequence", "type": "string" }
+            ]),
+        );
+        types.insert(
+            "Fee".to_owned(),
+            json!([
+                { "name": "feePayer", "type": "string" },
+                { "name": "amount", "type": "Coin[]" },
+                { "name": "gas", "type": "string" }
+            ]),
+        );
+        types.insert(
+            "Coin".to_owned(),
+            json!([
+                { "name": "denom", "type": "string" },
+                { "name": "amount", "type": "string" }
+            ]),
+        );
+        types.insert(
+            "Msg".to_owned(),
+            json!([
+                { "name": "type", "type": "string" },
+                { "name": "value", "type": "MsgValue" }
+            ]),
+        );
+
+        let typed_data = json!({
+            "domain": {
+                "name": "Cosmos Web3",
+                "version": "1.0.0",
+                "chainId": eip155_chain_id,
+                "verifyingContract": "cosmos",
+                "salt": "0"
+            },
+            "message": {
+                "account_number": self.tx_info.account_number.to_string(),
+                "chain_id": chain_id.as_str(),
+                "fee": {
+                    "feePayer": sender_address,
+                    "amount": [{ "denom": fee_amount.denom, "amount": fee_amount.amount }],
+                    "gas": self.tx_info.gas_limit.to_string()
+                },
+                "memo": self.tx_info.memo_note.clone().unwrap_or_default(),
+                "msgs": msgs,
+                "sequence": self.tx_info.sequence_number.to_string()
+            },
+            "primaryType": "Tx",
+            "types": types
+        });
+        Ok(typed_data.to_string())
+    }
+
+    /// Get the EIP-712 typed data (JSON) to be signed by the sender,
+    /// e.g. via `eth_signTypedData_v4` of an external wallet.
+    pub fn get_sign_payload(
+        &self,
+        sender_pubkey: PublicKeyBytesWrapper,
+    ) -> Result<String, CosmosError> {
+        let sender_account_id = self.sender_account_id(&sender_pubkey.0)?;
+        Ok(self.eip712_typed_data(sender_account_id.as_ref())?)
+    }
+
+    /// Assemble the signed transaction with the EIP-712 signature (hex string of 65 bytes)
+    /// of the sign payload.
+    pub fn build_signed_tx(
+        self,
+        sender_pubkey: PublicKeyBytesWrapper,
+        signature: &str,
+    ) -> Result<Vec<u8>, CosmosError> {
+        let signature = hex_decode(signature).wrap_err("invalid signature")?;
+        if signature.len() != 65 {
+            return Err(eyre!("the signature must be of 65 bytes").into());
+        }
+        let sender_account_id = self.sender_account_id(&sender_pubkey.0)?;
+        let chain_id = self.tx_info.network.get_chain_id()?;
+
+        let mut msgs_any: Vec<Any> = Vec::new();
+        for msg in self.msgs.iter() {
+            msgs_any.push(msg.to_any(sender_account_id.clone())?);
+        }
+        let mut tx_body = tx::Body::new(
+            msgs_any,
+            self.tx_info.memo_note.unwrap_or_default(),
+            self.tx_info.timeout_height,
+        );
+        tx_body.extension_options.push(Any {
+            type_url: ETHERMINT_WEB3_TX_TYPE_URL.to_owned(),
+            value: ExtensionOptionsWeb3Tx {
+                typed_data_chain_id: parse_ethermint_chain_id(chain_id.as_str())?,
+                fee_payer: sender_account_id.to_string(),
+                fee_payer_sig: signature,
+            }
+            .encode_to_vec(),
+        });
+
+        let public_key = Any {
+            type_url: ETHERMINT_PUBKEY_TYPE_URL.to_owned(),
+            value: EthSecp256k1PubKey {
+                key: sender_pubkey.0,
+            }
+            .encode_to_vec(),
+        };
+        let signer_info = SignerInfo {
+            public_key: Some(SignerPublicKey::Any(public_key)),
+            mode_info: ModeInfo::single(SignMode::LegacyAminoJson),
+            sequence: self.tx_info.sequence_number,
+        };
+        let auth_info = signer_info.auth_info(Fee::from_amount_and_gas(
+            (&self.tx_info.fee_amount).try_into()?,
+            self.tx_info.gas_limit,
+        ));
+
+        // the signature is only verified from the extension option
+        Ok(TxRaw {
+            body_bytes: tx_body.into_bytes()?,
+            auth_info_bytes: auth_info.into_bytes()?,
+            signatures: vec![vec![]],
+        }
+        .encode_to_vec())
+    }
+
+    /// Sign the messages with the Ethereum key and assemble the signed transaction.
+    pub fn sign(self, secret_key: Arc<SecretKey>) -> Result<Vec<u8>, CosmosError> {
+        let sender_pubkey = secret_key.get_public_key_bytes();
+        let typed_data = self.get_sign_payload(PublicKeyBytesWrapper(sender_pubkey.clone()))?;
+        let signature = EthSigner::new(secret_key)
+            .sign_typed_data(&typed_data)
+            .map_err(|e| eyre!("{e}"))?;
+        self.build_signed_tx(PublicKeyBytesWrapper(sender_pubkey), &signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::ethereum::eip712::Eip712TypedData;
+    use crate::{recover_eip712_signer, HDWallet, Network};
+    use cosmrs::tx::mode_info;
+    use cosmrs::Tx;
+    use ibc_proto::ibc::core::client::v1::Height;
+
+    /// the bech32 addresses of `0x2c600e0a72b3ae39e9b27d2e310b180abe779368` and
+    /// `0x74aeb73c4f6c10750bcd8608b0347f3e4750151c`
+    const SENDER: &str = "crc193squznjkwhrn6dj05hrzzccp2l80ymg37l8pr";
+    const RECIPIENT: &str = "crc1wjhtw0z0dsg82z7dscytqdrl8er4q9gutvk55g";
+    const VALIDATOR: &str = "crcvaloper1wjhtw0z0dsg82z7dscytqdrl8er4q9gupn8c2p";
+
+    const MNEMONIC: &str = "apple elegant knife hawk there screen vehicle lounge tube sun engage bus custom market pioneer casual wink present cat metal ride shallow fork brief";
+
+    fn secret_key() -> Arc<SecretKey> {
+        let wallet = HDWallet::recover_wallet(MNEMONIC.to_owned(), None).unwrap();
+        wallet.get_key("m/44'/60'/0'/0/0".to_owned()).unwrap()
+    }
+
+    fn tx_info() -> CosmosSDKTxInfo {
+        CosmosSDKTxInfo {
+            account_number: 1,
+            sequence_number: 0,
+            gas_limit: 200_000,
+            fee_amount: SingleCoin::Other {
+                amount: "1000000000000000".to_owned(),
+                denom: "basecro".to_owned(),
+            },
+            timeout_height: 0,
+            memo_note: None,
+            network: Network::CronosMainnet,
+        }
+    }
+
+    fn delegate() -> CosmosSDKMsg {
+        CosmosSDKMsg::StakingDelegate {
+            validator_address: "crcvaloper1qs8vjhxj5wkx0vz5d7cyn8scldlqfeeuvkfv4g".to_owned(),
+            amount: SingleCoin::Other {
+                amount: "1000000000000000000".to_owned(),
+                denom: "basecro".to_owned(),
+            },
+        }
+    }
+
+    #[test]
+    fn ethermint_chain_id_is_parsed() {
+        assert_eq!(parse_ethermint_chain_id("cronosmainnet_25-1").unwrap(), 25);
+        assert_eq!(parse_ethermint_chain_id("cronos_777-1").unwrap(), 777);
+        assert!(parse_ethermint_chain_id("cosmoshub-4").is_err());
+        assert!(parse_ethermint_chain_id("cronos_25").is_err());
+    }
+
+    #[test]
+    fn sign_payload_wraps_amino_json() {
+        let secret_key = secret_key();
+        let builder = EthermintEip712TxBuilder::new(tx_info(), vec![delegate()]);
+        let typed_data = builder
+            .get_sign_payload(PublicKeyBytesWrapper(secret_key.get_public_key_bytes()))
+            .unwrap();
+        let typed_data: Value = serde_json::from_str(&typed_data).unwrap();
+
+        assert_eq!(typed_data["domain"]["chainId"], 25);
+        let message = &typed_data["message"];
+        assert_eq!(message["chain_id"], "cronosmainnet_25-1");
+        assert_eq!(message["fee"]["gas"], "200000");
+        let msg = &message["msgs"][0];
+        assert_eq!(msg["type"], "cosmos-sdk/MsgDelegate");
+        assert_eq!(
+            msg["value"]["delegator_address"],
+            message["fee"]["feePayer"]
+        );
+        assert!(msg["value"]["delegator_address"]
+            .as_str()
+            .unwrap()
+            .starts_with("crc1"));
+        assert_eq!(
+            typed_data["types"]["MsgValue"],
+            json!([
+                { "name": "delegator_address", "type": "string" },
+                { "name": "validator_address", "type": "string" },
+                { "name": "amount", "type": "TypeAmount" }
+            ])
+        );
+        assert_eq!(
+            typed_data["types"]["TypeAmount"],
+            json!([
+                { "name": "denom", "type": "string" },
+                { "name": "amount", "type": "string" }
+            ])
+        );
+    }
+
+    #[test]
+    fn ibc_transfer_omits_empty_fields() {
+        let secret_key = secret_key();
+        let msg = CosmosSDKMsg::IbcTransfer {
+            receiver: "cosmos19dyl0uyzes4k23lscla02n06fc22h4uqsdwq6z".to_owned(),
+            source_port: "transfer".to_owned(),
+            source_channel: "channel-5".to_owned(),
+            token: SingleCoin::Other {
+                amount: "100".to_owned(),
+                denom: "basecro".to_owned(),
+            },
+            timeout_height: Height {
+                revision_number: 4,
+                revision_height: 0,
+            },
+            timeout_timestamp: 1645800000000000000,
+        };
+        let typed_data = EthermintEip712TxBuilder::new(tx_info(), vec![msg])
+            .get_sign_payload(PublicKeyBytesWrapper(secret_key.get_public_key_bytes()))
+            .unwrap();
+        let typed_data: Value = serde_json::from_str(&typed_data).unwrap();
+
+        let value = &typed_data["message"]["msgs"][0]["value"];
+        assert_eq!(value["timeout_height"], json!({ "revision_number": "4" }));
+        assert_eq!(value["timeout_timestamp"], "1645800000000000000");
+        assert_eq!(
+            typed_data["types"]["TypeTimeoutHeight"],
+            json!([{ "name": "revision_number", "type": "uint64" }])
+        );
+    }
+
+    fn basecro(amount: &str) -> SingleCoin {
+        SingleCoin::Other {
+            amount: amount.to_owned(),
+            denom: "basecro".to_owned(),
+        }
+    }
+
+    /// the EIP-712 hash of the typed data of the message from `SENDER`
+    fn eip712_hash(msg: CosmosSDKMsg) -> (Value, String) {
+        let typed_data = EthermintEip712TxBuilder::new(tx_info(), vec![msg])
+            .eip712_typed_data(SENDER)
+            .unwrap();
+        let hash = Eip712TypedData::new(&typed_data).unwrap().encode().unwrap();
+        (
+            serde_json::from_str(&typed_data).unwrap(),
+            hex::encode(hash),
+        )
+    }
+
+    // The expected typed data follow the layout of `WrapTxToTypedData` of Ethermint (legacy
+    // EIP-712) and the hashes are computed by an independent EIP-712 implementation.
+    #[test]
+    fn typed_data_matches_ethermint_vectors() {
+        let (typed_data, hash) = eip712_hash(CosmosSDKMsg::BankSend {
+            recipient_address: RECIPIENT.to_owned(),
+            amount: basecro("100"),
+        });
+        assert_eq!(
+            typed_data,
+            json!({
+                "domain": {
+                    "name": "Cosmos Web3",
+                    "version": "1.0.0",
+                    "chainId": 25,
+                    "verifyingContract": "cosmos",
+                    "salt": "0"
+                },
+                "message": {
+                    "account_number": "1",
+                    "chain_id": "cronosmainnet_25-1",
+                    "fee": {
+                        "feePayer": SENDER,
+                        "amount": [{ "denom": "basecro", "amount": "1000000000000000" }],
+                        "gas": "200000"
+                    },
+                    "memo": "",
+                    "msgs": [{
+                        "type": "cosmos-sdk/MsgSend",
+                        "value": {
+                            "from_address": SENDER,
+                            "to_address": RECIPIENT,
+                            "amount": [{ "denom": "basecro", "amount": "100" }]
+                        }
+                    }],
+                    "sequence": "0"
+                },
+                "primaryType": "Tx",
+                "types": {
+                    "EIP712Domain": [
+                        { "name": "name", "type": "string" },
+                        { "name": "version", "type": "string" },
+                        { "name": "chainId", "type": "uint256" },
+                        { "name": "verifyingContract", "type": "string" },
+                        { "name": "salt", "type": "string" }
+                    ],
+                    "Tx": [
+                        { "name": "account_number", "type": "string" },
+                        { "name": "chain_id", "type": "string" },
+                        { "name": "fee", "type": "Fee" },
+                        { "name": "memo", "type": "string" },
+                        { "name": "msgs", "type": "Msg[]" },
+                        { "name": "sequence", "type": "string" }
+                    ],
+                    "Fee": [
+                        { "name": "feePayer", "type": "string" },
+                        { "name": "amount", "type": "Coin[]" },
+                        { "name": "gas", "type": "string" }
+                    ],
+                    "Coin": [
+                        { "name": "denom", "type": "string" },
+                        { "name": "amount", "type": "string" }
+                    ],
+                    "Msg": [
+                        { "name": "type", "type": "string" },
+                        { "name": "value", "type": "MsgValue" }
+                    ],
+                    "MsgValue": [
+                        { "name": "from_address", "type": "string" },
+                        { "name": "to_address", "type": "string" },
+                        { "name": "amount", "type": "TypeAmount[]" }
+                    ],
+                    "TypeAmount": [
+                        { "name": "denom", "type": "string" },
+                        { "name": "amount", "type": "string" }
+                    ]
+                }
+            })
+        );
+        assert_eq!(
+            hash,
+            "a99d77d0acd12d718cfeae9ebaaa7d37bcc3b24ff82f63ad8ebb3ae8ea6b190f"
+        );
+
+        let (_, hash) = eip712_hash(CosmosSDKMsg::StakingDelegate {
+            validator_address: VALIDATOR.to_owned(),
+            amount: basecro("100"),
+        });
+        assert_eq!(
+            hash,
+            "04350d7c98079ca6fd2f5e50e6d4a94d0aa55205e823d4f64ffd6327c2939bfa"
+        );
+    }
+
+    #[test]
+    fn timestamp_only_ibc_transfer_omits_timeout_height() {
+        let (typed_data, hash) = eip712_hash(CosmosSDKMsg::IbcTransfer {
+            receiver: "cosmos193squznjkwhrn6dj05hrzzccp2l80ymgxq93na".to_owned(),
+            source_port: "transfer".to_owned(),
+            source_channel: "channel-5".to_owned(),
+            token: basecro("100"),
+            timeout_height: Height {
+                revision_number: 0,
+                revision_height: 0,
+            },
+            timeout_timestamp: 1645800000000000000,
+        });
+
+        assert!(typed_data["message"]["msgs"][0]["value"]
+            .get("timeout_height")
+            .is_none());
+        assert!(typed_data["types"].get("TypeTimeoutHeight").is_none());
+        assert_eq!(
+            typed_data["types"]["MsgValue"],
+            json!([
+                { "name": "source_port", "type": "string" },
+                { "name": "source_channel", "type": "string" },
+                { "name": "token", "type": "TypeToken" },
+                { "name": "sender", "type": "string" },
+                { "name": "receiver", "type": "string" },
+                { "name": "timeout_timestamp", "type": "uint64" }
+            ])
+        );
+        assert_eq!(
+            hash,
+            "043e621591ee47e0b180749a9f0673cf726ad9aea6ac250b854d88a0cd688f07"
+        );
+    }
+
+    #[test]
+    fn signed_tx_has_web3_extension_option() {
+        let secret_key = secret_key();
+        let sender_pubkey = PublicKeyBytesWrapper(secret_key.get_public_key_bytes());
+        let builder = EthermintEip712TxBuilder::new(tx_info(), vec![]).add_msg(delegate());
+        let typed_data = builder.get_sign_payload(sender_pubkey).unwrap();
+        let tx_raw = builder.sign(secret_key).unwrap();
+
+        let tx = Tx::from_bytes(&tx_raw).unwrap();
+        assert_eq!(tx.body.messages.len(), 1);
+        let extension_option = &tx.body.extension_options[0];
+        assert_eq!(extension_option.type_url, ETHERMINT_WEB3_TX_TYPE_URL);
+        let web3_tx = ExtensionOptionsWeb3Tx::decode(extension_option.value.as_slice()).unwrap();
+        assert_eq!(web3_tx.typed_data_chain_id, 25);
+        assert!(web3_tx.fee_payer.starts_with("crc1"));
+
+        let signer_info = &tx.auth_info.signer_infos[0];
+        assert!(matches!(
+            signer_info.mode_info,
+            ModeInfo::Single(mode_info::Single {
+                mode: SignMode::LegacyAminoJson
+            })
+        ));
+        match &signer_info.public_key {
+            Some(SignerPublicKey::Any(any)) => assert_eq!(any.type_url, ETHERMINT_PUBKEY_TYPE_URL),
+            _ => panic!("unexpected public key"),
+        }
+
+        let signer =
+            recover_eip712_signer(&typed_data, &hex::encode(web3_tx.fee_payer_sig)).unwrap();
+        assert_eq!(
+            AccountId::new("crc", &hex_decode(&signer).unwrap())
+                .unwrap()
+                .to_string(),
+            web3_tx.fee_payer
+        );
+    }
+
+    #[test]
+    fn mixed_or_unsupported_messages_are_rejected() {
+        let secret_key = secret_key();
+        let mixed = EthermintEip712TxBuilder::new(tx_info(), vec![delegate()]).add_msg(
+            CosmosSDKMsg::DistributionWithdrawDelegatorReward {
+                validator_address: "crcvaloper1qs8vjhxj5wkx0vz5d7cyn8scldlqfeeuvkfv4g".to_owned(),
+            },
+        );
+        assert!(mixed.sign(secret_key.clone()).is_err());
+
+        let unsupported = EthermintEip712TxBuilder::new(
+            tx_info(),
+            vec![CosmosSDKMsg::NftBurn {
+                id: "nft".to_owned(),
+                denom_id: "denom".to_owned(),
+            }],
+        );
+        assert!(unsupported.sign(secret_key).is_err());
+    }
+}
@@ -59,6 +59,19 @@ pub trait CosmosParser {
     fn transform_tx_body(&self, tx_body: &mut CosmosTxBody) -> Result<(), CosmosError>;
 }
 
+/// Parse the chain ID, `CosmosTxBody` (with the standard Cosmos messages) and `CosmosAuthInfo`
+/// of a `SignDoc` of Protobuf.
+pub(crate) fn parse_protobuf_sign_doc(
+    sign_doc: &[u8],
+) -> Result<(String, CosmosTxBody, CosmosAuthInfo), CosmosError> {
+    let sign_doc = cosmos_sdk_proto::cosmos::tx::v1beta1::SignDoc::decode(sign_doc)
+        .wrap_err("Failed to decode SignDoc from Protobuf")?;
+    let parser = base_parser::BaseParser;
+    let tx_body = parser.parse_protobuf_tx_body(&hex::encode(&sign_doc.body_bytes))?;
+    let auth_info = parser.parse_protobuf_auto_info(&hex::encode(&sign_doc.auth_info_bytes))?;
+    Ok((sign_doc.chain_id, tx_body, auth_info))
+}
+
 #[cfg(test)]
 mod cosmos_parsing_tests {
     use super::*;
@@ -0,0 +1,828 @@
+use super::cosmos_sdk::parse_protobuf_sign_doc;
+use crate::{
+    get_msg_sign_payload, Coin, CosmosError, CosmosRawMsg, CosmosRawNormalMsg, CosmosSDKMsg,
+    CosmosSDKTxInfo, DynamicTransactionRequest, HDWallet, HdWrapError, PublicKeyBytesWrapper,
+    SingleCoin,
+};
+use cosmrs::crypto::{secp256k1, secp256k1::SigningKey, PublicKey};
+use cosmrs::proto::cosmos::crypto::secp256k1::PubKey;
+use cosmrs::proto::cosmos::tx::v1beta1::{AuthInfo, SignDoc, TxRaw};
+use ethers::core::k256::ecdsa::{signature::Verifier, Signature as EcdsaSignature, VerifyingKey};
+use ethers::prelude::{LocalWallet, NameOrAddress, Signature, Signer, U256};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::Bytes;
+use ethers::utils::{format_ether, format_units, hex, rlp, to_checksum};
+use prost::Message;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// the current version of the transaction envelopes
+pub const TX_ENVELOPE_VERSION: u32 = 1;
+
+/// Errors of the transaction envelopes
+#[derive(Debug, thiserror::Error)]
+pub enum TxEnvelopeError {
+    #[error("Unsupported envelope version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("CBOR error: {0}")]
+    CborError(String),
+    #[error("Invalid payload: {0}")]
+    InvalidPayload(String),
+    #[error("The derivation path of the signer is missing")]
+    MissingDerivationPath,
+    #[error("The derived address {derived} does not match the signer {expected}")]
+    AddressMismatch { expected: String, derived: String },
+    #[error("The chain ID {actual} of the payload does not match the chain {expected}")]
+    ChainMismatch { expected: String, actual: String },
+    #[error("The summary does not match the payload")]
+    SummaryMismatch,
+    #[error("The signer {signer} does not match the expected {expected}")]
+    SignerMismatch { expected: String, signer: String },
+    #[error("The signature is invalid for the payload")]
+    InvalidSignature,
+    #[error("HD wallet error: {0}")]
+    WalletError(#[from] HdWrapError),
+    #[error("Cosmos error: {0}")]
+    CosmosError(#[from] CosmosError),
+    #[error("Signing error: {0}")]
+    SigningError(String),
+}
+
+/// The chain of the transaction
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TxEnvelopeChain {
+    /// an EVM chain
+    Evm { chain_id: u64 },
+    /// a Cosmos SDK chain
+    Cosmos {
+        chain_id: String,
+        bech32_prefix: String,
+        coin_type: u32,
+    },
+}
+
+/// The signer of the transaction: the offline device needs the derivation path,
+/// and checks the derived address if it is given
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxEnvelopeSigner {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+}
+
+/// The data to be signed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TxEnvelopePayload {
+    /// the RLP encoding of the unsigned transaction
+    /// (prefixed with the type of EIP-2718 typed transactions)
+    EvmTransaction { unsigned_tx: Bytes },
+    /// the protobuf encoding of `SignDoc` (`SIGN_MODE_DIRECT`)
+    CosmosSignDoc { sign_doc: Bytes },
+}
+
+/// A human-readable field of the transaction, shown before signing it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TxSummaryItem {
+    pub label: String,
+    pub value: String,
+}
+
+impl TxSummaryItem {
+    fn new(label: &str, value: impl ToString) -> Self {
+        Self {
+            label: label.to_owned(),
+            value: value.to_string(),
+        }
+    }
+}
+
+/// The signature of the transaction
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TxEnvelopeSignature {
+    /// the signature with `v` as per EIP-155
+    Evm { signature: Signature },
+    /// the 64-byte signature and the compressed public key of the signer
+    Cosmos { signature: Bytes, public_key: Bytes },
+}
+
+fn check_version(version: u32) -> Result<(), TxEnvelopeError> {
+    if version == 0 || version > TX_ENVELOPE_VERSION {
+        return Err(TxEnvelopeError::UnsupportedVersion(version));
+    }
+    Ok(())
+}
+
+fn to_cbor<T: Serialize>(value: &T) -> Result<Vec<u8>, TxEnvelopeError> {
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(value, &mut bytes)
+        .map_err(|e| TxEnvelopeError::CborError(e.to_string()))?;
+    Ok(bytes)
+}
+
+fn from_cbor<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, TxEnvelopeError> {
+    ciborium::de::from_reader(bytes).map_err(|e| TxEnvelopeError::CborError(e.to_string()))
+}
+
+fn decode_eth_transaction(
+    unsigned_tx: &Bytes,
+    chain_id: u64,
+) -> Result<TypedTransaction, TxEnvelopeError> {
+    let mut tx: TypedTransaction =
+        rlp::decode(unsigned_tx).map_err(|e| TxEnvelopeError::InvalidPayload(e.to_string()))?;
+    tx.set_chain_id(chain_id);
+    Ok(tx)
+}
+
+fn format_gwei(value: U256) -> String {
+    format_units(value, "gwei")
+        .map(|gwei| format!("{gwei} gwei"))
+        .unwrap_or_else(|_| format!("{value} wei"))
+}
+
+fn format_coin(coin: &SingleCoin) -> String {
+    match Coin::try_from(coin) {
+        Ok(coin) => format!("{}{}", coin.amount, coin.denom),
+        Err(_) => format!("{coin:?}"),
+    }
+}
+
+fn format_coins(coins: &[SingleCoin]) -> String {
+    coins.iter().map(format_coin).collect::<Vec<_>>().join(", ")
+}
+
+/// the human-readable summary of the Cosmos SDK message
+fn summarize_msg(msg: &CosmosRawMsg) -> String {
+    match msg {
+        CosmosRawMsg::Normal { msg } => match msg {
+            CosmosRawNormalMsg::BankSend {
+                to_address, amount, ..
+            } => format!("send {} to {to_address}", format_coins(amount)),
+            CosmosRawNormalMsg::StakingDelegate {
+                validator_address,
+                amount,
+                ..
+            } => format!("delegate {} to {validator_address}", format_coin(amount)),
+            CosmosRawNormalMsg::StakingUndelegate {
+                validator_address,
+                amount,
+                ..
+            } => format!(
+                "undelegate {} from {validator_address}",
+                format_coin(amount)
+            ),
+            CosmosRawNormalMsg::StakingBeginRedelegate {
+                validator_src_address,
+                validator_dst_address,
+                amount,
+                ..
+            } => format!(
+                "redelegate {} from {validator_src_address} to {validator_dst_address}",
+                format_coin(amount)
+            ),
+            CosmosRawNormalMsg::DistributionWithdrawDelegatorReward {
+                validator_address, ..
+            } => format!("withdraw the rewards from {validator_address}"),
+            CosmosRawNormalMsg::DistributionSetWithdrawAddress {
+                withdraw_address, ..
+            } => format!("set the withdraw address to {withdraw_address}"),
+            CosmosRawNormalMsg::IbcTransfer {
+                receiver,
+                source_port,
+                source_channel,
+                token,
+                ..
+            } => format!(
+                "transfer {} to {receiver} via {source_port}/{source_channel}",
+                format_coin(token)
+            ),
+        },
+        // the other messages are shown as they are
+        CosmosRawMsg::Any { type_url, value } => {
+            format!("{type_url}: 0x{}", hex::encode(value))
+        }
+        _ => serde_json::to_string(msg).unwrap_or_default(),
+    }
+}
+
+/// the human-readable summary of the EVM transaction
+fn summarize_eth_transaction(tx: &TypedTransaction, chain_id: u64) -> Vec<TxSummaryItem> {
+    let mut summary = vec![TxSummaryItem::new("Chain ID", chain_id)];
+    summary.push(match tx.to() {
+        Some(NameOrAddress::Address(to)) => TxSummaryItem::new("To", to_checksum(to, None)),
+        Some(NameOrAddress::Name(name)) => TxSummaryItem::new("To", name),
+        None => TxSummaryItem::new("To", "(contract creation)"),
+    });
+    summary.push(TxSummaryItem::new(
+        "Value",
+        format_ether(tx.value().cloned().unwrap_or_default()),
+    ));
+    if let Some(gas) = tx.gas() {
+        summary.push(TxSummaryItem::new("Gas limit", gas));
+    }
+    match tx {
+        TypedTransaction::Eip1559(tx) => {
+            if let Some(max_fee_per_gas) = tx.max_fee_per_gas {
+                summary.push(TxSummaryItem::new(
+                    "Max fee per gas",
+                    format_gwei(max_fee_per_gas),
+                ));
+            }
+            if let Some(max_priority_fee_per_gas) = tx.max_priority_fee_per_gas {
+                summary.push(TxSummaryItem::new(
+                    "Max priority fee per gas",
+                    format_gwei(max_priority_fee_per_gas),
+                ));
+            }
+        }
+        _ => {
+            if let Some(gas_price) = tx.gas_price() {
+                summary.push(TxSummaryItem::new("Gas price", format_gwei(gas_price)));
+            }
+        }
+    }
+    if let Some(nonce) = tx.nonce() {
+        summary.push(TxSummaryItem::new("Nonce", nonce));
+    }
+    if let Some(data) = tx.data().filter(|data| !data.is_empty()) {
+        // the function selector and the length of the calldata
+        summary.push(TxSummaryItem::new(
+            "Data",
+            format!(
+                "0x{} ({} bytes)",
+                hex::encode(&data[..data.len().min(4)]),
+                data.len()
+            ),
+        ));
+    }
+    summary
+}
+
+/// the human-readable summary of the `SignDoc` (after checking its chain ID)
+fn summarize_cosmos_sign_doc(
+    sign_doc: &[u8],
+    expected_chain_id: &str,
+) -> Result<Vec<TxSummaryItem>, TxEnvelopeError> {
+    let (chain_id, tx_body, auth_info) = parse_protobuf_sign_doc(sign_doc)?;
+    if chain_id != expected_chain_id {
+        return Err(TxEnvelopeError::ChainMismatch {
+            expected: expected_chain_id.to_owned(),
+            actual: chain_id,
+        });
+    }
+    let mut summary: Vec<TxSummaryItem> = tx_body
+        .messages
+        .iter()
+        .enumerate()
+        .map(|(i, msg)| TxSummaryItem::new(&format!("Message {}", i + 1), summarize_msg(msg)))
+        .collect();
+    summary.push(TxSummaryItem::new(
+        "Fee",
+        format_coins(&auth_info.fee.amount),
+    ));
+    summary.push(TxSummaryItem::new("Gas limit", auth_info.fee.gas_limit));
+    if !tx_body.memo.is_empty() {
+        summary.push(TxSummaryItem::new("Memo", tx_body.memo));
+    }
+    if tx_body.timeout_height > 0 {
+        summary.push(TxSummaryItem::new("Timeout height", tx_body.timeout_height));
+    }
+    Ok(summary)
+}
+
+/// A versioned envelope of an unsigned transaction (or `SignDoc`) of an EVM or Cosmos SDK chain,
+/// which is created online, signed on an offline device and finalized online.
+/// It is serialized in JSON or CBOR.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UnsignedTxEnvelope {
+    pub version: u32,
+    pub chain: TxEnvelopeChain,
+    pub signer: TxEnvelopeSigner,
+    pub payload: TxEnvelopePayload,
+    pub summary: Vec<TxSummaryItem>,
+}
+
+impl UnsignedTxEnvelope {
+    /// Create the envelope of the EVM transaction on the chain
+    pub fn from_eth_transaction(
+        mut tx: DynamicTransactionRequest,
+        chain_id: u64,
+        signer: TxEnvelopeSigner,
+    ) -> Result<Self, TxEnvelopeError> {
+        tx.chain_id = Some(chain_id.into());
+        let chain = TxEnvelopeChain::Evm { chain_id };
+        let payload = TxEnvelopePayload::EvmTransaction {
+            unsigned_tx: tx.to_type_tx().rlp(),
+        };
+        // the summary is built from the encoded transaction, as the offline device does
+        let summary = Self::summarize(&chain, &payload)?;
+        Ok(Self {
+            version: TX_ENVELOPE_VERSION,
+            chain,
+            signer,
+            payload,
+            summary,
+        })
+    }
+
+    /// Create the envelope of the `SignDoc` of the Cosmos SDK messages
+    /// (the online device only needs the public key of the signer)
+    pub fn from_cosmos_tx(
+        tx_info: CosmosSDKTxInfo,
+        msgs: Vec<CosmosSDKMsg>,
+        sender_pubkey: PublicKeyBytesWrapper,
+        signer: TxEnvelopeSigner,
+    ) -> Result<Self, TxEnvelopeError> {
+        let chain = TxEnvelopeChain::Cosmos {
+            chain_id: tx_info
+                .network
+                .get_chain_id()
+                .map_err(CosmosError::from)?
+                .to_string(),
+            bech32_prefix: tx_info.network.get_bech32_hrp().to_owned(),
+            coin_type: tx_info.network.get_coin_type(),
+        };
+        let sign_doc = get_msg_sign_payload(tx_info, msgs, sender_pubkey)?;
+        let payload = TxEnvelopePayload::CosmosSignDoc {
+            sign_doc: sign_doc.into(),
+        };
+        let summary = Self::summarize(&chain, &payload)?;
+        Ok(Self {
+            version: TX_ENVELOPE_VERSION,
+            chain,
+            signer,
+            payload,
+            summary,
+        })
+    }
+
+    /// Build the human-readable summary from the payload
+    fn summarize(
+        chain: &TxEnvelopeChain,
+        payload: &TxEnvelopePayload,
+    ) -> Result<Vec<TxSummaryItem>, TxEnvelopeError> {
+        match (chain, payload) {
+            (
+                TxEnvelopeChain::Evm { chain_id },
+                TxEnvelopePayload::EvmTransaction { unsigned_tx },
+            ) => {
+                let tx = decode_eth_transaction(unsigned_tx, *chain_id)?;
+                Ok(summarize_eth_transaction(&tx, *chain_id))
+            }
+            (
+                TxEnvelopeChain::Cosmos { chain_id, .. },
+                TxEnvelopePayload::CosmosSignDoc { sign_doc },
+            ) => summarize_cosmos_sign_doc(sign_doc, chain_id),
+            _ => Err(TxEnvelopeError::InvalidPayload(
+                "the payload does not match the chain".to_owned(),
+            )),
+        }
+    }
+
+    /// Rebuild the summary from the payload (on the offline device) and check it matches
+    /// the one of the envelope, so the summary shown to the user can be trusted.
+    /// Return the rebuilt summary.
+    pub fn verify_summary(&self) -> Result<Vec<TxSummaryItem>, TxEnvelopeError> {
+        let summary = Self::summarize(&self.chain, &self.payload)?;
+        if summary != self.summary {
+            return Err(TxEnvelopeError::SummaryMismatch);
+        }
+        Ok(summary)
+    }
+
+    /// the JSON string of the envelope
+    pub fn to_json(&self) -> Result<String, TxEnvelopeError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// the envelope from its JSON string
+    pub fn from_json(json: &str) -> Result<Self, TxEnvelopeError> {
+        let envelope: Self = serde_json::from_str(json)?;
+        check_version(envelope.version)?;
+        Ok(envelope)
+    }
+
+    /// the CBOR encoding of the envelope
+    pub fn to_cbor(&self) -> Result<Vec<u8>, TxEnvelopeError> {
+        to_cbor(self)
+    }
+
+    /// the envelope from its CBOR encoding
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, TxEnvelopeError> {
+        let envelope: Self = from_cbor(bytes)?;
+        check_version(envelope.version)?;
+        Ok(envelope)
+    }
+
+    fn check_address(&self, derived: String) -> Result<(), TxEnvelopeError> {
+        match &self.signer.address {
+            Some(expected) if !expected.eq_ignore_ascii_case(&derived) => {
+                Err(TxEnvelopeError::AddressMismatch {
+                    expected: expected.clone(),
+                    derived,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Sign the envelope on the offline device with the key of the derivation path of the signer.
+    /// The envelope is rejected if its summary or chain does not match the payload.
+    pub fn sign(&self, wallet: &HDWallet) -> Result<SignedTxEnvelope, TxEnvelopeError> {
+        let derivation_path = self
+            .signer
+            .derivation_path
+            .clone()
+            .ok_or(TxEnvelopeError::MissingDerivationPath)?;
+        self.verify_summary()?;
+        let secret_key = wallet.get_key(derivation_path.clone())?;
+        let (address, signature) = match (&self.chain, &self.payload) {
+            (
+                TxEnvelopeChain::Evm { chain_id },
+                TxEnvelopePayload::EvmTransaction { unsigned_tx },
+            ) => {
+                let signer =
+                    LocalWallet::from(secret_key.get_eth_signing_key()?).with_chain_id(*chain_id);
+                let address = to_checksum(&signer.address(), None);
+                self.check_address(address.clone())?;
+                let tx = decode_eth_transaction(unsigned_tx, *chain_id)?;
+                let signature = signer
+                    .sign_transaction_sync(&tx)
+                    .map_err(|e| TxEnvelopeError::SigningError(e.to_string()))?;
+                (address, TxEnvelopeSignature::Evm { signature })
+            }
+            (
+                TxEnvelopeChain::Cosmos { bech32_prefix, .. },
+                TxEnvelopePayload::CosmosSignDoc { sign_doc },
+            ) => {
+                let public_key = PublicKey::from(secret_key.get_signing_key().public_key());
+                let address = public_key
+                    .account_id(bech32_prefix)
+                    .map_err(HdWrapError::AccountId)?
+                    .to_string();
+                self.check_address(address.clone())?;
+                let signature = SigningKey::new(Box::new(secret_key.get_signing_key()))
+                    .sign(sign_doc)
+                    .map_err(|e| TxEnvelopeError::SigningError(e.to_string()))?;
+                (
+                    address,
+                    TxEnvelopeSignature::Cosmos {
+                        signature: signature.to_vec().into(),
+                        public_key: secret_key.get_public_key_bytes().into(),
+                    },
+                )
+            }
+            _ => {
+                return Err(TxEnvelopeError::InvalidPayload(
+                    "the payload does not match the chain".to_owned(),
+                ))
+            }
+        };
+        Ok(SignedTxEnvelope {
+            version: self.version,
+            chain: self.chain.clone(),
+            signer: TxEnvelopeSigner {
+                address: Some(address),
+                derivation_path: Some(derivation_path),
+            },
+            payload: self.payload.clone(),
+            signature,
+        })
+    }
+}
+
+/// A versioned envelope of a signed transaction, returned by the offline device
+/// with the address of the signer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SignedTxEnvelope {
+    pub version: u32,
+    pub chain: TxEnvelopeChain,
+    #[serde(default)]
+    pub signer: TxEnvelopeSigner,
+    pub payload: TxEnvelopePayload,
+    pub signature: TxEnvelopeSignature,
+}
+
+impl SignedTxEnvelope {
+    /// the JSON string of the envelope
+    pub fn to_json(&self) -> Result<String, TxEnvelopeError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// the envelope from its JSON string
+    pub fn from_json(json: &str) -> Result<Self, TxEnvelopeError> {
+        let envelope: Self = serde_json::from_str(json)?;
+        check_version(envelope.version)?;
+        Ok(envelope)
+    }
+
+    /// the CBOR encoding of the envelope
+    pub fn to_cbor(&self) -> Result<Vec<u8>, TxEnvelopeError> {
+        to_cbor(self)
+    }
+
+    /// the envelope from its CBOR encoding
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, TxEnvelopeError> {
+        let envelope: Self = from_cbor(bytes)?;
+        check_version(envelope.version)?;
+        Ok(envelope)
+    }
+
+    fn check_signer(&self, signer: String) -> Result<(), TxEnvelopeError> {
+        match &self.signer.address {
+            Some(expected) if !expected.eq_ignore_ascii_case(&signer) => {
+                Err(TxEnvelopeError::SignerMismatch {
+                    expected: expected.clone(),
+                    signer,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Assemble the signed transaction to be broadcast (online): the raw EVM transaction
+    /// or the protobuf encoding of the Cosmos SDK `TxRaw`.
+    /// The signature is verified against the payload and the signer's address
+    /// (and against the signer's public key in the `SignDoc` of Cosmos SDK transactions).
+    pub fn finalize(&self) -> Result<Vec<u8>, TxEnvelopeError> {
+        match (&self.chain, &self.payload, &self.signature) {
+            (
+                TxEnvelopeChain::Evm { chain_id },
+                TxEnvelopePayload::EvmTransaction { unsigned_tx },
+                TxEnvelopeSignature::Evm { signature },
+            ) => {
+                let tx = decode_eth_transaction(unsigned_tx, *chain_id)?;
+                let signer = signature
+                    .recover(tx.sighash())
+                    .map_err(|_| TxEnvelopeError::InvalidSignature)?;
+                self.check_signer(to_checksum(&signer, None))?;
+                Ok(tx.rlp_signed(signature).to_vec())
+            }
+            (
+                TxEnvelopeChain::Cosmos { bech32_prefix, .. },
+                TxEnvelopePayload::CosmosSignDoc {
+                    sign_doc: sign_doc_bytes,
+                },
+                TxEnvelopeSignature::Cosmos {
+                    signature,
+                    public_key,
+                },
+            ) => {
+                let sign_doc = SignDoc::decode(sign_doc_bytes.as_ref())
+                    .map_err(|e| TxEnvelopeError::InvalidPayload(e.to_string()))?;
+                let auth_info = AuthInfo::decode(sign_doc.auth_info_bytes.as_slice())
+                    .map_err(|e| TxEnvelopeError::InvalidPayload(e.to_string()))?;
+                let signer_key = auth_info
+                    .signer_infos
+                    .first()
+                    .and_then(|signer_info| signer_info.public_key.as_ref())
+                    .ok_or_else(|| {
+                        TxEnvelopeError::InvalidPayload(
+                            "missing the public key of the signer".to_owned(),
+                        )
+                    })
+                    .and_then(|any| {
+                        PubKey::decode(any.value.as_slice())
+                            .map_err(|e| TxEnvelopeError::InvalidPayload(e.to_string()))
+                    })?
+                    .key;
+                if signer_key[..] != public_key[..] {
+                    return Err(TxEnvelopeError::SignerMismatch {
+                        expected: hex::encode(&signer_key),
+                        signer: hex::encode(public_key),
+                    });
+                }
+                let verifying_key = VerifyingKey::from_sec1_bytes(public_key)
+                    .map_err(|_| TxEnvelopeError::InvalidSignature)?;
+                let ecdsa_signature = EcdsaSignature::from_slice(signature)
+                    .map_err(|_| TxEnvelopeError::InvalidSignature)?;
+                verifying_key
+                    .verify(sign_doc_bytes, &ecdsa_signature)
+                    .map_err(|_| TxEnvelopeError::InvalidSignature)?;
+                let address = secp256k1::VerifyingKey::from_sec1_bytes(public_key)
+                    .map(PublicKey::from)
+                    .map_err(|_| TxEnvelopeError::InvalidSignature)?
+                    .account_id(bech32_prefix)
+                    .map_err(HdWrapError::AccountId)?;
+                self.check_signer(address.to_string())?;
+                Ok(TxRaw {
+                    body_bytes: sign_doc.body_bytes,
+                    auth_info_bytes: sign_doc.auth_info_bytes,
+                    signatures: vec![signature.to_vec()],
+                }
+                .encode_to_vec())
+            }
+            _ => Err(TxEnvelopeError::InvalidPayload(
+                "the signature does not match the payload".to_owned(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_signed_msg_tx, eth_sign_transaction_with_chainid, Network};
+
+    const MNEMONIC: &str = "apple elegant knife hawk there screen vehicle lounge tube sun engage bus custom market pioneer casual wink present cat metal ride shallow fork brief";
+
+    const TX: &str = r#"{
+        "to": "0x2c600e0a72b3ae39e9b27d2e310b180abe779368",
+        "gas": "0x5208",
+        "maxFeePerGas": "0x12a05f200",
+        "maxPriorityFeePerGas": "0x3b9aca00",
+        "nonce": "0x1",
+        "value": "0xde0b6b3a7640000"
+    }"#;
+
+    fn tx_info() -> CosmosSDKTxInfo {
+        CosmosSDKTxInfo {
+            account_number: 1,
+            sequence_number: 0,
+            gas_limit: 100_000,
+            timeout_height: 9001,
+            fee_amount: SingleCoin::ATOM { amount: 1 },
+            memo_note: Some("offline".to_owned()),
+            network: Network::CosmosHub,
+        }
+    }
+
+    fn msg() -> CosmosSDKMsg {
+        CosmosSDKMsg::BankSend {
+            recipient_address: "cosmos1l5s7tnj28a7zxeeckhgwlhjys8dlrrefgqr4pj".to_owned(),
+            amount: SingleCoin::ATOM { amount: 100 },
+        }
+    }
+
+    fn envelope_sign_doc(envelope: &SignedTxEnvelope) -> Vec<u8> {
+        match &envelope.payload {
+            TxEnvelopePayload::CosmosSignDoc { sign_doc } => sign_doc.to_vec(),
+            _ => panic!("not a SignDoc"),
+        }
+    }
+
+    #[test]
+    fn test_eth_envelope() {
+        let wallet = HDWallet::recover_wallet(MNEMONIC.to_string(), None).unwrap();
+        let secret_key = wallet.get_key("m/44'/60'/0'/0/0".to_string()).unwrap();
+        let tx: DynamicTransactionRequest = serde_json::from_str(TX).unwrap();
+        let envelope = UnsignedTxEnvelope::from_eth_transaction(
+            tx,
+            25,
+            TxEnvelopeSigner {
+                address: None,
+                derivation_path: Some("m/44'/60'/0'/0/0".to_owned()),
+            },
+        )
+        .unwrap();
+        assert!(envelope
+            .summary
+            .contains(&TxSummaryItem::new("Value", "1.000000000000000000")));
+
+        // online -> offline (JSON) -> online (CBOR)
+        let offline = UnsignedTxEnvelope::from_json(&envelope.to_json().unwrap()).unwrap();
+        assert_eq!(offline, envelope);
+        let signed = offline.sign(&wallet).unwrap();
+        let online = SignedTxEnvelope::from_cbor(&signed.to_cbor().unwrap()).unwrap();
+        assert_eq!(online, signed);
+        assert_eq!(
+            online.finalize().unwrap(),
+            eth_sign_transaction_with_chainid(TX, secret_key, 25).unwrap()
+        );
+
+        // a signature over a different payload is rejected
+        let mut tx: DynamicTransactionRequest = serde_json::from_str(TX).unwrap();
+        tx.nonce = Some(2.into());
+        let other_signed =
+            UnsignedTxEnvelope::from_eth_transaction(tx, 25, envelope.signer.clone())
+                .unwrap()
+                .sign(&wallet)
+                .unwrap();
+        let mut forged = signed.clone();
+        forged.signature = other_signed.signature;
+        assert!(matches!(
+            forged.finalize(),
+            Err(TxEnvelopeError::SignerMismatch { .. })
+        ));
+
+        let mut other = envelope.clone();
+        other.signer.address = Some("0x2c600e0a72b3ae39e9b27d2e310b180abe779368".to_owned());
+        assert!(matches!(
+            other.sign(&wallet),
+            Err(TxEnvelopeError::AddressMismatch { .. })
+        ));
+        other.signer.derivation_path = None;
+        assert!(matches!(
+            other.sign(&wallet),
+            Err(TxEnvelopeError::MissingDerivationPath)
+        ));
+        let mut tampered = envelope.clone();
+        tampered.summary[2] = TxSummaryItem::new("Value", "0.000000000000000001");
+        assert!(matches!(
+            tampered.sign(&wallet),
+            Err(TxEnvelopeError::SummaryMismatch)
+        ));
+        assert_eq!(envelope.verify_summary().unwrap(), envelope.summary);
+
+        let mut json: serde_json::Value = serde_json::to_value(&envelope).unwrap();
+        json["version"] = (TX_ENVELOPE_VERSION + 1).into();
+        assert!(matches!(
+            UnsignedTxEnvelope::from_json(&json.to_string()),
+            Err(TxEnvelopeError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_cosmos_envelope() {
+        let wallet = HDWallet::recover_wallet(MNEMONIC.to_string(), None).unwrap();
+        let secret_key = wallet.get_key("m/44'/118'/0'/0/0".to_string()).unwrap();
+        let envelope = UnsignedTxEnvelope::from_cosmos_tx(
+            tx_info(),
+            vec![msg()],
+            PublicKeyBytesWrapper(secret_key.get_public_key_bytes()),
+            TxEnvelopeSigner {
+                address: Some("cosmos1l5s7tnj28a7zxeeckhgwlhjys8dlrrefgqr4pj".to_owned()),
+                derivation_path: Some("m/44'/118'/0'/0/0".to_owned()),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            envelope.chain,
+            TxEnvelopeChain::Cosmos {
+                chain_id: "cosmoshub-4".to_owned(),
+                bech32_prefix: "cosmos".to_owned(),
+                coin_type: 118,
+            }
+        );
+        assert_eq!(
+            envelope.summary[0].value,
+            "send 100000000uatom to cosmos1l5s7tnj28a7zxeeckhgwlhjys8dlrrefgqr4pj"
+        );
+
+        let offline = UnsignedTxEnvelope::from_cbor(&envelope.to_cbor().unwrap()).unwrap();
+        let signed = offline.sign(&wallet).unwrap();
+        let online = SignedTxEnvelope::from_json(&signed.to_json().unwrap()).unwrap();
+        assert_eq!(
+            online.finalize().unwrap(),
+            build_signed_msg_tx(tx_info(), vec![msg()], secret_key).unwrap()
+        );
+
+        // a signature over a different payload is rejected
+        let mut other_info = tx_info();
+        other_info.sequence_number = 1;
+        let other_signed = UnsignedTxEnvelope::from_cosmos_tx(
+            other_info,
+            vec![msg()],
+            PublicKeyBytesWrapper(secret_key.get_public_key_bytes()),
+            envelope.signer.clone(),
+        )
+        .unwrap()
+        .sign(&wallet)
+        .unwrap();
+        let mut forged = signed.clone();
+        forged.signature = other_signed.signature;
+        assert!(matches!(
+            forged.finalize(),
+            Err(TxEnvelopeError::InvalidSignature)
+        ));
+        // so is a signature of another key
+        let other_key = wallet.get_key("m/44'/118'/0'/0/1".to_string()).unwrap();
+        let mut forged = signed.clone();
+        forged.signature = TxEnvelopeSignature::Cosmos {
+            signature: SigningKey::new(Box::new(other_key.get_signing_key()))
+                .sign(&envelope_sign_doc(&signed))
+                .unwrap()
+                .to_vec()
+                .into(),
+            public_key: other_key.get_public_key_bytes().into(),
+        };
+        assert!(matches!(
+            forged.finalize(),
+            Err(TxEnvelopeError::SignerMismatch { .. })
+        ));
+        let mut tampered = envelope.clone();
+        tampered.summary[0] = TxSummaryItem::new(
+            "Message 1",
+            "send 1uatom to cosmos1l5s7tnj28a7zxeeckhgwlhjys8dlrrefgqr4pj",
+        );
+        assert!(matches!(
+            tampered.sign(&wallet),
+            Err(TxEnvelopeError::SummaryMismatch)
+        ));
+
+        let mut tampered = envelope;
+        tampered.chain = TxEnvelopeChain::Cosmos {
+            chain_id: "theta-testnet-001".to_owned(),
+            bech32_prefix: "cosmos".to_owned(),
+            coin_type: 118,
+        };
+        assert!(matches!(
+            tampered.sign(&wallet),
+            Err(TxEnvelopeError::ChainMismatch { .. })
+        ));
+    }
+}
@@ -12,13 +12,18 @@ use std::default::Default;
 use std::str::FromStr;
 use std::sync::Arc;
 mod abi_contract;
+mod decoder;
 mod error;
+mod signature;
 mod signer;
 use ethers::types::Signature;
 
 #[cfg(feature = "abi-contract")]
 pub use abi_contract::*;
+#[cfg(feature = "abi-contract")]
+pub use decoder::*;
 pub use error::*;
+pub use signature::*;
 #[cfg(feature = "abi-contract")]
 pub use signer::*;
 
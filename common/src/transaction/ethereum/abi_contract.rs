@@ -1,8 +1,10 @@
 #![cfg(feature = "abi-contract")]
 
-use crate::node::ethereum::abi::EthAbiToken;
-use crate::EthError;
-use ethers::prelude::abi::{Contract, Token};
+use crate::contract::decode_revert_reason;
+use crate::node::ethereum::abi::{parse_human_readable_abi, EthAbiToken};
+use crate::{EthDecodedParam, EthError};
+use ethers::prelude::abi::{Contract, Error, RawLog, Token};
+use ethers::prelude::H256;
 use serde::{Deserialize, Serialize};
 /// Ethereum ABI token to ffi bind
 // enable serde serialization
@@ -50,6 +52,14 @@ impl TryFrom<&EthAbiTokenBind> for EthAbiToken {
     }
 }
 
+/// An event log decoded against the contract ABI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthDecodedEvent {
+    /// the event name, e.g. "Transfer"
+    pub name: String,
+    pub params: Vec<EthDecodedParam>,
+}
+
 /// Ethereum ABI Contract
 pub struct EthAbiContract {
     contract: Contract,
@@ -64,6 +74,14 @@ impl EthAbiContract {
         })
     }
 
+    /// Create an instance via human-readable ABI fragments, e.g.
+    /// `function transfer(address to, uint256 amount) returns (bool)`.
+    pub fn from_human_readable(fragments: Vec<String>) -> Result<Self, EthError> {
+        Ok(Self {
+            contract: parse_human_readable_abi(&fragments)?,
+        })
+    }
+
     /// Encode input data of specified function and arguments. The encoded data
     /// should be set to field data of EthTxInfo when invoking function
     /// build_signed_eth_tx.
@@ -95,6 +113,66 @@ impl EthAbiContract {
             .collect::<Result<Vec<EthAbiToken>, _>>()?;
         self.encode(function_name, tokens)
     }
+
+    /// Decode the output data (returned by `eth_call`) of the specified function.
+    pub fn decode_output(
+        &self,
+        function_name: &str,
+        data: &[u8],
+    ) -> Result<Vec<EthAbiTokenBind>, EthError> {
+        let function = self.contract.function(function_name)?;
+        Ok(function
+            .decode_output(data)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Decode the event log given its topics (32 bytes each) and data.
+    /// The event is found by its signature in the first topic
+    /// (or by trying the anonymous events).
+    pub fn decode_event_log(
+        &self,
+        topics: Vec<Vec<u8>>,
+        data: &[u8],
+    ) -> Result<EthDecodedEvent, EthError> {
+        if topics.iter().any(|topic| topic.len() != 32) {
+            return Err(Error::InvalidData.into());
+        }
+        let topics: Vec<H256> = topics.iter().map(|topic| H256::from_slice(topic)).collect();
+        self.contract
+            .events()
+            .filter(|event| event.anonymous || topics.first() == Some(&event.signature()))
+            .find_map(|event| {
+                let log = event
+                    .parse_log(RawLog {
+                        topics: topics.clone(),
+                        data: data.to_vec(),
+                    })
+                    .ok()?;
+                let params = event
+                    .inputs
+                    .iter()
+                    .zip(log.params)
+                    .map(|(input, param)| EthDecodedParam {
+                        name: param.name,
+                        param_type: input.kind.to_string(),
+                        value: param.value.into(),
+                    })
+                    .collect();
+                Some(EthDecodedEvent {
+                    name: event.name.clone(),
+                    params,
+                })
+            })
+            .ok_or_else(|| Error::InvalidData.into())
+    }
+
+    /// Decode the revert data into a readable message:
+    /// `Error(string)`, `Panic(uint256)` or one of the custom errors declared in the ABI.
+    pub fn decode_error(&self, data: &[u8]) -> Option<String> {
+        decode_revert_reason(data, Some(&self.contract))
+    }
 }
 
 #[cfg(test)]
@@ -334,4 +412,88 @@ mod tests {
             "f8ae808203e8825208944592d8f8d7b001e72cb26a73e4fa1806a51ac79d880de0b6b3a7640000b844a22cb4650000000000000000000000002c600e0a72b3ae39e9b27d2e310b180abe779368000000000000000000000000000000000000000000000000000000000000000126a0baba2566fac3a7cbd4e181e9f7cad3953d2c6b1831662dc44f96cf0ee39a119ba005dbe5ede09fb98d540cf390c88b98d11d86c617c411d502f9acebe3cb4bcbbe",
         );
     }
+
+    #[test]
+    fn abi_contract_decoding_test() {
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let mut contract_file_path = PathBuf::new();
+        contract_file_path.push(dir);
+        contract_file_path.push("src/contract/erc20-abi.json");
+        let abi_contract_str = std::fs::read_to_string(contract_file_path).unwrap();
+        let abi_contract = EthAbiContract::new(&abi_contract_str).unwrap();
+
+        // Decode the function output.
+        let amount = Token::Uint(100.into());
+        let output = ethers::abi::encode(&[amount.clone()]);
+        assert_eq!(
+            abi_contract.decode_output("balanceOf", &output).unwrap(),
+            vec![EthAbiTokenBind::Uint {
+                data: "100".to_owned()
+            }]
+        );
+
+        // Decode the event log with the indexed addresses in the topics.
+        let from = H256::from_low_u64_be(1);
+        let to = H256::from_low_u64_be(2);
+        let signature = abi_contract.contract.event("Transfer").unwrap().signature();
+        let event = abi_contract
+            .decode_event_log(
+                vec![
+                    signature.as_bytes().to_vec(),
+                    from.as_bytes().to_vec(),
+                    to.as_bytes().to_vec(),
+                ],
+                &output,
+            )
+            .unwrap();
+        assert_eq!(event.name, "Transfer");
+        assert_eq!(event.params.len(), 3);
+        assert_eq!(event.params[0].param_type, "address");
+        assert_eq!(
+            event.params[1].value,
+            EthAbiTokenBind::Address {
+                data: "0x0000000000000000000000000000000000000002".to_owned()
+            }
+        );
+        assert_eq!(
+            event.params[2].value,
+            EthAbiTokenBind::Uint {
+                data: "100".to_owned()
+            }
+        );
+        assert!(abi_contract
+            .decode_event_log(vec![from.as_bytes().to_vec()], &output)
+            .is_err());
+
+        // Decode the revert reason.
+        let mut revert = hex::decode("08c379a0").unwrap();
+        revert.extend(ethers::abi::encode(&[Token::String(
+            "ERC20: transfer amount exceeds balance".to_owned(),
+        )]));
+        assert_eq!(
+            abi_contract.decode_error(&revert).as_deref(),
+            Some("ERC20: transfer amount exceeds balance")
+        );
+    }
+
+    #[test]
+    fn abi_contract_human_readable_test() {
+        let abi_contract = EthAbiContract::from_human_readable(vec![
+            "function transfer(address to, uint256 amount) returns (bool)".to_owned(),
+        ])
+        .unwrap();
+        let tokens = vec![
+            EthAbiToken::from_address_str("0x2c600e0a72b3ae39e9b27d2e310b180abe779368").unwrap(),
+            EthAbiToken::from_uint_str("100").unwrap(),
+        ];
+        assert_eq!(
+            hex::encode(abi_contract.encode("transfer", tokens).unwrap()),
+            "a9059cbb0000000000000000000000002c600e0a72b3ae39e9b27d2e310b180abe7793680000000000000000000000000000000000000000000000000000000000000064"
+        );
+        let output = ethers::abi::encode(&[Token::Bool(true)]);
+        assert_eq!(
+            abi_contract.decode_output("transfer", &output).unwrap(),
+            vec![EthAbiTokenBind::Bool { data: true }]
+        );
+    }
 }
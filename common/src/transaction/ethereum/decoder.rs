@@ -0,0 +1,356 @@
+#![cfg(feature = "abi-contract")]
+
+use crate::{DynamicTransactionRequest, EthAbiTokenBind, EthError};
+use ethers::prelude::abi::{Contract, Function, Token};
+use ethers::prelude::{NameOrAddress, TransactionRequest, I256};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::{hex, rlp::Rlp, to_checksum};
+use serde::{Deserialize, Serialize};
+
+/// the ABIs of the common token contracts, used for decoding the calldata
+const BUNDLED_ABIS: [&str; 4] = [
+    include_str!("../../contract/erc20-abi.json"),
+    include_str!("../../contract/erc721-abi.json"),
+    include_str!("../../contract/erc1155-abi.json"),
+    include_str!("../../contract/erc4907-abi.json"),
+];
+
+/// A decoded parameter of the function call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthDecodedParam {
+    /// the parameter name in the ABI (can be empty)
+    pub name: String,
+    /// the Solidity type, e.g. "uint256"
+    pub param_type: String,
+    pub value: EthAbiTokenBind,
+}
+
+/// A function call decoded from the transaction data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthDecodedCall {
+    /// the function name, e.g. "transfer"
+    pub function_name: String,
+    /// the function signature, e.g. "transfer(address,uint256)"
+    pub signature: String,
+    /// the 4-byte function selector in hex (with the 0x prefix)
+    pub selector: String,
+    pub params: Vec<EthDecodedParam>,
+}
+
+/// The human-readable details of the transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthDecodedTx {
+    /// "legacy", "eip2930" or "eip1559"
+    pub tx_type: String,
+    pub chain_id: Option<u64>,
+    /// the nonce as a decimal string
+    pub nonce: Option<String>,
+    /// the sender address (recovered from the signature if the transaction is signed)
+    pub from: Option<String>,
+    /// the recipient address or ENS name (none for a contract creation)
+    pub to: Option<String>,
+    /// the transferred value in wei as a decimal string
+    pub value: String,
+    /// the gas limit as a decimal string
+    pub gas_limit: Option<String>,
+    /// the gas price in wei of legacy and EIP-2930 transactions
+    pub gas_price: Option<String>,
+    /// the max fee per gas in wei of EIP-1559 transactions
+    pub max_fee_per_gas: Option<String>,
+    /// the max priority fee per gas in wei of EIP-1559 transactions
+    pub max_priority_fee_per_gas: Option<String>,
+    pub data: Vec<u8>,
+    pub signed: bool,
+    /// the decoded function call if the data matches one of the known ABIs
+    pub call: Option<EthDecodedCall>,
+}
+
+impl From<Token> for EthAbiTokenBind {
+    fn from(token: Token) -> Self {
+        let tokens = |tokens: Vec<Token>| tokens.into_iter().map(Into::into).collect();
+        match token {
+            Token::Address(data) => EthAbiTokenBind::Address {
+                data: to_checksum(&data, None),
+            },
+            Token::FixedBytes(data) => EthAbiTokenBind::FixedBytes { data },
+            Token::Bytes(data) => EthAbiTokenBind::Bytes { data },
+            Token::Int(data) => EthAbiTokenBind::Int {
+                data: I256::from_raw(data).to_string(),
+            },
+            Token::Uint(data) => EthAbiTokenBind::Uint {
+                data: data.to_string(),
+            },
+            Token::Bool(data) => EthAbiTokenBind::Bool { data },
+            Token::String(data) => EthAbiTokenBind::Str { data },
+            Token::FixedArray(data) => EthAbiTokenBind::FixedArray { data: tokens(data) },
+            Token::Array(data) => EthAbiTokenBind::Array { data: tokens(data) },
+            Token::Tuple(data) => EthAbiTokenBind::Tuple { data: tokens(data) },
+        }
+    }
+}
+
+/// the signature without the outputs, e.g. "transfer(address,uint256)"
+fn function_signature(function: &Function) -> String {
+    let inputs = function
+        .inputs
+        .iter()
+        .map(|param| param.kind.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{}({})", function.name, inputs)
+}
+
+/// if the RLP is an unsigned EIP-155 legacy transaction, i.e.
+/// `[nonce, gasPrice, gas, to, value, data, chainId, 0, 0]` (r == s == 0)
+fn is_unsigned_eip155(rlp: &Rlp) -> bool {
+    let is_zero = |index| {
+        rlp.at(index)
+            .and_then(|item| item.data().map(|data| data.iter().all(|byte| *byte == 0)))
+            .unwrap_or(false)
+    };
+    rlp.is_list() && rlp.item_count() == Ok(9) && is_zero(7) && is_zero(8)
+}
+
+/// Decoder of EVM transactions and their calldata
+/// against the bundled token ABIs and the user-supplied ones
+pub struct EthTxDecoder {
+    abis: Vec<Contract>,
+}
+
+impl EthTxDecoder {
+    /// Create the decoder with the user-supplied ABI contracts
+    /// (they take precedence over the bundled ERC-20/721/1155/4907 ones)
+    pub fn new(abi_contracts: Vec<String>) -> Result<Self, EthError> {
+        let abis = abi_contracts
+            .iter()
+            .map(String::as_str)
+            .chain(BUNDLED_ABIS)
+            .map(|abi_contract| Contract::load(abi_contract.as_bytes()))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { abis })
+    }
+
+    /// Decode the calldata into the function name and parameters;
+    /// none if it doesn't match any of the known ABIs
+    pub fn decode_calldata(&self, data: &[u8]) -> Option<EthDecodedCall> {
+        if data.len() < 4 {
+            return None;
+        }
+        let (selector, params) = data.split_at(4);
+        self.abis
+            .iter()
+            .flat_map(|abi| abi.functions())
+            .filter(|function| function.short_signature() == selector)
+            // functions with the same selector may have different parameter names
+            .find_map(|function| {
+                let tokens = function.decode_input(params).ok()?;
+                let params = function
+                    .inputs
+                    .iter()
+                    .zip(tokens)
+                    .map(|(param, token)| EthDecodedParam {
+                        name: param.name.clone(),
+                        param_type: param.kind.to_string(),
+                        value: token.into(),
+                    })
+                    .collect();
+                Some(EthDecodedCall {
+                    function_name: function.name.clone(),
+                    signature: function_signature(function),
+                    selector: format!("0x{}", hex::encode(selector)),
+                    params,
+                })
+            })
+    }
+
+    /// Decode the raw signed or unsigned transaction bytes
+    pub fn decode_raw_tx(&self, raw_tx: &[u8]) -> Result<EthDecodedTx, EthError> {
+        let rlp = Rlp::new(raw_tx);
+        if is_unsigned_eip155(&rlp) {
+            // the chain ID is in the place of `v`, which can't be recovered
+            let tx = TransactionRequest::decode_unsigned_rlp(&rlp)
+                .map_err(|e| EthError::TxDecodeError(e.to_string()))?;
+            return Ok(self.decode_typed_tx(&TypedTransaction::Legacy(tx), false));
+        }
+        match TypedTransaction::decode_signed(&rlp) {
+            Ok((mut tx, signature)) => {
+                let from = signature
+                    .recover(tx.sighash())
+                    .map_err(|e| EthError::TxDecodeError(e.to_string()))?;
+                tx.set_from(from);
+                Ok(self.decode_typed_tx(&tx, true))
+            }
+            Err(_) => {
+                let tx = rlp
+                    .as_val::<TypedTransaction>()
+                    .map_err(|e| EthError::TxDecodeError(e.to_string()))?;
+                Ok(self.decode_typed_tx(&tx, false))
+            }
+        }
+    }
+
+    /// Decode the transaction request in JSON (as used by `eth_sign_transaction`)
+    pub fn decode_tx_request(&self, json_str: &str) -> Result<EthDecodedTx, EthError> {
+        let tx: DynamicTransactionRequest =
+            serde_json::from_str(json_str).map_err(EthError::JsonError)?;
+        Ok(self.decode_typed_tx(&tx.to_type_tx(), false))
+    }
+
+    /// Decode the typed transaction
+    pub fn decode_typed_tx(&self, tx: &TypedTransaction, signed: bool) -> EthDecodedTx {
+        let (tx_type, gas_price, max_fee_per_gas, max_priority_fee_per_gas) = match tx {
+            TypedTransaction::Legacy(tx) => ("legacy", tx.gas_price, None, None),
+            TypedTransaction::Eip2930(tx) => ("eip2930", tx.tx.gas_price, None, None),
+            TypedTransaction::Eip1559(tx) => (
+                "eip1559",
+                None,
+                tx.max_fee_per_gas,
+                tx.max_priority_fee_per_gas,
+            ),
+        };
+        let data = tx.data().map(|data| data.to_vec()).unwrap_or_default();
+        EthDecodedTx {
+            tx_type: tx_type.to_owned(),
+            chain_id: tx.chain_id().map(|chain_id| chain_id.as_u64()),
+            nonce: tx.nonce().map(ToString::to_string),
+            from: tx.from().map(|from| to_checksum(from, None)),
+            to: tx.to().map(|to| match to {
+                NameOrAddress::Name(name) => name.clone(),
+                NameOrAddress::Address(address) => to_checksum(address, None),
+            }),
+            value: tx.value().cloned().unwrap_or_default().to_string(),
+            gas_limit: tx.gas().map(ToString::to_string),
+            gas_price: gas_price.map(|fee| fee.to_string()),
+            max_fee_per_gas: max_fee_per_gas.map(|fee| fee.to_string()),
+            max_priority_fee_per_gas: max_priority_fee_per_gas.map(|fee| fee.to_string()),
+            call: self.decode_calldata(&data),
+            data,
+            signed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_signed_eth_tx, EthAmount, EthNetwork, EthTxInfo, SecretKey};
+    use ethers::prelude::Address;
+    use std::sync::Arc;
+
+    const TRANSFER_DATA: &str = "a9059cbb0000000000000000000000002c600e0a72b3ae39e9b27d2e310b180abe7793680000000000000000000000000000000000000000000000000000000000000064";
+
+    #[test]
+    fn erc20_transfer_is_decoded() {
+        let decoder = EthTxDecoder::new(vec![]).unwrap();
+        let call = decoder
+            .decode_calldata(&hex::decode(TRANSFER_DATA).unwrap())
+            .unwrap();
+        assert_eq!(call.function_name, "transfer");
+        assert_eq!(call.signature, "transfer(address,uint256)");
+        assert_eq!(call.selector, "0xa9059cbb");
+        assert_eq!(call.params.len(), 2);
+        assert_eq!(call.params[0].param_type, "address");
+        match &call.params[0].value {
+            EthAbiTokenBind::Address { data } => assert_eq!(
+                data.to_lowercase(),
+                "0x2c600e0a72b3ae39e9b27d2e310b180abe779368"
+            ),
+            value => panic!("unexpected value {value:?}"),
+        }
+        assert_eq!(
+            call.params[1].value,
+            EthAbiTokenBind::Uint {
+                data: "100".to_owned()
+            }
+        );
+        assert!(decoder.decode_calldata(&[0xde, 0xad, 0xbe, 0xef]).is_none());
+    }
+
+    #[test]
+    fn user_abi_is_used() {
+        let abi = r#"[{"inputs":[{"name":"delta","type":"int256"}],"name":"adjust","outputs":[],"stateMutability":"nonpayable","type":"function"}]"#;
+        let decoder = EthTxDecoder::new(vec![abi.to_owned()]).unwrap();
+        let data = decoder.abis[0]
+            .function("adjust")
+            .unwrap()
+            .encode_input(&[Token::Int(I256::from(-5).into_raw())])
+            .unwrap();
+        let call = decoder.decode_calldata(&data).unwrap();
+        assert_eq!(call.params[0].name, "delta");
+        assert_eq!(
+            call.params[0].value,
+            EthAbiTokenBind::Int {
+                data: "-5".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn signed_tx_is_decoded() {
+        let secret_key = SecretKey::from_hex(
+            "24e585759e492f5e810607c82c202476c22c5876b10247ebf8b2bb7f75dbed2e".to_owned(),
+        )
+        .unwrap();
+        let tx_info = EthTxInfo {
+            to_address: "0x2c600e0a72b3ae39e9b27d2e310b180abe779368".to_owned(),
+            amount: EthAmount::EthDecimal {
+                amount: "0".to_owned(),
+            },
+            nonce: "3".to_owned(),
+            gas_limit: "60000".to_owned(),
+            gas_price: EthAmount::WeiDecimal {
+                amount: "1000".to_owned(),
+            },
+            data: Some(hex::decode(TRANSFER_DATA).unwrap()),
+            legacy_tx: true,
+        };
+        let raw_tx =
+            build_signed_eth_tx(tx_info, EthNetwork::Cronos, Arc::new(secret_key)).unwrap();
+        let decoder = EthTxDecoder::new(vec![]).unwrap();
+        let tx = decoder.decode_raw_tx(&raw_tx).unwrap();
+        assert!(tx.signed);
+        assert_eq!(tx.tx_type, "legacy");
+        assert_eq!(tx.chain_id, Some(25));
+        assert_eq!(tx.nonce.as_deref(), Some("3"));
+        assert_eq!(tx.gas_price.as_deref(), Some("1000"));
+        assert!(tx.from.is_some());
+        assert_eq!(tx.call.unwrap().function_name, "transfer");
+    }
+
+    #[test]
+    fn unsigned_eip155_tx_is_decoded() {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to("0x2c600e0a72b3ae39e9b27d2e310b180abe779368"
+                .parse::<Address>()
+                .unwrap())
+            .nonce(7u64)
+            .gas(60000u64)
+            .gas_price(1000u64)
+            .data(hex::decode(TRANSFER_DATA).unwrap())
+            .chain_id(25u64)
+            .into();
+        // encoded as `[..., chainId, 0, 0]`
+        let raw_tx = tx.rlp();
+        let decoder = EthTxDecoder::new(vec![]).unwrap();
+        let tx = decoder.decode_raw_tx(&raw_tx).unwrap();
+        assert!(!tx.signed);
+        assert_eq!(tx.tx_type, "legacy");
+        assert_eq!(tx.chain_id, Some(25));
+        assert_eq!(tx.nonce.as_deref(), Some("7"));
+        assert_eq!(tx.from, None);
+        assert_eq!(tx.call.unwrap().function_name, "transfer");
+    }
+
+    #[test]
+    fn tx_request_is_decoded() {
+        let json_str = r#"{"from":"0x68418d0fdb846e8736aa613159035a9d9fde11f0","to":"0x4592d8f8d7b001e72cb26a73e4fa1806a51ac79d","gas":"0x5208","value":"0xde0b6b3a7640000","data":"0x","nonce":"0x0","maxPriorityFeePerGas":"0x1","maxFeePerGas":"0x77359401","chainId":"0x0539"}"#;
+        let decoder = EthTxDecoder::new(vec![]).unwrap();
+        let tx = decoder.decode_tx_request(json_str).unwrap();
+        assert!(!tx.signed);
+        assert_eq!(tx.tx_type, "eip1559");
+        assert_eq!(tx.chain_id, Some(1337));
+        assert_eq!(tx.value, "1000000000000000000");
+        assert_eq!(tx.max_fee_per_gas.as_deref(), Some("2000000001"));
+        assert!(tx.call.is_none());
+    }
+}
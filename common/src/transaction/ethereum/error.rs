@@ -64,6 +64,24 @@ pub enum EthError {
     ClientError(reqwest::Error),
     #[error("Cannot set http agent")]
     HttpAgentError,
+    #[error("Fee estimation failed: {0}")]
+    FeeEstimationError(ProviderError),
+    #[error("Cannot replace the transaction: {0}")]
+    ReplacementError(String),
+    #[error("Cannot get the account nonce: {0}")]
+    NonceError(ProviderError),
+    #[error("Cannot decode the transaction: {0}")]
+    TxDecodeError(String),
+    #[error("Cannot get the logs: {0}")]
+    GetLogsError(ProviderError),
+    #[error("Batched call failed: {0}")]
+    MulticallError(String),
+    #[error("Cannot resolve the name: {0}")]
+    NameResolutionError(String),
+    #[error("Cannot build the permit: {0}")]
+    PermitError(String),
+    #[error("Invalid payment request: {0}")]
+    PaymentRequestError(String),
 }
 
 /// EIP-712 related errors
@@ -0,0 +1,241 @@
+use crate::transaction::ethereum::EthError;
+use crate::utils::{hash_from_str, hex_decode};
+use ethers::prelude::{Address, Signature, H256, U256};
+use ethers::utils::{hash_message, hex, to_checksum};
+use std::str::FromStr;
+
+/// the order of the secp256k1 curve
+const SECP256K1_N: U256 = U256([
+    0xbfd25e8cd0364141,
+    0xbaaedce6af48a03b,
+    0xfffffffffffffffe,
+    0xffffffffffffffff,
+]);
+
+/// Parse the signature from its hex string, either the 65-byte `r || s || v` form
+/// or the 64-byte compact `r || yParityAndS` form of
+/// [EIP-2098](https://eips.ethereum.org/EIPS/eip-2098).
+/// The `v` value is normalized to 27 or 28 (from 0 or 1, or an EIP-155 value).
+pub fn parse_eth_signature(signature: &str) -> Result<Signature, EthError> {
+    let bytes = hex_decode(signature).map_err(|_| EthError::HexConversion)?;
+    let (r, s, v) = match bytes.len() {
+        65 => {
+            let v = normalize_eth_signature_v(bytes[64] as u64)?;
+            (
+                U256::from_big_endian(&bytes[0..32]),
+                U256::from_big_endian(&bytes[32..64]),
+                v,
+            )
+        }
+        64 => {
+            // the highest bit of `s` is the y parity
+            let mut s = [0u8; 32];
+            s.copy_from_slice(&bytes[32..64]);
+            let y_parity = s[0] >> 7;
+            s[0] &= 0x7f;
+            (
+                U256::from_big_endian(&bytes[0..32]),
+                U256::from_big_endian(&s),
+                27 + y_parity as u64,
+            )
+        }
+        _ => return Err(EthError::SignatureError),
+    };
+    Ok(Signature { r, s, v })
+}
+
+/// Normalize the `v` value of the signature to 27 or 28.
+/// It accepts the recovery id (0 or 1), the legacy value (27 or 28)
+/// and the EIP-155 value (`chain_id * 2 + 35` or `chain_id * 2 + 36`).
+pub fn normalize_eth_signature_v(v: u64) -> Result<u64, EthError> {
+    match v {
+        0 | 1 => Ok(v + 27),
+        27 | 28 => Ok(v),
+        v if v >= 35 => Ok((v - 35) % 2 + 27),
+        _ => Err(EthError::SignatureError),
+    }
+}
+
+/// Normalize the signature (65-byte or EIP-2098 compact hex string)
+/// to the 65-byte form with `v` of 27 or 28.
+/// Return a signature of hex string with prefix `0x`.
+pub fn normalize_eth_signature(signature: &str) -> Result<String, EthError> {
+    Ok(format!("0x{}", parse_eth_signature(signature)?))
+}
+
+/// Convert the signature (65-byte or EIP-2098 compact hex string)
+/// to the 64-byte compact form of EIP-2098.
+/// A high-s signature is first normalized to its low-s equivalent (with the flipped y parity),
+/// as the highest bit of `s` holds the y parity in the compact form.
+/// Return a signature of hex string with prefix `0x`.
+pub fn compact_eth_signature(signature: &str) -> Result<String, EthError> {
+    let mut signature = parse_eth_signature(signature)?;
+    if signature.s.is_zero() || signature.s >= SECP256K1_N {
+        return Err(EthError::SignatureError);
+    }
+    if signature.s > SECP256K1_N / 2 {
+        signature.s = SECP256K1_N - signature.s;
+        signature.v = if signature.v == 27 { 28 } else { 27 };
+    }
+    let mut bytes = [0u8; 64];
+    signature.r.to_big_endian(&mut bytes[0..32]);
+    signature.s.to_big_endian(&mut bytes[32..64]);
+    if signature.v == 28 {
+        bytes[32] |= 0x80;
+    }
+    Ok(format!("0x{}", hex::encode(bytes)))
+}
+
+/// the address which signed the hash
+fn recover_address(hash: H256, signature: &str) -> Result<Address, EthError> {
+    parse_eth_signature(signature)?
+        .recover(hash)
+        .map_err(|_| EthError::SignatureError)
+}
+
+/// the address from its hex string
+fn expected_address_from_str(address: &str) -> Result<Address, EthError> {
+    Address::from_str(address).map_err(|_| EthError::HexConversion)
+}
+
+/// Recover the address which signed the message as per EIP-191 (`personal_sign`).
+/// Argument `signature` is either a 65-byte or an EIP-2098 compact hex string.
+/// Return the checksummed address.
+pub fn recover_eth_personal_signer(message: &str, signature: &str) -> Result<String, EthError> {
+    let address = recover_address(hash_message(message), signature)?;
+    Ok(to_checksum(&address, None))
+}
+
+/// Recover the address which signed the hash value directly (`eth_sign`).
+/// Argument `hash` must be a hex value of 32 bytes (H256), and `signature` is either
+/// a 65-byte or an EIP-2098 compact hex string.
+/// Return the checksummed address.
+pub fn recover_eth_hash_signer(hash: &str, signature: &str) -> Result<String, EthError> {
+    let address = recover_address(
+        hash_from_str(hash).map_err(|_| EthError::HexConversion)?,
+        signature,
+    )?;
+    Ok(to_checksum(&address, None))
+}
+
+/// Check if the message was signed as per EIP-191 (`personal_sign`) by the expected address.
+/// NOTE: it only verifies the signatures of externally owned accounts;
+/// `verify_eth_account_personal_signature` also verifies the ones of contract wallets.
+pub fn verify_eth_personal_signature(
+    message: &str,
+    signature: &str,
+    expected_address: &str,
+) -> Result<bool, EthError> {
+    let expected_address = expected_address_from_str(expected_address)?;
+    Ok(recover_address(hash_message(message), signature)? == expected_address)
+}
+
+/// Check if the hash value was signed directly (`eth_sign`) by the expected address.
+/// NOTE: it only verifies the signatures of externally owned accounts;
+/// `verify_eth_account_signature` also verifies the ones of contract wallets.
+pub fn verify_eth_hash_signature(
+    hash: &str,
+    signature: &str,
+    expected_address: &str,
+) -> Result<bool, EthError> {
+    let expected_address = expected_address_from_str(expected_address)?;
+    Ok(recover_address(
+        hash_from_str(hash).map_err(|_| EthError::HexConversion)?,
+        signature,
+    )? == expected_address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EthNetwork, HDWallet, WalletCoin, WalletCoinFunc};
+
+    const MNEMONIC: &str = "apple elegant knife hawk there screen vehicle lounge tube sun engage bus custom market pioneer casual wink present cat metal ride shallow fork brief";
+
+    const HASH: &str = "0x01020304050607085152535455565758a1a2a3a4a5a6a7a8f1f2f3f4f5f6f7f8";
+
+    // signatures of `HASH` and "Hello World!" by the key `m/44'/118'/0'/0/0` of `MNEMONIC`
+    const HASH_SIGNATURE: &str = "0x379a17ae4fe51a4a40dab0a8736f9ebd11f0b5465f38192519e7b0e0bdd440137f7c7db0dfa1c78294d6dbf4c0797dcb161ca8f2dea0cd79267833269e1396261c";
+    const PERSONAL_SIGNATURE: &str = "0xb2aba6568054aff557402a3a9369309687019a29bb6180146d7a44043d6f8b19797e9a27c8c2b416a98cab29822927e76602924062725940e4bad56a9971faca1b";
+
+    fn get_address() -> String {
+        let wallet = HDWallet::recover_wallet(MNEMONIC.to_string(), None).unwrap();
+        let secret_key = wallet.get_key("m/44'/118'/0'/0/0".to_string()).unwrap();
+        WalletCoinFunc {
+            coin: WalletCoin::Ethereum {
+                network: EthNetwork::Mainnet,
+            },
+        }
+        .derive_address(&secret_key)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_signature_recovery() {
+        let address = get_address();
+        assert_eq!(
+            recover_eth_personal_signer("Hello World!", PERSONAL_SIGNATURE)
+                .unwrap()
+                .to_lowercase(),
+            address.to_lowercase()
+        );
+        assert_eq!(
+            recover_eth_hash_signer(HASH, HASH_SIGNATURE)
+                .unwrap()
+                .to_lowercase(),
+            address.to_lowercase()
+        );
+        assert!(
+            verify_eth_personal_signature("Hello World!", PERSONAL_SIGNATURE, &address).unwrap()
+        );
+        assert!(
+            !verify_eth_personal_signature("Hello World", PERSONAL_SIGNATURE, &address).unwrap()
+        );
+        assert!(verify_eth_hash_signature(HASH, HASH_SIGNATURE, &address).unwrap());
+        assert!(!verify_eth_hash_signature(HASH, PERSONAL_SIGNATURE, &address).unwrap());
+    }
+
+    #[test]
+    fn test_signature_normalization() {
+        let address = get_address();
+        let compact = compact_eth_signature(PERSONAL_SIGNATURE).unwrap();
+        assert_eq!(compact.len(), 2 + 128);
+        assert_eq!(
+            normalize_eth_signature(&compact).unwrap(),
+            PERSONAL_SIGNATURE
+        );
+        assert!(verify_eth_personal_signature("Hello World!", &compact, &address).unwrap());
+
+        // the same signature with v of the recovery id (0) and of EIP-155 on Cronos (25)
+        let recovery_id = format!("{}00", &PERSONAL_SIGNATURE[..130]);
+        let eip155 = format!("{}{:02x}", &PERSONAL_SIGNATURE[..130], 25 * 2 + 35);
+        assert_eq!(
+            normalize_eth_signature(&recovery_id).unwrap(),
+            PERSONAL_SIGNATURE
+        );
+        assert_eq!(
+            normalize_eth_signature(&eip155).unwrap(),
+            PERSONAL_SIGNATURE
+        );
+
+        // the high-s equivalent is compacted as the low-s one
+        let signature = parse_eth_signature(PERSONAL_SIGNATURE).unwrap();
+        let high_s = Signature {
+            r: signature.r,
+            s: SECP256K1_N - signature.s,
+            v: 55 - signature.v,
+        };
+        assert!(high_s.s > SECP256K1_N / 2);
+        assert_eq!(compact_eth_signature(&high_s.to_string()).unwrap(), compact);
+        let invalid_s = Signature {
+            s: SECP256K1_N,
+            ..signature
+        };
+        assert!(compact_eth_signature(&invalid_s.to_string()).is_err());
+
+        assert_eq!(normalize_eth_signature_v(1).unwrap(), 28);
+        assert_eq!(normalize_eth_signature_v(38).unwrap(), 28);
+        assert!(normalize_eth_signature_v(29).is_err());
+        assert!(parse_eth_signature("0x1234").is_err());
+    }
+}
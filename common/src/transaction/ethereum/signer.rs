@@ -1,11 +1,11 @@
 // FIXME: Ethereum signer only has one signing function of EIP-712 for now.
 #![cfg(feature = "abi-contract")]
 
-use crate::node::ethereum::eip712::Eip712TypedData;
+use crate::node::ethereum::eip712::{Eip712TypedData, Eip712TypedDataPreview};
 use crate::transaction::ethereum::EthError;
 use crate::wallet::SecretKey;
-use ethers::prelude::{LocalWallet, H256};
-use ethers::utils::hash_message;
+use ethers::prelude::{Address, LocalWallet, H256};
+use ethers::utils::{hash_message, to_checksum};
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -104,10 +104,40 @@ impl EthSigner {
     }
 }
 
+/// Inspect the EIP-712 typed data (JSON) before signing it: the domain separator,
+/// the struct hash, the resolved `encodeType` strings and the flattened fields
+/// of the domain and the message.
+pub fn preview_eip712_typed_data(
+    json_typed_data: &str,
+) -> Result<Eip712TypedDataPreview, EthError> {
+    Eip712TypedData::new(json_typed_data)?.preview()
+}
+
+/// Recover the address of the signer of the EIP-712 typed data (JSON)
+/// from the signature (hex string of 65 bytes).
+/// Return the checksummed address.
+pub fn recover_eip712_signer(json_typed_data: &str, signature: &str) -> Result<String, EthError> {
+    let address = Eip712TypedData::new(json_typed_data)?.recover_signer(signature)?;
+    Ok(to_checksum(&address, None))
+}
+
+/// Check if the EIP-712 typed data (JSON) was signed by the expected address.
+pub fn verify_eip712_signature(
+    json_typed_data: &str,
+    signature: &str,
+    expected_address: &str,
+) -> Result<bool, EthError> {
+    let expected_address =
+        Address::from_str(expected_address).map_err(|_| EthError::HexConversion)?;
+    let address = Eip712TypedData::new(json_typed_data)?.recover_signer(signature)?;
+    Ok(address == expected_address)
+}
+
 #[cfg(test)]
 mod ethereum_signing_tests {
     use super::*;
     use crate::wallet::HDWallet;
+    use ethers::prelude::Signer;
 
     const MNEMONIC: &str = "apple elegant knife hawk there screen vehicle lounge tube sun engage bus custom market pioneer casual wink present cat metal ride shallow fork brief";
 
@@ -212,4 +242,20 @@ mod ethereum_signing_tests {
             .unwrap();
         assert_eq!(signature, "0x1518da25a02d4aaf8635df026148d6b260a5d2f214530f003837c40a04088e0819aea58addc9e9ecc60fe5d3391b4b78c04b86181fbb8cf3a9a2d0112ee6b1391c");
     }
+
+    #[test]
+    fn test_eip712_signature_verification() {
+        let signer = get_signer();
+        let signature = signer.sign_typed_data(SIMPLE_JSON_TYPED_DATA).unwrap();
+        let address = recover_eip712_signer(SIMPLE_JSON_TYPED_DATA, &signature).unwrap();
+        assert_eq!(
+            address.to_lowercase(),
+            format!("{:?}", signer.wallet.address())
+        );
+        assert!(verify_eip712_signature(SIMPLE_JSON_TYPED_DATA, &signature, &address).unwrap());
+        assert!(
+            !verify_eip712_signature(RECURSIVELY_NESTED_JSON_TYPED_DATA, &signature, &address)
+                .unwrap()
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use ethers::types::H256;
 use ethers::utils::hex::{self, FromHexError};
 
 pub(crate) fn hex_decode(hex_string: &str) -> Result<Vec<u8>, FromHexError> {
@@ -5,6 +6,15 @@ pub(crate) fn hex_decode(hex_string: &str) -> Result<Vec<u8>, FromHexError> {
     hex::decode(hex_string)
 }
 
+/// the 32-byte hash value from its hex string (with or without the `0x` prefix)
+pub(crate) fn hash_from_str(hash: &str) -> Result<H256, FromHexError> {
+    let bytes = hex_decode(hash)?;
+    if bytes.len() != H256::len_bytes() {
+        return Err(FromHexError::InvalidStringLength);
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
 #[cfg(test)]
 mod utils_tests {
     use super::*;
@@ -23,4 +33,14 @@ mod utils_tests {
 
         assert_eq!(hex_decode(&hex_string[2..]).unwrap(), decoded_data);
     }
+
+    #[test]
+    fn test_utils_hash_from_str() {
+        let hash_string = "0xaf6f293f2621bfb5a70d7cf123596bd14827f73769c24edf2688b3ce2c86d747";
+        let hash = hash_from_str(hash_string).unwrap();
+        assert_eq!(hash.as_bytes(), hex_decode(hash_string).unwrap().as_slice());
+        assert_eq!(hash_from_str(&hash_string[2..]).unwrap(), hash);
+        assert!(hash_from_str(&hash_string[..64]).is_err());
+        assert!(hash_from_str("0xzz").is_err());
+    }
 }
@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+// A minimal ENS-compatible registry for the name resolution tests,
+// which is the resolver of all the names.
+contract TestENS {
+    mapping(bytes32 => address) public addr;
+    mapping(bytes32 => string) public name;
+
+    function setAddr(bytes32 node, address addr_) external {
+        addr[node] = addr_;
+    }
+
+    function setName(bytes32 node, string calldata name_) external {
+        name[node] = name_;
+    }
+
+    function resolver(bytes32) external view returns (address) {
+        return address(this);
+    }
+
+    function supportsInterface(bytes4 interfaceId) external pure returns (bool) {
+        return
+            interfaceId == 0x01ffc9a7 || // supportsInterface(bytes4)
+            interfaceId == 0x3b3b57de || // addr(bytes32)
+            interfaceId == 0x691f3431; // name(bytes32)
+    }
+}
@@ -17,6 +17,8 @@ using namespace org::defi_wallet_core;
 
 void test_uint();
 void test_approval();
+void test_ens();
+void test_qr_code();
 
 Box<Wallet> createWallet(String mymnemonics);
 String getEnv(String key);
@@ -217,6 +219,8 @@ void cronos_process() {
 
   test_uint();
   test_approval();
+  test_ens();
+  test_qr_code();
 }
 
 void test_approval() {
@@ -394,6 +398,70 @@ void test_approval() {
   begin = measure_time(begin, "erc1155.balance_of");
 }
 
+void test_ens() {
+  timepoint begin = std::chrono::high_resolution_clock::now();
+  String mycronosrpc = getEnv("MYCRONOSRPC");
+  int chainid = 777; // defined in cronos-devnet.yaml
+
+  String signer2_mnemonics = getEnv("SIGNER2_MNEMONIC");
+  Box<Wallet> signer2_wallet = createWallet(signer2_mnemonics);
+  String signer2_address = signer2_wallet->get_eth_address(0);
+  begin = measure_time(begin, "get_eth_address");
+  // the checksummed address of signer2
+  String alice_address = "0x03EB2cbae6754c6e459F444783d1557dCa0f4e1A";
+
+  // the TestENS registry deployed and set up in integration_tests/test_basic.py
+  String ens_registry_address = getEnv("ENS_REGISTRY_ADDRESS");
+  set_eth_name_registry(chainid, ens_registry_address);
+  begin = measure_time(begin, "set_eth_name_registry");
+  assert(resolve_eth_name("alice.cro", chainid, mycronosrpc) == alice_address);
+  begin = measure_time(begin, "resolve_eth_name");
+  // the names are normalized to lowercase
+  assert(resolve_eth_name("Alice.cro", chainid, mycronosrpc) == alice_address);
+  begin = measure_time(begin, "resolve_eth_name");
+  assert(lookup_eth_name(signer2_address, chainid, mycronosrpc) ==
+         "alice.cro");
+  begin = measure_time(begin, "lookup_eth_name");
+
+  // signer1 has no reverse record
+  String signer1_mnemonics = getEnv("SIGNER1_MNEMONIC");
+  Box<Wallet> signer1_wallet = createWallet(signer1_mnemonics);
+  String signer1_address = signer1_wallet->get_eth_address(0);
+  assert(lookup_eth_name(signer1_address, chainid, mycronosrpc) == "");
+  begin = measure_time(begin, "lookup_eth_name");
+
+  // bob.cro has no address
+  bool resolved = true;
+  try {
+    resolve_eth_name("bob.cro", chainid, mycronosrpc);
+  } catch (const rust::cxxbridge1::Error &e) {
+    resolved = false;
+  }
+  assert(!resolved);
+  begin = measure_time(begin, "resolve_eth_name");
+}
+
+void test_qr_code() {
+  timepoint begin = std::chrono::high_resolution_clock::now();
+  String request =
+      "ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7/"
+      "transfer?address=0x8e23ee67d1332ad560396262c48ffbb01f93d052&uint256=1";
+  // the QR code to be shown on a terminal
+  cout << render_qr_terminal(request, "M", 4) << endl;
+  begin = measure_time(begin, "render_qr_terminal");
+  String svg = render_qr_svg(request, "M", 4, 8);
+  assert(std::string(svg).find("<svg") != std::string::npos);
+  begin = measure_time(begin, "render_qr_svg");
+
+  // render the pixels and scan them back
+  QrGrayscaleImage image = render_qr_grayscale(request, "M", 4, 4);
+  assert(image.pixels.size() == image.width * image.width);
+  begin = measure_time(begin, "render_qr_grayscale");
+  Vec<uint8_t> pixels = image.pixels;
+  assert(decode_qr_grayscale(image.width, image.width, pixels) == request);
+  begin = measure_time(begin, "decode_qr_grayscale");
+}
+
 void test_uint() {
   timepoint begin = std::chrono::high_resolution_clock::now();
   assert(u256("15") == u256("15", 10));
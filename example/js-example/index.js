@@ -23,6 +23,7 @@ testEip199PersonalSign();
 testEip712SimpleTypedDataSign();
 testEip712RecursivelyNestedTypedDataSign();
 testBuildEthereumContractBatchTransfer();
+testQrCodeRoundTrip();
 const client = await testCosmosClient();
 await testQueryAccountBalance(client);
 testBuildAndSignCosmosTx();
@@ -250,6 +251,20 @@ function testBuildEthereumContractBatchTransfer() {
   console.dir(details);
 }
 
+function testQrCodeRoundTrip() {
+  const request = "ethereum:0x89205a3a3b2a69de6dbf7f01ed13b2108b2c43e7/transfer?address=0x8e23ee67d1332ad560396262c48ffbb01f93d052&uint256=1";
+
+  // Render the QR code as an SVG image to be shown on the page.
+  const svg = wasm.render_qr_svg(request, "M", 4, 8);
+  console.assert(svg.includes("<svg"), "The QR code is not rendered as SVG");
+
+  // Render its pixels and scan them back.
+  const image = wasm.render_qr_grayscale(request, "M", 4, 4);
+  const decoded = wasm.decode_qr_grayscale(image.width, image.width, image.pixels);
+  console.assert(decoded === request, "The decoded QR code does not match the request");
+  console.log(`Decoded QR code: ${decoded}`);
+}
+
 function testCosmosClient() {
   const config = new wasm.CosmosClientConfig(
     CHAINMAIN_API_URL,
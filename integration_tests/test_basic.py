@@ -1,10 +1,11 @@
 #!/usr/bin/env python3
 
+import os
 import subprocess
 import time
 from pathlib import Path
 
-from .utils import ADDRS, CONTRACTS, CPP_EXAMPLE_PATH, KEYS, Contract
+from .utils import ADDRS, CONTRACTS, CPP_EXAMPLE_PATH, KEYS, Contract, namehash
 
 
 def test_basic(chainmain, cronos):
@@ -88,6 +89,28 @@ def test_basic(chainmain, cronos):
     )
     print("URI of SHIELD:", contract.functions.uri(4).call())
 
+    # ENS-compatible name registry: "alice.cro" is signer2 and the reverse way round
+    ens = Contract(
+        CONTRACTS["TestENS"],
+        KEYS["signer1"],
+    )
+    contract = ens.deploy(w3)
+    print("ENS address:", contract.address)
+    tx = contract.functions.setAddr(
+        namehash("alice.cro"), ADDRS["signer2"]
+    ).buildTransaction({"from": ADDRS["signer1"]})
+    ens.send(tx)
+    reverse_name = ADDRS["signer2"][2:].lower() + ".addr.reverse"
+    tx = contract.functions.setName(
+        namehash(reverse_name), "alice.cro"
+    ).buildTransaction({"from": ADDRS["signer1"]})
+    ens.send(tx)
+    print("alice.cro:", contract.functions.addr(namehash("alice.cro")).call())
+    assert contract.functions.addr(namehash("alice.cro")).call() == ADDRS["signer2"]
+    assert contract.functions.name(namehash(reverse_name)).call() == "alice.cro"
+    # the C++ example takes the registry from the environment
+    os.environ["ENS_REGISTRY_ADDRESS"] = contract.address
+
     # TODO Test `CPP_EXAMPLE_PATH` or cppexamplestatic
     # Pass input and assert output
     cmd = ""
@@ -6,6 +6,7 @@ from pathlib import Path
 
 from dotenv import load_dotenv
 from eth_account import Account
+from web3 import Web3
 from web3._utils.transactions import fill_nonce, fill_transaction_defaults
 
 load_dotenv(Path(__file__).parent.parent / "scripts/.env")
@@ -23,6 +24,7 @@ TEST_CONTRACTS = {
     "TestERC20": "TestERC20.sol",
     "TestERC721": "TestERC721.sol",
     "TestERC1155": "TestERC1155.sol",
+    "TestENS": "TestENS.sol",
 }
 
 CPP_EXAMPLE_PATH = os.getenv("CPP_EXAMPLE_PATH")
@@ -46,6 +48,15 @@ CONTRACTS = {
 }
 
 
+def namehash(name):
+    "ENS namehash (EIP-137) of the name"
+    node = b"\0" * 32
+    if name:
+        for label in reversed(name.split(".")):
+            node = Web3.keccak(node + Web3.keccak(text=label))
+    return node
+
+
 class Contract:
     "General contract."
 